@@ -0,0 +1,41 @@
+// Record the value node evaluates each expression of javascript.json to
+//
+// Usage: node record.js [javascript.json]
+// New cases only need an "expression", the recorded type and value are rewritten
+// An "issue" explains a known difference of minusone, and is kept
+
+const fs = require("fs");
+const path = require("path");
+
+const corpus = process.argv[2] || path.join(__dirname, "javascript.json");
+
+function encode(value) {
+    if (Array.isArray(value)) {
+        return { type: "array", value: Array.from(value, encode) };
+    }
+    switch (typeof value) {
+        case "string":
+            return { type: "string", value };
+        case "number":
+            return { type: "double", value: Object.is(value, -0) ? "-0" : String(value) };
+        case "boolean":
+            return { type: "bool", value };
+        case "bigint":
+            return { type: "bigint", value: value.toString() };
+        case "undefined":
+            return { type: "undefined" };
+        default:
+            if (value === null) {
+                return { type: "null" };
+            }
+            throw new Error(`unsupported ${typeof value} value`);
+    }
+}
+
+const cases = JSON.parse(fs.readFileSync(corpus, "utf8")).map(({ expression, issue }) => {
+    const value = (0, eval)(`(${expression})`);
+    return { expression, ...encode(value), ...(issue && { issue }) };
+});
+
+fs.writeFileSync(corpus, JSON.stringify(cases, null, 2) + "\n");
+console.log(`Recorded ${cases.length} values with node ${process.version}`);
@@ -0,0 +1,50 @@
+# Record the value pwsh evaluates each expression of powershell.json to
+#
+# Usage: pwsh record.ps1 [powershell.json]
+# New cases only need an "expression", the recorded type and value are rewritten
+# An "issue" explains a known difference of minusone, and is kept
+
+param(
+    [string]$Corpus = (Join-Path $PSScriptRoot "powershell.json")
+)
+
+$IntegerTypes = "Byte", "SByte", "Int16", "UInt16", "Int32", "UInt32", "Int64"
+
+function ConvertTo-Oracle($Value) {
+    if ($null -eq $Value) {
+        return [ordered]@{ type = "null" }
+    }
+    if ($Value -is [array]) {
+        return [ordered]@{ type = "array"; value = @($Value | ForEach-Object { ConvertTo-Oracle $_ }) }
+    }
+    $name = $Value.GetType().Name
+    if ($name -in $IntegerTypes) {
+        return [ordered]@{ type = "int"; value = [long]$Value }
+    }
+    switch ($name) {
+        "String" { return [ordered]@{ type = "string"; value = $Value } }
+        "Char" { return [ordered]@{ type = "char"; value = [string]$Value } }
+        "Boolean" { return [ordered]@{ type = "bool"; value = $Value } }
+        { $_ -in "Double", "Single", "Decimal" } {
+            return [ordered]@{ type = "double"; value = ([double]$Value).ToString("R", [cultureinfo]::InvariantCulture) }
+        }
+    }
+    throw "unsupported $name value"
+}
+
+$cases = foreach ($case in Get-Content -Raw $Corpus | ConvertFrom-Json) {
+    # parentheses keep arrays of one item, as minusone infers the value of the statement
+    $value = & ([scriptblock]::Create("Write-Output -NoEnumerate ($($case.expression))"))
+    $recorded = [ordered]@{ expression = $case.expression }
+    $oracle = ConvertTo-Oracle $value
+    foreach ($key in $oracle.Keys) {
+        $recorded[$key] = $oracle[$key]
+    }
+    if ($case.issue) {
+        $recorded.issue = $case.issue
+    }
+    $recorded
+}
+
+ConvertTo-Json -InputObject @($cases) -Depth 8 | Set-Content -Encoding utf8NoBOM $Corpus
+Write-Host "Recorded $($cases.Count) values with pwsh $($PSVersionTable.PSVersion)"
@@ -0,0 +1,301 @@
+use crate::error::MinusOneResult;
+use crate::rule::Rule;
+use crate::tree::{HashMapStorage, Node, Tree};
+use sha2::{Digest, Sha256};
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+/// Byte values smaller than this are keys, IVs or short strings rather than artefacts
+pub const DEFAULT_ARTEFACT_THRESHOLD: usize = 256;
+
+/// Nature of an embedded byte value, guessed from its content
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum ArtefactKind {
+    Pe,
+    DotNetAssembly,
+    Elf,
+    Zip,
+    Office,
+    OfficeOpenXml,
+    Shellcode,
+    Data,
+}
+
+impl ArtefactKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArtefactKind::Pe => "pe",
+            ArtefactKind::DotNetAssembly => "dotnet-assembly",
+            ArtefactKind::Elf => "elf",
+            ArtefactKind::Zip => "zip",
+            ArtefactKind::Office => "office",
+            ArtefactKind::OfficeOpenXml => "office-open-xml",
+            ArtefactKind::Shellcode => "shellcode",
+            ArtefactKind::Data => "data",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ArtefactKind::Pe => "Windows executable",
+            ArtefactKind::DotNetAssembly => ".NET assembly",
+            ArtefactKind::Elf => "Linux executable",
+            ArtefactKind::Zip => "Zip archive",
+            ArtefactKind::Office => "Office document",
+            ArtefactKind::OfficeOpenXml => "Word document in the Office Open XML format",
+            ArtefactKind::Shellcode => "Native code, recognized by its prologue",
+            ArtefactKind::Data => "Unrecognized binary data",
+        }
+    }
+
+    /// Extension of the dumped file
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArtefactKind::Pe | ArtefactKind::DotNetAssembly => "exe",
+            ArtefactKind::Elf => "elf",
+            ArtefactKind::Zip => "zip",
+            ArtefactKind::Office => "doc",
+            ArtefactKind::OfficeOpenXml => "docx",
+            ArtefactKind::Shellcode | ArtefactKind::Data => "bin",
+        }
+    }
+}
+
+impl Display for ArtefactKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Prologues of common shellcode generators and position independent code
+const SHELLCODE_PROLOGUES: [&[u8]; 6] = [
+    // cld; and rsp, 0xfffffffffffffff0 (Metasploit and Cobalt Strike x64)
+    b"\xfc\x48\x83\xe4\xf0",
+    // cld; call (Metasploit and Cobalt Strike x86)
+    b"\xfc\xe8\x82\x00\x00\x00",
+    b"\xfc\xe8\x89\x00\x00\x00",
+    b"\xfc\xe8\x8f\x00\x00\x00",
+    // pushad; mov ebp, esp
+    b"\x60\x89\xe5",
+    // call $+5
+    b"\xe8\x00\x00\x00\x00",
+];
+
+/// fnstenv [esp-0xc], used to get the instruction pointer by encoders like shikata_ga_nai
+const FNSTENV_GETPC: &[u8] = b"\xd9\x74\x24\xf4";
+
+const OLE_MAGIC: &[u8] = b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1";
+
+fn read_u16(data: &[u8], offset: usize) -> Option<usize> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<usize> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+}
+
+/// A PE with a CLR runtime header is a .NET assembly
+fn pe_kind(data: &[u8]) -> Option<ArtefactKind> {
+    let header = read_u32(data, 0x3c)?;
+    if data.get(header..header + 4)? != b"PE\0\0" {
+        return None;
+    }
+
+    let optional_header = header + 24;
+    let directories = match read_u16(data, optional_header) {
+        Some(0x10b) => optional_header + 96,
+        Some(0x20b) => optional_header + 112,
+        _ => return Some(ArtefactKind::Pe),
+    };
+    let count = read_u32(data, directories - 4).unwrap_or_default();
+    match read_u32(data, directories + 14 * 8) {
+        Some(clr_header) if count > 14 && clr_header != 0 => Some(ArtefactKind::DotNetAssembly),
+        _ => Some(ArtefactKind::Pe),
+    }
+}
+
+/// Guess the nature of a byte value from its magic, or its first instructions
+///
+/// # Example
+/// ```
+/// use minusone::artefact::{ArtefactKind, classify};
+///
+/// assert_eq!(classify(b"\x7fELF\x02\x01\x01"), ArtefactKind::Elf);
+/// assert_eq!(classify(b"\xfc\x48\x83\xe4\xf0\xe8\xc0\x00\x00\x00"), ArtefactKind::Shellcode);
+/// assert_eq!(classify(b"Hello, minusone!"), ArtefactKind::Data);
+/// ```
+pub fn classify(data: &[u8]) -> ArtefactKind {
+    if data.starts_with(b"MZ")
+        && let Some(kind) = pe_kind(data)
+    {
+        return kind;
+    }
+    if data.starts_with(b"\x7fELF") {
+        return ArtefactKind::Elf;
+    }
+    if data.starts_with(OLE_MAGIC) {
+        return ArtefactKind::Office;
+    }
+    if data.starts_with(b"PK\x03\x04") || data.starts_with(b"PK\x05\x06") {
+        // Word documents are zip archives with a content types part and a word folder,
+        // other Office Open XML documents are dumped as the archives they are
+        let contains = |part: &[u8]| data.windows(part.len()).any(|window| window == part);
+        if contains(b"[Content_Types].xml") && contains(b"word/") {
+            return ArtefactKind::OfficeOpenXml;
+        }
+        return ArtefactKind::Zip;
+    }
+    if SHELLCODE_PROLOGUES
+        .iter()
+        .any(|prologue| data.starts_with(prologue))
+        || data
+            .get(..16)
+            .is_some_and(|start| start.windows(4).any(|window| window == FNSTENV_GETPC))
+    {
+        return ArtefactKind::Shellcode;
+    }
+    ArtefactKind::Data
+}
+
+/// A byte value inferred by the deobfuscation
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Artefact {
+    pub kind: ArtefactKind,
+    /// Lowercase hexadecimal SHA-256 of the data
+    pub sha256: String,
+    pub data: Vec<u8>,
+    /// Range of the first node holding the value, in the source the tree was built from
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Artefact {
+    pub fn new(data: &[u8], start: usize, end: usize) -> Self {
+        Artefact {
+            kind: classify(data),
+            sha256: sha256(data),
+            data: data.to_vec(),
+            start,
+            end,
+        }
+    }
+
+    /// Name of the file the artefact is dumped to
+    pub fn file_name(&self) -> String {
+        format!("{}.{}", self.sha256, self.kind.extension())
+    }
+
+    /// Short description that stands for the value in the linted output
+    ///
+    /// # Example
+    /// ```
+    /// use minusone::artefact::Artefact;
+    ///
+    /// assert_eq!(
+    ///     Artefact::new(b"minusone", 0, 0).reference(),
+    ///     "data, 8 bytes, sha256:3c0ac5c1854242be9bbb1c2b3aa6c77816663958ffc4fb77a3fbf104ffa4985a"
+    /// );
+    /// ```
+    pub fn reference(&self) -> String {
+        format!(
+            "{}, {} bytes, sha256:{}",
+            self.kind,
+            self.data.len(),
+            self.sha256
+        )
+    }
+}
+
+fn sha256(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// A language that can infer byte values
+pub trait ArtefactLanguage {
+    /// Bytes held by an inferred value, if it is a byte value
+    fn artefact_bytes(&self) -> Option<&[u8]>;
+}
+
+/// This rule will list the byte values of a tree bigger than a threshold
+/// It's meant to be run on the deobfuscated tree, and never modifies it
+///
+/// Only the outermost node holding a value is reported,
+/// and a value found several times is reported once
+pub struct ArtefactCollector<T> {
+    pub artefacts: Vec<Artefact>,
+    threshold: usize,
+    language: PhantomData<T>,
+}
+
+impl<T> ArtefactCollector<T> {
+    pub fn new(threshold: usize) -> Self {
+        ArtefactCollector {
+            artefacts: vec![],
+            threshold,
+            language: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for ArtefactCollector<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_ARTEFACT_THRESHOLD)
+    }
+}
+
+impl<'a, T: ArtefactLanguage> Rule<'a> for ArtefactCollector<T> {
+    type Language = T;
+
+    fn enter(&mut self, node: &Node<'a, Self::Language>) -> MinusOneResult<bool> {
+        let Some(data) = node.data().and_then(T::artefact_bytes) else {
+            return Ok(true);
+        };
+        if data.len() < self.threshold {
+            return Ok(true);
+        }
+
+        let artefact = Artefact::new(data, node.start_abs(), node.end_abs());
+        if !self
+            .artefacts
+            .iter()
+            .any(|known| known.sha256 == artefact.sha256)
+        {
+            self.artefacts.push(artefact);
+        }
+        Ok(false)
+    }
+
+    fn leave(&mut self, _node: &Node<'a, Self::Language>) -> MinusOneResult<()> {
+        Ok(())
+    }
+}
+
+/// Byte values of a deobfuscated tree bigger than a threshold
+pub fn collect_artefacts<T: ArtefactLanguage>(
+    root: &Tree<HashMapStorage<T>>,
+    threshold: usize,
+) -> MinusOneResult<Vec<Artefact>> {
+    let mut collector = ArtefactCollector::new(threshold);
+    root.apply(&mut collector)?;
+    Ok(collector.artefacts)
+}
+
+/// Write each artefact in a directory, named by its SHA-256
+///
+/// The directory is created if needed. Returns the path of the written files
+pub fn dump_artefacts(artefacts: &[Artefact], directory: &Path) -> MinusOneResult<Vec<PathBuf>> {
+    std::fs::create_dir_all(directory)?;
+    let mut paths = vec![];
+    for artefact in artefacts {
+        let path = directory.join(artefact.file_name());
+        std::fs::write(&path, &artefact.data)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
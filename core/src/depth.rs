@@ -0,0 +1,78 @@
+use std::cell::Cell;
+use std::thread::LocalKey;
+
+/// Bounds the recursion of a rule running a nested deobfuscation,
+/// the depth is kept in a thread local shared by all its runs
+pub struct DepthCounter {
+    key: &'static LocalKey<Cell<usize>>,
+    max: usize,
+    label: &'static str,
+}
+
+impl DepthCounter {
+    pub const fn new(key: &'static LocalKey<Cell<usize>>, max: usize, label: &'static str) -> Self {
+        Self { key, max, label }
+    }
+
+    // returns `None` once the cap is reached
+    pub fn enter(&self) -> Option<DepthGuard> {
+        let depth = self.key.with(|c| c.get());
+        if depth >= self.max {
+            log::trace!(
+                "{}: depth {} reached the cap, refusing to recurse",
+                self.label,
+                depth
+            );
+            return None;
+        }
+        self.key.with(|c| c.set(depth + 1));
+        Some(DepthGuard { key: self.key })
+    }
+
+    pub fn depth(&self) -> usize {
+        self.key.with(|c| c.get())
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+}
+
+pub struct DepthGuard {
+    key: &'static LocalKey<Cell<usize>>,
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        self.key.with(|c| c.set(c.get().saturating_sub(1)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    thread_local! {
+        static TEST_DEPTH: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn test_counter() -> DepthCounter {
+        DepthCounter::new(&TEST_DEPTH, 2, "test")
+    }
+
+    #[test]
+    fn test_depth_guard_brackets_and_caps() {
+        let counter = test_counter();
+        assert_eq!(counter.depth(), 0);
+
+        let g1 = counter.enter().unwrap();
+        let g2 = counter.enter().unwrap();
+        assert_eq!(counter.depth(), 2);
+        assert!(counter.enter().is_none());
+
+        drop(g2);
+        assert!(counter.enter().is_some());
+        drop(g1);
+        assert_eq!(counter.depth(), 0);
+    }
+}
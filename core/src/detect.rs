@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// Obfuscation techniques recognized by the static analysis
+/// of the `r#static` module of each language
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum Technique {
+    StringConcat,
+    CharCode,
+    FormatString,
+    TickInsertion,
+    RandomCase,
+    WildcardCmdlet,
+    Base64,
+    Encryption,
+    Compression,
+    DynamicInvocation,
+    JsFuck,
+    StringArray,
+    HexIdentifier,
+    EscapedString,
+}
+
+impl Technique {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::StringConcat => "string-concat",
+            Technique::CharCode => "char-code",
+            Technique::FormatString => "format-string",
+            Technique::TickInsertion => "tick-insertion",
+            Technique::RandomCase => "random-case",
+            Technique::WildcardCmdlet => "wildcard-cmdlet",
+            Technique::Base64 => "base64",
+            Technique::Encryption => "encryption",
+            Technique::Compression => "compression",
+            Technique::DynamicInvocation => "dynamic-invocation",
+            Technique::JsFuck => "jsfuck",
+            Technique::StringArray => "string-array",
+            Technique::HexIdentifier => "hex-identifier",
+            Technique::EscapedString => "escaped-string",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Technique::StringConcat => "String split into concatenated constants",
+            Technique::CharCode => "String built from character codes",
+            Technique::FormatString => "String reordered through a format operator",
+            Technique::TickInsertion => "Escape characters inserted into names",
+            Technique::RandomCase => "Randomized case of command names",
+            Technique::WildcardCmdlet => "Command name hidden behind wildcards",
+            Technique::Base64 => "Base64 encoded layer",
+            Technique::Encryption => "Encrypted layer",
+            Technique::Compression => "Compressed layer",
+            Technique::DynamicInvocation => "Code executed from a computed string",
+            Technique::JsFuck => "JSFuck encoded expression",
+            Technique::StringArray => "Strings hoisted into an indexed array",
+            Technique::HexIdentifier => "Generated hexadecimal identifiers",
+            Technique::EscapedString => "Printable characters written as escape sequences",
+        }
+    }
+
+    /// Contribution of the first occurrence to the obfuscation score
+    pub fn weight(&self) -> u32 {
+        match self {
+            Technique::StringConcat => 10,
+            Technique::CharCode => 15,
+            Technique::FormatString => 15,
+            Technique::TickInsertion => 15,
+            Technique::RandomCase => 10,
+            Technique::WildcardCmdlet => 15,
+            Technique::Base64 => 15,
+            Technique::Encryption => 25,
+            Technique::Compression => 20,
+            Technique::DynamicInvocation => 10,
+            Technique::JsFuck => 50,
+            Technique::StringArray => 30,
+            Technique::HexIdentifier => 15,
+            Technique::EscapedString => 10,
+        }
+    }
+}
+
+impl Display for Technique {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Pattern {
+    pub start_offset: usize,
+    pub end_offset: usize,
+}
+
+impl Pattern {
+    pub fn new(start_offset: usize, end_offset: usize) -> Self {
+        Pattern {
+            start_offset,
+            end_offset,
+        }
+    }
+}
+
+/// Nodes are left bottom-up, so a pattern covering the previous ones replaces them
+pub fn push_outermost(nodes: &mut Vec<Pattern>, pattern: Pattern) {
+    while let Some(last) = nodes.last()
+        && last.start_offset >= pattern.start_offset
+        && last.end_offset <= pattern.end_offset
+    {
+        nodes.pop();
+    }
+    nodes.push(pattern);
+}
+
+/// A detection rule keeps track of the location
+/// of every pattern it has recognized
+pub trait Detection {
+    fn technique(&self) -> Technique;
+    fn get_nodes(&self) -> &Vec<Pattern>;
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Finding {
+    pub technique: Technique,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Result of the static analysis of a script
+///
+/// # Example
+/// ```
+/// use minusone::detect::{Detection, Pattern, Report, Technique};
+///
+/// struct Concat(Vec<Pattern>);
+///
+/// impl Detection for Concat {
+///     fn technique(&self) -> Technique {
+///         Technique::StringConcat
+///     }
+///     fn get_nodes(&self) -> &Vec<Pattern> {
+///         &self.0
+///     }
+/// }
+///
+/// let report = Report::new(&[&Concat(vec![Pattern::new(0, 9), Pattern::new(12, 20)])]);
+/// assert_eq!(report.techniques(), vec![(Technique::StringConcat, 2)]);
+/// assert_eq!(report.score(), 12);
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Report {
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    pub fn new(detections: &[&dyn Detection]) -> Self {
+        let mut findings: Vec<Finding> = detections
+            .iter()
+            .flat_map(|detection| {
+                detection.get_nodes().iter().map(|pattern| Finding {
+                    technique: detection.technique(),
+                    start: pattern.start_offset,
+                    end: pattern.end_offset,
+                })
+            })
+            .collect();
+        findings.sort_by_key(|finding| (finding.start, finding.end, finding.technique));
+        Self { findings }
+    }
+
+    /// Number of occurrences of each technique found
+    pub fn techniques(&self) -> Vec<(Technique, usize)> {
+        let mut count = BTreeMap::new();
+        for finding in &self.findings {
+            *count.entry(finding.technique).or_insert(0) += 1;
+        }
+        count.into_iter().collect()
+    }
+
+    /// Obfuscation score between 0 and 100
+    ///
+    /// Each technique contributes its weight, plus a quarter of it
+    /// for each other occurrence, up to twice its weight
+    pub fn score(&self) -> u32 {
+        self.techniques()
+            .iter()
+            .map(|(technique, count)| {
+                let weight = technique.weight();
+                weight + weight * (*count as u32 - 1).min(4) / 4
+            })
+            .sum::<u32>()
+            .min(100)
+    }
+}
+
+/// Heuristic used to flag long constant strings that are probably a base64 layer
+pub fn looks_like_base64(value: &str) -> bool {
+    let trimmed = value.trim_end_matches('=');
+    value.len() >= 64
+        && value.len() - trimmed.len() <= 2
+        && trimmed
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/')
+        && trimmed.chars().any(|c| c.is_ascii_digit())
+        && trimmed.chars().any(|c| c.is_ascii_uppercase())
+        && trimmed.chars().any(|c| c.is_ascii_lowercase())
+}
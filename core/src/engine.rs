@@ -1,5 +1,10 @@
+use crate::artefact::{Artefact, ArtefactLanguage, collect_artefacts};
 use crate::debug::DebugView;
-use crate::error::MinusOneResult;
+use crate::detect::Report;
+use crate::error::{Error, MinusOneResult, ParseError};
+use crate::profile::{RuleProfile, with_limits};
+use crate::query::QueryRule;
+use crate::rule::{RuleInfo, RuleSetBuilderType};
 use crate::tree::{EmptyStorage, HashMapStorage, Tree};
 use log::debug;
 use std::fmt::Debug;
@@ -23,13 +28,31 @@ pub trait DeobfuscationBackend {
         root: &mut Tree<HashMapStorage<Self::Language>>,
         ruleset: Vec<&str>,
     ) -> MinusOneResult<()>;
+
+    fn deobfuscate_tree_with_query_rules(
+        root: &mut Tree<HashMapStorage<Self::Language>>,
+        ctx: RuleSetBuilderType,
+        query_rules: &[QueryRule<Self::Language>],
+    ) -> MinusOneResult<()>;
+
     fn lint_tree<'a>(
         root: &Tree<'a, HashMapStorage<Self::Language>>,
         tab_chr: &str,
         keep_dead_code: bool,
     ) -> MinusOneResult<String>;
 
-    fn language_rules<'a>() -> Vec<&'a str>;
+    /// Same as `lint_tree`, with the byte values from the threshold
+    /// replaced by a reference to their artefact
+    fn lint_tree_with_artefacts<'a>(
+        root: &Tree<'a, HashMapStorage<Self::Language>>,
+        tab_chr: &str,
+        keep_dead_code: bool,
+        threshold: usize,
+    ) -> MinusOneResult<String>;
+
+    fn language_rules() -> Vec<RuleInfo>;
+
+    fn analyze(src: &str) -> MinusOneResult<Report>;
 }
 
 pub struct DeobfuscateEngine<'a, B: DeobfuscationBackend> {
@@ -42,13 +65,34 @@ impl<'a, B: DeobfuscationBackend> DeobfuscateEngine<'a, B> {
         B::remove_extra(src, keep_dead_code)
     }
 
+    /// Detect the obfuscation techniques used by a script, without deobfuscating it
+    pub fn analyze(src: &str) -> MinusOneResult<Report> {
+        B::analyze(src)
+    }
+
+    /// Build the tree of a script
+    ///
+    /// A partially invalid script is accepted as long
+    /// as the parser recognized a well-formed part in it
     pub fn from_source(src: &'a str) -> MinusOneResult<Self> {
+        let root = B::build_deob_tree(src)?;
+        if !root.is_processable() {
+            return Err(Error::unprocessable_source());
+        }
+
         Ok(Self {
-            root: B::build_deob_tree(src)?,
+            root,
             backend: PhantomData,
         })
     }
 
+    /// Regions of the source the parser failed on
+    ///
+    /// The linter keeps them as is in its output
+    pub fn parse_errors(&self) -> Vec<ParseError> {
+        self.root.parse_errors()
+    }
+
     pub fn debug(&self, custom_debug_view: Option<DebugView<B::Language>>)
     where
         B::Language: Debug,
@@ -73,6 +117,25 @@ impl<'a, B: DeobfuscationBackend> DeobfuscateEngine<'a, B> {
         B::lint_tree(&self.root, tab_chr, keep_dead_code)
     }
 
+    /// Lint, with the byte values from the threshold replaced by a
+    /// comment naming their artefact, see [`DeobfuscateEngine::artefacts`]
+    pub fn lint_with_artefacts(
+        &mut self,
+        tab_chr: &str,
+        keep_dead_code: bool,
+        threshold: usize,
+    ) -> MinusOneResult<String> {
+        B::lint_tree_with_artefacts(&self.root, tab_chr, keep_dead_code, threshold)
+    }
+
+    /// Byte values inferred by the deobfuscation from the threshold, classified by their content
+    pub fn artefacts(&self, threshold: usize) -> MinusOneResult<Vec<Artefact>>
+    where
+        B::Language: ArtefactLanguage,
+    {
+        collect_artefacts(&self.root, threshold)
+    }
+
     pub fn deobfuscate_with_custom_ruleset(&mut self, ruleset: Vec<&str>) -> MinusOneResult<()> {
         debug!(
             "Starting deobfuscation process with {} custom rules: {}",
@@ -91,7 +154,42 @@ impl<'a, B: DeobfuscationBackend> DeobfuscateEngine<'a, B> {
         B::deobfuscate_tree_without_custom_ruleset(&mut self.root, ruleset)
     }
 
-    pub fn language_rules() -> Vec<&'a str> {
+    /// Deobfuscate using the language rules, followed by additional query rules
+    /// The include/exclude filter applies to both of them
+    pub fn deobfuscate_with_query_rules(
+        &mut self,
+        ctx: RuleSetBuilderType,
+        query_rules: &[QueryRule<B::Language>],
+    ) -> MinusOneResult<()> {
+        debug!(
+            "Starting deobfuscation process with {} additional query rules",
+            query_rules.len()
+        );
+        B::deobfuscate_tree_with_query_rules(&mut self.root, ctx, query_rules)
+    }
+
+    /// Deobfuscate using the rules and the budgets of a profile,
+    /// with some rules or `@category` added and removed
+    pub fn deobfuscate_with_profile(
+        &mut self,
+        profile: RuleProfile,
+        include: &[&str],
+        exclude: &[&str],
+    ) -> MinusOneResult<()> {
+        debug!(
+            "Starting deobfuscation process with the {} profile",
+            profile
+        );
+        let _limits = with_limits(profile.limits());
+        B::deobfuscate_tree_with_query_rules(
+            &mut self.root,
+            profile.rule_set(&B::language_rules(), include, exclude),
+            &[],
+        )
+    }
+
+    /// Built-in rules of the language, with their metadata
+    pub fn language_rules() -> Vec<RuleInfo> {
         B::language_rules()
     }
     pub fn root(&self) -> &Tree<'a, HashMapStorage<B::Language>> {
@@ -1,3 +1,5 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
 use std::str::Utf8Error;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -5,12 +7,35 @@ pub enum MinusOneErrorKind {
     Parsing,
     InvalidChildIndex,
     InvalidParent,
-    InvalidProgram,
-    InvalidProgramIndex,
     NestedTransactions,
+    /// The source or an inferred buffer is not valid UTF-8
+    Encoding,
+    /// A compressed stream could not be inflated
+    Decompression,
+    /// A block cipher rejected its key, IV or padding
+    Decryption,
+    /// The tree-sitter grammar could not be loaded
+    Language,
     Unknown,
 }
 
+impl Display for MinusOneErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            MinusOneErrorKind::Parsing => "parsing",
+            MinusOneErrorKind::InvalidChildIndex => "invalid-child-index",
+            MinusOneErrorKind::InvalidParent => "invalid-parent",
+            MinusOneErrorKind::NestedTransactions => "nested-transactions",
+            MinusOneErrorKind::Encoding => "encoding",
+            MinusOneErrorKind::Decompression => "decompression",
+            MinusOneErrorKind::Decryption => "decryption",
+            MinusOneErrorKind::Language => "language",
+            MinusOneErrorKind::Unknown => "unknown",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 #[derive(Debug)]
 pub struct MinusOneError {
     /// Kind of error
@@ -46,11 +71,58 @@ impl MinusOneError {
     }
 }
 
+impl Display for MinusOneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for MinusOneError {}
+
+/// Where an error was raised while visiting the tree
+#[derive(Debug)]
+pub struct ErrorContext {
+    /// Name of the rule that was running
+    pub rule: Option<String>,
+    /// Kind of the node that was visited
+    pub node_kind: Option<String>,
+    /// Byte range of the node that was visited
+    pub range: Option<Range<usize>>,
+    /// The error itself
+    pub cause: Error,
+}
+
+impl Display for ErrorContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.rule {
+            Some(rule) => write!(f, "rule {} failed", rule)?,
+            None => write!(f, "failed")?,
+        }
+        if let Some(node_kind) = &self.node_kind {
+            write!(f, " on {}", node_kind)?;
+        }
+        if let Some(range) = &self.range {
+            write!(f, " at {}..{}", range.start, range.end)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
     /// MinusOne error
     MinusOneError(MinusOneError),
     Utf8Error(Utf8Error),
+    /// Error raised while reading a compressed stream
+    IoError(std::io::Error),
+    /// Invalid padding of data going through a block cipher
+    PaddingError(Box<dyn std::error::Error + Send + Sync>),
+    /// Key or IV of invalid length for the cipher
+    InvalidLength(cbc::cipher::InvalidLength),
+    /// Grammar incompatible with the tree-sitter runtime
+    LanguageError(tree_sitter::LanguageError),
+    /// An error located in the tree
+    Context(Box<ErrorContext>),
 }
 
 impl Error {
@@ -65,24 +137,17 @@ impl Error {
         ))
     }
 
-    pub fn invalid_program() -> Self {
-        Error::MinusOneError(MinusOneError::new(
-            MinusOneErrorKind::InvalidProgram,
-            "A valid program root node is excepted.",
-        ))
-    }
-
-    pub fn invalid_program_index(index: usize) -> Self {
+    pub fn invalid_parent() -> Self {
         Error::MinusOneError(MinusOneError::new(
-            MinusOneErrorKind::InvalidProgramIndex,
-            format!("The program is excepted to start at index 0. Found index {index}").as_str(),
+            MinusOneErrorKind::InvalidParent,
+            "A parent node is expected",
         ))
     }
 
-    pub fn invalid_parent() -> Self {
+    pub fn unprocessable_source() -> Self {
         Error::MinusOneError(MinusOneError::new(
-            MinusOneErrorKind::InvalidParent,
-            "A parent node is expected",
+            MinusOneErrorKind::Parsing,
+            "The parser did not recognize any valid construct in the source",
         ))
     }
 
@@ -92,6 +157,119 @@ impl Error {
             "Cannot start transaction: transaction already started",
         ))
     }
+
+    /// Return the kind of error, whatever the context it was raised in
+    ///
+    /// # Example
+    /// ```
+    /// use minusone::error::{Error, MinusOneErrorKind};
+    /// let error = Error::invalid_child().in_rule("Forward").at_node("pipeline", 4..12);
+    ///
+    /// assert_eq!(error.kind(), MinusOneErrorKind::InvalidChildIndex);
+    /// assert_eq!(error.rule(), Some("Forward"));
+    /// assert_eq!(error.node_kind(), Some("pipeline"));
+    /// assert_eq!(error.range(), Some(4..12));
+    /// assert_eq!(error.to_string(), "rule Forward failed on pipeline at 4..12");
+    /// ```
+    pub fn kind(&self) -> MinusOneErrorKind {
+        match self {
+            Error::MinusOneError(e) => e.kind(),
+            Error::Utf8Error(_) => MinusOneErrorKind::Encoding,
+            Error::IoError(_) => MinusOneErrorKind::Decompression,
+            Error::PaddingError(_) | Error::InvalidLength(_) => MinusOneErrorKind::Decryption,
+            Error::LanguageError(_) => MinusOneErrorKind::Language,
+            Error::Context(context) => context.cause.kind(),
+        }
+    }
+
+    /// Message of the underlying error, without its context
+    pub fn message(&self) -> String {
+        match self {
+            Error::Context(context) => context.cause.message(),
+            _ => self.to_string(),
+        }
+    }
+
+    fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::Context(context) => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Name of the rule the error was raised in
+    pub fn rule(&self) -> Option<&str> {
+        self.context()?.rule.as_deref()
+    }
+
+    /// Kind of the node the error was raised on
+    pub fn node_kind(&self) -> Option<&str> {
+        self.context()?.node_kind.as_deref()
+    }
+
+    /// Byte range of the node the error was raised on
+    pub fn range(&self) -> Option<Range<usize>> {
+        self.context()?.range.clone()
+    }
+
+    fn into_context(self) -> Box<ErrorContext> {
+        match self {
+            Error::Context(context) => context,
+            cause => Box::new(ErrorContext {
+                rule: None,
+                node_kind: None,
+                range: None,
+                cause,
+            }),
+        }
+    }
+
+    /// Attach the name of the rule that raised the error
+    ///
+    /// The innermost rule is kept when rule sets are nested
+    pub fn in_rule(self, rule: &str) -> Self {
+        let mut context = self.into_context();
+        context.rule.get_or_insert_with(|| rule.to_string());
+        Error::Context(context)
+    }
+
+    /// Attach the node the error was raised on
+    pub fn at_node(self, node_kind: &str, range: Range<usize>) -> Self {
+        let mut context = self.into_context();
+        if context.node_kind.is_none() {
+            context.node_kind = Some(node_kind.to_string());
+            context.range = Some(range);
+        }
+        Error::Context(context)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::MinusOneError(e) => write!(f, "{}", e),
+            Error::Utf8Error(_) => write!(f, "Invalid UTF-8 sequence"),
+            Error::IoError(_) => write!(f, "Unable to read the stream"),
+            Error::PaddingError(_) => write!(f, "Invalid block cipher padding"),
+            Error::InvalidLength(_) => write!(f, "Invalid key or IV length"),
+            Error::LanguageError(_) => write!(f, "Unable to load the grammar"),
+            Error::Context(context) => write!(f, "{}", context),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::MinusOneError(_) => None,
+            Error::Utf8Error(e) => Some(e),
+            Error::IoError(e) => Some(e),
+            Error::PaddingError(e) => Some(e.as_ref()),
+            Error::InvalidLength(e) => Some(e),
+            Error::LanguageError(e) => Some(e),
+            Error::Context(context) => Some(&context.cause),
+        }
+    }
 }
 
 impl From<Utf8Error> for Error {
@@ -100,4 +278,50 @@ impl From<Utf8Error> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        Error::IoError(e)
+    }
+}
+
+impl From<cbc::cipher::block_padding::Error> for Error {
+    fn from(e: cbc::cipher::block_padding::Error) -> Error {
+        Error::PaddingError(Box::new(e))
+    }
+}
+
+impl From<cbc::cipher::inout::PadError> for Error {
+    fn from(e: cbc::cipher::inout::PadError) -> Error {
+        Error::PaddingError(Box::new(e))
+    }
+}
+
+impl From<cbc::cipher::InvalidLength> for Error {
+    fn from(e: cbc::cipher::InvalidLength) -> Error {
+        Error::InvalidLength(e)
+    }
+}
+
+impl From<tree_sitter::LanguageError> for Error {
+    fn from(e: tree_sitter::LanguageError) -> Error {
+        Error::LanguageError(e)
+    }
+}
+
 pub type MinusOneResult<T> = Result<T, Error>;
+
+/// A region of the source the parser could not make sense of
+///
+/// Parsing goes on after such a region, so a script
+/// can carry several of them
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    /// Start offset of the region
+    pub start: usize,
+    /// End offset of the region
+    pub end: usize,
+    /// Kind of the node surrounding the region, none if the whole source is invalid
+    pub context: Option<String>,
+    /// Token expected by the parser, if the region is a missing one
+    pub missing: Option<String>,
+}
@@ -1,11 +1,14 @@
+use crate::detect::Report;
 use crate::engine::{CleanBackend, CleanEngine, DeobfuscateEngine, DeobfuscationBackend};
 use crate::error::MinusOneResult;
 use crate::js::post_process::*;
+use crate::js::r#static::analyze_javascript;
 use crate::js::strategy::JavaScriptStrategy;
 use crate::js::{
     JavaScript, JavaScriptRuleSet, build_javascript_tree_for_storage, remove_javascript_extra,
 };
-use crate::rule::RuleSetBuilderType;
+use crate::query::QueryRule;
+use crate::rule::{RuleInfo, RuleSetBuilderType};
 use crate::tree::{EmptyStorage, HashMapStorage, Tree};
 use log::{error, trace};
 
@@ -35,7 +38,7 @@ impl JavaScriptBackend {
         record_all: bool,
     ) -> MinusOneResult<(String, Vec<crate::js::trace::Step>)> {
         let mut steps = Vec::new();
-        let out = lint_impl(root, keep_dead_code, &mut |rule, current| {
+        let out = lint_impl(root, keep_dead_code, None, &mut |rule, current| {
             crate::js::trace::push_text_step(&mut steps, "post", rule, current, record_all);
         })?;
         Ok((out, steps))
@@ -234,9 +237,13 @@ pub fn clean_impl(
 fn lint_impl(
     root: &Tree<HashMapStorage<JavaScript>>,
     keep_dead_code: bool,
+    artefact_threshold: Option<usize>,
     on_step: &mut dyn FnMut(&str, &str),
 ) -> MinusOneResult<String> {
-    let mut linter = crate::js::linter::Linter::default();
+    let mut linter = match artefact_threshold {
+        Some(threshold) => crate::js::linter::Linter::default().set_artefact_threshold(threshold),
+        None => crate::js::linter::Linter::default(),
+    };
     root.apply(&mut linter)?;
     on_step("Linter", &linter.output);
 
@@ -296,16 +303,41 @@ impl DeobfuscationBackend for JavaScriptBackend {
         Ok(())
     }
 
+    fn deobfuscate_tree_with_query_rules(
+        root: &mut Tree<HashMapStorage<Self::Language>>,
+        ctx: RuleSetBuilderType,
+        query_rules: &[QueryRule<Self::Language>],
+    ) -> MinusOneResult<()> {
+        root.apply_mut_with_strategy(
+            &mut JavaScriptRuleSet::with_query_rules(ctx, query_rules),
+            JavaScriptStrategy,
+        )?;
+        Ok(())
+    }
+
     fn lint_tree<'a>(
         root: &Tree<'a, HashMapStorage<Self::Language>>,
         _tab_chr: &str,
         keep_dead_code: bool,
     ) -> MinusOneResult<String> {
-        lint_impl(root, keep_dead_code, &mut |_, _| {})
+        lint_impl(root, keep_dead_code, None, &mut |_, _| {})
+    }
+
+    fn lint_tree_with_artefacts<'a>(
+        root: &Tree<'a, HashMapStorage<Self::Language>>,
+        _tab_chr: &str,
+        keep_dead_code: bool,
+        threshold: usize,
+    ) -> MinusOneResult<String> {
+        lint_impl(root, keep_dead_code, Some(threshold), &mut |_, _| {})
+    }
+
+    fn language_rules() -> Vec<RuleInfo> {
+        JavaScriptRuleSet::rules()
     }
 
-    fn language_rules<'a>() -> Vec<&'a str> {
-        JavaScriptRuleSet::new(RuleSetBuilderType::WithoutRules(vec![])).names()
+    fn analyze(src: &str) -> MinusOneResult<Report> {
+        analyze_javascript(src)
     }
 }
 
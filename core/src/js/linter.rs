@@ -1,3 +1,4 @@
+use crate::artefact::{Artefact, ArtefactLanguage};
 use crate::error::MinusOneResult;
 use crate::js::JavaScript;
 use crate::rule::Rule;
@@ -7,25 +8,29 @@ use crate::tree::Node;
 struct RemoveCode {
     source: String,
     pub output: String,
+    // offset of the program in the source
+    base: usize,
     last_index: usize,
 }
 
 impl RemoveCode {
     pub fn start_program<T>(&mut self, root: &Node<T>) -> MinusOneResult<()> {
         self.source = root.text()?.to_string();
+        self.base = root.start_abs();
+        self.last_index = root.start_abs();
         Ok(())
     }
 
     pub fn end_program(&mut self) -> MinusOneResult<()> {
-        self.output += &self.source[self.last_index..];
+        self.output += &self.source[(self.last_index - self.base)..];
         Ok(())
     }
 
     pub fn remove_node<T>(&mut self, node: &Node<T>) -> MinusOneResult<()> {
-        while self.source.chars().nth(self.last_index) == Some('\n') {
+        while self.source.chars().nth(self.last_index - self.base) == Some('\n') {
             self.last_index += 1;
         }
-        self.output += &self.source[self.last_index..node.start_abs()];
+        self.output += &self.source[(self.last_index - self.base)..(node.start_abs() - self.base)];
         self.last_index = node.end_abs();
         Ok(())
     }
@@ -79,9 +84,18 @@ pub struct Linter {
     last_index: usize,
     started: bool,
     depth: usize,
+    // byte values from this size are replaced by a reference to their artefact
+    artefact_threshold: Option<usize>,
 }
 
 impl Linter {
+    /// Replace the byte values from this size by a comment
+    /// with the kind, the size and the SHA-256 of their artefact
+    pub fn set_artefact_threshold(mut self, threshold: usize) -> Self {
+        self.artefact_threshold = Some(threshold);
+        self
+    }
+
     fn is_function_like_kind(kind: &str) -> bool {
         matches!(
             kind,
@@ -114,6 +128,11 @@ impl Linter {
                 self.skip_until(node.end_abs());
                 return Ok(false);
             }
+            // keep what the parser failed on as is
+            "ERROR" => {
+                self.copy_until(node.end_abs());
+                return Ok(false);
+            }
             _ => (),
         }
 
@@ -138,6 +157,18 @@ impl Linter {
             }
 
             self.copy_until(node.start_abs());
+            if let Some(threshold) = self.artefact_threshold
+                && let Some(bytes) = data.artefact_bytes()
+                && bytes.len() >= threshold
+            {
+                let reference = Artefact::new(bytes, node.start_abs(), node.end_abs()).reference();
+                self.output += &match data {
+                    JavaScript::Buffer(_) => format!("Buffer.from([/* {} */])", reference),
+                    _ => format!("String.fromCharCode(/* {} */)", reference),
+                };
+                self.skip_until(node.end_abs());
+                return Ok(false);
+            }
             // Preserve parentheses for conditions in control-flow statements to keep the output as valid JavaScript
             if node.kind() == "parenthesized_expression"
                 && let Some(parent) = node.parent()
@@ -7,6 +7,7 @@ use crate::js::strategy::JavaScriptStrategy;
 use crate::js::subprogram::{build_and_reduce, enter_map_filter, take_seed_result, with_seed};
 use crate::js::utils::{get_positional_arguments, is_write_target, method_name};
 use crate::js::{JavaScript, JavaScriptRuleSet};
+use crate::profile::limits;
 use crate::rule::{RuleMut, RuleSetBuilderType};
 use crate::tree::{ControlFlow, Node, NodeMut};
 use log::{trace, warn};
@@ -433,8 +434,6 @@ impl<'a> RuleMut<'a> for ArrayMapFilter {
     }
 }
 
-const MAX_FOR_ITERATIONS: usize = 20_000;
-
 thread_local! {
     static FOR_LOOP_ENABLED: Cell<bool> = const { Cell::new(false) };
     static INSIDE_SIMULATED_FOR: Cell<bool> = const { Cell::new(false) };
@@ -709,7 +708,7 @@ fn run_for_iterations(
 ) -> Option<Vec<(String, JavaScript)>> {
     let program = format!("{body_src}\n{update_src};\n({condition_src})");
     let mut state = state;
-    for _ in 0..MAX_FOR_ITERATIONS {
+    for _ in 0..limits().for_iterations {
         let (new_state, condition) = run_seeded_program(&program, state)?;
         state = new_state;
 
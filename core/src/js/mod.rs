@@ -19,6 +19,7 @@ pub mod objects;
 pub mod post_process;
 pub mod regex;
 pub mod specials;
+pub mod r#static;
 pub mod step;
 pub mod strategy;
 pub mod string;
@@ -57,8 +58,10 @@ use self::string::*;
 use self::ternary::*;
 use self::r#typeof::*;
 use self::var::*;
+use crate::artefact::ArtefactLanguage;
 use crate::error::{Error, MinusOneResult};
-use crate::rule::{RuleMut, RuleSet, RuleSetBuilderType};
+use crate::query::{QueryLanguage, QueryRule, QueryValue};
+use crate::rule::{RuleCategory, RuleInfo, RuleMut, RuleSafety, RuleSet, RuleSetBuilderType};
 use crate::tree::{HashMapStorage, Storage, Tree};
 use indexmap::IndexMap;
 use tree_sitter_javascript::LANGUAGE as javascript_language;
@@ -190,6 +193,63 @@ impl PartialEq<JavaScript> for &JavaScript {
     }
 }
 
+impl ArtefactLanguage for JavaScript {
+    fn artefact_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Bytes(bytes) | Buffer(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+}
+
+impl QueryLanguage for JavaScript {
+    fn language() -> tree_sitter::Language {
+        javascript_language.into()
+    }
+
+    fn builtin_rules() -> Vec<RuleInfo> {
+        JavaScriptRuleSet::rules()
+    }
+
+    fn to_query_value(&self) -> Option<QueryValue> {
+        match self {
+            Raw(Num(n)) if n.fract() == 0.0 && n.abs() < 9007199254740992.0 => {
+                Some(QueryValue::Int(*n as i64))
+            }
+            Raw(Num(n)) => Some(QueryValue::Float(*n)),
+            Raw(Str(s)) => Some(QueryValue::Str(s.clone())),
+            Raw(Bool(b)) => Some(QueryValue::Bool(*b)),
+            NaN => Some(QueryValue::Float(f64::NAN)),
+            Null => Some(QueryValue::Null),
+            Array(values) => values
+                .iter()
+                .map(JavaScript::to_query_value)
+                .collect::<Option<Vec<QueryValue>>>()
+                .map(QueryValue::Array),
+            Bytes(bytes) | Buffer(bytes) => Some(QueryValue::Bytes(bytes.clone())),
+            _ => None,
+        }
+    }
+
+    fn from_query_value(value: QueryValue) -> Option<Self> {
+        match value {
+            QueryValue::Int(n) => Some(Raw(Num(n as f64))),
+            QueryValue::Float(n) if n.is_nan() => Some(NaN),
+            QueryValue::Float(n) => Some(Raw(Num(n))),
+            QueryValue::Str(s) => Some(Raw(Str(s))),
+            QueryValue::Bool(b) => Some(Raw(Bool(b))),
+            QueryValue::Null => Some(Null),
+            QueryValue::Array(values) => values
+                .into_iter()
+                .map(JavaScript::from_query_value)
+                .collect::<Option<Vec<JavaScript>>>()
+                .map(Array),
+            QueryValue::Bytes(bytes) => Some(Bytes(bytes)),
+            QueryValue::Type(_) => None,
+        }
+    }
+}
+
 pub struct JavaScriptRuleSet<'a> {
     ruleset: RuleSet<'a, JavaScript>,
 }
@@ -201,25 +261,43 @@ impl JavaScript {
 }
 
 macro_rules! impl_javascript_ruleset {
-    ( $($ty:ident),* ) => {
+    ( $( $ty:ident : $category:ident, $safety:ident, $default_enabled:literal, [ $( $kind:literal ),* ], $description:literal );* ) => {
         /// This is the rule set use to perform
         /// inferred type in JavaScript deobfuscation
         pub type JavaScriptDefaultRuleSet = ( $($ty,)* );
 
         impl<'a> JavaScriptRuleSet<'a> {
             pub fn new(ctx: RuleSetBuilderType) -> Self {
+                Self::with_query_rules(ctx, &[])
+            }
+
+            /// Build the rule set with additional query rules,
+            /// applied after the built-in ones and filtered the same way
+            pub fn with_query_rules(ctx: RuleSetBuilderType, query_rules: &'a [QueryRule<JavaScript>]) -> Self {
+                let mut rules: Vec<(&'a str, Box<dyn RuleMut<'a, Language = JavaScript> + 'a>)> = vec![
+                    $( (stringify!($ty), Box::new($ty::default())), )*
+                ];
+                rules.extend(query_rules.iter().map(|r| {
+                    (r.name(), Box::new(r) as Box<dyn RuleMut<'a, Language = JavaScript> + 'a>)
+                }));
+
                 Self {
-                    ruleset: RuleSet::new(
-                        vec![
-                            $( (stringify!($ty), Box::new($ty::default())), )*
-                        ],
-                        ctx
-                    )
+                    ruleset: RuleSet::new(rules, ctx.expand_categories(&Self::rules()))
                 }
             }
 
-            pub fn names(self) -> Vec<&'a str> {
-                vec![ $( stringify!($ty), )* ]
+            /// Built-in rules, in the order they are applied
+            pub fn rules() -> Vec<RuleInfo> {
+                vec![ $(
+                    RuleInfo {
+                        name: stringify!($ty),
+                        description: $description,
+                        category: RuleCategory::$category,
+                        node_kinds: &[ $( $kind ),* ],
+                        safety: RuleSafety::$safety,
+                        default_enabled: $default_enabled,
+                    },
+                )* ]
             }
 
         }
@@ -227,64 +305,64 @@ macro_rules! impl_javascript_ruleset {
 }
 
 impl_javascript_ruleset!(
-    ParseInt, // Parse integer literals (decimal, hex, octal, binary), parseInt/Number/BigInt/parseFloat calls
-    ParseBool, // Parse boolean literals (true, false)
-    ParseString, // Parse string literals (single and double quotes)
-    ParseRegex, // Parse regex literals and RegExp constructors
-    ParseFunction, // Parse function and arrow-function expressions as first-class values
-    ParseArray, // Parse arrays
-    ParseSpecials, // Parse specials (undefined, NaN, null)
-    ParseObject, // Parse objects
-    PosNeg,   // Infer unary - operations on integers
-    AddInt,   // Infer addition operations on integers
-    Substract, // Infer subtraction operations on any JavaScript value
-    MultDivMod, // Infer *, / and % operations on integers
-    PowInt,   // Infer ** operations on integers
-    ShiftInt, // Infer <<, >> and >>> operations on integers
-    BitwiseInt, // Infer &, |, ^ and ~ operations on integers
-    MathBuiltins, // Infer static Math.x(...) builtins
-    IsNaN,    // Infer global isNaN(...) calls
-    IsFinite, // Infer global isFinite(...) calls
-    ObjectField, // Track objects field assignments and access
-    NotBool,  // Infer unary ! operations on booleans
-    BoolAlgebra, // Infer boolean algebra operations (&&, ||)
-    AddBool,  // Infer boolean addition operations
-    CombineArrays, // Infer + operations on two arrays
-    ArrayConcat, // Infer Array.prototype.concat calls on literal arrays
-    StringBuiltins, // Shared dispatcher for string literal builtins (.at, etc.)
-    NumberBuiltins, // Shared dispatcher for string literal builtins (.toPrecision, etc.)
-    ArrayBuiltins, // Shared dispatcher for array literals builtins (.at, etc.)
-    ArrayMapFilter, // Infer deterministic array .map(...) and .filter(...) calls
-    ForLoop,  // Simulate deterministic for loops and propagate final variable state
-    IteratorBuiltins, // Shared dispatcher for iterators literals builtins (.next, etc.)
-    BracketCharAt, // Infer charAt calls on string literals and reduces them to single-character string literals using arrays indexes
-    CharCodeAt, // Infer charCodeAt calls on string literals and reduces them to integer literals using arrays indexes
-    FromCharCode, // Infer String.fromCharCode static calls on deterministic literal arguments
-    Forward,    // Forward inferred type in the most simple cases
-    ArrayPlusMinus, // Infer unary plus and minus on arrays
-    Concat,     // Infer string concatenation with + operator on string literals
-    RegexConcat, // Infer regex concatenation with + operator on string literals
-    ConcatFunction, // Infer function source concatenation with `+` and reduce them to single string literals
-    EncodeDecodeBuiltins, // infer encode/decode built-ins functions
-    StringRaw,      // Infer String.raw tagged template literals
-    TemplateString, // Infer template string literals with deterministic substitutions
-    GetArrayElement, // Get element at array index
-    AddSubSpecials, // Infer add and sub on Undefined and NaN
-    ToString,       // Infer toString calls
-    B64,            // Infer atob & btoa calls and reduce them to string literals
-    Ternary,        // Infer ternary operator with deterministic condition
-    BufferFrom,     // Infer deterministic Buffer.from(...) calls
-    BufferAlloc,    // Infer deterministic Buffer.alloc(...) calls
-    Var,            // Track variable assignments and propagate known values to usage sites
-    BufferIndex,    // Infer deterministic Buffer[index] reads/writes
-    BufferToString, // Infer Buffer.toString(...) calls
-    RegexExec,      // Infer deterministic regex test/exec calls
-    FnCall,         // Resolve predictable function calls to their return values
-    JsFuckLevelNine, // Resolve the JSFuck level-9 Function("return '\uXXXX'")() universal builder
-    StrictEq,       // Infer strict equality === and !==
-    LooseEq,        // Infer strict equality == and !=
-    CmpOrd,         // Infer comparison operators <, >, <= and >=
-    Typeof          // Infer typeof calls
+    ParseInt: Parsing, Pure, true, ["number", "identifier", "call_expression"], "Parse integer literals (decimal, hex, octal, binary), parseInt/Number/BigInt/parseFloat calls";
+    ParseBool: Parsing, Pure, true, ["true", "false"], "Parse boolean literals (true, false)";
+    ParseString: Parsing, Pure, true, ["string", "call_expression", "new_expression"], "Parse string literals (single and double quotes)";
+    ParseRegex: Regex, Pure, true, ["regex", "call_expression", "new_expression"], "Parse regex literals and RegExp constructors";
+    ParseFunction: Functions, Pure, true, ["function_expression", "arrow_function", "generator_function", "function"], "Parse function and arrow-function expressions as first-class values";
+    ParseArray: Arrays, Pure, true, ["array", "call_expression", "new_expression"], "Parse arrays";
+    ParseSpecials: Parsing, Pure, true, ["undefined", "identifier", "null"], "Parse specials (undefined, NaN, null)";
+    ParseObject: Objects, Pure, true, ["object"], "Parse objects";
+    PosNeg: Arithmetic, Pure, true, ["unary_expression"], "Infer unary - operations on integers";
+    AddInt: Arithmetic, Pure, true, ["binary_expression"], "Infer addition operations on integers";
+    Substract: Arithmetic, Pure, true, ["binary_expression"], "Infer subtraction operations on any JavaScript value";
+    MultDivMod: Arithmetic, Pure, true, ["binary_expression"], "Infer *, / and % operations on integers";
+    PowInt: Arithmetic, Pure, true, ["binary_expression"], "Infer ** operations on integers";
+    ShiftInt: Arithmetic, Pure, true, ["binary_expression"], "Infer <<, >> and >>> operations on integers";
+    BitwiseInt: Arithmetic, Pure, true, ["binary_expression", "unary_expression"], "Infer &, |, ^ and ~ operations on integers";
+    MathBuiltins: Arithmetic, Pure, true, ["call_expression"], "Infer static Math.x(...) builtins";
+    IsNaN: Arithmetic, Pure, true, ["call_expression"], "Infer global isNaN(...) calls";
+    IsFinite: Arithmetic, Pure, true, ["call_expression"], "Infer global isFinite(...) calls";
+    ObjectField: Objects, Pure, true, ["program", "function_declaration", "function", "arrow_function", "method_definition", "generator_function_declaration", "generator_function", "statement_block"], "Track objects field assignments and access";
+    NotBool: Logic, Pure, true, ["unary_expression"], "Infer unary ! operations on booleans";
+    BoolAlgebra: Logic, Pure, true, ["binary_expression"], "Infer boolean algebra operations (&&, ||)";
+    AddBool: Arithmetic, Pure, true, ["binary_expression"], "Infer boolean addition operations";
+    CombineArrays: Arrays, Pure, true, ["binary_expression"], "Infer + operations on two arrays";
+    ArrayConcat: Arrays, Pure, true, ["call_expression"], "Infer Array.prototype.concat calls on literal arrays";
+    StringBuiltins: Strings, Pure, true, ["call_expression"], "Shared dispatcher for string literal builtins (.at, etc.)";
+    NumberBuiltins: Arithmetic, Pure, true, ["call_expression"], "Shared dispatcher for number literal builtins (.toPrecision, etc.)";
+    ArrayBuiltins: Arrays, Pure, true, ["call_expression", "binary_expression"], "Shared dispatcher for array literals builtins (.at, etc.)";
+    ArrayMapFilter: Arrays, Evaluates, true, ["call_expression"], "Infer deterministic array .map(...) and .filter(...) calls";
+    ForLoop: ControlFlow, Evaluates, true, ["program", "for_statement"], "Simulate deterministic for loops and propagate final variable state";
+    IteratorBuiltins: Arrays, Pure, true, ["call_expression"], "Shared dispatcher for iterators literals builtins (.next, etc.)";
+    BracketCharAt: Strings, Pure, true, ["subscript_expression"], "Infer charAt calls on string literals and reduces them to single-character string literals using arrays indexes";
+    CharCodeAt: Strings, Pure, true, ["call_expression"], "Infer charCodeAt calls on string literals and reduces them to integer literals using arrays indexes";
+    FromCharCode: Strings, Pure, true, ["call_expression"], "Infer String.fromCharCode static calls on deterministic literal arguments";
+    Forward: Parsing, Pure, true, ["parenthesized_expression", "sequence_expression"], "Forward inferred type in the most simple cases";
+    ArrayPlusMinus: Arrays, Pure, true, ["unary_expression"], "Infer unary plus and minus on arrays";
+    Concat: Strings, Pure, true, ["binary_expression"], "Infer string concatenation with + operator on string literals";
+    RegexConcat: Regex, Pure, true, ["binary_expression"], "Infer regex concatenation with + operator on string literals";
+    ConcatFunction: Functions, Pure, true, ["binary_expression"], "Infer function source concatenation with `+` and reduce them to single string literals";
+    EncodeDecodeBuiltins: Encoding, Pure, true, ["call_expression"], "Infer encode/decode built-ins functions";
+    StringRaw: Strings, Pure, true, ["call_expression"], "Infer String.raw tagged template literals";
+    TemplateString: Strings, Pure, true, ["template_string"], "Infer template string literals with deterministic substitutions";
+    GetArrayElement: Arrays, Pure, true, ["subscript_expression"], "Get element at array index";
+    AddSubSpecials: Arithmetic, Pure, true, ["binary_expression"], "Infer add and sub on Undefined and NaN";
+    ToString: Strings, Pure, true, ["call_expression"], "Infer toString calls";
+    B64: Encoding, Pure, true, ["call_expression"], "Infer atob & btoa calls and reduce them to string literals";
+    Ternary: ControlFlow, Pure, true, ["ternary_expression"], "Infer ternary operator with deterministic condition";
+    BufferFrom: Encoding, Pure, true, ["call_expression"], "Infer deterministic Buffer.from(...) calls";
+    BufferAlloc: Encoding, Pure, true, ["call_expression"], "Infer deterministic Buffer.alloc(...) calls";
+    Var: Variables, Pure, true, ["program", "function_declaration", "statement_block", "for_statement", "for_in_statement", "variable_declarator", "assignment_expression", "update_expression", "call_expression", "identifier", "subscript_expression", "member_expression"], "Track variable assignments and propagate known values to usage sites";
+    BufferIndex: Encoding, Pure, true, ["program", "variable_declarator", "assignment_expression", "identifier", "subscript_expression"], "Infer deterministic Buffer[index] reads/writes";
+    BufferToString: Encoding, Pure, true, ["call_expression"], "Infer Buffer.toString(...) calls";
+    RegexExec: Regex, Pure, true, ["call_expression"], "Infer deterministic regex test/exec calls";
+    FnCall: Functions, Evaluates, true, ["program", "subscript_expression", "function", "function_expression", "arrow_function", "generator_function", "variable_declarator", "function_declaration", "assignment_expression", "call_expression"], "Resolve predictable function calls to their return values";
+    JsFuckLevelNine: Functions, Evaluates, true, ["call_expression"], "Resolve the JSFuck level-9 Function(\"return '\\uXXXX'\")() universal builder";
+    StrictEq: Logic, Pure, true, ["binary_expression"], "Infer strict equality === and !==";
+    LooseEq: Logic, Pure, true, ["binary_expression"], "Infer loose equality == and !=";
+    CmpOrd: Logic, Pure, true, ["binary_expression"], "Infer comparison operators <, >, <= and >=";
+    Typeof: Objects, Pure, true, ["unary_expression"], "Infer typeof calls"
 );
 
 impl<'a> RuleMut<'a> for JavaScriptRuleSet<'a> {
@@ -338,9 +416,7 @@ impl<'a> JavaScriptRuleSet<'a> {
 
 pub fn remove_javascript_extra(source: &str) -> MinusOneResult<String> {
     let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&javascript_language.into())
-        .expect("Error loading javascript grammar");
+    parser.set_language(&javascript_language.into())?;
 
     // Trim to assert program is at the beginning
     let source = source.trim();
@@ -349,12 +425,14 @@ pub fn remove_javascript_extra(source: &str) -> MinusOneResult<String> {
     let tree_sitter_remove_extra = parser.parse(source, None).unwrap();
     let root = Tree::<HashMapStorage<JavaScript>>::new(source.as_bytes(), tree_sitter_remove_extra);
 
-    let root_node = root.root().or(Err(Error::invalid_program()))?;
-    if root_node.kind() != "program" {
-        return Err(Error::invalid_program());
+    if !root.is_processable() {
+        return Err(Error::unprocessable_source());
     }
-    if root_node.start_abs() != 0 {
-        return Err(Error::invalid_program_index(root_node.start_abs()));
+
+    // The whole source is an error region, keep it as is
+    let root_node = root.root()?;
+    if root_node.kind() != "program" {
+        return Ok(source.to_string());
     }
 
     let mut source_without_extra = RemoveComment::default();
@@ -370,9 +448,7 @@ pub fn build_javascript_tree_for_storage<T: Storage + Default>(
     source: &str,
 ) -> MinusOneResult<Tree<'_, T>> {
     let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&javascript_language.into())
-        .expect("Error loading javascript grammar");
+    parser.set_language(&javascript_language.into())?;
 
     let tree_sitter = parser.parse(source, None).unwrap();
     Ok(Tree::<T>::new(source.as_bytes(), tree_sitter))
@@ -0,0 +1,834 @@
+use crate::detect::{Detection, Pattern, Report, Technique, looks_like_base64, push_outermost};
+use crate::error::{Error, MinusOneResult};
+use crate::init::Init;
+use crate::js::build_javascript_tree_for_storage;
+use crate::rule::RuleMut;
+use crate::tree::{ControlFlow, HashMapStorage, Node, NodeMut};
+use log::trace;
+use std::collections::{HashMap, HashSet};
+
+/// Inferred component of the static analysis
+/// A node is static when its value doesn't depend on the runtime
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum JavaScriptDetect {
+    Static(bool),
+}
+
+impl JavaScriptDetect {
+    fn is_static(&self) -> bool {
+        match self {
+            JavaScriptDetect::Static(value) => *value,
+        }
+    }
+}
+
+/// Children of a list node, without the punctuation
+fn elements<'a>(node: &Node<'a, JavaScriptDetect>) -> Vec<Node<'a, JavaScriptDetect>> {
+    node.iter()
+        .filter(|child| !matches!(child.kind(), "[" | "]" | "(" | ")" | "," | "comment"))
+        .collect()
+}
+
+fn arguments<'a>(call: &Node<'a, JavaScriptDetect>) -> Vec<Node<'a, JavaScriptDetect>> {
+    call.named_child("arguments")
+        .map(|arguments| elements(&arguments))
+        .unwrap_or_default()
+}
+
+/// Content of a string literal, without the quotes
+fn string_content(node: &Node<JavaScriptDetect>) -> MinusOneResult<Option<String>> {
+    if node.kind() != "string" {
+        return Ok(None);
+    }
+    let text = node.text()?;
+    Ok(Some(text[1..text.len() - 1].to_string()))
+}
+
+/// Name of the property reached by a callee : a.b or a['b']
+fn property_name(callee: &Node<JavaScriptDetect>) -> MinusOneResult<Option<String>> {
+    match callee.kind() {
+        "member_expression" => Ok(callee
+            .named_child("property")
+            .map(|property| property.text().map(str::to_string))
+            .transpose()?),
+        "subscript_expression" => match callee.named_child("index") {
+            Some(index) => string_content(&index),
+            None => Ok(None),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// Flatten a chain of binary operations using the same operator
+fn operands<'a>(
+    node: Node<'a, JavaScriptDetect>,
+    operator: &str,
+    result: &mut Vec<Node<'a, JavaScriptDetect>>,
+) -> MinusOneResult<()> {
+    if node.kind() == "parenthesized_expression" && node.child_count() == 3 {
+        operands(
+            node.child(1).ok_or(Error::invalid_child())?,
+            operator,
+            result,
+        )?;
+    } else if node.kind() == "binary_expression"
+        && node
+            .named_child("operator")
+            .ok_or(Error::invalid_child())?
+            .text()?
+            == operator
+    {
+        operands(
+            node.named_child("left").ok_or(Error::invalid_child())?,
+            operator,
+            result,
+        )?;
+        operands(
+            node.named_child("right").ok_or(Error::invalid_child())?,
+            operator,
+            result,
+        )?;
+    } else {
+        result.push(node);
+    }
+    Ok(())
+}
+
+/// This rule will flag static expressions
+/// Those expressions can be computed without running the script
+///
+/// # Example
+/// ```
+/// use minusone::js::build_javascript_tree_for_storage;
+/// use minusone::js::r#static::{JavaScriptDetect, Static};
+/// use minusone::tree::HashMapStorage;
+///
+/// let mut tree = build_javascript_tree_for_storage::<HashMapStorage<JavaScriptDetect>>("'a'+'b'").unwrap();
+/// tree.apply_mut(&mut Static::default()).unwrap();
+///
+/// let binary_expression = tree.root().unwrap().child(0).unwrap().child(0).unwrap();
+/// assert_eq!(binary_expression.data(), Some(&JavaScriptDetect::Static(true)));
+/// ```
+#[derive(Default)]
+pub struct Static;
+
+impl<'a> RuleMut<'a> for Static {
+    type Language = JavaScriptDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        match view.kind() {
+            "string" | "number" | "true" | "false" | "null" => {
+                trace!("Static (L): Setting node with static: true");
+                node.set(JavaScriptDetect::Static(true))
+            }
+            "template_string" => {
+                let data = view
+                    .iter()
+                    .all(|child| child.kind() != "template_substitution");
+                trace!("Static (L): Setting node with static: {}", data);
+                node.set(JavaScriptDetect::Static(data))
+            }
+            "parenthesized_expression" => {
+                if view.child_count() == 3
+                    && let Some(data) = view.child(1).ok_or(Error::invalid_child())?.data()
+                {
+                    trace!("Static (L): Setting node with static: {}", data.is_static());
+                    node.set(JavaScriptDetect::Static(data.is_static()))
+                }
+            }
+            "unary_expression" => {
+                if let Some(data) = view
+                    .named_child("argument")
+                    .ok_or(Error::invalid_child())?
+                    .data()
+                {
+                    trace!("Static (L): Setting node with static: {}", data.is_static());
+                    node.set(JavaScriptDetect::Static(data.is_static()))
+                }
+            }
+            "binary_expression" => {
+                if let (Some(left), Some(right)) = (
+                    view.named_child("left")
+                        .ok_or(Error::invalid_child())?
+                        .data(),
+                    view.named_child("right")
+                        .ok_or(Error::invalid_child())?
+                        .data(),
+                ) {
+                    let data = left.is_static() && right.is_static();
+                    trace!("Static (L): Setting node with static: {}", data);
+                    node.set(JavaScriptDetect::Static(data))
+                }
+            }
+            "array" => {
+                let data = elements(&view)
+                    .iter()
+                    .all(|element| element.data().map(JavaScriptDetect::is_static) == Some(true));
+                trace!("Static (L): Setting node with static: {}", data);
+                node.set(JavaScriptDetect::Static(data))
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+/// Static string split into at least two concatenated parts
+#[derive(Default)]
+pub struct StaticConcat {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for StaticConcat {
+    type Language = JavaScriptDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() == "binary_expression"
+            && let Some(JavaScriptDetect::Static(true)) = view.data()
+        {
+            let mut parts = vec![];
+            operands(node.view(), "+", &mut parts)?;
+            let strings = parts
+                .iter()
+                .filter(|part| matches!(part.kind(), "string" | "template_string"))
+                .count();
+            if strings >= 2 {
+                push_outermost(
+                    &mut self.detected_nodes,
+                    Pattern::new(view.start_abs(), view.end_abs()),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Detection for StaticConcat {
+    fn technique(&self) -> Technique {
+        Technique::StringConcat
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// String.fromCharCode and String.fromCodePoint calls
+#[derive(Default)]
+pub struct CharCode {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for CharCode {
+    type Language = JavaScriptDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() == "call_expression"
+            && let Some(callee) = view.named_child("function")
+            && matches!(
+                property_name(&callee)?.as_deref(),
+                Some("fromCharCode" | "fromCodePoint")
+            )
+        {
+            push_outermost(
+                &mut self.detected_nodes,
+                Pattern::new(view.start_abs(), view.end_abs()),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Detection for CharCode {
+    fn technique(&self) -> Technique {
+        Technique::CharCode
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// atob and Buffer.from(.., 'base64') calls, and long base64 strings
+#[derive(Default)]
+pub struct Base64Layer {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for Base64Layer {
+    type Language = JavaScriptDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        let detected = match view.kind() {
+            "call_expression" => match view.named_child("function") {
+                Some(callee) if callee.kind() == "identifier" => callee.text()? == "atob",
+                Some(callee) => {
+                    property_name(&callee)?.as_deref() == Some("from")
+                        && match arguments(&view).get(1) {
+                            Some(encoding) => {
+                                string_content(encoding)?.as_deref() == Some("base64")
+                            }
+                            None => false,
+                        }
+                }
+                None => false,
+            },
+            "string" => string_content(&view)?.is_some_and(|content| looks_like_base64(&content)),
+            _ => false,
+        };
+
+        if detected {
+            self.detected_nodes
+                .push(Pattern::new(view.start_abs(), view.end_abs()));
+        }
+        Ok(())
+    }
+}
+
+impl Detection for Base64Layer {
+    fn technique(&self) -> Technique {
+        Technique::Base64
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// crypto.createDecipheriv and CryptoJS decrypt calls
+#[derive(Default)]
+pub struct EncryptionLayer {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for EncryptionLayer {
+    type Language = JavaScriptDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() == "call_expression"
+            && let Some(callee) = view.named_child("function")
+        {
+            let detected = match property_name(&callee)?.as_deref() {
+                Some("createDecipheriv" | "createDecipher") => true,
+                Some("decrypt") => callee.named_child("object").is_some_and(|object| {
+                    object.text().is_ok_and(|text| {
+                        ["AES", "DES", "TripleDES", "RC4", "Rabbit"]
+                            .iter()
+                            .any(|algorithm| text.ends_with(algorithm))
+                    })
+                }),
+                _ => false,
+            };
+
+            if detected {
+                self.detected_nodes
+                    .push(Pattern::new(view.start_abs(), view.end_abs()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Detection for EncryptionLayer {
+    fn technique(&self) -> Technique {
+        Technique::Encryption
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// zlib and pako decompression calls, and DecompressionStream objects
+#[derive(Default)]
+pub struct CompressionLayer {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for CompressionLayer {
+    type Language = JavaScriptDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        let detected = match view.kind() {
+            "call_expression" => match view.named_child("function") {
+                Some(callee) => matches!(
+                    property_name(&callee)?.as_deref(),
+                    Some(
+                        "inflate"
+                            | "inflateSync"
+                            | "inflateRaw"
+                            | "inflateRawSync"
+                            | "gunzip"
+                            | "gunzipSync"
+                            | "ungzip"
+                            | "unzip"
+                            | "unzipSync"
+                            | "brotliDecompress"
+                            | "brotliDecompressSync"
+                    )
+                ),
+                None => false,
+            },
+            "new_expression" => match view.named_child("constructor") {
+                Some(constructor) => constructor.text()? == "DecompressionStream",
+                None => false,
+            },
+            _ => false,
+        };
+
+        if detected {
+            self.detected_nodes
+                .push(Pattern::new(view.start_abs(), view.end_abs()));
+        }
+        Ok(())
+    }
+}
+
+impl Detection for CompressionLayer {
+    fn technique(&self) -> Technique {
+        Technique::Compression
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// eval, Function constructor, and timers called with a string
+#[derive(Default)]
+pub struct DynamicInvocation {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for DynamicInvocation {
+    type Language = JavaScriptDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        let callee = match view.kind() {
+            "call_expression" => view.named_child("function"),
+            "new_expression" => view.named_child("constructor"),
+            _ => None,
+        };
+
+        if let Some(callee) = callee
+            && callee.kind() == "identifier"
+        {
+            let detected = match callee.text()? {
+                "eval" | "Function" => true,
+                "setTimeout" | "setInterval" => arguments(&view)
+                    .first()
+                    .is_some_and(|handler| handler.kind() == "string"),
+                _ => false,
+            };
+
+            if detected {
+                push_outermost(
+                    &mut self.detected_nodes,
+                    Pattern::new(view.start_abs(), view.end_abs()),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Detection for DynamicInvocation {
+    fn technique(&self) -> Technique {
+        Technique::DynamicInvocation
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// Expression only made of []()!+ : (![]+[])[+[]]
+#[derive(Default)]
+pub struct JsFuck {
+    symbols_only: HashSet<usize>,
+    detected_nodes: Vec<Pattern>,
+}
+
+impl JsFuck {
+    /// Shorter expressions are too common in regular code to be meaningful
+    const MIN_LENGTH: usize = 32;
+}
+
+impl<'a> RuleMut<'a> for JsFuck {
+    type Language = JavaScriptDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        let symbols_only = if view.child_count() == 0 {
+            view.text()?.chars().all(|c| "[]()!+".contains(c))
+        } else {
+            view.iter()
+                .all(|child| self.symbols_only.contains(&child.id()))
+        };
+
+        if symbols_only {
+            self.symbols_only.insert(view.id());
+            if view.end_abs() - view.start_abs() >= Self::MIN_LENGTH {
+                push_outermost(
+                    &mut self.detected_nodes,
+                    Pattern::new(view.start_abs(), view.end_abs()),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Detection for JsFuck {
+    fn technique(&self) -> Technique {
+        Technique::JsFuck
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// Array of at least 5 strings assigned to a variable,
+/// which is then indexed or returned by a function
+#[derive(Default)]
+pub struct StringArray {
+    declared: HashMap<String, Pattern>,
+    used: HashSet<String>,
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for StringArray {
+    type Language = JavaScriptDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        match view.kind() {
+            "variable_declarator" => {
+                if let (Some(name), Some(value)) =
+                    (view.named_child("name"), view.named_child("value"))
+                    && value.kind() == "array"
+                {
+                    let items = elements(&value);
+                    if items.len() >= 5 && items.iter().all(|item| item.kind() == "string") {
+                        self.declared.insert(
+                            name.text()?.to_string(),
+                            Pattern::new(view.start_abs(), view.end_abs()),
+                        );
+                    }
+                }
+            }
+            "subscript_expression" => {
+                if let Some(object) = view.named_child("object")
+                    && object.kind() == "identifier"
+                {
+                    self.used.insert(object.text()?.to_string());
+                }
+            }
+            "return_statement" => {
+                if let Some(value) = view.child(1)
+                    && value.kind() == "identifier"
+                {
+                    self.used.insert(value.text()?.to_string());
+                }
+            }
+            // the whole script was visited
+            "program" => {
+                for (name, pattern) in &self.declared {
+                    if self.used.contains(name) {
+                        self.detected_nodes.push(pattern.clone());
+                    }
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+impl Detection for StringArray {
+    fn technique(&self) -> Technique {
+        Technique::StringArray
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// Declaration of generated identifiers : var _0x3f2a1b
+#[derive(Default)]
+pub struct HexIdentifier {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl HexIdentifier {
+    fn is_generated(name: &str) -> bool {
+        name.strip_prefix("_0x").is_some_and(|digits| {
+            digits.len() >= 4 && digits.chars().all(|c| c.is_ascii_hexdigit())
+        })
+    }
+}
+
+impl<'a> RuleMut<'a> for HexIdentifier {
+    type Language = JavaScriptDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() == "identifier"
+            && Self::is_generated(view.text()?)
+            && let Some(parent) = view.parent()
+            && match parent.kind() {
+                "variable_declarator" | "function_declaration" => {
+                    parent.named_child("name").map(|name| name.id()) == Some(view.id())
+                }
+                "formal_parameters" => true,
+                _ => false,
+            }
+        {
+            self.detected_nodes
+                .push(Pattern::new(view.start_abs(), view.end_abs()));
+        }
+        Ok(())
+    }
+}
+
+impl Detection for HexIdentifier {
+    fn technique(&self) -> Technique {
+        Technique::HexIdentifier
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// String with printable characters written as \x or \u escapes : '\x68\x69'
+#[derive(Default)]
+pub struct EscapedString {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl EscapedString {
+    fn is_printable_escape(escape: &str) -> bool {
+        let digits = escape
+            .strip_prefix("\\x")
+            .or_else(|| escape.strip_prefix("\\u"))
+            .map(|digits| digits.trim_matches(['{', '}']));
+
+        digits
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .is_some_and(|code| (0x20..0x7f).contains(&code))
+    }
+}
+
+impl<'a> RuleMut<'a> for EscapedString {
+    type Language = JavaScriptDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() == "string" {
+            let mut escapes = 0;
+            for child in view.iter() {
+                if child.kind() == "escape_sequence" && Self::is_printable_escape(child.text()?) {
+                    escapes += 1;
+                }
+            }
+
+            if escapes >= 2 {
+                self.detected_nodes
+                    .push(Pattern::new(view.start_abs(), view.end_abs()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Detection for EscapedString {
+    fn technique(&self) -> Technique {
+        Technique::EscapedString
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// The static analysis rule set
+/// Static has to be first, as other rules use the static flag it sets
+pub type JavaScriptDetectRuleSet = (
+    Static,
+    StaticConcat,
+    CharCode,
+    Base64Layer,
+    EncryptionLayer,
+    CompressionLayer,
+    DynamicInvocation,
+    JsFuck,
+    StringArray,
+    HexIdentifier,
+    EscapedString,
+);
+
+/// Run the static analysis on a JavaScript script
+/// The source is parsed again and never modified
+///
+/// # Example
+/// ```
+/// use minusone::detect::Technique;
+/// use minusone::js::r#static::analyze_javascript;
+///
+/// let report = analyze_javascript("var a = 'con' + 'sole';").unwrap();
+/// assert_eq!(report.techniques(), vec![(Technique::StringConcat, 1)]);
+/// assert_eq!(report.findings[0].start, 8);
+/// assert_eq!(report.findings[0].end, 22);
+/// ```
+pub fn analyze_javascript(source: &str) -> MinusOneResult<Report> {
+    let mut tree = build_javascript_tree_for_storage::<HashMapStorage<JavaScriptDetect>>(source)?;
+    let mut rules = JavaScriptDetectRuleSet::init();
+    tree.apply_mut(&mut rules)?;
+
+    Ok(Report::new(&[
+        &rules.1, &rules.2, &rules.3, &rules.4, &rules.5, &rules.6, &rules.7, &rules.8, &rules.9,
+        &rules.10,
+    ]))
+}
@@ -1,62 +1,11 @@
+use crate::depth::{DepthCounter, DepthGuard};
 use crate::js::strategy::JavaScriptStrategy;
 use crate::js::{JavaScript, JavaScriptRuleSet, build_javascript_tree};
+use crate::profile::limits;
 use crate::rule::RuleSetBuilderType;
 use crate::tree::{HashMapStorage, Tree};
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::thread::LocalKey;
-
-pub struct DepthCounter {
-    key: &'static LocalKey<Cell<usize>>,
-    max: usize,
-    label: &'static str,
-}
-
-impl DepthCounter {
-    pub const fn new(key: &'static LocalKey<Cell<usize>>, max: usize, label: &'static str) -> Self {
-        Self { key, max, label }
-    }
-
-    // returns `None` once the cap is reached
-    pub fn enter(&self) -> Option<DepthGuard> {
-        let depth = self.key.with(|c| c.get());
-        if depth >= self.max {
-            log::trace!(
-                "{}: depth {} reached the cap, refusing to recurse",
-                self.label,
-                depth
-            );
-            return None;
-        }
-        self.key.with(|c| c.set(depth + 1));
-        Some(DepthGuard { key: self.key })
-    }
-
-    pub fn depth(&self) -> usize {
-        self.key.with(|c| c.get())
-    }
-
-    pub fn max(&self) -> usize {
-        self.max
-    }
-}
-
-pub struct DepthGuard {
-    key: &'static LocalKey<Cell<usize>>,
-}
-
-impl Drop for DepthGuard {
-    fn drop(&mut self) {
-        self.key.with(|c| c.set(c.get().saturating_sub(1)));
-    }
-}
-
-/// Nested sub-pipelines allowed for a single `FnCall` resolution.
-pub const MAX_FNCALL_DEPTH: usize = 2;
-/// Nested `.map()` / `.filter()` callbacks allowed.
-pub const MAX_MAP_FILTER_DEPTH: usize = 4;
-/// Nested `for` / `for..in` simulations allowed.
-pub const MAX_FOR_DEPTH: usize = 3;
 
 thread_local! {
     static FNCALL_DEPTH: Cell<usize> = const { Cell::new(0) };
@@ -65,15 +14,19 @@ thread_local! {
 }
 
 fn fncall_counter() -> DepthCounter {
-    DepthCounter::new(&FNCALL_DEPTH, MAX_FNCALL_DEPTH, "FnCall")
+    DepthCounter::new(&FNCALL_DEPTH, limits().fncall_depth, "FnCall")
 }
 
 fn map_filter_counter() -> DepthCounter {
-    DepthCounter::new(&MAP_FILTER_DEPTH, MAX_MAP_FILTER_DEPTH, "ArrayMapFilter")
+    DepthCounter::new(
+        &MAP_FILTER_DEPTH,
+        limits().map_filter_depth,
+        "ArrayMapFilter",
+    )
 }
 
 fn for_counter() -> DepthCounter {
-    DepthCounter::new(&FOR_DEPTH, MAX_FOR_DEPTH, "ForLoop")
+    DepthCounter::new(&FOR_DEPTH, limits().for_depth, "ForLoop")
 }
 
 pub fn enter_fncall() -> Option<DepthGuard> {
@@ -153,30 +106,6 @@ mod tests {
     use super::*;
     use crate::js::Value::Num;
 
-    thread_local! {
-        static TEST_DEPTH: Cell<usize> = const { Cell::new(0) };
-    }
-
-    fn test_counter() -> DepthCounter {
-        DepthCounter::new(&TEST_DEPTH, 2, "test")
-    }
-
-    #[test]
-    fn test_depth_guard_brackets_and_caps() {
-        let counter = test_counter();
-        assert_eq!(counter.depth(), 0);
-
-        let g1 = counter.enter().unwrap();
-        let g2 = counter.enter().unwrap();
-        assert_eq!(counter.depth(), 2);
-        assert!(counter.enter().is_none());
-
-        drop(g2);
-        assert!(counter.enter().is_some());
-        drop(g1);
-        assert_eq!(counter.depth(), 0);
-    }
-
     #[test]
     fn test_seed_is_restored_on_nested_runs() {
         assert!(!is_seed_active());
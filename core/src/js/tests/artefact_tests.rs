@@ -0,0 +1,27 @@
+#[cfg(test)]
+mod tests_js_artefact {
+    use crate::artefact::ArtefactKind;
+    use crate::engine::DeobfuscateEngine;
+    use crate::js::backend::JavaScriptBackend;
+
+    // 30 times the x64 Metasploit prologue: cld; and rsp, -16; call
+    const SHELLCODE: &str = "/EiD5PDowAAAAPxIg+Tw6MAAAAD8SIPk8OjAAAAA/EiD5PDowAAAAPxIg+Tw6MAAAAD8SIPk8OjAAAAA/EiD5PDowAAAAPxIg+Tw6MAAAAD8SIPk8OjAAAAA/EiD5PDowAAAAPxIg+Tw6MAAAAD8SIPk8OjAAAAA/EiD5PDowAAAAPxIg+Tw6MAAAAD8SIPk8OjAAAAA/EiD5PDowAAAAPxIg+Tw6MAAAAD8SIPk8OjAAAAA/EiD5PDowAAAAPxIg+Tw6MAAAAD8SIPk8OjAAAAA/EiD5PDowAAAAPxIg+Tw6MAAAAD8SIPk8OjAAAAA/EiD5PDowAAAAPxIg+Tw6MAAAAD8SIPk8OjAAAAA";
+
+    #[test]
+    fn test_buffer_artefact() {
+        let source = format!("console.log(Buffer.from('{}', 'base64'));", SHELLCODE);
+        let cleaned = DeobfuscateEngine::<JavaScriptBackend>::remove_extra(&source, true).unwrap();
+        let mut engine = DeobfuscateEngine::<JavaScriptBackend>::from_javascript(&cleaned).unwrap();
+        engine.deobfuscate().unwrap();
+
+        let artefacts = engine.artefacts(256).unwrap();
+        assert_eq!(artefacts.len(), 1);
+        assert_eq!(artefacts[0].kind, ArtefactKind::Shellcode);
+        assert_eq!(artefacts[0].data.len(), 300);
+
+        assert_eq!(
+            engine.lint_with_artefacts("    ", true, 256).unwrap(),
+            "console.log(Buffer.from([/* shellcode, 300 bytes, sha256:d0b6fa3176b5113a03beb961a80287a9514bb81916f5957b13f9f02a48de0dba */]));"
+        );
+    }
+}
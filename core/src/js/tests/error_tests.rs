@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests_js_error {
+    use crate::error::{Error, MinusOneErrorKind, MinusOneResult};
+    use crate::js::JavaScript;
+    use crate::js::build_javascript_tree;
+    use crate::js::integer::ParseInt;
+    use crate::rule::{RuleMut, RuleSet, RuleSetBuilderType};
+    use crate::tree::{ControlFlow, NodeMut};
+    use std::error::Error as _;
+
+    #[derive(Default)]
+    struct FailOnNumber;
+
+    impl<'a> RuleMut<'a> for FailOnNumber {
+        type Language = JavaScript;
+
+        fn enter(
+            &mut self,
+            _node: &mut NodeMut<'a, JavaScript>,
+            _flow: ControlFlow,
+        ) -> MinusOneResult<()> {
+            Ok(())
+        }
+
+        fn leave(
+            &mut self,
+            node: &mut NodeMut<'a, JavaScript>,
+            _flow: ControlFlow,
+        ) -> MinusOneResult<()> {
+            if node.view().kind() == "number" {
+                return Err(Error::invalid_child());
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_error_located_on_node() {
+        let mut tree = build_javascript_tree("var a = 'x' + 42;").unwrap();
+        let error = tree.apply_mut(&mut FailOnNumber).unwrap_err();
+
+        assert_eq!(error.kind(), MinusOneErrorKind::InvalidChildIndex);
+        assert_eq!(error.rule(), None);
+        assert_eq!(error.node_kind(), Some("number"));
+        assert_eq!(error.range(), Some(14..16));
+        assert_eq!(error.message(), "A child was expected at this index");
+    }
+
+    #[test]
+    fn test_error_located_in_rule() {
+        let mut tree = build_javascript_tree("var a = 'x' + 42;").unwrap();
+        let mut ruleset = RuleSet::<JavaScript>::new(
+            vec![
+                ("ParseInt", Box::new(ParseInt::default())),
+                ("FailOnNumber", Box::new(FailOnNumber)),
+            ],
+            RuleSetBuilderType::WithoutRules(vec![]),
+        );
+        let error = tree.apply_mut(&mut ruleset).unwrap_err();
+
+        assert_eq!(error.rule(), Some("FailOnNumber"));
+        assert_eq!(error.node_kind(), Some("number"));
+        assert_eq!(
+            error.to_string(),
+            "rule FailOnNumber failed on number at 14..16"
+        );
+        assert_eq!(
+            error.source().map(|cause| cause.to_string()),
+            Some("A child was expected at this index".to_string())
+        );
+    }
+
+    #[test]
+    fn test_utf8_error_kind() {
+        let bytes = vec![0xff, 0xfe];
+        let error: Error = std::str::from_utf8(&bytes).unwrap_err().into();
+
+        assert_eq!(error.kind(), MinusOneErrorKind::Encoding);
+        assert!(error.source().is_some());
+    }
+}
@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod tests_js_fuzz {
+    //! Malformed trees, as the fuzz targets produce them: every crash
+    //! found by fuzzing ends up here as a regression test
+    use crate::engine::DeobfuscateEngine;
+    use crate::js::backend::JavaScriptBackend;
+
+    /// Errors are expected on such inputs, panics are not
+    fn deobfuscate(input: &str) {
+        let Ok(cleaned) = DeobfuscateEngine::<JavaScriptBackend>::remove_extra(input, false) else {
+            return;
+        };
+        let Ok(mut engine) = DeobfuscateEngine::<JavaScriptBackend>::from_javascript(&cleaned)
+        else {
+            return;
+        };
+        if engine.deobfuscate().is_ok() {
+            let _ = engine.lint(false);
+        }
+    }
+
+    #[test]
+    fn test_unbalanced_function_scopes() {
+        deobfuscate("function f() { var a = 1; } }\nvar b = a;");
+        deobfuscate("} } function f() { var a = 1;\nf();");
+        deobfuscate("var f = function() { var a = 1; }}}; f();");
+    }
+
+    #[test]
+    fn test_truncated_sources() {
+        deobfuscate("var a = [1, 2].map(function(x) {");
+        deobfuscate("for (var i = 0; i < 10; i++) {");
+        deobfuscate("if (a) { var b = 1; } else");
+    }
+}
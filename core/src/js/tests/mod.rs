@@ -1,13 +1,16 @@
 mod array_tests;
+mod artefact_tests;
 mod b64_tests;
 mod bool_tests;
 mod buffer_tests;
 mod comparator_tests;
 mod converter_tests;
 mod encode_decode_tests;
+mod error_tests;
 mod fncall_tests;
 mod forward_tests;
 mod function_tests;
+mod fuzz_tests;
 mod integer_tests;
 mod jsfuck_tests;
 mod linter_tests;
@@ -15,8 +18,14 @@ mod loop_tests;
 mod maths_tests;
 mod object_tests;
 mod objectify_tests;
+mod oracle_tests;
+mod parse_error_tests;
 mod post_process_tests;
+mod profile_tests;
+mod query_tests;
 mod regex_tests;
+mod ruleset_tests;
 mod specials_tests;
+mod static_tests;
 mod string_tests;
 mod var_tests;
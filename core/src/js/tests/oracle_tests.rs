@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests_js_oracle {
+    use crate::js::JavaScript;
+    use crate::js::Value::{BigInt, Bool, Num, Str};
+    use crate::js::subprogram::build_and_reduce;
+    use crate::oracle::{Recorded, check_oracles};
+
+    fn recorded(value: &JavaScript) -> Option<Recorded> {
+        Some(match value {
+            JavaScript::Raw(Str(s)) => Recorded::Str(s.clone()),
+            JavaScript::Raw(Num(n)) => Recorded::Double(*n),
+            JavaScript::Raw(Bool(b)) => Recorded::Bool(*b),
+            JavaScript::Raw(BigInt(i)) => Recorded::BigInt(i.to_string()),
+            JavaScript::NaN => Recorded::Double(f64::NAN),
+            JavaScript::Null => Recorded::Null,
+            JavaScript::Undefined => Recorded::Undefined,
+            // atob returns a binary string
+            JavaScript::Bytes(bytes) => Recorded::Str(bytes.iter().map(|&b| b as char).collect()),
+            JavaScript::Array(items) => {
+                Recorded::Array(items.iter().map(recorded).collect::<Option<_>>()?)
+            }
+            _ => return None,
+        })
+    }
+
+    /// Value of the expression, alone in its program
+    fn infer(expression: &str) -> Option<Recorded> {
+        let source = format!("({});", expression);
+        let tree = build_and_reduce(&source)?;
+        let root = tree.root().ok()?;
+        let statement = root
+            .iter()
+            .find(|child| child.kind() == "expression_statement")?;
+        recorded(statement.child(0)?.data()?)
+    }
+
+    #[test]
+    fn test_recorded_node_values() {
+        check_oracles(
+            "node",
+            include_str!("../../../oracles/javascript.json"),
+            infer,
+        );
+    }
+}
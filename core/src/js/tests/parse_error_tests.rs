@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests_js_parse_error {
+    use crate::engine::DeobfuscateEngine;
+    use crate::error::{Error, MinusOneErrorKind, ParseError};
+    use crate::js::backend::JavaScriptBackend;
+
+    fn deobfuscate(input: &str) -> (String, Vec<ParseError>) {
+        let cleaned = DeobfuscateEngine::<JavaScriptBackend>::remove_extra(input, true).unwrap();
+        let mut engine = DeobfuscateEngine::<JavaScriptBackend>::from_javascript(&cleaned).unwrap();
+        engine.deobfuscate().unwrap();
+        (engine.lint(true).unwrap(), engine.parse_errors())
+    }
+
+    #[test]
+    fn test_no_parse_error() {
+        let (output, errors) = deobfuscate("var a = 'he' + 'llo';");
+        assert_eq!(output, "var a = 'hello';");
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_error_region_kept_as_is() {
+        let (output, errors) = deobfuscate("var a = 'a' + 'b'; )))) console.log('c' + 'd');");
+        assert_eq!(output, "var a = 'ab'; )))) console.log('cd');");
+        assert_eq!(
+            errors,
+            vec![ParseError {
+                start: 19,
+                end: 23,
+                context: Some("program".to_string()),
+                missing: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_truncated_source() {
+        let (output, errors) = deobfuscate("console.log('mi' + 'nus' + 'one'); function f( {");
+        assert_eq!(output, "console.log('minusone'); function f( {");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].start, 35);
+        assert_eq!(errors[0].missing, None);
+    }
+
+    #[test]
+    fn test_missing_token() {
+        let (output, errors) = deobfuscate("if (x) { console.log('a' + 'b')");
+        assert_eq!(output, "if (x) { console.log('ab')");
+        assert_eq!(
+            errors,
+            vec![ParseError {
+                start: 31,
+                end: 31,
+                context: Some("statement_block".to_string()),
+                missing: Some("}".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_leading_byte_order_mark() {
+        let cleaned =
+            DeobfuscateEngine::<JavaScriptBackend>::remove_extra("\u{feff}var a = 1;", true)
+                .unwrap();
+        assert_eq!(cleaned, "var a = 1;");
+    }
+
+    #[test]
+    fn test_nothing_to_process() {
+        let error = DeobfuscateEngine::<JavaScriptBackend>::from_javascript("}}}))")
+            .err()
+            .unwrap();
+        let Error::MinusOneError(error) = error else {
+            panic!("a parsing error was expected");
+        };
+        assert_eq!(error.kind(), MinusOneErrorKind::Parsing);
+
+        assert!(DeobfuscateEngine::<JavaScriptBackend>::remove_extra("}}}))", true).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests_js_profile {
+    use crate::engine::DeobfuscateEngine;
+    use crate::js::backend::JavaScriptBackend;
+    use crate::profile::{Limits, RuleProfile, with_limits};
+    use crate::rule::RuleSetBuilderType;
+    use std::collections::HashSet;
+
+    fn deobfuscate_with(
+        input: &str,
+        profile: RuleProfile,
+        include: &[&str],
+        exclude: &[&str],
+    ) -> String {
+        let mut engine = DeobfuscateEngine::<JavaScriptBackend>::from_source(input).unwrap();
+        engine
+            .deobfuscate_with_profile(profile, include, exclude)
+            .unwrap();
+        engine.lint(false).unwrap()
+    }
+
+    const FNCALL: &str = "function test() { return 'hello'; } console.log(test());";
+
+    #[test]
+    fn test_safe_profile_has_no_evaluating_rule() {
+        let rules = DeobfuscateEngine::<JavaScriptBackend>::language_rules();
+        let RuleSetBuilderType::WithRules(names) = RuleProfile::Safe.rule_set(&rules, &[], &[])
+        else {
+            unreachable!()
+        };
+        assert!(!names.is_empty());
+        assert!(
+            rules
+                .iter()
+                .filter(|rule| names.contains(&rule.name))
+                .all(|rule| !rule.executes_code())
+        );
+    }
+
+    #[test]
+    fn test_default_profile_is_the_default_ruleset() {
+        let rules = DeobfuscateEngine::<JavaScriptBackend>::language_rules();
+        let RuleSetBuilderType::WithRules(names) = RuleProfile::Default.rule_set(&rules, &[], &[])
+        else {
+            unreachable!()
+        };
+        assert_eq!(names.len(), rules.len());
+    }
+
+    #[test]
+    fn test_default_profile_matches_no_profile() {
+        let rules = DeobfuscateEngine::<JavaScriptBackend>::language_rules();
+        let RuleSetBuilderType::WithRules(names) = RuleProfile::Default.rule_set(&rules, &[], &[])
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            names,
+            rules.iter().map(|rule| rule.name).collect::<Vec<_>>()
+        );
+
+        let src = "function test() { return 'hello'; } for(var i = 0; i < 5; i++) {} console.log(test(), i);";
+        let mut engine = DeobfuscateEngine::<JavaScriptBackend>::from_source(src).unwrap();
+        engine.deobfuscate().unwrap();
+        assert_eq!(
+            deobfuscate_with(src, RuleProfile::Default, &[], &[]),
+            engine.lint(false).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_profile_rules_are_listed_once() {
+        let rules = DeobfuscateEngine::<JavaScriptBackend>::language_rules();
+        let RuleSetBuilderType::WithRules(names) =
+            RuleProfile::Safe.rule_set(&rules, &["addint", "FnCall", "fncall"], &[])
+        else {
+            unreachable!()
+        };
+        let lowercase: HashSet<String> = names.iter().map(|name| name.to_lowercase()).collect();
+        assert_eq!(lowercase.len(), names.len());
+        assert!(names.contains(&"FnCall"));
+    }
+
+    #[test]
+    fn test_safe_profile_does_not_call_functions() {
+        assert!(
+            deobfuscate_with(FNCALL, RuleProfile::Safe, &[], &[]).contains("console.log(test());")
+        );
+        assert_eq!(
+            deobfuscate_with("console.log(1 + 2);", RuleProfile::Safe, &[], &[]),
+            "console.log(3);"
+        );
+    }
+
+    #[test]
+    fn test_profile_with_included_rule() {
+        assert!(
+            deobfuscate_with(FNCALL, RuleProfile::Safe, &["fncall"], &[])
+                .contains("console.log('hello');")
+        );
+    }
+
+    #[test]
+    fn test_profile_with_excluded_category() {
+        assert!(
+            deobfuscate_with(FNCALL, RuleProfile::Aggressive, &[], &["@functions"])
+                .contains("console.log(test());")
+        );
+        assert!(
+            deobfuscate_with(FNCALL, RuleProfile::Aggressive, &[], &[])
+                .contains("console.log('hello');")
+        );
+    }
+
+    #[test]
+    fn test_for_loop_iteration_budget() {
+        let src = "for(var i = 0; i < 5; i++) {} var x = i;";
+        assert!(deobfuscate_with(src, RuleProfile::Default, &[], &[]).ends_with("var x = 5;"));
+
+        let _limits = with_limits(Limits {
+            for_iterations: 3,
+            ..Limits::DEFAULT
+        });
+        let mut engine = DeobfuscateEngine::<JavaScriptBackend>::from_source(src).unwrap();
+        engine.deobfuscate().unwrap();
+        assert!(!engine.lint(false).unwrap().ends_with("var x = 5;"));
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests_js_query {
+    use crate::engine::DeobfuscateEngine;
+    use crate::js::JavaScript;
+    use crate::js::array::ParseArray;
+    use crate::js::backend::JavaScriptBackend;
+    use crate::js::build_javascript_tree;
+    use crate::js::forward::Forward;
+    use crate::js::integer::ParseInt;
+    use crate::js::linter::Linter;
+    use crate::js::string::ParseString;
+    use crate::query::{QueryRule, parse_query_rules};
+    use crate::rule::RuleSetBuilderType;
+
+    fn deobfuscate(rules: &str, input: &str) -> String {
+        let rules = parse_query_rules::<JavaScript>(rules).unwrap();
+        let mut tree = build_javascript_tree(input).unwrap();
+        tree.apply_mut(&mut (
+            ParseInt::default(),
+            ParseString::default(),
+            ParseArray::default(),
+            Forward::default(),
+            &rules[0],
+        ))
+        .unwrap();
+
+        let mut linter = Linter::default();
+        tree.apply(&mut linter).unwrap();
+        linter.output
+    }
+
+    const DECODER: &str = r#"
+# custom hex decoder found in a loader
+rule HexDecode
+match ((call_expression
+         function: (identifier) @f
+         arguments: (arguments . (_) @s .))
+       (#eq? @f "_0xdec"))
+emit utf8(hex(@s))
+"#;
+
+    #[test]
+    fn test_query_rule_emit() {
+        assert_eq!(
+            deobfuscate(DECODER, "var x = _0xdec('6d696e75736f6e65');"),
+            "var x = 'minusone';"
+        );
+    }
+
+    #[test]
+    fn test_query_rule_text_predicate() {
+        assert_eq!(
+            deobfuscate(DECODER, "var x = other('6d696e75736f6e65');"),
+            "var x = other('6d696e75736f6e65');"
+        );
+    }
+
+    #[test]
+    fn test_query_rule_unknown_capture_data() {
+        assert_eq!(
+            deobfuscate(DECODER, "var x = _0xdec(y);"),
+            "var x = _0xdec(y);"
+        );
+    }
+
+    #[test]
+    fn test_query_rule_condition() {
+        let rules = r#"
+rule XorKey
+match ((call_expression
+         function: (identifier) @f
+         arguments: (arguments . (_) @data (_) @key .))
+       (#eq? @f "xor"))
+where is(@key, "int")
+where and(eq(len(@data), 3), ne(@key, 0))
+emit char(xor(@data, @key))
+"#;
+        assert_eq!(
+            deobfuscate(rules, "var x = xor([0x6b, 0x69, 0x6c], 2);"),
+            "var x = 'ikn';"
+        );
+        assert_eq!(
+            deobfuscate(rules, "var x = xor([0x6b, 0x69, 0x6c], 0);"),
+            "var x = xor([107, 105, 108], 0);"
+        );
+    }
+
+    #[test]
+    fn test_query_rule_multiline_emit() {
+        let rules = r#"
+rule Rebuild
+match ((call_expression
+         function: (identifier) @f
+         arguments: (arguments . (string) @a (string) @b .))
+       (#eq? @f "build"))
+emit concat(
+        upper(@a),
+        reverse(@b))
+"#;
+        assert_eq!(deobfuscate(rules, "build('mi', 'enosun');"), "'MInusone';");
+    }
+
+    #[test]
+    fn test_query_rule_in_ruleset() {
+        let rules = parse_query_rules::<JavaScript>(DECODER).unwrap();
+        let mut engine =
+            DeobfuscateEngine::<JavaScriptBackend>::from_javascript("var x = _0xdec('6d' + '31');")
+                .unwrap();
+        engine
+            .deobfuscate_with_query_rules(RuleSetBuilderType::WithoutRules(vec![]), &rules)
+            .unwrap();
+        assert_eq!(engine.lint(true).unwrap(), "var x = 'm1';");
+    }
+
+    #[test]
+    fn test_query_rule_excluded_from_ruleset() {
+        let rules = parse_query_rules::<JavaScript>(DECODER).unwrap();
+        let mut engine =
+            DeobfuscateEngine::<JavaScriptBackend>::from_javascript("var x = _0xdec('6d31');")
+                .unwrap();
+        engine
+            .deobfuscate_with_query_rules(
+                RuleSetBuilderType::WithoutRules(vec!["hexdecode"]),
+                &rules,
+            )
+            .unwrap();
+        assert_eq!(engine.lint(true).unwrap(), "var x = _0xdec('6d31');");
+    }
+
+    #[test]
+    fn test_query_rule_invalid() {
+        assert!(QueryRule::<JavaScript>::new("Bad", "(call_expression", &[], "null").is_err());
+        assert!(
+            QueryRule::<JavaScript>::new("Bad", "(call_expression) @c", &[], "upper(@d)").is_err()
+        );
+        assert!(
+            QueryRule::<JavaScript>::new("Bad", "(call_expression) @c", &[], "unknown(@c)")
+                .is_err()
+        );
+        assert!(parse_query_rules::<JavaScript>("rule A\nmatch (string) @s\n").is_err());
+        assert!(parse_query_rules::<JavaScript>("match (string) @s\nemit @s\n").is_err());
+        assert!(
+            parse_query_rules::<JavaScript>("rule forward\nmatch (string) @s\nemit @s\n").is_err()
+        );
+    }
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests_js_ruleset {
+    use crate::engine::DeobfuscateEngine;
+    use crate::js::backend::JavaScriptBackend;
+    use crate::rule::RuleCategory;
+    use std::collections::HashSet;
+
+    fn deobfuscate_with(input: &str, rules: Vec<&str>) -> String {
+        let mut engine = DeobfuscateEngine::<JavaScriptBackend>::from_source(input).unwrap();
+        engine.deobfuscate_with_custom_ruleset(rules).unwrap();
+        engine.lint(false).unwrap()
+    }
+
+    #[test]
+    fn test_rules_are_described() {
+        let rules = DeobfuscateEngine::<JavaScriptBackend>::language_rules();
+        let names: HashSet<&str> = rules.iter().map(|rule| rule.name).collect();
+        assert_eq!(names.len(), rules.len());
+        assert!(rules.iter().all(|rule| !rule.description.is_empty()));
+        assert!(rules.iter().all(|rule| !rule.node_kinds.is_empty()));
+    }
+
+    #[test]
+    fn test_evaluating_rules() {
+        let evaluating: Vec<&str> = DeobfuscateEngine::<JavaScriptBackend>::language_rules()
+            .into_iter()
+            .filter(|rule| rule.executes_code())
+            .map(|rule| rule.name)
+            .collect();
+        assert_eq!(
+            evaluating,
+            vec!["ArrayMapFilter", "ForLoop", "FnCall", "JsFuckLevelNine"]
+        );
+    }
+
+    #[test]
+    fn test_with_rule_category() {
+        assert_eq!(
+            DeobfuscateEngine::<JavaScriptBackend>::language_rules()
+                .iter()
+                .find(|rule| rule.name == "AddInt")
+                .unwrap()
+                .category,
+            RuleCategory::Arithmetic
+        );
+        assert_eq!(
+            deobfuscate_with("console.log(1 + 2 * 3);", vec!["@parsing", "@arithmetic"]),
+            "console.log(7);"
+        );
+    }
+}
@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests_js_static {
+    use crate::detect::Technique;
+    use crate::detect::Technique::*;
+    use crate::js::r#static::analyze_javascript;
+
+    fn techniques(input: &str) -> Vec<Technique> {
+        analyze_javascript(input)
+            .unwrap()
+            .techniques()
+            .into_iter()
+            .map(|(technique, _)| technique)
+            .collect()
+    }
+
+    #[test]
+    fn test_static_concat() {
+        assert_eq!(
+            techniques("console['lo' + 'g']('a' + 'b' + 'c');"),
+            vec![StringConcat]
+        );
+    }
+
+    #[test]
+    fn test_static_concat_outermost_only() {
+        let report = analyze_javascript("x = 'a' + ('b' + 'c');").unwrap();
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].start, 4);
+        assert_eq!(report.findings[0].end, 21);
+    }
+
+    #[test]
+    fn test_dynamic_concat_is_not_static() {
+        assert_eq!(techniques("console.log('a' + b + 'c');"), vec![]);
+    }
+
+    #[test]
+    fn test_char_code() {
+        assert_eq!(
+            techniques("String.fromCharCode(104, 105); String['fromCodePoint'](104)"),
+            vec![CharCode]
+        );
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(
+            techniques("atob('aGVsbG8='); Buffer.from('aGVsbG8=', 'base64')"),
+            vec![Base64]
+        );
+        assert_eq!(
+            analyze_javascript("atob('aGVsbG8='); Buffer.from('aGVsbG8=', 'base64')")
+                .unwrap()
+                .techniques(),
+            vec![(Base64, 2)]
+        );
+    }
+
+    #[test]
+    fn test_base64_long_string() {
+        assert_eq!(
+            techniques(
+                "var p = 'dmFyIGEgPSBkb2N1bWVudC5jcmVhdGVFbGVtZW50KCdzY3JpcHQnKTsgYS5zcmMgPSAnaHR0cCc7';"
+            ),
+            vec![Base64]
+        );
+    }
+
+    #[test]
+    fn test_layers() {
+        assert_eq!(
+            techniques(
+                "eval(zlib.inflateSync(crypto.createDecipheriv('aes-128-cbc', k, iv).update(d)))"
+            ),
+            vec![Encryption, Compression, DynamicInvocation]
+        );
+    }
+
+    #[test]
+    fn test_dynamic_invocation() {
+        assert_eq!(
+            analyze_javascript(
+                "new Function('return 1')(); setTimeout('alert(1)', 10); setTimeout(f, 10);"
+            )
+            .unwrap()
+            .techniques(),
+            vec![(DynamicInvocation, 2)]
+        );
+    }
+
+    #[test]
+    fn test_jsfuck() {
+        let source = "(![]+[])[+[]]+(![]+[])[!+[]+!+[]]+(!![]+[])[+!+[]]";
+        let report = analyze_javascript(source).unwrap();
+        assert_eq!(report.techniques(), vec![(JsFuck, 1)]);
+        assert_eq!(report.findings[0].start, 0);
+        assert_eq!(report.findings[0].end, source.len());
+    }
+
+    #[test]
+    fn test_short_symbols_are_not_jsfuck() {
+        assert_eq!(techniques("a = [] + []; b = !![];"), vec![]);
+    }
+
+    #[test]
+    fn test_string_array() {
+        assert_eq!(
+            techniques(
+                "var _0x1a2b = ['log', 'hello', 'world', 'foo', 'bar']; console[_0x1a2b[0]](_0x1a2b[1]);"
+            ),
+            vec![StringArray, HexIdentifier]
+        );
+    }
+
+    #[test]
+    fn test_string_array_returned() {
+        assert_eq!(
+            techniques("function f() { var a = ['a', 'b', 'c', 'd', 'e']; return a; }"),
+            vec![StringArray]
+        );
+    }
+
+    #[test]
+    fn test_unused_string_array() {
+        assert_eq!(techniques("var a = ['a', 'b', 'c', 'd', 'e'];"), vec![]);
+    }
+
+    #[test]
+    fn test_hex_identifier() {
+        assert_eq!(
+            analyze_javascript("function _0x4f2a(_0x12ab, b) { var _0xbeef = 1; return _0x12ab; }")
+                .unwrap()
+                .techniques(),
+            vec![(HexIdentifier, 3)]
+        );
+    }
+
+    #[test]
+    fn test_escaped_string() {
+        assert_eq!(
+            techniques("console.log('\\x68\\x65\\u006c\\u{6c}o')"),
+            vec![EscapedString]
+        );
+        assert_eq!(techniques("console.log('caf\\u00e9\\n')"), vec![]);
+    }
+
+    #[test]
+    fn test_clean_script() {
+        let report =
+            analyze_javascript("function add(a, b) { return a + b; }\nconsole.log(add(1, 2));")
+                .unwrap();
+        assert_eq!(report.findings, vec![]);
+        assert_eq!(report.score(), 0);
+    }
+
+    #[test]
+    fn test_score() {
+        let report = analyze_javascript(
+            "var _0x1a2b = ['log', 'hello', 'world', 'foo', 'bar']; console[_0x1a2b[0]](_0x1a2b[1], '!' + '?');",
+        )
+        .unwrap();
+        assert_eq!(
+            report.techniques(),
+            vec![(StringConcat, 1), (StringArray, 1), (HexIdentifier, 1)]
+        );
+        assert_eq!(report.score(), 55);
+    }
+
+    #[test]
+    fn test_score_is_capped() {
+        let source = "(![]+[])[+[]]+(![]+[])[!+[]+!+[]]+(!![]+[])[+!+[]]";
+        let report =
+            analyze_javascript(&format!("{source};{source};{source};eval(atob('YQ=='))")).unwrap();
+        assert_eq!(report.score(), 100);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::engine::{DeobfuscateEngine, DeobfuscationBackend};
+use crate::js::backend::JavaScriptBackend;
+use crate::ps::backend::PowershellBackend;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Languages handled by a deobfuscation backend
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ScriptLanguage {
+    Powershell,
+    JavaScript,
+}
+
+impl ScriptLanguage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScriptLanguage::Powershell => "powershell",
+            ScriptLanguage::JavaScript => "javascript",
+        }
+    }
+}
+
+impl Display for ScriptLanguage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ScriptLanguage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ps" | "ps1" | "powershell" => Ok(ScriptLanguage::Powershell),
+            "js" | "javascript" => Ok(ScriptLanguage::JavaScript),
+            _ => Err(format!("Unsupported language: {}", s)),
+        }
+    }
+}
+
+/// Number of bytes the grammar of a backend failed on
+fn failed_bytes<B: DeobfuscationBackend>(src: &str) -> usize {
+    match DeobfuscateEngine::<B>::from_source(src) {
+        Ok(engine) => engine
+            .parse_errors()
+            .iter()
+            .map(|error| error.end - error.start)
+            .sum(),
+        Err(_) => usize::MAX,
+    }
+}
+
+/// Guess the language of a script
+///
+/// The language whose grammar fails on the fewest bytes wins.
+/// Most short scripts are valid for both grammars,
+/// ties are broken by counting `$variable` against JavaScript keywords
+///
+/// # Example
+/// ```
+/// use minusone::language::{ScriptLanguage, detect_language};
+///
+/// assert_eq!(detect_language("var a = 1 + 2;"), ScriptLanguage::JavaScript);
+/// assert_eq!(detect_language("$a = 1 + 2"), ScriptLanguage::Powershell);
+/// ```
+pub fn detect_language(src: &str) -> ScriptLanguage {
+    let powershell = failed_bytes::<PowershellBackend>(src);
+    let javascript = failed_bytes::<JavaScriptBackend>(src);
+    if powershell != javascript {
+        return if powershell < javascript {
+            ScriptLanguage::Powershell
+        } else {
+            ScriptLanguage::JavaScript
+        };
+    }
+
+    // $variable and ${variable}
+    let variables = src
+        .as_bytes()
+        .windows(2)
+        .filter(|pair| {
+            pair[0] == b'$' && (pair[1].is_ascii_alphabetic() || b"_{".contains(&pair[1]))
+        })
+        .count();
+    let keywords = ["var ", "let ", "const ", "function", "=>", "console."]
+        .iter()
+        .map(|keyword| src.matches(keyword).count())
+        .sum::<usize>();
+    if keywords > variables {
+        ScriptLanguage::JavaScript
+    } else {
+        ScriptLanguage::Powershell
+    }
+}
@@ -14,10 +14,18 @@ extern crate tree_sitter_traversal2;
 pub mod ps;
 #[macro_use]
 pub mod js;
+pub mod artefact;
 pub mod debug;
+pub mod depth;
+pub mod detect;
 pub mod engine;
 pub mod error;
 pub mod init;
+pub mod language;
+#[cfg(test)]
+mod oracle;
+pub mod profile;
+pub mod query;
 pub mod rule;
 pub mod scope;
 pub mod step;
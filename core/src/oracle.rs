@@ -0,0 +1,181 @@
+//! Differential testing against values recorded from the real interpreters
+//!
+//! Each corpus in `core/oracles` is a JSON list of expressions with the value
+//! pwsh or node evaluated them to, written by the `record` script next to it.
+//! The harness infers each expression with a language rule set, and reports
+//! every value that differs from the recorded one.
+//! Expressions minusone leaves unresolved are listed, but are not a failure,
+//! neither are the mismatches of the cases with an `issue` explaining them.
+
+use serde_json::Value as Json;
+use std::fmt::Display;
+
+/// A value as recorded from an interpreter
+#[derive(Debug, Clone)]
+pub enum Recorded {
+    Str(String),
+    Int(i64),
+    Double(f64),
+    /// .NET char, inferred by minusone as a string of one char
+    Char(char),
+    Bool(bool),
+    BigInt(String),
+    Null,
+    Undefined,
+    Array(Vec<Recorded>),
+}
+
+impl Recorded {
+    fn from_json(json: &Json) -> Result<Self, String> {
+        let value = json.get("value");
+        let as_str = || {
+            value
+                .and_then(Json::as_str)
+                .ok_or_else(|| format!("expected a string value in {}", json))
+        };
+        match json.get("type").and_then(Json::as_str) {
+            Some("string") => Ok(Recorded::Str(as_str()?.to_string())),
+            Some("int") => value
+                .and_then(Json::as_i64)
+                .map(Recorded::Int)
+                .ok_or_else(|| format!("expected an integer value in {}", json)),
+            // doubles are recorded as their round-trip string, to keep NaN, infinities and -0
+            Some("double") => as_str()?
+                .parse::<f64>()
+                .map(Recorded::Double)
+                .map_err(|e| format!("{} in {}", e, json)),
+            Some("char") => as_str()?
+                .chars()
+                .next()
+                .map(Recorded::Char)
+                .ok_or_else(|| format!("expected a char in {}", json)),
+            Some("bool") => value
+                .and_then(Json::as_bool)
+                .map(Recorded::Bool)
+                .ok_or_else(|| format!("expected a boolean value in {}", json)),
+            Some("bigint") => Ok(Recorded::BigInt(as_str()?.to_string())),
+            Some("null") => Ok(Recorded::Null),
+            Some("undefined") => Ok(Recorded::Undefined),
+            Some("array") => value
+                .and_then(Json::as_array)
+                .ok_or_else(|| format!("expected a list value in {}", json))?
+                .iter()
+                .map(Recorded::from_json)
+                .collect::<Result<_, _>>()
+                .map(Recorded::Array),
+            _ => Err(format!("unknown recorded type in {}", json)),
+        }
+    }
+
+    /// Same value, with the doubles compared by their bits so NaN and -0 are checked too
+    pub fn matches(&self, other: &Recorded) -> bool {
+        match (self, other) {
+            (Recorded::Double(a), Recorded::Double(b)) => {
+                a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())
+            }
+            (Recorded::Char(c), Recorded::Str(s)) | (Recorded::Str(s), Recorded::Char(c)) => {
+                s.chars().eq(std::iter::once(*c))
+            }
+            (Recorded::Array(a), Recorded::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.matches(b))
+            }
+            (Recorded::Str(a), Recorded::Str(b)) => a == b,
+            (Recorded::Int(a), Recorded::Int(b)) => a == b,
+            (Recorded::Char(a), Recorded::Char(b)) => a == b,
+            (Recorded::Bool(a), Recorded::Bool(b)) => a == b,
+            (Recorded::BigInt(a), Recorded::BigInt(b)) => a == b,
+            (Recorded::Null, Recorded::Null) | (Recorded::Undefined, Recorded::Undefined) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Display for Recorded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Recorded::Str(s) => write!(f, "string {:?}", s),
+            Recorded::Int(i) => write!(f, "int {}", i),
+            Recorded::Double(d) => write!(f, "double {:?}", d),
+            Recorded::Char(c) => write!(f, "char {:?}", c),
+            Recorded::Bool(b) => write!(f, "bool {}", b),
+            Recorded::BigInt(i) => write!(f, "bigint {}", i),
+            Recorded::Null => write!(f, "null"),
+            Recorded::Undefined => write!(f, "undefined"),
+            Recorded::Array(items) => {
+                write!(f, "array [")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+pub struct OracleCase {
+    pub expression: String,
+    pub expected: Recorded,
+    /// Known difference with the interpreter
+    pub issue: Option<String>,
+}
+
+/// Cases of a recorded corpus
+pub fn load_oracles(corpus: &str) -> Vec<OracleCase> {
+    let cases: Vec<Json> = serde_json::from_str(corpus).expect("invalid oracle corpus");
+    cases
+        .iter()
+        .map(|case| OracleCase {
+            expression: case
+                .get("expression")
+                .and_then(Json::as_str)
+                .expect("oracle case without expression")
+                .to_string(),
+            expected: Recorded::from_json(case).unwrap(),
+            issue: case.get("issue").and_then(Json::as_str).map(str::to_string),
+        })
+        .collect()
+}
+
+/// Infer every case of a corpus, and panic with the list of the mismatches, if any
+pub fn check_oracles(language: &str, corpus: &str, infer: impl Fn(&str) -> Option<Recorded>) {
+    let cases = load_oracles(corpus);
+    let mut unresolved = vec![];
+    let mut mismatches = vec![];
+    for case in &cases {
+        match (infer(&case.expression), &case.issue) {
+            (None, _) => unresolved.push(case.expression.as_str()),
+            (Some(inferred), Some(issue)) if !inferred.matches(&case.expected) => {
+                println!("known issue: {}: {}", case.expression, issue)
+            }
+            (Some(inferred), None) if !inferred.matches(&case.expected) => {
+                mismatches.push(format!(
+                    "{}\n    recorded: {}\n    inferred: {}",
+                    case.expression, case.expected, inferred
+                ))
+            }
+            (Some(_), Some(_)) => println!("fixed issue, remove it: {}", case.expression),
+            (Some(_), None) => {}
+        }
+    }
+
+    println!(
+        "{}: {} cases, {} unresolved, {} mismatches",
+        language,
+        cases.len(),
+        unresolved.len(),
+        mismatches.len()
+    );
+    for expression in unresolved {
+        println!("unresolved: {}", expression);
+    }
+    assert!(
+        mismatches.is_empty(),
+        "{} value(s) differ from {}:\n{}",
+        mismatches.len(),
+        language,
+        mismatches.join("\n")
+    );
+}
@@ -0,0 +1,229 @@
+use crate::rule::{RuleInfo, RuleSafety, RuleSetBuilderType, expand_rule_names};
+use log::warn;
+use std::cell::Cell;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Budgets of the rules that evaluate the script
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Limits {
+    /// Nested sub-pipelines allowed for a single `FnCall` resolution
+    pub fncall_depth: usize,
+    /// Nested `.map()` / `.filter()` callbacks allowed
+    pub map_filter_depth: usize,
+    /// Nested `for` / `for..in` simulations allowed
+    pub for_depth: usize,
+    /// Iterations of a single simulated `for` loop
+    pub for_iterations: usize,
+    /// Pipeline items a script block is evaluated for, larger inputs are left as is
+    pub pipeline_items: usize,
+    /// Nested script block layers deobfuscated, deeper layers are left as is
+    pub layer_depth: usize,
+    /// Iterations of a key derivation, `Rfc2898DeriveBytes` or `PasswordDeriveBytes`
+    pub kdf_iterations: usize,
+    /// Bytes a key derivation object returns, over all its `GetBytes` calls
+    pub derived_bytes: usize,
+}
+
+impl Limits {
+    pub const DEFAULT: Limits = Limits {
+        fncall_depth: 2,
+        map_filter_depth: 4,
+        for_depth: 3,
+        for_iterations: 20_000,
+        pipeline_items: 1024,
+        layer_depth: 4,
+        kdf_iterations: 100_000,
+        derived_bytes: 4096,
+    };
+
+    pub const AGGRESSIVE: Limits = Limits {
+        fncall_depth: 6,
+        map_filter_depth: 8,
+        for_depth: 6,
+        for_iterations: 500_000,
+        pipeline_items: 65_536,
+        layer_depth: 8,
+        kdf_iterations: 1_000_000,
+        derived_bytes: 65_536,
+    };
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits::DEFAULT
+    }
+}
+
+thread_local! {
+    static LIMITS: Cell<Limits> = const { Cell::new(Limits::DEFAULT) };
+}
+
+/// Budgets used by the deobfuscation running on this thread
+pub fn limits() -> Limits {
+    LIMITS.with(|c| c.get())
+}
+
+/// Use other budgets until the guard is dropped
+///
+/// # Example
+/// ```
+/// use minusone::profile::{Limits, limits, with_limits};
+///
+/// {
+///     let _guard = with_limits(Limits::AGGRESSIVE);
+///     assert_eq!(limits(), Limits::AGGRESSIVE);
+/// }
+/// assert_eq!(limits(), Limits::DEFAULT);
+/// ```
+pub fn with_limits(limits: Limits) -> LimitsGuard {
+    LimitsGuard {
+        previous: LIMITS.with(|c| c.replace(limits)),
+    }
+}
+
+pub struct LimitsGuard {
+    previous: Limits,
+}
+
+impl Drop for LimitsGuard {
+    fn drop(&mut self) {
+        LIMITS.with(|c| c.set(self.previous));
+    }
+}
+
+/// Named selection of rules and budgets, for users who don't know the rules by name
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum RuleProfile {
+    /// Constant folding only, the script is never evaluated
+    Safe,
+    /// The rules used when no rule and no profile is given, every built-in rule
+    #[default]
+    Default,
+    /// Every rule, with higher budgets for function calls, loops and pipelines
+    Aggressive,
+}
+
+impl RuleProfile {
+    pub const ALL: [RuleProfile; 3] = [
+        RuleProfile::Safe,
+        RuleProfile::Default,
+        RuleProfile::Aggressive,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuleProfile::Safe => "safe",
+            RuleProfile::Default => "default",
+            RuleProfile::Aggressive => "aggressive",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            RuleProfile::Safe => "Constant folding only, no rule evaluates the script",
+            RuleProfile::Default => "The rules used without a profile, every built-in rule",
+            RuleProfile::Aggressive => {
+                "Every rule, with higher budgets for function calls, loops and pipelines"
+            }
+        }
+    }
+
+    /// The rule is part of the profile
+    pub fn enables(&self, rule: &RuleInfo) -> bool {
+        match self {
+            RuleProfile::Safe => rule.default_enabled && rule.safety == RuleSafety::Pure,
+            RuleProfile::Default => rule.default_enabled,
+            RuleProfile::Aggressive => true,
+        }
+    }
+
+    pub fn limits(&self) -> Limits {
+        match self {
+            RuleProfile::Safe | RuleProfile::Default => Limits::DEFAULT,
+            RuleProfile::Aggressive => Limits::AGGRESSIVE,
+        }
+    }
+
+    /// Rules of the profile, with the included rules added and the excluded rules removed
+    ///
+    /// Both lists accept `@category` entries, and exclusion wins over inclusion.
+    /// A rule is listed once, whatever the case it is named with
+    ///
+    /// # Example
+    /// ```
+    /// use minusone::profile::RuleProfile;
+    /// use minusone::rule::{RuleCategory, RuleInfo, RuleSafety, RuleSetBuilderType};
+    ///
+    /// let rule = |name, category, safety| RuleInfo {
+    ///     name,
+    ///     description: "",
+    ///     category,
+    ///     node_kinds: &[],
+    ///     safety,
+    ///     default_enabled: true,
+    /// };
+    /// let rules = [
+    ///     rule("Forward", RuleCategory::Parsing, RuleSafety::Pure),
+    ///     rule("DecodeBase64", RuleCategory::Encoding, RuleSafety::Pure),
+    ///     rule("FnCall", RuleCategory::Functions, RuleSafety::Evaluates),
+    /// ];
+    ///
+    /// let RuleSetBuilderType::WithRules(names) =
+    ///     RuleProfile::Safe.rule_set(&rules, &["fncall", "FORWARD"], &["@encoding"])
+    /// else {
+    ///     unreachable!()
+    /// };
+    /// assert_eq!(names, vec!["Forward", "fncall"]);
+    /// ```
+    pub fn rule_set<'a>(
+        &self,
+        rules: &[RuleInfo],
+        include: &[&'a str],
+        exclude: &[&'a str],
+    ) -> RuleSetBuilderType<'a> {
+        let include = expand_rule_names(include.to_vec(), rules);
+        let exclude: Vec<String> = expand_rule_names(exclude.to_vec(), rules)
+            .iter()
+            .map(|name| name.to_lowercase())
+            .collect();
+        for name in &exclude {
+            if !rules.iter().any(|rule| rule.name.to_lowercase() == *name) {
+                warn!("Unknown rule: '{}', skipping", name);
+            }
+        }
+
+        let mut names: Vec<&'a str> = vec![];
+        for name in rules
+            .iter()
+            .filter(|rule| self.enables(rule))
+            .map(|rule| rule.name)
+            .chain(include)
+        {
+            if !exclude.contains(&name.to_lowercase())
+                && !names.iter().any(|known| known.eq_ignore_ascii_case(name))
+            {
+                names.push(name);
+            }
+        }
+        RuleSetBuilderType::WithRules(names)
+    }
+}
+
+impl Display for RuleProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for RuleProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_lowercase();
+        RuleProfile::ALL
+            .into_iter()
+            .find(|profile| profile.name() == name)
+            .ok_or_else(|| format!("Unknown rule profile: '{}'", s))
+    }
+}
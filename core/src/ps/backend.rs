@@ -1,8 +1,12 @@
+use crate::detect::Report;
 use crate::engine::{CleanBackend, CleanEngine, DeobfuscateEngine, DeobfuscationBackend};
-use crate::error::MinusOneResult;
+use crate::error::{Error, MinusOneErrorKind, MinusOneResult};
 use crate::init::Init;
 use crate::ps;
-use crate::rule::RuleSetBuilderType;
+use crate::ps::cmdline::{CommandLine, ScriptSource};
+use crate::ps::host::HostProfile;
+use crate::query::QueryRule;
+use crate::rule::{RuleInfo, RuleSetBuilderType};
 use crate::tree::{EmptyStorage, HashMapStorage, Tree};
 use ps::linter::RemoveUnusedVar;
 use ps::{build_powershell_tree_for_storage, remove_powershell_extra};
@@ -45,6 +49,42 @@ impl PowershellBackend {
         Ok((out, steps))
     }
 
+    /// Script run by a powershell.exe command line, with the switches it was launched with
+    ///
+    /// cmd.exe wrappers are removed and -EncodedCommand is decoded. The script is
+    /// given as it is, ready for [`DeobfuscateEngine::remove_extra`]
+    pub fn from_command_line(line: &str) -> MinusOneResult<(String, CommandLine)> {
+        Self::from_parsed_command_line(CommandLine::parse(line)?)
+    }
+
+    /// Script run by an already parsed command line, see [`PowershellBackend::from_command_line`]
+    ///
+    /// A script read from the standard input is only known once given with [`CommandLine::with_stdin`]
+    pub fn from_parsed_command_line(cmdline: CommandLine) -> MinusOneResult<(String, CommandLine)> {
+        let script = match (&cmdline.script, &cmdline.source) {
+            (Some(script), _) => script.clone(),
+            (None, ScriptSource::File(file)) => {
+                return Err(Error::new(
+                    MinusOneErrorKind::Parsing,
+                    &format!("the script is run from the file {}", file),
+                ));
+            }
+            (None, ScriptSource::Stdin) => {
+                return Err(Error::new(
+                    MinusOneErrorKind::Parsing,
+                    "the script is read from the standard input",
+                ));
+            }
+            (None, _) => {
+                return Err(Error::new(
+                    MinusOneErrorKind::Parsing,
+                    "the command line does not run any script",
+                ));
+            }
+        };
+        Ok((script, cmdline))
+    }
+
     /// Runs the full pre-process/reduce/lint pipeline and returns a
     /// `Stepper` that hands out each recorded transform one at a time via
     /// `next`.
@@ -104,6 +144,18 @@ impl DeobfuscationBackend for PowershellBackend {
         Ok(())
     }
 
+    fn deobfuscate_tree_with_query_rules(
+        root: &mut Tree<HashMapStorage<Self::Language>>,
+        ctx: RuleSetBuilderType,
+        query_rules: &[QueryRule<Self::Language>],
+    ) -> MinusOneResult<()> {
+        root.apply_mut_with_strategy(
+            &mut ps::PowershellRuleSet::with_query_rules(ctx, query_rules),
+            ps::strategy::PowershellStrategy,
+        )?;
+        Ok(())
+    }
+
     fn lint_tree<'a>(
         root: &Tree<'a, HashMapStorage<Self::Language>>,
         tab_chr: &str,
@@ -115,8 +167,26 @@ impl DeobfuscationBackend for PowershellBackend {
         CleanEngine::<PowershellBackend>::from_source(&ps_linter_view.output)?.clean(keep_dead_code)
     }
 
-    fn language_rules<'a>() -> Vec<&'a str> {
-        ps::PowershellRuleSet::new(RuleSetBuilderType::WithoutRules(vec![])).names()
+    fn lint_tree_with_artefacts<'a>(
+        root: &Tree<'a, HashMapStorage<Self::Language>>,
+        tab_chr: &str,
+        keep_dead_code: bool,
+        threshold: usize,
+    ) -> MinusOneResult<String> {
+        let mut ps_linter_view = ps::linter::Linter::default()
+            .set_tab(tab_chr)
+            .set_artefact_threshold(threshold);
+        root.apply(&mut ps_linter_view)?;
+
+        CleanEngine::<PowershellBackend>::from_source(&ps_linter_view.output)?.clean(keep_dead_code)
+    }
+
+    fn language_rules() -> Vec<RuleInfo> {
+        ps::PowershellRuleSet::rules()
+    }
+
+    fn analyze(src: &str) -> MinusOneResult<Report> {
+        ps::r#static::analyze_powershell(src)
     }
 }
 
@@ -126,7 +196,7 @@ impl CleanBackend for PowershellBackend {
     }
 
     fn clean_tree(root: &Tree<EmptyStorage>, keep_dead_code: bool) -> MinusOneResult<String> {
-        if !keep_dead_code {
+        if !keep_dead_code && root.root()?.kind() == "program" {
             let mut rule = ps::var::UnusedVar::default();
             root.apply(&mut rule)?;
             let mut clean_view = RemoveUnusedVar::new(rule);
@@ -156,6 +226,27 @@ impl<'a> DeobfuscateEngine<'a, PowershellBackend> {
             .apply_mut_with_strategy(&mut tracer, ps::strategy::PowershellStrategy)?;
         Ok(tracer.steps)
     }
+
+    /// Deobfuscate as if the script was run on a specific host
+    ///
+    /// Environment and automatic variables are inferred from the profile
+    pub fn deobfuscate_with_host_profile(
+        &mut self,
+        ctx: RuleSetBuilderType,
+        query_rules: &[QueryRule<ps::Powershell>],
+        profile: &HostProfile,
+    ) -> MinusOneResult<()> {
+        self.root_mut().apply_mut_with_strategy(
+            &mut ps::PowershellRuleSet::with_host_profile(ctx, query_rules, profile),
+            ps::strategy::PowershellStrategy,
+        )?;
+        Ok(())
+    }
+
+    /// What the script would do, as seen on the deobfuscated tree
+    pub fn behaviours(&self) -> MinusOneResult<Vec<ps::behaviour::Behaviour>> {
+        ps::behaviour::summarize_powershell(self.root())
+    }
 }
 
 impl<'a> CleanEngine<'a, PowershellBackend> {
@@ -0,0 +1,458 @@
+use crate::error::MinusOneResult;
+use crate::ps::Powershell::{self, Array, Bytes, Raw, Type};
+use crate::ps::cmdlets::{canonical_command_name, resolved_command_name};
+use crate::ps::r#static::type_name;
+use crate::ps::var::{Var, find_variable_node};
+use crate::rule::Rule;
+use crate::tree::{HashMapStorage, Node, Tree};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// What a script would do if it was run
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum BehaviourKind {
+    Download,
+    Execution,
+    Persistence,
+    DefenderTampering,
+    AssemblyLoad,
+    AmsiBypass,
+    NativeCode,
+}
+
+impl BehaviourKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BehaviourKind::Download => "download",
+            BehaviourKind::Execution => "execution",
+            BehaviourKind::Persistence => "persistence",
+            BehaviourKind::DefenderTampering => "defender-tampering",
+            BehaviourKind::AssemblyLoad => "assembly-load",
+            BehaviourKind::AmsiBypass => "amsi-bypass",
+            BehaviourKind::NativeCode => "native-code",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            BehaviourKind::Download => "Fetches content from the network",
+            BehaviourKind::Execution => "Starts a process or runs code",
+            BehaviourKind::Persistence => "Survives a reboot through a Run key or a scheduled task",
+            BehaviourKind::DefenderTampering => "Weakens Windows Defender settings",
+            BehaviourKind::AssemblyLoad => "Loads a .NET assembly in memory",
+            BehaviourKind::AmsiBypass => "Disables the Antimalware Scan Interface",
+            BehaviourKind::NativeCode => "Allocates memory to run native code",
+        }
+    }
+}
+
+impl Display for BehaviourKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Argument of a sink, with its inferred value when there is one
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Argument {
+    /// Name of the parameter, none for a positional argument
+    pub name: Option<String>,
+    pub value: String,
+}
+
+/// A sink cmdlet or .NET call found in the script
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Behaviour {
+    pub kind: BehaviourKind,
+    /// Canonical name of the cmdlet or .NET member : Invoke-WebRequest, Net.WebClient.DownloadString
+    pub api: String,
+    pub arguments: Vec<Argument>,
+    pub start: usize,
+    pub end: usize,
+}
+
+const AMSI_MARKERS: [&str; 5] = [
+    "AmsiUtils",
+    "amsiInitFailed",
+    "AmsiScanBuffer",
+    "amsiContext",
+    "amsi.dll",
+];
+
+const WEBCLIENT_MEMBERS: [&str; 5] = [
+    "DownloadString",
+    "DownloadFile",
+    "DownloadData",
+    "OpenRead",
+    "UploadString",
+];
+
+const ASSEMBLY_MEMBERS: [&str; 3] = ["Load", "LoadFile", "LoadFrom"];
+
+/// Value inferred by the deobfuscation, or the source text if none
+fn inferred(node: &Node<Powershell>) -> MinusOneResult<String> {
+    Ok(match node.data() {
+        Some(Raw(value)) => value.to_string(),
+        Some(Array(values)) => values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<String>>()
+            .join(", "),
+        Some(Bytes(bytes)) => format!("<{} bytes>", bytes.len()),
+        Some(Type(typename)) => typename.clone(),
+        _ => node.text()?.to_string(),
+    })
+}
+
+/// Name of the cmdlet, function or alias called by a command
+/// with its original case when it is known
+fn command_name(node: &Node<Powershell>) -> MinusOneResult<Option<String>> {
+    let Some(command_name) = node.named_child("command_name") else {
+        return Ok(None);
+    };
+    let name = resolved_command_name(&command_name)?.replace('`', "");
+    let name = canonical_command_name(&name)
+        .map(String::from)
+        .unwrap_or(name);
+    Ok(Some(name))
+}
+
+fn command_arguments(node: &Node<Powershell>) -> MinusOneResult<Vec<Argument>> {
+    let mut arguments = vec![];
+    let Some(command_elements) = node.named_child("command_elements") else {
+        return Ok(arguments);
+    };
+
+    let mut parameter: Option<String> = None;
+    for element in command_elements.iter() {
+        match element.kind() {
+            "command_argument_sep" => (),
+            "command_parameter" => {
+                // a parameter directly followed by another one is a switch
+                if let Some(name) = parameter.take() {
+                    arguments.push(Argument {
+                        name: Some(name),
+                        value: "True".to_string(),
+                    });
+                }
+                parameter = Some(
+                    element
+                        .text()?
+                        .trim_start_matches('-')
+                        .trim_end_matches(':')
+                        .to_string(),
+                );
+            }
+            _ => arguments.push(Argument {
+                name: parameter.take(),
+                value: inferred(&element)?,
+            }),
+        }
+    }
+
+    if let Some(name) = parameter {
+        arguments.push(Argument {
+            name: Some(name),
+            value: "True".to_string(),
+        });
+    }
+    Ok(arguments)
+}
+
+fn invokation_arguments(args_list: &Node<Powershell>) -> MinusOneResult<Vec<Argument>> {
+    let mut arguments = vec![];
+    if let Some(argument_expression_list) = args_list.named_child("argument_expression_list") {
+        for argument in argument_expression_list.iter() {
+            if argument.kind() != "," {
+                arguments.push(Argument {
+                    name: None,
+                    value: inferred(&argument)?,
+                });
+            }
+        }
+    }
+    Ok(arguments)
+}
+
+/// Type of the object created by a New-Object command within the node
+fn new_object_type(node: &Node<Powershell>) -> MinusOneResult<Option<String>> {
+    if node.kind() == "command" {
+        if command_name(node)?.as_deref() == Some("New-Object") {
+            let typename = command_arguments(node)?
+                .into_iter()
+                .find(|argument| {
+                    argument
+                        .name
+                        .as_ref()
+                        .is_none_or(|name| name.eq_ignore_ascii_case("typename"))
+                })
+                .map(|argument| type_name(&argument.value));
+            return Ok(typename);
+        }
+        return Ok(None);
+    }
+
+    for child in node.iter() {
+        if let Some(typename) = new_object_type(&child)? {
+            return Ok(Some(typename));
+        }
+    }
+    Ok(None)
+}
+
+fn canonical_member(member: &str, candidates: &[&'static str]) -> Option<&'static str> {
+    candidates
+        .iter()
+        .find(|candidate| candidate.eq_ignore_ascii_case(member))
+        .copied()
+}
+
+/// Range of the top level statement that holds the node
+fn statement_range(node: &Node<Powershell>) -> (usize, usize) {
+    let mut range = (node.start_abs(), node.end_abs());
+    let mut current = node.parent();
+    while let Some(parent) = current
+        && !matches!(parent.kind(), "statement_list" | "program")
+    {
+        range = (parent.start_abs(), parent.end_abs());
+        current = parent.parent();
+    }
+    range
+}
+
+/// This rule will list the sinks cmdlet and .NET calls of a script
+/// It's meant to be run on the deobfuscated tree, and never modifies it
+///
+/// Arguments are reported with the value inferred by the deobfuscation
+/// and offsets are relative to the source the tree was built from
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::behaviour::{BehaviourKind, BehaviourSummary};
+///
+/// let tree = build_powershell_tree("iwr -Uri http://example.com/a.ps1").unwrap();
+/// let mut summary = BehaviourSummary::default();
+/// tree.apply(&mut summary).unwrap();
+///
+/// assert_eq!(summary.behaviours[0].kind, BehaviourKind::Download);
+/// assert_eq!(summary.behaviours[0].api, "Invoke-WebRequest");
+/// assert_eq!(summary.behaviours[0].arguments[0].name.as_deref(), Some("Uri"));
+/// assert_eq!(summary.behaviours[0].arguments[0].value, "http://example.com/a.ps1");
+/// ```
+#[derive(Default)]
+pub struct BehaviourSummary {
+    pub behaviours: Vec<Behaviour>,
+    // variable name -> type of the object created with New-Object
+    objects: HashMap<String, String>,
+}
+
+impl BehaviourSummary {
+    fn push(
+        &mut self,
+        kind: BehaviourKind,
+        api: &str,
+        arguments: Vec<Argument>,
+        (start, end): (usize, usize),
+    ) {
+        self.behaviours.push(Behaviour {
+            kind,
+            api: api.to_string(),
+            arguments,
+            start,
+            end,
+        });
+    }
+
+    fn command(&mut self, node: &Node<Powershell>) -> MinusOneResult<()> {
+        let Some(name) = command_name(node)? else {
+            return Ok(());
+        };
+        let arguments = command_arguments(node)?;
+        let any_value = |pattern: &str| {
+            arguments
+                .iter()
+                .any(|argument| argument.value.to_lowercase().contains(pattern))
+        };
+        let any_parameter = |prefixes: &[&str]| {
+            arguments.iter().any(|argument| {
+                argument.name.as_ref().is_some_and(|name| {
+                    let name = name.to_lowercase();
+                    prefixes.iter().any(|prefix| name.starts_with(prefix))
+                })
+            })
+        };
+
+        let kind = match name.to_lowercase().as_str() {
+            "invoke-webrequest" | "invoke-restmethod" | "start-bitstransfer" => {
+                Some(BehaviourKind::Download)
+            }
+            "start-process" | "invoke-item" | "invoke-expression" | "invoke-command" => {
+                Some(BehaviourKind::Execution)
+            }
+            "set-itemproperty" | "new-itemproperty" if any_value("\\currentversion\\run") => {
+                Some(BehaviourKind::Persistence)
+            }
+            "new-scheduledtask" | "new-scheduledtaskaction" | "register-scheduledtask" => {
+                Some(BehaviourKind::Persistence)
+            }
+            "schtasks" | "schtasks.exe" if any_value("/create") => Some(BehaviourKind::Persistence),
+            "add-mppreference" | "set-mppreference" if any_parameter(&["exclusion", "disable"]) => {
+                Some(BehaviourKind::DefenderTampering)
+            }
+            "add-type" if any_value("virtualalloc") => Some(BehaviourKind::NativeCode),
+            _ => None,
+        };
+
+        if let Some(kind) = kind {
+            self.push(kind, &name, arguments, (node.start_abs(), node.end_abs()));
+        }
+        Ok(())
+    }
+
+    fn invokation(&mut self, node: &Node<Powershell>) -> MinusOneResult<()> {
+        let (Some(target), Some(operator), Some(member_name), Some(args_list)) =
+            (node.child(0), node.child(1), node.child(2), node.child(3))
+        else {
+            return Ok(());
+        };
+
+        let member = match member_name.data() {
+            Some(Raw(value)) => value.to_string(),
+            _ => member_name.text()?.replace('`', ""),
+        };
+
+        let typename = if operator.text()? == "::" {
+            Some(match target.data() {
+                Some(Type(typename)) => type_name(typename),
+                _ => type_name(target.text()?.trim_matches(['[', ']'])),
+            })
+        } else if let Some(variable) = find_variable_node(&target)
+            && variable.start_abs() == target.start_abs()
+            && variable.end_abs() == target.end_abs()
+            && let Some(var_name) = Var::extract(variable.text()?)
+        {
+            self.objects.get(&var_name).cloned()
+        } else {
+            new_object_type(&target)?
+        };
+
+        let api = match typename.as_deref() {
+            Some("net.webclient") | Some("webclient") => {
+                canonical_member(&member, &WEBCLIENT_MEMBERS)
+                    .map(|member| (BehaviourKind::Download, format!("Net.WebClient.{}", member)))
+            }
+            Some("reflection.assembly") => {
+                canonical_member(&member, &ASSEMBLY_MEMBERS).map(|member| {
+                    (
+                        BehaviourKind::AssemblyLoad,
+                        format!("Reflection.Assembly.{}", member),
+                    )
+                })
+            }
+            Some("diagnostics.process") if member.eq_ignore_ascii_case("start") => Some((
+                BehaviourKind::Execution,
+                "Diagnostics.Process.Start".to_string(),
+            )),
+            _ if member.eq_ignore_ascii_case("virtualalloc") => {
+                Some((BehaviourKind::NativeCode, "VirtualAlloc".to_string()))
+            }
+            _ => None,
+        };
+
+        if let Some((kind, api)) = api {
+            let arguments = invokation_arguments(&args_list)?;
+            self.push(kind, &api, arguments, (node.start_abs(), node.end_abs()));
+        }
+        Ok(())
+    }
+
+    fn amsi(&mut self, node: &Node<Powershell>) -> MinusOneResult<()> {
+        let value = match node.data() {
+            Some(Raw(value)) => value.to_string(),
+            _ => return Ok(()),
+        };
+        let lowercase = value.to_lowercase();
+        let Some(marker) = AMSI_MARKERS
+            .iter()
+            .find(|marker| lowercase.contains(&marker.to_lowercase()))
+        else {
+            return Ok(());
+        };
+
+        // the value is forwarded to the parents of the node, report it only once
+        let (start, end) = statement_range(node);
+        if let Some(behaviour) = self.behaviours.iter_mut().find(|behaviour| {
+            behaviour.kind == BehaviourKind::AmsiBypass
+                && behaviour.start == start
+                && behaviour.end == end
+        }) {
+            if !behaviour
+                .arguments
+                .iter()
+                .any(|argument| argument.value == value)
+            {
+                behaviour.arguments.push(Argument { name: None, value });
+            }
+            return Ok(());
+        }
+
+        self.push(
+            BehaviourKind::AmsiBypass,
+            marker,
+            vec![Argument { name: None, value }],
+            (start, end),
+        );
+        Ok(())
+    }
+}
+
+impl<'a> Rule<'a> for BehaviourSummary {
+    type Language = Powershell;
+
+    fn enter(&mut self, node: &Node<'a, Self::Language>) -> MinusOneResult<bool> {
+        match node.kind() {
+            "command" => self.command(node)?,
+            "invokation_expression" => self.invokation(node)?,
+            "assignment_expression" => {
+                if let (Some(left), Some(right)) = (node.child(0), node.child(2))
+                    && let Some(variable) = find_variable_node(&left)
+                    && let Some(var_name) = Var::extract(variable.text()?)
+                {
+                    match new_object_type(&right)? {
+                        Some(typename) => self.objects.insert(var_name, typename),
+                        None => self.objects.remove(&var_name),
+                    };
+                }
+            }
+            _ => (),
+        }
+        self.amsi(node)?;
+        Ok(true)
+    }
+
+    fn leave(&mut self, _node: &Node<'a, Self::Language>) -> MinusOneResult<()> {
+        Ok(())
+    }
+}
+
+/// List what a deobfuscated script would do
+///
+/// # Example
+/// ```
+/// use minusone::ps::behaviour::{summarize_powershell, BehaviourKind};
+/// use minusone::ps::build_powershell_tree;
+///
+/// let tree = build_powershell_tree("Start-Process calc.exe").unwrap();
+/// let behaviours = summarize_powershell(&tree).unwrap();
+///
+/// assert_eq!(behaviours[0].kind, BehaviourKind::Execution);
+/// assert_eq!(behaviours[0].arguments[0].value, "calc.exe");
+/// ```
+pub fn summarize_powershell(
+    tree: &Tree<HashMapStorage<Powershell>>,
+) -> MinusOneResult<Vec<Behaviour>> {
+    let mut summary = BehaviourSummary::default();
+    tree.apply(&mut summary)?;
+    Ok(summary.behaviours)
+}
@@ -1,7 +1,11 @@
 use crate::error::MinusOneResult;
 use crate::ps::Powershell;
 use crate::ps::Powershell::{Array, PSItem, Raw, Type};
-use crate::ps::Value::{Bool, Num, Str};
+use crate::ps::Value::{Bool, Float, Num, Str};
+use crate::ps::convert::IntegerType;
+use crate::ps::math::{
+    double_to_integer, is_decimal_typename, is_double_typename, parse_real_literal,
+};
 use crate::rule::RuleMut;
 use crate::tree::{ControlFlow, NodeMut};
 use log::{trace, warn};
@@ -60,6 +64,53 @@ impl<'a> RuleMut<'a> for Cast {
                 if let (Some(type_literal), Some(expression)) = (view.child(0), view.child(1)) {
                     match (type_literal.data(), expression.data()) // type_identifier
                     {
+                        // integer types round doubles half to even : [int]65.5 => 66
+                        (Some(Type(t)), Some(Raw(Float(f)))) if IntegerType::from_typename(t).is_some() => {
+                            if let Some(number) = IntegerType::from_typename(t).and_then(|integer_type| double_to_integer(*f, integer_type)) {
+                                trace!("cast (L): Setting node with casted integer value: {}", number);
+                                node.set(Raw(Num(number)));
+                            } else {
+                                warn!("cast (L): Failed to cast value {} to {}, out of range", f, t);
+                            }
+                        }
+                        (Some(Type(t)), Some(Raw(v))) if is_double_typename(t) => {
+                            let number = match v {
+                                Num(n) => Some(*n as f64),
+                                Float(f) => Some(*f),
+                                Str(s) => parse_real_literal(s.trim()),
+                                Bool(b) => Some(*b as i64 as f64),
+                            };
+                            if let Some(number) = number {
+                                trace!("cast (L): Setting node with casted double value: {}", number);
+                                node.set(Raw(Float(number)));
+                            } else {
+                                warn!("cast (L): Failed to cast value {} to double", v);
+                            }
+                        }
+                        // decimals are only tracked when they hold an integer
+                        (Some(Type(t)), Some(Raw(Num(n)))) if is_decimal_typename(t) => {
+                            trace!("cast (L): Setting node with casted decimal value: {}", n);
+                            node.set(Raw(Num(*n)));
+                        }
+                        (Some(Type(t)), Some(Raw(Float(f)))) if t == "char" => {
+                            // only integral values, the rounding of the conversion is not known
+                            if f.fract() == 0.0
+                                && let Some(c) = double_to_integer(*f, IntegerType::UInt16).and_then(|n| char::from_u32(n as u32))
+                            {
+                                trace!("cast (L): Setting node with casted char value: {}", c);
+                                node.set(Raw(Str(c.to_string())));
+                            } else {
+                                warn!("cast (L): Failed to cast value {} to char", f);
+                            }
+                        }
+                        (Some(Type(t)), Some(Raw(Float(f)))) if t == "string" => {
+                            trace!("cast (L): Setting node with casted string value: {}", f);
+                            node.set(Raw(Str(Float(*f).to_string())));
+                        }
+                        (Some(Type(t)), Some(Raw(Float(f)))) if t == "bool" => {
+                            trace!("cast (L): Setting node with casted bool value: {}", *f != 0.0);
+                            node.set(Raw(Bool(*f != 0.0)));
+                        }
                         (Some(Type(t)), Some(Raw(v))) if t == "int" => {
                             if let Some(number) = v.clone().to_i64() {
                                 trace!("cast (L): Setting node with casted int value: {}", number);
@@ -132,6 +183,9 @@ impl<'a> RuleMut<'a> for Cast {
 
                                         char::from_u32(*number as u32)
                                     }
+                                    Float(f) if f.fract() == 0.0 => {
+                                        double_to_integer(*f, IntegerType::UInt16).and_then(|n| char::from_u32(n as u32))
+                                    }
                                     _ => {
                                         warn!("cast (L): Failed to cast value {:?} to char, invalid type", e);
                                         None
@@ -1,10 +1,11 @@
+use crate::error::MinusOneResult;
 use crate::ps::Powershell::{self, Raw};
 use crate::ps::Value::Str;
 use crate::regex::Regex;
 use crate::rule::RuleMut;
-use crate::tree::{ControlFlow, NodeMut};
+use crate::tree::{ControlFlow, Node, NodeMut};
 use log::{trace, warn};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::OnceLock;
 
 // to updated the list, just run `Get-Command` in you powershell and copy the output
@@ -12,8 +13,35 @@ use std::sync::OnceLock;
 const WIN_CMDLETS: &str = include_str!("cmdlets/win.txt");
 const UNIX_CMDLETS: &str = include_str!("cmdlets/unix.txt");
 
+// to update the list, run `Get-Alias` in a Windows PowerShell without profile
+const DEFAULT_ALIASES: &str = include_str!("cmdlets/aliases.txt");
+// one line per command : its type, its name, then its parameters with their aliases (Name|alias)
+// parameters of a cmdlet are listed by `(Get-Command <name>).Parameters.Values | % { @($_.Name) + $_.Aliases -join '|' }`
+// the common parameters are added to each cmdlet
+const COMMAND_PARAMETERS: &str = include_str!("cmdlets/parameters.txt");
+
 // maps lowercase cmdlet name -> original case
 static CMDLET_NAMES: OnceLock<HashMap<String, String>> = OnceLock::new();
+// maps lowercase alias -> command it stands for
+static ALIASES: OnceLock<HashMap<String, String>> = OnceLock::new();
+// maps lowercase command name -> parameters
+static PARAMETERS: OnceLock<HashMap<String, Vec<Parameter>>> = OnceLock::new();
+
+/// A parameter of a command, with its aliases as lowercase
+pub struct Parameter {
+    pub name: String,
+    pub aliases: Vec<String>,
+}
+
+impl Parameter {
+    fn parse(column: &str) -> Self {
+        let mut names = column.split('|');
+        Parameter {
+            name: names.next().unwrap_or_default().to_string(),
+            aliases: names.map(str::to_lowercase).collect(),
+        }
+    }
+}
 
 pub(crate) fn parse_cmdlet_names(content: &str) -> impl Iterator<Item = String> + '_ {
     content.lines().filter_map(|line| {
@@ -61,6 +89,112 @@ pub fn resolve_wildcard_cmdlet(name: &str) -> Option<String> {
     }
 }
 
+/// Original case of a known cmdlet, function or alias name
+pub fn canonical_cmdlet_name(name: &str) -> Option<&'static str> {
+    cmdlet_names().get(&name.to_lowercase()).map(String::as_str)
+}
+
+fn aliases() -> &'static HashMap<String, String> {
+    ALIASES.get_or_init(|| {
+        DEFAULT_ALIASES
+            .lines()
+            .filter_map(|line| line.strip_prefix("Alias"))
+            .filter_map(|line| line.trim().split_once(" -> "))
+            .map(|(alias, command)| (alias.to_lowercase(), command.trim().to_string()))
+            .collect()
+    })
+}
+
+/// Command a default alias stands for : iex => Invoke-Expression
+pub fn resolve_alias(name: &str) -> Option<&'static str> {
+    aliases().get(&name.to_lowercase()).map(String::as_str)
+}
+
+/// Canonical name of a command, aliases are expanded
+///
+/// # Example
+/// ```
+/// use minusone::ps::cmdlets::canonical_command_name;
+///
+/// assert_eq!(canonical_command_name("iex"), Some("Invoke-Expression"));
+/// assert_eq!(canonical_command_name("%"), Some("ForEach-Object"));
+/// assert_eq!(canonical_command_name("wRITE-hOST"), Some("Write-Host"));
+/// assert_eq!(canonical_command_name("foo"), None);
+/// ```
+pub fn canonical_command_name(name: &str) -> Option<&'static str> {
+    resolve_alias(name).or_else(|| canonical_cmdlet_name(name))
+}
+
+fn parameters() -> &'static HashMap<String, Vec<Parameter>> {
+    PARAMETERS.get_or_init(|| {
+        let mut common = vec![];
+        let mut parameters = HashMap::new();
+        for line in COMMAND_PARAMETERS.lines() {
+            let mut columns = line.split_whitespace();
+            match (columns.next(), columns.next()) {
+                (Some("Common"), Some(first)) => {
+                    common = std::iter::once(first).chain(columns).collect();
+                }
+                (Some("Cmdlet"), Some(name)) => {
+                    let list = columns
+                        .chain(common.iter().copied())
+                        .map(Parameter::parse)
+                        .collect();
+                    parameters.insert(name.to_lowercase(), list);
+                }
+                (Some("Application"), Some(name)) => {
+                    parameters.insert(name.to_lowercase(), columns.map(Parameter::parse).collect());
+                }
+                _ => (),
+            }
+        }
+        parameters
+    })
+}
+
+/// Parameters of a known command, applications are looked up by their file name
+pub fn command_parameters(command: &str) -> Option<&'static [Parameter]> {
+    let name = command.to_lowercase();
+    let name = canonical_command_name(&name)
+        .map(str::to_lowercase)
+        .unwrap_or(name);
+    let file_name = name.rsplit(['\\', '/']).next().unwrap_or(&name);
+    let file_name = file_name.strip_suffix(".exe").unwrap_or(file_name);
+    parameters().get(file_name).map(Vec::as_slice)
+}
+
+/// Full name of a parameter given by its name, one of its aliases or an unambiguous prefix
+///
+/// # Example
+/// ```
+/// use minusone::ps::cmdlets::canonical_parameter_name;
+///
+/// assert_eq!(canonical_parameter_name("powershell.exe", "nop"), Some("NoProfile"));
+/// assert_eq!(canonical_parameter_name("powershell", "w"), Some("WindowStyle"));
+/// assert_eq!(canonical_parameter_name("powershell", "e"), Some("EncodedCommand"));
+/// assert_eq!(canonical_parameter_name("iwr", "usebasic"), Some("UseBasicParsing"));
+/// assert_eq!(canonical_parameter_name("Start-Process", "w"), None);
+/// ```
+pub fn canonical_parameter_name(command: &str, parameter: &str) -> Option<&'static str> {
+    let parameters = command_parameters(command)?;
+    let given = parameter.to_lowercase();
+    if let Some(exact) = parameters
+        .iter()
+        .find(|p| p.name.to_lowercase() == given || p.aliases.contains(&given))
+    {
+        return Some(exact.name.as_str());
+    }
+
+    let mut candidates = parameters.iter().filter(|p| {
+        p.name.to_lowercase().starts_with(&given)
+            || p.aliases.iter().any(|alias| alias.starts_with(&given))
+    });
+    match (candidates.next(), candidates.next()) {
+        (Some(unique), None) if !given.is_empty() => Some(unique.name.as_str()),
+        _ => None,
+    }
+}
+
 pub fn resolved_command_name(
     node: &crate::tree::Node<Powershell>,
 ) -> crate::error::MinusOneResult<String> {
@@ -101,3 +235,85 @@ impl<'a> RuleMut<'a> for WildcardCmdlet {
         Ok(())
     }
 }
+
+/// Elements of a command bound to its parameters
+pub(crate) struct BoundParameters<'a> {
+    pub(crate) positional: VecDeque<Node<'a, Powershell>>,
+    // None for a switch
+    pub(crate) named: HashMap<&'static str, Option<Node<'a, Powershell>>>,
+}
+
+impl<'a> BoundParameters<'a> {
+    /// Bind the elements of a command to its parameters
+    ///
+    /// Parameters are matched on their unambiguous prefix, like Powershell does
+    /// Returns None when a parameter is unknown, or a value is missing
+    pub(crate) fn bind(
+        command: &Node<'a, Powershell>,
+        parameters: &[(&'static str, bool)],
+    ) -> MinusOneResult<Option<Self>> {
+        let mut bound = BoundParameters {
+            positional: VecDeque::new(),
+            named: HashMap::new(),
+        };
+        let Some(command_elements) = command.named_child("command_elements") else {
+            return Ok(Some(bound));
+        };
+
+        let mut expected: Option<&'static str> = None;
+        for element in command_elements.iter() {
+            match element.kind() {
+                "command_argument_sep" => (),
+                "command_parameter" => {
+                    if expected.is_some() {
+                        return Ok(None);
+                    }
+                    let given = element
+                        .text()?
+                        .trim_start_matches('-')
+                        .trim_end_matches(':')
+                        .to_lowercase();
+                    let candidates: Vec<&(&'static str, bool)> =
+                        match parameters.iter().find(|(name, _)| *name == given) {
+                            Some(exact) => vec![exact],
+                            None => parameters
+                                .iter()
+                                .filter(|(name, _)| name.starts_with(&given))
+                                .collect(),
+                        };
+                    let [&(name, is_switch)] = candidates[..] else {
+                        return Ok(None);
+                    };
+                    if is_switch {
+                        bound.named.insert(name, None);
+                    } else {
+                        expected = Some(name);
+                    }
+                }
+                _ => match expected.take() {
+                    Some(name) => {
+                        bound.named.insert(name, Some(element));
+                    }
+                    None => bound.positional.push_back(element),
+                },
+            }
+        }
+
+        if expected.is_some() {
+            return Ok(None);
+        }
+        Ok(Some(bound))
+    }
+
+    pub(crate) fn is_set(&self, name: &str) -> bool {
+        self.named.contains_key(name)
+    }
+
+    /// Value of a parameter, by name or else from the next positional argument
+    pub(crate) fn take(&mut self, name: &str) -> Option<Node<'a, Powershell>> {
+        match self.named.remove(name) {
+            Some(value) => value,
+            None => self.positional.pop_front(),
+        }
+    }
+}
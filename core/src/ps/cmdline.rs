@@ -0,0 +1,463 @@
+use crate::error::{Error, MinusOneErrorKind, MinusOneResult};
+use crate::ps::utils::string::decode;
+use base64::{Engine as _, engine::general_purpose};
+
+/// Switches of powershell.exe, in the order it tries them
+///
+/// Each switch is matched by any prefix of its name at least as long as its shortest form,
+/// or by one of its aliases
+const SWITCHES: [(&str, &str, &[&str]); 17] = [
+    ("psconsolefile", "psc", &[]),
+    ("version", "v", &[]),
+    ("nologo", "nol", &[]),
+    ("noexit", "noe", &[]),
+    ("sta", "s", &[]),
+    ("mta", "mta", &[]),
+    ("noprofile", "nop", &[]),
+    ("noninteractive", "noni", &[]),
+    ("command", "c", &[]),
+    ("windowstyle", "w", &[]),
+    ("file", "f", &[]),
+    ("executionpolicy", "ex", &["ep"]),
+    ("encodedcommand", "e", &["ec"]),
+    ("encodedarguments", "encodeda", &["ea"]),
+    ("inputformat", "in", &["if"]),
+    ("outputformat", "o", &["of"]),
+    ("configurationname", "config", &[]),
+];
+
+/// Full name of a powershell.exe switch, given the way powershell.exe matches them
+///
+/// # Example
+/// ```
+/// use minusone::ps::cmdline::switch_name;
+///
+/// assert_eq!(switch_name("-nop"), Some("noprofile"));
+/// assert_eq!(switch_name("-w"), Some("windowstyle"));
+/// assert_eq!(switch_name("/enc"), Some("encodedcommand"));
+/// assert_eq!(switch_name("-eP"), Some("executionpolicy"));
+/// assert_eq!(switch_name("-no"), None);
+/// ```
+pub fn switch_name(arg: &str) -> Option<&'static str> {
+    let key = arg
+        .strip_prefix(['-', '/', '\u{2013}', '\u{2014}', '\u{2015}'])?
+        .to_lowercase();
+    SWITCHES
+        .iter()
+        .find(|(name, shortest, aliases)| {
+            (name.starts_with(&key) && key.len() >= shortest.len())
+                || aliases.contains(&key.as_str())
+        })
+        .map(|(name, _, _)| *name)
+}
+
+/// Split a command line into arguments, the way CommandLineToArgvW does
+///
+/// # Example
+/// ```
+/// use minusone::ps::cmdline::split_arguments;
+///
+/// assert_eq!(
+///     split_arguments(r#"powershell -c "Write-Host \"a b\"" c"#),
+///     vec!["powershell", "-c", "Write-Host \"a b\"", "c"]
+/// );
+/// ```
+pub fn split_arguments(line: &str) -> Vec<String> {
+    let mut args = vec![];
+    let mut chars = line.trim().chars().peekable();
+    while chars.peek().is_some() {
+        let mut arg = String::new();
+        let mut in_quotes = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    let mut count = 1;
+                    while chars.next_if_eq(&'\\').is_some() {
+                        count += 1;
+                    }
+                    if chars.peek() == Some(&'"') {
+                        // 2n backslashes then a quote : n backslashes and a delimiter
+                        // 2n + 1 backslashes then a quote : n backslashes and a quote
+                        arg.extend(std::iter::repeat_n('\\', count / 2));
+                        if count % 2 == 1 {
+                            arg.push('"');
+                            chars.next();
+                        }
+                    } else {
+                        arg.extend(std::iter::repeat_n('\\', count));
+                    }
+                }
+                '"' if in_quotes && chars.peek() == Some(&'"') => {
+                    arg.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = !in_quotes,
+                c if c.is_whitespace() && !in_quotes => break,
+                c => arg.push(c),
+            }
+        }
+        args.push(arg);
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+    args
+}
+
+/// Split a cmd.exe command on its | and & operators, and remove its caret escaping
+///
+/// Quoted parts are kept as they are, carets included
+///
+/// # Example
+/// ```
+/// use minusone::ps::cmdline::split_cmd_commands;
+///
+/// assert_eq!(
+///     split_cmd_commands(r#"echo a^|b | p^ow^ershell "-c" "^x""#),
+///     vec!["echo a|b", r#"powershell "-c" "^x""#]
+/// );
+/// ```
+pub fn split_cmd_commands(command: &str) -> Vec<String> {
+    let mut commands = vec![];
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            '^' if !in_quotes => current.extend(chars.next()),
+            '|' | '&' if !in_quotes => {
+                chars.next_if_eq(&c);
+                commands.push(current.trim().to_string());
+                current.clear();
+            }
+            c => current.push(c),
+        }
+    }
+    commands.push(current.trim().to_string());
+    commands.retain(|command| !command.is_empty());
+    commands
+}
+
+/// Lowercase file name of an executable, without its path and extension
+fn executable_name(path: &str) -> String {
+    let name = path
+        .rsplit(['\\', '/'])
+        .next()
+        .unwrap_or(path)
+        .to_lowercase();
+    match name.strip_suffix(".exe") {
+        Some(name) => name.to_string(),
+        None => name,
+    }
+}
+
+fn is_powershell(path: &str) -> bool {
+    matches!(executable_name(path).as_str(), "powershell" | "pwsh")
+}
+
+/// First token of a raw command line, and what follows it
+fn split_executable(line: &str) -> (&str, &str) {
+    let line = line.trim_start();
+    let end = match line.strip_prefix('"') {
+        Some(quoted) => quoted.find('"').map_or(line.len(), |i| i + 2),
+        None => line.find(char::is_whitespace).unwrap_or(line.len()),
+    };
+    (line[..end].trim_matches('"'), &line[end..])
+}
+
+/// Command run by cmd.exe /c, /k or /r, with its outer quotes removed
+fn cmd_command(args: &str) -> Option<&str> {
+    let mut rest = args.trim_start();
+    loop {
+        // cmd /c"powershell ..." : the quoted command can follow the switch without a space
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '"')
+            .unwrap_or(rest.len());
+        let (switch, remaining) = rest.split_at(end);
+        if !switch.starts_with('/') {
+            return None;
+        }
+        rest = remaining.trim_start();
+        if ["/c", "/k", "/r"]
+            .iter()
+            .any(|s| switch.eq_ignore_ascii_case(s))
+        {
+            break;
+        }
+    }
+    Some(match rest.strip_prefix('"') {
+        Some(quoted) => match quoted.rfind('"') {
+            Some(last) => &quoted[..last],
+            None => quoted,
+        },
+        None => rest,
+    })
+}
+
+/// How the script is given to powershell.exe
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptSource {
+    /// -Command, or the first argument which is not a switch
+    Command,
+    /// -EncodedCommand, the script is base64 encoded UTF-16LE
+    EncodedCommand,
+    /// -Command -, the script is read from the standard input
+    Stdin,
+    /// -File, the script is read from the disk
+    File(String),
+    /// No script, powershell.exe is run interactively
+    None,
+}
+
+/// Switches powershell.exe was launched with, beside the script
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LaunchFlags {
+    pub no_profile: bool,
+    pub no_logo: bool,
+    pub no_exit: bool,
+    pub non_interactive: bool,
+    pub sta: bool,
+    pub mta: bool,
+    pub window_style: Option<String>,
+    pub execution_policy: Option<String>,
+    pub version: Option<String>,
+    pub input_format: Option<String>,
+    pub output_format: Option<String>,
+    pub configuration_name: Option<String>,
+    pub console_file: Option<String>,
+    pub encoded_arguments: Option<String>,
+}
+
+impl LaunchFlags {
+    /// Switches that are set, written with their full name
+    ///
+    /// # Example
+    /// ```
+    /// use minusone::ps::cmdline::CommandLine;
+    ///
+    /// let cmdline = CommandLine::parse("powershell -nop -w hidden -c 1").unwrap();
+    /// assert_eq!(cmdline.flags.to_vec(), vec!["-NoProfile", "-WindowStyle hidden"]);
+    /// ```
+    pub fn to_vec(&self) -> Vec<String> {
+        let switches = [
+            ("-NoProfile", self.no_profile),
+            ("-NoLogo", self.no_logo),
+            ("-NoExit", self.no_exit),
+            ("-NonInteractive", self.non_interactive),
+            ("-Sta", self.sta),
+            ("-Mta", self.mta),
+        ];
+        let values = [
+            ("-WindowStyle", &self.window_style),
+            ("-ExecutionPolicy", &self.execution_policy),
+            ("-Version", &self.version),
+            ("-InputFormat", &self.input_format),
+            ("-OutputFormat", &self.output_format),
+            ("-ConfigurationName", &self.configuration_name),
+            ("-PSConsoleFile", &self.console_file),
+            ("-EncodedArguments", &self.encoded_arguments),
+        ];
+        switches
+            .iter()
+            .filter(|(_, set)| *set)
+            .map(|(name, _)| name.to_string())
+            .chain(
+                values
+                    .iter()
+                    .filter_map(|(name, value)| Some(format!("{} {}", name, value.as_ref()?))),
+            )
+            .collect()
+    }
+}
+
+/// A powershell.exe or pwsh invocation, as seen in process creation events
+pub struct CommandLine {
+    /// Path of the powershell executable, as written on the command line
+    pub executable: String,
+    /// Command lines of the cmd.exe that launched powershell, outermost first
+    pub wrappers: Vec<String>,
+    pub flags: LaunchFlags,
+    pub source: ScriptSource,
+    /// The script, when it is part of the command line
+    pub script: Option<String>,
+    /// Arguments given to a -File script
+    pub arguments: Vec<String>,
+}
+
+impl CommandLine {
+    /// Parse a powershell.exe command line, wrapped or not in cmd.exe
+    ///
+    /// # Example
+    /// ```
+    /// use minusone::ps::cmdline::{CommandLine, ScriptSource};
+    ///
+    /// let cmdline = CommandLine::parse(
+    ///     "cmd.exe /c \"powershell.exe -nop -w hidden -enc VwByAGkAdABlAC0ASABvAHMAdAAgADEA\""
+    /// ).unwrap();
+    /// assert_eq!(cmdline.source, ScriptSource::EncodedCommand);
+    /// assert_eq!(cmdline.script.as_deref(), Some("Write-Host 1"));
+    /// assert!(cmdline.flags.no_profile);
+    /// assert_eq!(cmdline.flags.window_style.as_deref(), Some("hidden"));
+    /// ```
+    pub fn parse(line: &str) -> MinusOneResult<Self> {
+        let mut wrappers = vec![];
+        let mut stdin = None;
+        let mut line = line.trim().to_string();
+        loop {
+            let (executable, args) = split_executable(&line);
+            if is_powershell(executable) {
+                break;
+            }
+            if executable_name(executable) != "cmd" {
+                return Err(Error::new(
+                    MinusOneErrorKind::Parsing,
+                    &format!("{} is not a powershell command line", executable),
+                ));
+            }
+            let command = cmd_command(args).ok_or_else(|| {
+                Error::new(MinusOneErrorKind::Parsing, "cmd.exe does not run a command")
+            })?;
+            let commands = split_cmd_commands(command);
+            let Some(index) = commands.iter().position(|command| {
+                let executable = split_executable(command).0;
+                is_powershell(executable) || executable_name(executable) == "cmd"
+            }) else {
+                return Err(Error::new(
+                    MinusOneErrorKind::Parsing,
+                    "cmd.exe does not run powershell",
+                ));
+            };
+            // echo <script> | powershell -
+            if index > 0
+                && let Some((echo, echoed)) = commands[index - 1].split_once(' ')
+                && echo.eq_ignore_ascii_case("echo")
+            {
+                stdin = Some(echoed.to_string());
+            }
+            wrappers.push(line.clone());
+            line = commands[index].clone();
+        }
+
+        let mut args = split_arguments(&line).into_iter();
+        let executable = args.next().unwrap_or_default();
+        let mut cmdline = CommandLine {
+            executable,
+            wrappers,
+            flags: LaunchFlags::default(),
+            source: ScriptSource::None,
+            script: None,
+            arguments: vec![],
+        };
+
+        while let Some(arg) = args.next() {
+            // - alone reads the script from the standard input
+            if arg == "-" {
+                cmdline.source = ScriptSource::Stdin;
+                break;
+            }
+            let Some(switch) = switch_name(&arg) else {
+                // the first argument which is not a switch starts the script
+                // pwsh runs it as a file, Windows PowerShell as a command
+                if executable_name(&cmdline.executable) == "pwsh" {
+                    cmdline.source = ScriptSource::File(arg);
+                    cmdline.arguments = args.collect();
+                } else {
+                    cmdline.source = ScriptSource::Command;
+                    cmdline.script = Some(
+                        std::iter::once(arg)
+                            .chain(args)
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    );
+                }
+                break;
+            };
+            let flags = &mut cmdline.flags;
+            match switch {
+                "noprofile" => flags.no_profile = true,
+                "nologo" => flags.no_logo = true,
+                "noexit" => flags.no_exit = true,
+                "noninteractive" => flags.non_interactive = true,
+                "sta" => flags.sta = true,
+                "mta" => flags.mta = true,
+                "command" => {
+                    let rest: Vec<String> = args.by_ref().collect();
+                    if rest.first().is_some_and(|arg| arg == "-") {
+                        cmdline.source = ScriptSource::Stdin;
+                    } else {
+                        cmdline.source = ScriptSource::Command;
+                        cmdline.script = Some(rest.join(" "));
+                    }
+                }
+                "file" => {
+                    match args.next() {
+                        Some(file) if file == "-" => cmdline.source = ScriptSource::Stdin,
+                        Some(file) => cmdline.source = ScriptSource::File(file),
+                        None => (),
+                    }
+                    cmdline.arguments = args.by_ref().collect();
+                }
+                "encodedcommand" => {
+                    let encoded = args.next().unwrap_or_default();
+                    cmdline.source = ScriptSource::EncodedCommand;
+                    cmdline.script = Some(decode_encoded_command(&encoded)?);
+                }
+                switch => {
+                    let value = args.next();
+                    match switch {
+                        "windowstyle" => flags.window_style = value,
+                        "executionpolicy" => flags.execution_policy = value,
+                        "version" => flags.version = value,
+                        "inputformat" => flags.input_format = value,
+                        "outputformat" => flags.output_format = value,
+                        "configurationname" => flags.configuration_name = value,
+                        "psconsolefile" => flags.console_file = value,
+                        _ => flags.encoded_arguments = value,
+                    }
+                }
+            }
+        }
+
+        if cmdline.source == ScriptSource::Stdin
+            && let Some(stdin) = stdin
+        {
+            cmdline.script = Some(stdin);
+        }
+        Ok(cmdline)
+    }
+
+    /// Give the standard input of powershell.exe, read when the script comes from it
+    pub fn with_stdin(mut self, stdin: &str) -> Self {
+        if self.source == ScriptSource::Stdin {
+            self.script = Some(stdin.to_string());
+        }
+        self
+    }
+}
+
+/// Script given to -EncodedCommand : base64 of its UTF-16LE bytes
+///
+/// # Example
+/// ```
+/// use minusone::ps::cmdline::decode_encoded_command;
+///
+/// assert_eq!(decode_encoded_command("JABhAD0AMQA=").unwrap(), "$a=1");
+/// ```
+pub fn decode_encoded_command(encoded: &str) -> MinusOneResult<String> {
+    let bytes = general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|_| {
+            Error::new(
+                MinusOneErrorKind::Encoding,
+                "the encoded command is not base64",
+            )
+        })?;
+    decode("unicode", &bytes).ok_or_else(|| {
+        Error::new(
+            MinusOneErrorKind::Encoding,
+            "the encoded command is not UTF-16LE",
+        )
+    })
+}
@@ -1,6 +1,7 @@
 use crate::ps::Powershell;
 use crate::ps::Powershell::Raw;
-use crate::ps::Value::{Bool, Num, Str};
+use crate::ps::Value::{Bool, Float, Num, Str};
+use crate::ps::math::as_double;
 use crate::tree::Node;
 
 pub fn infer_comparison(
@@ -53,6 +54,23 @@ pub fn infer_comparison(
             Some(left_value < right_value)
         }
 
+        // Double comparison, integers are promoted
+        (
+            Some(Raw(left_value @ (Num(_) | Float(_)))),
+            operator @ ("-eq" | "-ne" | "-ge" | "-gt" | "-le" | "-lt"),
+            Some(Raw(right_value @ (Num(_) | Float(_)))),
+        ) => {
+            let (left_value, right_value) = (as_double(left_value)?, as_double(right_value)?);
+            Some(match operator {
+                "-eq" => left_value == right_value,
+                "-ne" => left_value != right_value,
+                "-ge" => left_value >= right_value,
+                "-gt" => left_value > right_value,
+                "-le" => left_value <= right_value,
+                _ => left_value < right_value,
+            })
+        }
+
         // Boolean comparison
         // Seems to be standardized with Rust???
         (Some(Raw(Bool(left_value))), "-eq", Some(Raw(Bool(right_value)))) => {
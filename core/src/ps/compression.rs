@@ -1,18 +1,20 @@
-use crate::error::MinusOneResult;
+use crate::error::{Error, MinusOneErrorKind, MinusOneResult};
 use crate::ps::Powershell;
-use crate::ps::Powershell::{Raw, Stream, Type};
-use crate::ps::Value::Str;
+use crate::ps::Powershell::{Bytes, Raw, Stream, Type, ZipArchive, ZipEntry};
+use crate::ps::Value::{Bool, Num, Str};
+use crate::ps::cmdlets::{BoundParameters, resolved_command_name};
+use crate::ps::convert::arguments;
 use crate::ps::tool::StringTool;
 use crate::ps::utils::bytes::*;
 use crate::ps::utils::string::decode;
 use crate::rule::RuleMut;
 use crate::tree::{ControlFlow, Node, NodeMut};
 use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
-use log::trace;
+use log::{trace, warn};
 use std::io::Read;
 
 /// Cap on decompressed output, so a crafted archive can't be used to exhaust memory.
-const MAX_DECOMPRESSED_SIZE: u64 = 100 * 1024 * 1024;
+pub(crate) const MAX_DECOMPRESSED_SIZE: u64 = 100 * 1024 * 1024;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum CompressionKind {
@@ -30,7 +32,14 @@ fn is_memory_stream_typename(typename: &str) -> bool {
 }
 
 fn is_stream_reader_typename(typename: &str) -> bool {
-    strip_system_prefix(typename) == "io.streamreader"
+    matches!(
+        strip_system_prefix(typename),
+        "io.streamreader" | "io.binaryreader"
+    )
+}
+
+fn is_zip_archive_typename(typename: &str) -> bool {
+    strip_system_prefix(typename) == "io.compression.ziparchive"
 }
 
 fn is_compression_mode_typename(typename: &str) -> bool {
@@ -46,7 +55,7 @@ fn compression_kind_of(typename: &str) -> Option<CompressionKind> {
     }
 }
 
-fn decompress(kind: CompressionKind, data: &[u8]) -> Option<Vec<u8>> {
+fn decompress(kind: CompressionKind, data: &[u8]) -> MinusOneResult<Vec<u8>> {
     let mut out = Vec::new();
     match kind {
         CompressionKind::Gzip => GzDecoder::new(data)
@@ -58,9 +67,193 @@ fn decompress(kind: CompressionKind, data: &[u8]) -> Option<Vec<u8>> {
         CompressionKind::ZLib => ZlibDecoder::new(data)
             .take(MAX_DECOMPRESSED_SIZE)
             .read_to_end(&mut out),
+    }?;
+    Ok(out)
+}
+
+/// An entry of an in-memory zip archive
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveEntry {
+    /// Full name of the entry, with `/` as separator
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+impl ArchiveEntry {
+    /// `Name`, `FullName` and `Length` of a `ZipArchiveEntry`
+    fn member(&self, member: &str) -> Option<Powershell> {
+        match member {
+            "fullname" => Some(Raw(Str(self.name.clone()))),
+            "name" => Some(Raw(Str(self
+                .name
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_string()))),
+            "length" => Some(Raw(Num(self.data.len() as i64))),
+            _ => None,
+        }
+    }
+}
+
+fn invalid_zip(message: &str) -> Error {
+    Error::new(MinusOneErrorKind::Decompression, message)
+}
+
+fn read_le(data: &[u8], offset: usize, size: usize) -> MinusOneResult<usize> {
+    let bytes = data
+        .get(offset..offset + size)
+        .ok_or_else(|| invalid_zip("truncated zip archive"))?;
+    Ok(bytes
+        .iter()
+        .rev()
+        .fold(0, |value, byte| (value << 8) | *byte as usize))
+}
+
+/// Entries of a zip archive, read from its central directory
+///
+/// Only stored and deflated entries are supported, like `System.IO.Compression`.
+/// The whole content is bounded by the same cap as the compressed streams.
+///
+/// # Example
+/// ```
+/// use minusone::ps::compression::read_zip;
+///
+/// assert!(read_zip(b"not a zip").is_err());
+/// ```
+pub fn read_zip(data: &[u8]) -> MinusOneResult<Vec<ArchiveEntry>> {
+    // the end of central directory record is followed by a comment of at most 64KiB
+    let end = (0..=data.len().saturating_sub(22))
+        .rev()
+        .take(0x10000 + 1)
+        .find(|offset| data[*offset..].starts_with(b"PK\x05\x06"))
+        .ok_or_else(|| invalid_zip("end of central directory not found"))?;
+
+    let count = read_le(data, end + 10, 2)?;
+    let mut offset = read_le(data, end + 16, 4)?;
+    let mut remaining = MAX_DECOMPRESSED_SIZE;
+    let mut entries = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        if !data[offset.min(data.len())..].starts_with(b"PK\x01\x02") {
+            return Err(invalid_zip("invalid central directory entry"));
+        }
+        let method = read_le(data, offset + 10, 2)?;
+        let compressed_size = read_le(data, offset + 20, 4)?;
+        let name_size = read_le(data, offset + 28, 2)?;
+        let extra_size = read_le(data, offset + 30, 2)?;
+        let comment_size = read_le(data, offset + 32, 2)?;
+        let header = read_le(data, offset + 42, 4)?;
+        let name = data
+            .get(offset + 46..offset + 46 + name_size)
+            .ok_or_else(|| invalid_zip("truncated zip archive"))?;
+        offset += 46 + name_size + extra_size + comment_size;
+
+        if !data[header.min(data.len())..].starts_with(b"PK\x03\x04") {
+            return Err(invalid_zip("invalid local file header"));
+        }
+        let start = header + 30 + read_le(data, header + 26, 2)? + read_le(data, header + 28, 2)?;
+        let raw = data
+            .get(start..start + compressed_size)
+            .ok_or_else(|| invalid_zip("truncated zip archive"))?;
+
+        let mut content = Vec::new();
+        match method {
+            0 => Read::take(raw, remaining).read_to_end(&mut content),
+            8 => DeflateDecoder::new(raw)
+                .take(remaining)
+                .read_to_end(&mut content),
+            _ => return Err(invalid_zip("unsupported zip compression method")),
+        }?;
+        remaining -= content.len() as u64;
+
+        entries.push(ArchiveEntry {
+            name: String::from_utf8_lossy(name).to_string(),
+            data: content,
+        });
+    }
+    Ok(entries)
+}
+
+/// Bytes consumed by a `BinaryReader`/`Stream` read method, and the value it returns
+fn binary_read(
+    bytes: &[u8],
+    member: &str,
+    arguments: &[Powershell],
+) -> Option<(usize, Powershell)> {
+    let integer = |size: usize, signed: bool| {
+        let mut buffer = [0u8; 8];
+        buffer[..size].copy_from_slice(bytes.get(..size)?);
+        let value = u64::from_le_bytes(buffer);
+        let value = if signed {
+            let shift = 64 - size * 8;
+            ((value << shift) as i64) >> shift
+        } else {
+            i64::try_from(value).ok()?
+        };
+        Some((size, Raw(Num(value))))
+    };
+
+    match (member, arguments) {
+        ("readbytes", [Raw(Num(count))]) if *count >= 0 => {
+            let count = (*count as usize).min(bytes.len());
+            Some((count, Bytes(bytes[..count].to_vec())))
+        }
+        ("readbyte", []) => integer(1, false),
+        ("readsbyte", []) => integer(1, true),
+        ("readboolean", []) => bytes.first().map(|byte| (1, Raw(Bool(*byte != 0)))),
+        ("readint16", []) => integer(2, true),
+        ("readuint16", []) => integer(2, false),
+        ("readint32", []) => integer(4, true),
+        ("readuint32", []) => integer(4, false),
+        ("readint64", []) => integer(8, true),
+        _ => None,
+    }
+}
+
+/// Effect of a method call on a stream tracked by a variable
+///
+/// The stream value holds the bytes left to read : reads consume them, writes append to them.
+/// Returns false when the effect is unknown, like a `Read` in a buffer.
+pub fn call_stream(bytes: &mut Vec<u8>, member: &str, arguments: &[Powershell]) -> bool {
+    match (member, arguments) {
+        ("write", [data]) => match bytes_from_data(data) {
+            Some(data) => {
+                bytes.extend(data);
+                true
+            }
+            None => false,
+        },
+        ("write", [data, Raw(Num(offset)), Raw(Num(count))]) if *offset >= 0 && *count >= 0 => {
+            match bytes_from_data(data)
+                .zip(offset.checked_add(*count))
+                .and_then(|(data, end)| {
+                    data.get(*offset as usize..end as usize).map(<[u8]>::to_vec)
+                }) {
+                Some(data) => {
+                    bytes.extend(data);
+                    true
+                }
+                None => false,
+            }
+        }
+        ("writebyte", [Raw(Num(byte))]) if (0..=255).contains(byte) => {
+            bytes.push(*byte as u8);
+            true
+        }
+        ("readtoend" | "copyto", _) => {
+            bytes.clear();
+            true
+        }
+        ("toarray" | "getbuffer" | "flush" | "close" | "dispose", []) => true,
+        (member, arguments) => match binary_read(bytes, member, arguments) {
+            Some((size, _)) => {
+                bytes.drain(..size);
+                true
+            }
+            None => false,
+        },
     }
-    .ok()?;
-    Some(out)
 }
 
 fn comma_wrapped_bytes(node: &Node<'_, Powershell>) -> Option<Vec<u8>> {
@@ -113,7 +306,8 @@ fn new_object_with_args<'a>(
     Ok(Some((typename_node.text()?.to_lowercase(), args_list)))
 }
 
-/// Resolves `MemoryStream` / `GzipStream` / `DeflateStream` / `ZLibStream` / `StreamReader` chain
+/// Resolves `MemoryStream` / `GzipStream` / `DeflateStream` / `ZLibStream` / `StreamReader` /
+/// `BinaryReader` / `ZipArchive` chain
 ///
 /// ```powershell
 /// $s = New-Object IO.MemoryStream(,[Convert]::FromBase64String("H4s..."))
@@ -121,11 +315,15 @@ fn new_object_with_args<'a>(
 /// ```
 ///
 /// - `New-Object IO.MemoryStream(,<bytes>)` is tracked as a [`Powershell::Stream`] wrapping the
-///   backing buffer.
+///   backing buffer, `New-Object IO.MemoryStream` as an empty one.
 /// - `New-Object IO.Compression.{Gzip,Deflate,ZLib}Stream(<stream>, [IO.Compression.CompressionMode]::Decompress)`
 ///   decompresses eagerly and is tracked as a `Stream` over the decompressed bytes.
-/// - `New-Object IO.StreamReader(<stream>)` just carries the wrapped `Stream` forward, later
-///   consumed by [`StreamReadToEnd`].
+/// - `New-Object IO.StreamReader(<stream>)` and `New-Object IO.BinaryReader(<stream>)` just carry
+///   the wrapped `Stream` forward, later consumed by [`StreamReadToEnd`] and [`StreamRead`].
+/// - `New-Object IO.Compression.ZipArchive(<stream>)` reads the archive in memory, as a
+///   [`Powershell::ZipArchive`].
+///
+/// The `[T]::new(...)` syntax is handled the same way.
 ///
 /// Also resolves `[System.IO.Compression.CompressionMode]::Decompress`/`::Compress` to `compression.mode.*`
 ///
@@ -199,50 +397,95 @@ impl<'a> RuleMut<'a> for StreamType {
             return Ok(());
         }
 
-        if let Some((typename, args_list)) = new_object_with_args(&view)? {
-            let Some(argument_expression_list) = args_list.named_child("argument_expression_list")
-            else {
-                return Ok(());
-            };
-
-            if is_memory_stream_typename(&typename)
-                && argument_expression_list.child_count() == 1
-                && let Some(arg) = argument_expression_list.child(0)
-                && let Some(bytes) = comma_wrapped_bytes(&arg)
-            {
-                trace!(
-                    "StreamType (L): Setting node with MemoryStream buffer of {} bytes",
-                    bytes.len()
-                );
-                node.set(Stream(bytes));
-            } else if let Some(kind) = compression_kind_of(&typename)
-                && let (Some(arg_stream), Some(arg_mode)) = (
-                    argument_expression_list.child(0),
-                    argument_expression_list.child(2),
-                )
-                && let Some(source) = arg_stream.data().and_then(bytes_from_data)
-                && let Some(Type(mode_tag)) = arg_mode.data()
-                && mode_tag == "compression.mode.decompress"
-                && let Some(result) = decompress(kind, &source)
-            {
-                trace!(
-                    "StreamType (L): Setting node with decompressed buffer of {} bytes",
-                    result.len()
-                );
-                node.set(Stream(result));
-            } else if is_stream_reader_typename(&typename)
-                && argument_expression_list.child_count() == 1
-                && let Some(arg) = argument_expression_list.child(0)
-                && let Some(Stream(bytes)) = arg.data()
-            {
-                trace!("StreamType (L): Setting node with StreamReader over known buffer");
-                node.set(Stream(bytes.clone()));
-            }
+        let object = if let Some((typename, args_list)) = new_object_with_args(&view)? {
+            new_stream_object(&typename, &arguments(&args_list))
+        } else if view.kind() == "command"
+            && let Some(command_name) = view.named_child("command_name")
+            && resolved_command_name(&command_name)? == "new-object"
+            && let Some(mut parameters) = BoundParameters::bind(&view, &[("typename", false)])?
+            && let (Some(typename), None) = (
+                parameters.take("typename"),
+                parameters.positional.pop_front(),
+            )
+        {
+            // New-Object IO.MemoryStream
+            new_stream_object(&typename.text()?.to_lowercase(), &[])
+        } else if view.kind() == "invokation_expression"
+            && let (Some(type_node), Some(op), Some(member_name), Some(args_list)) =
+                (view.child(0), view.child(1), view.child(2), view.child(3))
+            && op.text()? == "::"
+            && member_name.text()?.to_string().normalize() == "new"
+            && let Some(Type(typename)) = type_node.data()
+        {
+            new_stream_object(typename, &arguments(&args_list))
+        } else {
+            None
+        };
+
+        if let Some(object) = object {
+            node.set(object);
         }
         Ok(())
     }
 }
 
+/// Stream object built by a constructor, from its arguments
+fn new_stream_object(typename: &str, arguments: &[Node<Powershell>]) -> Option<Powershell> {
+    if is_memory_stream_typename(typename) {
+        let bytes = match arguments {
+            [] => vec![],
+            [buffer] => comma_wrapped_bytes(buffer)?,
+            _ => return None,
+        };
+        trace!(
+            "StreamType (L): Setting node with MemoryStream buffer of {} bytes",
+            bytes.len()
+        );
+        Some(Stream(bytes))
+    } else if let Some(kind) = compression_kind_of(typename) {
+        let [stream, mode, ..] = arguments else {
+            return None;
+        };
+        let source = stream.data().and_then(bytes_from_data)?;
+        match mode.data() {
+            Some(Type(mode_tag)) if mode_tag == "compression.mode.decompress" => (),
+            _ => return None,
+        }
+        let result = decompress(kind, &source)
+            .inspect_err(|e| warn!("StreamType (L): {}: {:?}", e, e))
+            .ok()?;
+        trace!(
+            "StreamType (L): Setting node with decompressed buffer of {} bytes",
+            result.len()
+        );
+        Some(Stream(result))
+    } else if is_stream_reader_typename(typename) {
+        let [stream] = arguments else {
+            return None;
+        };
+        let Some(Stream(bytes)) = stream.data() else {
+            return None;
+        };
+        trace!("StreamType (L): Setting node with reader over known buffer");
+        Some(Stream(bytes.clone()))
+    } else if is_zip_archive_typename(typename) {
+        let [stream, ..] = arguments else {
+            return None;
+        };
+        let source = stream.data().and_then(bytes_from_data)?;
+        let entries = read_zip(&source)
+            .inspect_err(|e| warn!("StreamType (L): {}: {:?}", e, e))
+            .ok()?;
+        trace!(
+            "StreamType (L): Setting node with zip archive of {} entries",
+            entries.len()
+        );
+        Some(ZipArchive(entries))
+    } else {
+        None
+    }
+}
+
 /// This rule infers `ReadToEnd()` calls on a resolved [`StreamType`] stream.
 ///
 /// # Example
@@ -287,3 +530,243 @@ impl<'a> RuleMut<'a> for StreamReadToEnd {
         Ok(())
     }
 }
+
+/// This rule infers reads on a resolved [`StreamType`] stream
+///
+/// - `.ToArray()` and `.GetBuffer()` return the bytes of the stream
+/// - `BinaryReader` methods : `.ReadBytes(n)`, `.ReadByte()`, `.ReadInt32()`...
+/// - `.Length`
+///
+/// Calls on a variable move it forward, or fill it for `Write`/`CopyTo`, as tracked by
+/// [`crate::ps::var::Var`].
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::integer::ParseInt;
+/// use minusone::ps::method::DecodeBase64;
+/// use minusone::ps::var::Var;
+/// use minusone::ps::compression::{StreamType, StreamRead};
+///
+/// let mut tree = build_powershell_tree(
+///     r#"$r = New-Object IO.BinaryReader([IO.MemoryStream]::new([Convert]::FromBase64String("AwAAAGFiYw==")))
+/// $n = $r.ReadInt32()
+/// $r.ReadBytes($n)"#
+/// ).unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseType::default(),
+///     ParseString::default(),
+///     ParseInt::default(),
+///     DecodeBase64::default(),
+///     Var::default(),
+///     StreamType::default(),
+///     StreamRead::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert!(ps_litter_view.output.ends_with("@(97, 98, 99)"));
+/// ```
+#[derive(Default)]
+pub struct StreamRead;
+
+impl<'a> RuleMut<'a> for StreamRead {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+
+        let result = match view.kind() {
+            "member_access" => match (view.child(0), view.child(1), view.child(2)) {
+                (Some(object), Some(op), Some(member_name)) if op.text()? == "." => {
+                    match (
+                        object.data(),
+                        member_name.text()?.to_string().normalize().as_str(),
+                    ) {
+                        (Some(Stream(bytes)), "length") => Some(Raw(Num(bytes.len() as i64))),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            "invokation_expression" => {
+                let (Some(object), Some(op), Some(member_name), Some(args_list)) =
+                    (view.child(0), view.child(1), view.child(2), view.child(3))
+                else {
+                    return Ok(());
+                };
+                let Some(Stream(bytes)) = object.data() else {
+                    return Ok(());
+                };
+                if op.text()? != "." {
+                    return Ok(());
+                }
+                let member = member_name.text()?.to_string().normalize();
+                let Some(arguments) = arguments(&args_list)
+                    .iter()
+                    .map(|argument| argument.data().cloned())
+                    .collect::<Option<Vec<Powershell>>>()
+                else {
+                    return Ok(());
+                };
+
+                match (member.as_str(), &arguments[..]) {
+                    ("toarray" | "getbuffer", []) => Some(Bytes(bytes.clone())),
+                    (member, arguments) => binary_read(bytes, member, arguments).map(|(_, v)| v),
+                }
+            }
+            _ => None,
+        };
+
+        if let Some(result) = result {
+            trace!("StreamRead (L): Setting node with {:?}", result);
+            node.set(result);
+        }
+        Ok(())
+    }
+}
+
+/// This rule infers the entries of a resolved in-memory `ZipArchive`
+///
+/// - `.GetEntry('name')`, `.Entries[n]` and `.Entries.Count`
+/// - `.Open()` on an entry returns a [`Powershell::Stream`] over its content
+/// - `.Name`, `.FullName` and `.Length` of an entry
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::integer::ParseInt;
+/// use minusone::ps::method::DecodeBase64;
+/// use minusone::ps::compression::{StreamType, StreamReadToEnd, ZipArchiveRead};
+///
+/// let mut tree = build_powershell_tree(
+///     r#"(New-Object IO.StreamReader([IO.Compression.ZipArchive]::new([IO.MemoryStream]::new([Convert]::FromBase64String("UEsDBBQAAAAAAAAAIQDlK2BCEAAAABAAAAAFAAAAYS5wczFIZWxsbywgbWludXNvbmUhUEsBAhQDFAAAAAAAAAAhAOUrYEIQAAAAEAAAAAUAAAAAAAAAAAAAAIABAAAAAGEucHMxUEsFBgAAAAABAAEAMwAAADMAAAAAAA=="))).GetEntry('a.ps1').Open())).ReadToEnd()"#
+/// ).unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseType::default(),
+///     ParseString::default(),
+///     ParseInt::default(),
+///     DecodeBase64::default(),
+///     StreamType::default(),
+///     StreamReadToEnd::default(),
+///     ZipArchiveRead::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "\"Hello, minusone!\"");
+/// ```
+#[derive(Default)]
+pub struct ZipArchiveRead;
+
+impl<'a> RuleMut<'a> for ZipArchiveRead {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+
+        let result = match view.kind() {
+            "member_access" => match (view.child(0), view.child(1), view.child(2)) {
+                (Some(object), Some(op), Some(member_name)) if op.text()? == "." => {
+                    match (
+                        object.data(),
+                        member_name.text()?.to_string().normalize().as_str(),
+                    ) {
+                        (Some(ZipArchive(entries)), "entries") => Some(ZipArchive(entries.clone())),
+                        (Some(ZipArchive(entries)), "count") => {
+                            Some(Raw(Num(entries.len() as i64)))
+                        }
+                        (Some(ZipEntry(entry)), member) => entry.member(member),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            "element_access" => match (
+                view.child(0).and_then(|n| n.data().cloned()),
+                view.child(2).and_then(|n| n.data().cloned()),
+            ) {
+                (Some(ZipArchive(entries)), Some(Raw(Num(index)))) => {
+                    let index = if index < 0 {
+                        entries.len() as i64 + index
+                    } else {
+                        index
+                    };
+                    usize::try_from(index)
+                        .ok()
+                        .and_then(|index| entries.get(index))
+                        .map(|entry| ZipEntry(entry.clone()))
+                }
+                _ => None,
+            },
+            "invokation_expression" => {
+                let (Some(object), Some(op), Some(member_name), Some(args_list)) =
+                    (view.child(0), view.child(1), view.child(2), view.child(3))
+                else {
+                    return Ok(());
+                };
+                if op.text()? != "." {
+                    return Ok(());
+                }
+                let member = member_name.text()?.to_string().normalize();
+                let arguments = arguments(&args_list);
+
+                match (object.data(), member.as_str(), &arguments[..]) {
+                    // entry names are case sensitive
+                    (Some(ZipArchive(entries)), "getentry", [name]) => match name.data() {
+                        Some(Raw(Str(name))) => entries
+                            .iter()
+                            .find(|entry| entry.name == *name)
+                            .map(|entry| ZipEntry(entry.clone())),
+                        _ => None,
+                    },
+                    (Some(ZipEntry(entry)), "open", []) => Some(Stream(entry.data.clone())),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        if let Some(result) = result {
+            trace!("ZipArchiveRead (L): Setting node with {:?}", result);
+            node.set(result);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,651 @@
+use crate::error::MinusOneResult;
+use crate::ps::Powershell::{self, Bytes, PSItem, Raw, Type};
+use crate::ps::Value::{self, Bool, Float, Num, Str};
+use crate::ps::math::double_to_integer;
+use crate::ps::tool::StringTool;
+use crate::ps::utils::bytes::*;
+use crate::rule::RuleMut;
+use crate::tree::{ControlFlow, Node, NodeMut};
+use base64::{Engine as _, engine::general_purpose};
+use log::trace;
+
+/// .NET integer types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegerType {
+    SByte,
+    Byte,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+}
+
+impl IntegerType {
+    /// From a type name as inferred by `ParseType` : int, [System.Int32], byte...
+    pub fn from_typename(typename: &str) -> Option<Self> {
+        match typename.strip_prefix("system.").unwrap_or(typename) {
+            "sbyte" => Some(IntegerType::SByte),
+            "byte" => Some(IntegerType::Byte),
+            "int16" | "short" => Some(IntegerType::Int16),
+            "uint16" | "ushort" => Some(IntegerType::UInt16),
+            "int32" | "int" => Some(IntegerType::Int32),
+            "uint32" | "uint" => Some(IntegerType::UInt32),
+            "int64" | "long" => Some(IntegerType::Int64),
+            "uint64" | "ulong" => Some(IntegerType::UInt64),
+            _ => None,
+        }
+    }
+
+    /// From the name of a [Convert] method : ToInt32, ToByte...
+    fn from_convert_method(method: &str) -> Option<Self> {
+        method
+            .strip_prefix("to")
+            .and_then(IntegerType::from_typename)
+    }
+
+    /// Type of an integer literal, Powershell uses the smallest of int and long
+    pub fn of_literal(value: i64) -> Self {
+        if i32::try_from(value).is_ok() {
+            IntegerType::Int32
+        } else {
+            IntegerType::Int64
+        }
+    }
+
+    pub fn bits(self) -> u32 {
+        match self {
+            IntegerType::SByte | IntegerType::Byte => 8,
+            IntegerType::Int16 | IntegerType::UInt16 => 16,
+            IntegerType::Int32 | IntegerType::UInt32 => 32,
+            IntegerType::Int64 | IntegerType::UInt64 => 64,
+        }
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(
+            self,
+            IntegerType::SByte | IntegerType::Int16 | IntegerType::Int32 | IntegerType::Int64
+        )
+    }
+
+    fn range(self) -> (i128, i128) {
+        let bits = self.bits();
+        if self.is_signed() {
+            (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+        } else {
+            (0, (1i128 << bits) - 1)
+        }
+    }
+
+    /// The value if it fits in the type, values above i64 are not tracked
+    pub fn check(self, value: i128) -> Option<i64> {
+        let (min, max) = self.range();
+        if value < min || value > max {
+            return None;
+        }
+        i64::try_from(value).ok()
+    }
+
+    /// Reinterpret raw bits as a value of this type, like a hex parsing does
+    fn reinterpret(self, bits: u128) -> Option<i64> {
+        let width = self.bits();
+        if bits >> width != 0 {
+            return None;
+        }
+        if self.is_signed() && bits >> (width - 1) == 1 {
+            self.check(bits as i128 - (1i128 << width))
+        } else {
+            self.check(bits as i128)
+        }
+    }
+
+    /// Two's complement bits of a value of this type
+    fn to_bits(self, value: i64) -> u128 {
+        let mask = (1u128 << self.bits()) - 1;
+        (value as i128 as u128) & mask
+    }
+
+    /// Little endian bytes, as returned by [BitConverter]::GetBytes
+    fn to_le_bytes(self, value: i64) -> Vec<u8> {
+        value.to_le_bytes()[..(self.bits() / 8) as usize].to_vec()
+    }
+}
+
+/// Parse an integer in base 2, 8, 10 or 16, as [Convert]::ToInt32(s, base) does
+///
+/// # Example
+/// ```
+/// use minusone::ps::convert::{IntegerType, parse_integer};
+///
+/// assert_eq!(parse_integer("0x1F", 16, IntegerType::Int32), Some(31));
+/// assert_eq!(parse_integer("ffffffff", 16, IntegerType::Int32), Some(-1));
+/// assert_eq!(parse_integer("1ff", 16, IntegerType::Byte), None);
+/// ```
+pub fn parse_integer(s: &str, radix: u32, integer_type: IntegerType) -> Option<i64> {
+    match radix {
+        10 => integer_type.check(s.trim().parse::<i128>().ok()?),
+        2 | 8 | 16 => {
+            let digits = if radix == 16 {
+                s.strip_prefix("0x")
+                    .or_else(|| s.strip_prefix("0X"))
+                    .unwrap_or(s)
+            } else {
+                s
+            };
+            // no sign in these bases
+            if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+                return None;
+            }
+            integer_type.reinterpret(u128::from_str_radix(digits, radix).ok()?)
+        }
+        _ => None,
+    }
+}
+
+/// Render an integer in base 2, 8, 10 or 16, as [Convert]::ToString(n, base) does
+///
+/// Negative values are rendered as two's complement in the other bases
+pub fn integer_to_string(value: i64, radix: u32, integer_type: IntegerType) -> Option<String> {
+    let bits = integer_type.to_bits(value);
+    match radix {
+        2 => Some(format!("{:b}", bits)),
+        8 => Some(format!("{:o}", bits)),
+        10 => Some(value.to_string()),
+        16 => Some(format!("{:x}", bits)),
+        _ => None,
+    }
+}
+
+/// Apply a conversion on a value, or on each value of a pipeline item
+fn map_values(data: &Powershell, f: impl Fn(&Value) -> Option<Value>) -> Option<Powershell> {
+    match data {
+        Raw(value) => f(value).map(Raw),
+        PSItem(values) => values
+            .iter()
+            .map(f)
+            .collect::<Option<Vec<Value>>>()
+            .map(PSItem),
+        _ => None,
+    }
+}
+
+/// Arguments of a method call, without separators
+pub(crate) fn arguments<'a>(args_list: &Node<'a, Powershell>) -> Vec<Node<'a, Powershell>> {
+    match args_list.named_child("argument_expression_list") {
+        Some(argument_expression_list) => argument_expression_list
+            .iter()
+            .filter(|argument| argument.kind() != ",")
+            .collect(),
+        None => vec![],
+    }
+}
+
+fn is_convert_typename(typename: &str) -> bool {
+    typename.strip_prefix("system.").unwrap_or(typename) == "convert"
+}
+
+fn is_bitconverter_typename(typename: &str) -> bool {
+    typename.strip_prefix("system.").unwrap_or(typename) == "bitconverter"
+}
+
+fn is_number_styles_typename(typename: &str) -> bool {
+    typename.strip_prefix("system.").unwrap_or(typename) == "globalization.numberstyles"
+}
+
+/// Radix used by a [Globalization.NumberStyles] value
+fn number_styles_radix(styles: &Powershell) -> Option<u32> {
+    let styles = match styles {
+        Raw(Str(s)) => s.to_lowercase(),
+        Type(t) => t.strip_prefix("numberstyles.")?.to_string(),
+        _ => return None,
+    };
+    let styles: Vec<&str> = styles.split(',').map(str::trim).collect();
+    if styles
+        .iter()
+        .any(|s| *s == "hexnumber" || *s == "allowhexspecifier")
+    {
+        Some(16)
+    } else if styles.iter().all(|s| {
+        matches!(
+            *s,
+            "integer" | "none" | "allowleadingwhite" | "allowtrailingwhite" | "allowleadingsign"
+        )
+    }) {
+        Some(10)
+    } else {
+        None
+    }
+}
+
+/// [Convert]::ToInt32(v), [Convert]::ToByte(v, base) and friends
+fn convert_to_integer(
+    integer_type: IntegerType,
+    value: &Value,
+    radix: Option<u32>,
+) -> Option<Value> {
+    match (value, radix) {
+        (Str(s), Some(radix)) => parse_integer(s, radix, integer_type).map(Num),
+        (Str(s), None) => parse_integer(s, 10, integer_type).map(Num),
+        (Num(n), None) => integer_type.check(*n as i128).map(Num),
+        (Float(f), None) => double_to_integer(*f, integer_type).map(Num),
+        (Bool(b), None) => Some(Num(*b as i64)),
+        _ => None,
+    }
+}
+
+/// [Convert]::ToString(v) and [Convert]::ToString(n, base)
+fn convert_to_string(value: &Value, radix: Option<u32>) -> Option<Value> {
+    match (value, radix) {
+        (Num(n), Some(radix)) => integer_to_string(*n, radix, IntegerType::of_literal(*n)).map(Str),
+        (value, None) => Some(Str(value.to_string())),
+        _ => None,
+    }
+}
+
+/// [Convert]::ToChar(n)
+fn convert_to_char(value: &Value) -> Option<Value> {
+    match value {
+        Num(n) => u16::try_from(*n)
+            .ok()
+            .and_then(|n| char::from_u32(n as u32))
+            .map(|c| Str(c.to_string())),
+        Str(s) if s.chars().count() == 1 => Some(Str(s.clone())),
+        _ => None,
+    }
+}
+
+/// This rule infers numeric conversions
+///
+/// - `[Convert]::ToInt32('1F', 16)`, `[Convert]::ToByte('101', 2)`... parse a number in base 2, 8, 10 or 16
+/// - `[Convert]::ToString(255, 2)` renders a number in one of these bases
+/// - `[Convert]::ToChar(65)`
+/// - `[int]::Parse('42')`, `[byte]::Parse('ff', 'HexNumber')` with `[Globalization.NumberStyles]` resolved
+///
+/// Conversions are applied on each value of a pipeline item too,
+/// so `% { [Convert]::ToByte($_, 16) }` folds.
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::integer::ParseInt;
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::convert::ConvertNumber;
+///
+/// let mut tree = build_powershell_tree("[Convert]::ToInt32('1F', 16) + [Convert]::ToString(5, 2)").unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseString::default(),
+///     ParseInt::default(),
+///     ParseType::default(),
+///     ConvertNumber::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "31 + \"101\"");
+/// ```
+#[derive(Default)]
+pub struct ConvertNumber;
+
+impl<'a> RuleMut<'a> for ConvertNumber {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+
+        if view.kind() == "member_access"
+            && let (Some(type_node), Some(op), Some(member_name)) =
+                (view.child(0), view.child(1), view.child(2))
+            && op.text()? == "::"
+            && let Some(Type(typename)) = type_node.data()
+            && is_number_styles_typename(typename)
+        {
+            let member = member_name.text()?.to_string().normalize();
+            trace!(
+                "ConvertNumber (L): Setting node with number styles type: {}",
+                member
+            );
+            node.set(Type(format!("numberstyles.{}", member)));
+        } else if view.kind() == "invokation_expression"
+            && let (Some(type_node), Some(op), Some(member_name), Some(args_list)) =
+                (view.child(0), view.child(1), view.child(2), view.child(3))
+            && op.text()? == "::"
+            && let Some(Type(typename)) = type_node.data()
+        {
+            let member = member_name.text()?.to_string().normalize();
+            let arguments = arguments(&args_list);
+            let Some(value) = arguments.first().and_then(|argument| argument.data()) else {
+                return Ok(());
+            };
+
+            let result = if is_convert_typename(typename) {
+                let radix = match arguments.get(1).map(|argument| argument.data()) {
+                    None => None,
+                    Some(Some(Raw(Num(radix)))) if arguments.len() == 2 => {
+                        Some(u32::try_from(*radix).unwrap_or(0))
+                    }
+                    _ => return Ok(()),
+                };
+                match member.as_str() {
+                    "tostring" => map_values(value, |v| convert_to_string(v, radix)),
+                    "tochar" if radix.is_none() => map_values(value, convert_to_char),
+                    method => IntegerType::from_convert_method(method).and_then(|integer_type| {
+                        map_values(value, |v| convert_to_integer(integer_type, v, radix))
+                    }),
+                }
+            } else if let Some(integer_type) = IntegerType::from_typename(typename)
+                && member == "parse"
+            {
+                let radix = match arguments.get(1) {
+                    None => Some(10),
+                    Some(styles) if arguments.len() == 2 => {
+                        styles.data().and_then(number_styles_radix)
+                    }
+                    _ => None,
+                };
+                radix.and_then(|radix| {
+                    map_values(value, |v| match v {
+                        // Parse allows surrounding white spaces whatever the style
+                        Str(s) => parse_integer(s.trim(), radix, integer_type).map(Num),
+                        _ => None,
+                    })
+                })
+            } else {
+                None
+            };
+
+            if let Some(result) = result {
+                trace!(
+                    "ConvertNumber (L): Setting node with converted value: {:?}",
+                    result
+                );
+                node.set(result);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// This rule infers conversions between bytes and their text encodings
+///
+/// - `[Convert]::ToBase64String(bytes)`
+/// - `[Convert]::FromHexString('4142')` and `[Convert]::ToHexString(bytes)`
+/// - `[BitConverter]::ToString(bytes)` renders `41-42`
+/// - `[BitConverter]::GetBytes(n)` with the size of an int or a long
+/// - `[BitConverter]::ToInt32(bytes, index)`, `ToInt16`, `ToUInt32`...
+///
+/// Bytes are taken from byte arrays as well as decoded buffers.
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::encoding::{EncodingType, EncodingGetString};
+/// use minusone::ps::convert::ConvertBytes;
+///
+/// let mut tree = build_powershell_tree("[Text.Encoding]::UTF8.GetString([Convert]::FromHexString('6869'))").unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseString::default(),
+///     ParseType::default(),
+///     EncodingType::default(),
+///     EncodingGetString::default(),
+///     ConvertBytes::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "\"hi\"");
+/// ```
+#[derive(Default)]
+pub struct ConvertBytes;
+
+impl<'a> RuleMut<'a> for ConvertBytes {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+
+        if view.kind() != "invokation_expression" {
+            return Ok(());
+        }
+        let (Some(type_node), Some(op), Some(member_name), Some(args_list)) =
+            (view.child(0), view.child(1), view.child(2), view.child(3))
+        else {
+            return Ok(());
+        };
+        if op.text()? != "::" {
+            return Ok(());
+        }
+        let Some(Type(typename)) = type_node.data() else {
+            return Ok(());
+        };
+
+        let member = member_name.text()?.to_string().normalize();
+        let arguments = arguments(&args_list);
+        let Some(value) = arguments.first().and_then(|argument| argument.data()) else {
+            return Ok(());
+        };
+
+        let result = if is_convert_typename(typename) && arguments.len() == 1 {
+            match (member.as_str(), value) {
+                ("tobase64string", value) => bytes_from_data(value)
+                    .map(|bytes| Raw(Str(general_purpose::STANDARD.encode(bytes)))),
+                ("tohexstring", value) => {
+                    bytes_from_data(value).map(|bytes| Raw(Str(to_hex(&bytes).to_uppercase())))
+                }
+                ("fromhexstring", Raw(Str(s))) => from_hex(s).map(Bytes),
+                _ => None,
+            }
+        } else if is_bitconverter_typename(typename) {
+            match (member.as_str(), value, arguments.get(1).map(|a| a.data())) {
+                ("tostring", value, None) => bytes_from_data(value).map(|bytes| {
+                    Raw(Str(bytes
+                        .iter()
+                        .map(|b| format!("{:02X}", b))
+                        .collect::<Vec<String>>()
+                        .join("-")))
+                }),
+                ("getbytes", Raw(Num(n)), None) => {
+                    Some(Bytes(IntegerType::of_literal(*n).to_le_bytes(*n)))
+                }
+                ("getbytes", Raw(Bool(b)), None) => Some(Bytes(vec![*b as u8])),
+                (method, value, Some(Some(Raw(Num(index)))))
+                    if arguments.len() == 2 && method != "tostring" =>
+                {
+                    IntegerType::from_convert_method(method)
+                        .filter(|integer_type| integer_type.bits() > 8)
+                        .zip(bytes_from_data(value))
+                        .and_then(|(integer_type, bytes)| {
+                            let start = usize::try_from(*index).ok()?;
+                            let size = (integer_type.bits() / 8) as usize;
+                            let mut raw = [0u8; 16];
+                            raw[..size].copy_from_slice(bytes.get(start..start + size)?);
+                            integer_type.reinterpret(u128::from_le_bytes(raw))
+                        })
+                        .map(|n| Raw(Num(n)))
+                }
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        if let Some(result) = result {
+            trace!(
+                "ConvertBytes (L): Setting node with converted value: {:?}",
+                result
+            );
+            node.set(result);
+        }
+        Ok(())
+    }
+}
+
+/// Format a value as the format operator does for a `{0:spec}` item
+fn format_value(value: &Value, spec: Option<&str>) -> Option<String> {
+    let (Num(n), Some(spec)) = (value, spec.filter(|spec| !spec.is_empty())) else {
+        // strings and booleans ignore the format specifier
+        return Some(value.to_string());
+    };
+
+    // custom format made of zeros : {0:000}
+    if spec.chars().all(|c| c == '0') {
+        return Some(format!(
+            "{}{:0width$}",
+            if *n < 0 { "-" } else { "" },
+            n.unsigned_abs(),
+            width = spec.len()
+        ));
+    }
+
+    let mut chars = spec.chars();
+    let specifier = chars.next()?;
+    let precision = chars.as_str();
+    let precision: Option<usize> = if precision.is_empty() {
+        None
+    } else {
+        Some(precision.parse().ok()?)
+    };
+
+    match specifier {
+        'x' | 'X' => {
+            let hex = integer_to_string(*n, 16, IntegerType::of_literal(*n))?;
+            let hex = if specifier == 'X' {
+                hex.to_uppercase()
+            } else {
+                hex
+            };
+            Some(format!("{:0>width$}", hex, width = precision.unwrap_or(0)))
+        }
+        'd' | 'D' => Some(format!(
+            "{}{:0width$}",
+            if *n < 0 { "-" } else { "" },
+            n.unsigned_abs(),
+            width = precision.unwrap_or(0)
+        )),
+        'g' | 'G' if precision.is_none() => Some(n.to_string()),
+        'f' | 'F' | 'n' | 'N' => {
+            let digits = n.unsigned_abs().to_string();
+            let digits = if specifier == 'n' || specifier == 'N' {
+                // group by thousands
+                let mut grouped = String::new();
+                for (i, c) in digits.chars().enumerate() {
+                    if i > 0 && (digits.len() - i) % 3 == 0 {
+                        grouped.push(',');
+                    }
+                    grouped.push(c);
+                }
+                grouped
+            } else {
+                digits
+            };
+            let decimals = precision.unwrap_or(2);
+            Some(format!(
+                "{}{}{}{}",
+                if *n < 0 { "-" } else { "" },
+                digits,
+                if decimals > 0 { "." } else { "" },
+                "0".repeat(decimals)
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Composite formatting as done by the -f operator
+///
+/// Items are `{index[,alignment][:format]}`, braces are escaped by doubling them
+/// Returns None on an invalid format, or a format specifier that is not handled
+///
+/// # Example
+/// ```
+/// use minusone::ps::Value::{Num, Str};
+/// use minusone::ps::convert::format_composite;
+///
+/// assert_eq!(
+///     format_composite("0x{0:X4}{{{1,3}}}", &[Num(255), Str("a".to_string())]),
+///     Some("0x00FF{  a}".to_string())
+/// );
+/// assert_eq!(format_composite("{1}", &[Num(1)]), None);
+/// ```
+pub fn format_composite(format: &str, args: &[Value]) -> Option<String> {
+    let mut result = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                result.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                result.push('}');
+            }
+            '{' => {
+                let mut item = String::new();
+                loop {
+                    match chars.next()? {
+                        '}' => break,
+                        c => item.push(c),
+                    }
+                }
+
+                let (head, spec) = match item.split_once(':') {
+                    Some((head, spec)) => (head, Some(spec)),
+                    None => (item.as_str(), None),
+                };
+                let (index, alignment) = match head.split_once(',') {
+                    Some((index, alignment)) => (index, alignment.trim().parse::<i64>().ok()?),
+                    None => (head, 0),
+                };
+                let value = args.get(index.trim().parse::<usize>().ok()?)?;
+                let text = format_value(value, spec)?;
+
+                let width = alignment.unsigned_abs() as usize;
+                if alignment < 0 {
+                    result.push_str(&format!("{:<width$}", text));
+                } else {
+                    result.push_str(&format!("{:>width$}", text));
+                }
+            }
+            '}' => return None,
+            c => result.push(c),
+        }
+    }
+    Some(result)
+}
@@ -1,4 +1,4 @@
-use crate::error::MinusOneResult;
+use crate::error::{Error, MinusOneErrorKind, MinusOneResult};
 use crate::ps::Powershell;
 use crate::ps::Powershell::{Bytes, Crypto, Raw, Type};
 use crate::ps::Value::Num;
@@ -7,194 +7,247 @@ use crate::ps::utils::bytes::*;
 use crate::rule::RuleMut;
 use crate::tree::{ControlFlow, NodeMut};
 use aes::{Aes128, Aes192, Aes256};
-use cbc::cipher::block_padding::{NoPadding, Padding, Pkcs7};
-use cbc::cipher::{BlockModeDecrypt, BlockModeEncrypt, KeyInit, KeyIvInit};
-use log::trace;
+use cbc::cipher::block_padding::{AnsiX923, Iso10126, NoPadding, Padding, Pkcs7, ZeroPadding};
+use cbc::cipher::common::InnerInit;
+use cbc::cipher::{
+    BlockCipherDecrypt, BlockCipherEncrypt, BlockModeDecrypt, BlockModeEncrypt, InnerIvInit,
+    InvalidLength, KeyInit,
+};
+use des::{Des, TdesEde2, TdesEde3};
+use log::{trace, warn};
+use rc2::Rc2;
+
+/// Symmetric algorithms of `System.Security.Cryptography`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CipherAlgorithm {
+    #[default]
+    Aes,
+    Des,
+    TripleDes,
+    Rc2,
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum AesMode {
+pub enum CipherMode {
     Cbc,
     Ecb,
     Cfb8,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum AesPadding {
+pub enum CipherPadding {
     Pkcs7,
     None,
+    Zeros,
+    AnsiX923,
+    Iso10126,
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
-pub struct AesState {
+pub struct CipherState {
+    pub algorithm: CipherAlgorithm,
     pub key: Option<Vec<u8>>,
     pub iv: Option<Vec<u8>>,
-    pub mode: Option<AesMode>,
-    pub padding: Option<AesPadding>,
+    pub mode: Option<CipherMode>,
+    pub padding: Option<CipherPadding>,
     pub is_decrypt: Option<bool>,
 }
 
-fn aes_cbc_decrypt_padded<P: Padding>(key: &[u8], iv: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>> {
-    let mut buf = ciphertext.to_vec();
-    let plaintext: &[u8] = match key.len() {
-        16 => cbc::Decryptor::<Aes128>::new_from_slices(key, iv)
-            .ok()?
-            .decrypt_padded::<P>(&mut buf)
-            .ok()?,
-        24 => cbc::Decryptor::<Aes192>::new_from_slices(key, iv)
-            .ok()?
-            .decrypt_padded::<P>(&mut buf)
-            .ok()?,
-        32 => cbc::Decryptor::<Aes256>::new_from_slices(key, iv)
-            .ok()?
-            .decrypt_padded::<P>(&mut buf)
-            .ok()?,
-        _ => return None,
-    };
-    Some(plaintext.to_vec())
-}
+/// Names of the AES only versions, kept for existing users
+pub type AesMode = CipherMode;
+pub type AesPadding = CipherPadding;
+pub type AesState = CipherState;
 
-fn aes_cbc_encrypt_padded<P: Padding>(key: &[u8], iv: &[u8], plaintext: &[u8]) -> Option<Vec<u8>> {
-    let mut buf = vec![0u8; plaintext.len() + 16];
-    buf[..plaintext.len()].copy_from_slice(plaintext);
-    let ciphertext: &[u8] = match key.len() {
-        16 => cbc::Encryptor::<Aes128>::new_from_slices(key, iv)
-            .ok()?
-            .encrypt_padded::<P>(&mut buf, plaintext.len())
-            .ok()?,
-        24 => cbc::Encryptor::<Aes192>::new_from_slices(key, iv)
-            .ok()?
-            .encrypt_padded::<P>(&mut buf, plaintext.len())
-            .ok()?,
-        32 => cbc::Encryptor::<Aes256>::new_from_slices(key, iv)
-            .ok()?
-            .encrypt_padded::<P>(&mut buf, plaintext.len())
-            .ok()?,
-        _ => return None,
-    };
-    Some(ciphertext.to_vec())
+fn unknown_parameter(name: &str) -> Error {
+    Error::new(
+        MinusOneErrorKind::Decryption,
+        &format!("The cipher {} is not known", name),
+    )
 }
 
-fn aes_ecb_decrypt_padded<P: Padding>(key: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>> {
-    let mut buf = ciphertext.to_vec();
-    let plaintext: &[u8] = match key.len() {
-        16 => ecb::Decryptor::<Aes128>::new_from_slice(key)
-            .ok()?
-            .decrypt_padded::<P>(&mut buf)
-            .ok()?,
-        24 => ecb::Decryptor::<Aes192>::new_from_slice(key)
-            .ok()?
-            .decrypt_padded::<P>(&mut buf)
-            .ok()?,
-        32 => ecb::Decryptor::<Aes256>::new_from_slice(key)
-            .ok()?
-            .decrypt_padded::<P>(&mut buf)
-            .ok()?,
-        _ => return None,
-    };
-    Some(plaintext.to_vec())
+fn cbc_transform<C, P>(
+    cipher: C,
+    iv: &[u8],
+    is_decrypt: bool,
+    data: &[u8],
+) -> MinusOneResult<Vec<u8>>
+where
+    C: BlockCipherEncrypt + BlockCipherDecrypt,
+    P: Padding,
+{
+    if is_decrypt {
+        let mut buf = data.to_vec();
+        let plaintext =
+            cbc::Decryptor::<C>::inner_iv_slice_init(cipher, iv)?.decrypt_padded::<P>(&mut buf)?;
+        Ok(plaintext.to_vec())
+    } else {
+        let mut buf = vec![0u8; data.len() + C::block_size()];
+        buf[..data.len()].copy_from_slice(data);
+        let ciphertext = cbc::Encryptor::<C>::inner_iv_slice_init(cipher, iv)?
+            .encrypt_padded::<P>(&mut buf, data.len())?;
+        Ok(ciphertext.to_vec())
+    }
 }
 
-fn aes_ecb_encrypt_padded<P: Padding>(key: &[u8], plaintext: &[u8]) -> Option<Vec<u8>> {
-    let mut buf = vec![0u8; plaintext.len() + 16];
-    buf[..plaintext.len()].copy_from_slice(plaintext);
-    let ciphertext: &[u8] = match key.len() {
-        16 => ecb::Encryptor::<Aes128>::new_from_slice(key)
-            .ok()?
-            .encrypt_padded::<P>(&mut buf, plaintext.len())
-            .ok()?,
-        24 => ecb::Encryptor::<Aes192>::new_from_slice(key)
-            .ok()?
-            .encrypt_padded::<P>(&mut buf, plaintext.len())
-            .ok()?,
-        32 => ecb::Encryptor::<Aes256>::new_from_slice(key)
-            .ok()?
-            .encrypt_padded::<P>(&mut buf, plaintext.len())
-            .ok()?,
-        _ => return None,
-    };
-    Some(ciphertext.to_vec())
+fn ecb_transform<C, P>(cipher: C, is_decrypt: bool, data: &[u8]) -> MinusOneResult<Vec<u8>>
+where
+    C: BlockCipherEncrypt + BlockCipherDecrypt,
+    P: Padding,
+{
+    if is_decrypt {
+        let mut buf = data.to_vec();
+        let plaintext = ecb::Decryptor::<C>::inner_init(cipher).decrypt_padded::<P>(&mut buf)?;
+        Ok(plaintext.to_vec())
+    } else {
+        let mut buf = vec![0u8; data.len() + C::block_size()];
+        buf[..data.len()].copy_from_slice(data);
+        let ciphertext =
+            ecb::Encryptor::<C>::inner_init(cipher).encrypt_padded::<P>(&mut buf, data.len())?;
+        Ok(ciphertext.to_vec())
+    }
 }
 
-fn aes_cfb8_decrypt(key: &[u8], iv: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+fn cfb8_transform<C>(cipher: C, iv: &[u8], is_decrypt: bool, data: &[u8]) -> MinusOneResult<Vec<u8>>
+where
+    C: BlockCipherEncrypt,
+{
     let mut buf = data.to_vec();
-    match key.len() {
-        16 => cfb8::Decryptor::<Aes128>::new_from_slices(key, iv)
-            .ok()?
-            .decrypt(&mut buf),
-        24 => cfb8::Decryptor::<Aes192>::new_from_slices(key, iv)
-            .ok()?
-            .decrypt(&mut buf),
-        32 => cfb8::Decryptor::<Aes256>::new_from_slices(key, iv)
-            .ok()?
-            .decrypt(&mut buf),
-        _ => return None,
-    };
-    Some(buf)
+    if is_decrypt {
+        cfb8::Decryptor::<C>::inner_iv_slice_init(cipher, iv)
+            .map_err(|_| InvalidLength)?
+            .decrypt(&mut buf);
+    } else {
+        cfb8::Encryptor::<C>::inner_iv_slice_init(cipher, iv)
+            .map_err(|_| InvalidLength)?
+            .encrypt(&mut buf);
+    }
+    Ok(buf)
 }
 
-pub(crate) fn aes_cfb8_encrypt(key: &[u8], iv: &[u8], data: &[u8]) -> Option<Vec<u8>> {
-    let mut buf = data.to_vec();
-    match key.len() {
-        16 => cfb8::Encryptor::<Aes128>::new_from_slices(key, iv)
-            .ok()?
-            .encrypt(&mut buf),
-        24 => cfb8::Encryptor::<Aes192>::new_from_slices(key, iv)
-            .ok()?
-            .encrypt(&mut buf),
-        32 => cfb8::Encryptor::<Aes256>::new_from_slices(key, iv)
-            .ok()?
-            .encrypt(&mut buf),
-        _ => return None,
+fn padded_transform<C, P>(
+    cipher: C,
+    state: &CipherState,
+    is_decrypt: bool,
+    data: &[u8],
+) -> MinusOneResult<Vec<u8>>
+where
+    C: BlockCipherEncrypt + BlockCipherDecrypt,
+    P: Padding,
+{
+    let iv = || state.iv.as_deref().ok_or_else(|| unknown_parameter("IV"));
+    match state.mode.unwrap_or(CipherMode::Cbc) {
+        CipherMode::Cbc => cbc_transform::<C, P>(cipher, iv()?, is_decrypt, data),
+        CipherMode::Ecb => ecb_transform::<C, P>(cipher, is_decrypt, data),
+        CipherMode::Cfb8 => cfb8_transform(cipher, iv()?, is_decrypt, data),
+    }
+}
+
+fn block_transform<C>(
+    cipher: C,
+    state: &CipherState,
+    is_decrypt: bool,
+    data: &[u8],
+) -> MinusOneResult<Vec<u8>>
+where
+    C: BlockCipherEncrypt + BlockCipherDecrypt,
+{
+    match (state.padding.unwrap_or(CipherPadding::Pkcs7), is_decrypt) {
+        (CipherPadding::Pkcs7, _) => padded_transform::<C, Pkcs7>(cipher, state, is_decrypt, data),
+        // .NET leaves the zeros in the decrypted data
+        (CipherPadding::None, _) | (CipherPadding::Zeros, true) => {
+            padded_transform::<C, NoPadding>(cipher, state, is_decrypt, data)
+        }
+        (CipherPadding::Zeros, false) => {
+            padded_transform::<C, ZeroPadding>(cipher, state, is_decrypt, data)
+        }
+        (CipherPadding::AnsiX923, _) => {
+            padded_transform::<C, AnsiX923>(cipher, state, is_decrypt, data)
+        }
+        (CipherPadding::Iso10126, _) => {
+            padded_transform::<C, Iso10126>(cipher, state, is_decrypt, data)
+        }
+    }
+}
+
+pub(crate) fn aes_cfb8_encrypt(key: &[u8], iv: &[u8], data: &[u8]) -> MinusOneResult<Vec<u8>> {
+    let state = CipherState {
+        key: Some(key.to_vec()),
+        iv: Some(iv.to_vec()),
+        mode: Some(CipherMode::Cfb8),
+        ..CipherState::default()
     };
-    Some(buf)
+    cipher_transform(&state, false, data)
 }
 
-pub(crate) fn aes_transform(state: &AesState, is_decrypt: bool, data: &[u8]) -> Option<Vec<u8>> {
-    let key = state.key.as_ref()?;
-    let mode = state.mode.unwrap_or(AesMode::Cbc);
-    let padding = state.padding.unwrap_or(AesPadding::Pkcs7);
-
-    match (mode, is_decrypt) {
-        (AesMode::Cbc, true) => {
-            let iv = state.iv.as_ref()?;
-            match padding {
-                AesPadding::Pkcs7 => aes_cbc_decrypt_padded::<Pkcs7>(key, iv, data),
-                AesPadding::None => aes_cbc_decrypt_padded::<NoPadding>(key, iv, data),
-            }
+/// Run a `CreateDecryptor`/`CreateEncryptor` transform on data
+///
+/// The key length selects the variant of the algorithm, like .NET does
+pub(crate) fn cipher_transform(
+    state: &CipherState,
+    is_decrypt: bool,
+    data: &[u8],
+) -> MinusOneResult<Vec<u8>> {
+    let key = state
+        .key
+        .as_deref()
+        .ok_or_else(|| unknown_parameter("key"))?;
+
+    match (state.algorithm, key.len()) {
+        (CipherAlgorithm::Aes, 16) => {
+            block_transform(Aes128::new_from_slice(key)?, state, is_decrypt, data)
         }
-        (AesMode::Cbc, false) => {
-            let iv = state.iv.as_ref()?;
-            match padding {
-                AesPadding::Pkcs7 => aes_cbc_encrypt_padded::<Pkcs7>(key, iv, data),
-                AesPadding::None => aes_cbc_encrypt_padded::<NoPadding>(key, iv, data),
-            }
+        (CipherAlgorithm::Aes, 24) => {
+            block_transform(Aes192::new_from_slice(key)?, state, is_decrypt, data)
         }
-        (AesMode::Ecb, true) => match padding {
-            AesPadding::Pkcs7 => aes_ecb_decrypt_padded::<Pkcs7>(key, data),
-            AesPadding::None => aes_ecb_decrypt_padded::<NoPadding>(key, data),
-        },
-        (AesMode::Ecb, false) => match padding {
-            AesPadding::Pkcs7 => aes_ecb_encrypt_padded::<Pkcs7>(key, data),
-            AesPadding::None => aes_ecb_encrypt_padded::<NoPadding>(key, data),
-        },
-        (AesMode::Cfb8, true) => aes_cfb8_decrypt(key, state.iv.as_ref()?, data),
-        (AesMode::Cfb8, false) => aes_cfb8_encrypt(key, state.iv.as_ref()?, data),
+        (CipherAlgorithm::Aes, 32) => {
+            block_transform(Aes256::new_from_slice(key)?, state, is_decrypt, data)
+        }
+        (CipherAlgorithm::Des, 8) => {
+            block_transform(Des::new_from_slice(key)?, state, is_decrypt, data)
+        }
+        (CipherAlgorithm::TripleDes, 16) => {
+            block_transform(TdesEde2::new_from_slice(key)?, state, is_decrypt, data)
+        }
+        (CipherAlgorithm::TripleDes, 24) => {
+            block_transform(TdesEde3::new_from_slice(key)?, state, is_decrypt, data)
+        }
+        // the effective key size of .NET RC2 is the key size
+        (CipherAlgorithm::Rc2, 5..=16) => block_transform(
+            Rc2::new_with_eff_key_len(key, key.len() * 8),
+            state,
+            is_decrypt,
+            data,
+        ),
+        _ => Err(InvalidLength.into()),
     }
 }
 
-fn is_aes_constructor_typename(typename: &str) -> bool {
-    let t = typename.strip_prefix("system.").unwrap_or(typename);
-    matches!(
-        t,
+/// Algorithm created by `New-Object <typename>` or `[<typename>]::new()`
+fn cipher_constructor_algorithm(typename: &str) -> Option<CipherAlgorithm> {
+    match typename.strip_prefix("system.").unwrap_or(typename) {
         "security.cryptography.aesmanaged"
-            | "security.cryptography.aescryptoserviceprovider"
-            | "security.cryptography.rijndaelmanaged"
-    )
+        | "security.cryptography.aescryptoserviceprovider"
+        | "security.cryptography.aescng"
+        | "security.cryptography.rijndaelmanaged" => Some(CipherAlgorithm::Aes),
+        "security.cryptography.descryptoserviceprovider" => Some(CipherAlgorithm::Des),
+        "security.cryptography.tripledescryptoserviceprovider"
+        | "security.cryptography.tripledescng" => Some(CipherAlgorithm::TripleDes),
+        "security.cryptography.rc2cryptoserviceprovider" => Some(CipherAlgorithm::Rc2),
+        _ => None,
+    }
 }
 
-fn is_aes_factory_typename(typename: &str) -> bool {
-    let t = typename.strip_prefix("system.").unwrap_or(typename);
-    t == "security.cryptography.aes"
+/// Algorithm created by `[<typename>]::Create()`
+fn cipher_factory_algorithm(typename: &str) -> Option<CipherAlgorithm> {
+    match typename.strip_prefix("system.").unwrap_or(typename) {
+        "security.cryptography.aes" | "security.cryptography.rijndael" => {
+            Some(CipherAlgorithm::Aes)
+        }
+        "security.cryptography.des" => Some(CipherAlgorithm::Des),
+        "security.cryptography.tripledes" => Some(CipherAlgorithm::TripleDes),
+        "security.cryptography.rc2" => Some(CipherAlgorithm::Rc2),
+        _ => None,
+    }
 }
 
 fn is_cipher_mode_typename(typename: &str) -> bool {
@@ -207,25 +260,28 @@ fn is_padding_mode_typename(typename: &str) -> bool {
     t == "security.cryptography.paddingmode"
 }
 
-fn parse_aes_mode(name: &str) -> Option<AesMode> {
+fn parse_cipher_mode(name: &str) -> Option<CipherMode> {
     match name {
-        "cbc" => Some(AesMode::Cbc),
-        "ecb" => Some(AesMode::Ecb),
+        "cbc" => Some(CipherMode::Cbc),
+        "ecb" => Some(CipherMode::Ecb),
         // .net's CFB looks to be CFB-8
-        "cfb" => Some(AesMode::Cfb8),
+        "cfb" => Some(CipherMode::Cfb8),
         _ => None,
     }
 }
 
-fn parse_aes_padding(name: &str) -> Option<AesPadding> {
+fn parse_cipher_padding(name: &str) -> Option<CipherPadding> {
     match name {
-        "pkcs7" => Some(AesPadding::Pkcs7),
-        "none" => Some(AesPadding::None),
+        "pkcs7" => Some(CipherPadding::Pkcs7),
+        "none" => Some(CipherPadding::None),
+        "zeros" => Some(CipherPadding::Zeros),
+        "ansix923" => Some(CipherPadding::AnsiX923),
+        "iso10126" => Some(CipherPadding::Iso10126),
         _ => None,
     }
 }
 
-pub fn assign_aes_property(state: &mut AesState, member: &str, value: &Powershell) -> bool {
+pub fn assign_cipher_property(state: &mut CipherState, member: &str, value: &Powershell) -> bool {
     match member {
         "key" => {
             if let Some(bytes) = bytes_from_data(value) {
@@ -242,7 +298,7 @@ pub fn assign_aes_property(state: &mut AesState, member: &str, value: &Powershel
         "mode" => {
             if let Type(typename) = value
                 && let Some(tag) = typename.strip_prefix("crypto.mode.")
-                && let Some(mode) = parse_aes_mode(tag)
+                && let Some(mode) = parse_cipher_mode(tag)
             {
                 state.mode = Some(mode);
                 return true;
@@ -251,27 +307,47 @@ pub fn assign_aes_property(state: &mut AesState, member: &str, value: &Powershel
         "padding" => {
             if let Type(typename) = value
                 && let Some(tag) = typename.strip_prefix("crypto.padding.")
-                && let Some(padding) = parse_aes_padding(tag)
+                && let Some(padding) = parse_cipher_padding(tag)
             {
                 state.padding = Some(padding);
                 return true;
             }
         }
+        // a new key size drops the key, a new one is generated by .NET
+        "keysize" => {
+            if let Raw(Num(size)) = value {
+                if state
+                    .key
+                    .as_ref()
+                    .is_some_and(|key| key.len() * 8 != *size as usize)
+                {
+                    state.key = None;
+                }
+                return true;
+            }
+        }
+        "blocksize" | "feedbacksize" => return matches!(value, Raw(Num(_))),
         _ => (),
     }
     false
 }
 
-/// Resolves an AES algorithm object down to a `CreateDecryptor`/`CreateEncryptor` transform, from
-/// any of the ways PowerShell obfuscators reach one:
+/// See [`assign_cipher_property`]
+pub fn assign_aes_property(state: &mut AesState, member: &str, value: &Powershell) -> bool {
+    assign_cipher_property(state, member, value)
+}
+
+/// Resolves a symmetric algorithm object (AES, DES, TripleDES or RC2) down to a
+/// `CreateDecryptor`/`CreateEncryptor` transform, from any of the ways PowerShell obfuscators
+/// reach one:
 ///
 /// - `New-Object System.Security.Cryptography.AesManaged`
-/// - `[System.Security.Cryptography.AesManaged]::new()`
-/// - `[System.Security.Cryptography.Aes]::Create()`
+/// - `[System.Security.Cryptography.TripleDESCryptoServiceProvider]::new()`
+/// - `[System.Security.Cryptography.Aes]::Create()`, `[System.Security.Cryptography.DES]::Create()`
 ///
 /// The resulting object is tracked as a [`Powershell::Crypto`] value. `.Key`/`.IV`/`.Mode`/
 /// `.Padding` property assignments on a variable holding it are folded in by [`crate::ps::var::Var`]
-/// (via [`assign_aes_property`]), and `.CreateDecryptor(...)`/`.CreateEncryptor(...)` (with or
+/// (via [`assign_cipher_property`]), and `.CreateDecryptor(...)`/`.CreateEncryptor(...)` (with or
 /// without explicit key/iv arguments) resolves to a transform, later consumed by
 /// [`AesTransformFinalBlock`].
 ///
@@ -337,13 +413,14 @@ impl<'a> RuleMut<'a> for AesType {
             && let Some(Type(typename)) = type_node.data()
         {
             let member = member_name.text()?.to_string().normalize();
-            if is_cipher_mode_typename(typename) && parse_aes_mode(&member).is_some() {
+            if is_cipher_mode_typename(typename) && parse_cipher_mode(&member).is_some() {
                 trace!(
                     "AesType (L): Setting node with cipher mode type: {}",
                     member
                 );
                 node.set(Type(format!("crypto.mode.{}", member)));
-            } else if is_padding_mode_typename(typename) && parse_aes_padding(&member).is_some() {
+            } else if is_padding_mode_typename(typename) && parse_cipher_padding(&member).is_some()
+            {
                 trace!(
                     "AesType (L): Setting node with padding mode type: {}",
                     member
@@ -360,11 +437,20 @@ impl<'a> RuleMut<'a> for AesType {
             if op_text == "::"
                 && let Some(Type(typename)) = type_node.data()
                 && args_list.named_child("argument_expression_list").is_none()
-                && ((member == "new" && is_aes_constructor_typename(typename))
-                    || (member == "create" && is_aes_factory_typename(typename)))
+                && let Some(algorithm) = match member.as_str() {
+                    "new" => cipher_constructor_algorithm(typename),
+                    "create" => cipher_factory_algorithm(typename),
+                    _ => None,
+                }
             {
-                trace!("AesType (L): Setting node with AES algorithm object");
-                node.set(Crypto(AesState::default()));
+                trace!(
+                    "AesType (L): Setting node with {:?} algorithm object",
+                    algorithm
+                );
+                node.set(Crypto(CipherState {
+                    algorithm,
+                    ..CipherState::default()
+                }));
                 return Ok(());
             }
 
@@ -387,14 +473,12 @@ impl<'a> RuleMut<'a> for AesType {
                     ) {
                         new_state.key = Some(key);
                         new_state.iv = Some(iv);
-                        trace!(
-                            "AesType (L): Setting node with AES transform object (explicit key/iv)"
-                        );
+                        trace!("AesType (L): Setting node with transform object (explicit key/iv)");
                         node.set(Crypto(new_state));
                     }
                 } else {
                     // no argument: relies on .Key/.IV already assigned on the algorithm object
-                    trace!("AesType (L): Setting node with AES transform object (from state)");
+                    trace!("AesType (L): Setting node with transform object (from state)");
                     node.set(Crypto(new_state));
                 }
             }
@@ -417,10 +501,16 @@ impl<'a> RuleMut<'a> for AesType {
                 && let Some(last) = command_elements.child(command_elements.child_count() - 1)
                 && last.kind() == "generic_token"
                 && let Ok(typename) = last.text()
-                && is_aes_constructor_typename(&typename.to_lowercase())
+                && let Some(algorithm) = cipher_constructor_algorithm(&typename.to_lowercase())
             {
-                trace!("AesType (L): Setting node with New-Object AES algorithm object");
-                node.set(Crypto(AesState::default()));
+                trace!(
+                    "AesType (L): Setting node with New-Object {:?} algorithm object",
+                    algorithm
+                );
+                node.set(Crypto(CipherState {
+                    algorithm,
+                    ..CipherState::default()
+                }));
             }
         }
         Ok(())
@@ -429,8 +519,8 @@ impl<'a> RuleMut<'a> for AesType {
 
 /// This rule infers `TransformFinalBlock(bytes, offset, count)` calls on a resolved
 /// [`AesType`] transform, decrypting/encrypting the byte range according to the transform's
-/// tracked [`AesState`] (CBC/PKCS7 unless `Mode`/`Padding` were explicitly overridden, matching
-/// the .NET defaults for `AesManaged`/`AesCryptoServiceProvider`/`RijndaelManaged`).
+/// tracked [`CipherState`] (CBC/PKCS7 unless `Mode`/`Padding` were explicitly overridden, matching
+/// the .NET defaults of every `SymmetricAlgorithm`).
 ///
 /// # Example
 /// ```
@@ -509,8 +599,11 @@ impl<'a> RuleMut<'a> for AesTransformFinalBlock {
                 (arg_offset.data(), arg_count.data())
             && *offset >= 0
             && *count >= 0
-            && let Some(slice) = data.get(*offset as usize..(*offset + *count) as usize)
-            && let Some(result) = aes_transform(state, is_decrypt, slice)
+            && let Some(end) = offset.checked_add(*count)
+            && let Some(slice) = data.get(*offset as usize..end as usize)
+            && let Some(result) = cipher_transform(state, is_decrypt, slice)
+                .inspect_err(|e| warn!("AesTransformFinalBlock (L): {}: {:?}", e, e))
+                .ok()
         {
             trace!(
                 "AesTransformFinalBlock (L): Setting node with transformed bytes: {:?}",
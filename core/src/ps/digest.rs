@@ -0,0 +1,650 @@
+use crate::error::MinusOneResult;
+use crate::profile::limits;
+use crate::ps::Powershell;
+use crate::ps::Powershell::{Bytes, KeyDerivation, Raw, Type};
+use crate::ps::Value::{Num, Str};
+use crate::ps::cmdlets::{BoundParameters, resolved_command_name};
+use crate::ps::convert::arguments;
+use crate::ps::tool::StringTool;
+use crate::ps::utils::bytes::*;
+use crate::rule::RuleMut;
+use crate::tree::{ControlFlow, Node, NodeMut};
+use log::trace;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+/// Hash of data, by the lowercase name of the algorithm
+///
+/// # Example
+/// ```
+/// use minusone::ps::digest::compute_hash;
+///
+/// assert_eq!(
+///     compute_hash("md5", b"minusone").map(|h| h.len()),
+///     Some(16)
+/// );
+/// assert_eq!(compute_hash("crc32", b"minusone"), None);
+/// ```
+pub fn compute_hash(algorithm: &str, data: &[u8]) -> Option<Vec<u8>> {
+    Some(match algorithm {
+        "md5" => Md5::digest(data).to_vec(),
+        "sha1" => Sha1::digest(data).to_vec(),
+        "sha256" => Sha256::digest(data).to_vec(),
+        "sha384" => Sha384::digest(data).to_vec(),
+        "sha512" => Sha512::digest(data).to_vec(),
+        _ => return None,
+    })
+}
+
+/// Algorithm of a name given to `HashAlgorithm.Create`, `HashAlgorithmName` or `PasswordDeriveBytes`
+fn hash_name_tag(name: &str) -> Option<&'static str> {
+    let name = name.to_lowercase();
+    let name = name
+        .strip_prefix("system.security.cryptography.")
+        .unwrap_or(&name);
+    match name {
+        "md5" => Some("md5"),
+        "sha" | "sha1" | "sha-1" => Some("sha1"),
+        "sha256" | "sha-256" => Some("sha256"),
+        "sha384" | "sha-384" => Some("sha384"),
+        "sha512" | "sha-512" => Some("sha512"),
+        _ => None,
+    }
+}
+
+/// Algorithm of a `System.Security.Cryptography` type : its abstract class, or one of its implementations
+fn hash_type_tag(typename: &str) -> Option<&'static str> {
+    let typename = typename.strip_prefix("system.").unwrap_or(typename);
+    let name = typename.strip_prefix("security.cryptography.")?;
+    let name = ["managed", "cryptoserviceprovider", "cng"]
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .unwrap_or(name);
+    hash_name_tag(name)
+}
+
+fn is_hash_algorithm_name_typename(typename: &str) -> bool {
+    typename.strip_prefix("system.").unwrap_or(typename)
+        == "security.cryptography.hashalgorithmname"
+}
+
+fn is_hash_algorithm_typename(typename: &str) -> bool {
+    typename.strip_prefix("system.").unwrap_or(typename) == "security.cryptography.hashalgorithm"
+}
+
+/// Arguments given to `New-Object`, from `T(a, b, c)`, `-ArgumentList (a, b, c)` or `a, b, c`
+fn new_object_arguments<'a>(node: &Node<'a, Powershell>) -> Vec<Node<'a, Powershell>> {
+    if node.kind() == "argument_list" {
+        return arguments(node);
+    }
+
+    let mut list = node.smallest_child();
+    if list.kind() == "parenthesized_expression"
+        && let Some(inner) = list.child(1)
+    {
+        list = inner.smallest_child();
+    }
+
+    if list.kind() == "array_literal_expression" {
+        list.iter().filter(|n| n.kind() != ",").collect()
+    } else {
+        vec![list]
+    }
+}
+
+/// Hash algorithms folded over `ComputeHash`
+///
+/// - `[System.Security.Cryptography.SHA256]::Create()`, `[HashAlgorithm]::Create('MD5')`,
+///   `New-Object System.Security.Cryptography.MD5CryptoServiceProvider` and `::new()`
+///   resolve to a `crypto.hash.*` type tag
+/// - `[System.Security.Cryptography.HashAlgorithmName]::SHA256` resolves to the same tag,
+///   as it's given to [`DeriveKey`]
+/// - `.ComputeHash(bytes)`, `.ComputeHash(bytes, offset, count)` and `[SHA256]::HashData(bytes)`
+///   are hashed
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::encoding::{EncodingType, EncodingGetBytes};
+/// use minusone::ps::convert::ConvertBytes;
+/// use minusone::ps::digest::ComputeHash;
+///
+/// let mut tree = build_powershell_tree(
+///     "[BitConverter]::ToString([Security.Cryptography.MD5]::Create().ComputeHash([Text.Encoding]::UTF8.GetBytes('abc')))"
+/// ).unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseType::default(),
+///     ParseString::default(),
+///     EncodingType::default(),
+///     EncodingGetBytes::default(),
+///     ComputeHash::default(),
+///     ConvertBytes::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "\"90-01-50-98-3C-D2-4F-B0-D6-96-3F-7D-28-E1-7F-72\"");
+/// ```
+#[derive(Default)]
+pub struct ComputeHash;
+
+impl<'a> RuleMut<'a> for ComputeHash {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+
+        match view.kind() {
+            // [System.Security.Cryptography.HashAlgorithmName]::SHA256
+            "member_access" => {
+                if let (Some(type_node), Some(op), Some(member_name)) =
+                    (view.child(0), view.child(1), view.child(2))
+                    && op.text()? == "::"
+                    && let Some(Type(typename)) = type_node.data()
+                    && is_hash_algorithm_name_typename(typename)
+                    && let Some(tag) = hash_name_tag(member_name.text()?)
+                {
+                    trace!("ComputeHash (L): Setting node with hash name: {}", tag);
+                    node.set(Type(format!("crypto.hash.{}", tag)));
+                }
+            }
+            "invokation_expression" => {
+                let (Some(type_node), Some(op), Some(member_name), Some(args_list)) =
+                    (view.child(0), view.child(1), view.child(2), view.child(3))
+                else {
+                    return Ok(());
+                };
+                let member = member_name.text()?.to_string().normalize();
+                let arguments = arguments(&args_list);
+
+                let result = match (type_node.data(), op.text()?, member.as_str()) {
+                    (Some(Type(typename)), "::", "create") if arguments.is_empty() => {
+                        hash_type_tag(typename).map(|tag| Type(format!("crypto.hash.{}", tag)))
+                    }
+                    (Some(Type(typename)), "::", "new") if arguments.is_empty() => {
+                        hash_type_tag(typename).map(|tag| Type(format!("crypto.hash.{}", tag)))
+                    }
+                    // [HashAlgorithm]::Create('SHA256')
+                    (Some(Type(typename)), "::", "create")
+                        if is_hash_algorithm_typename(typename) =>
+                    {
+                        match arguments.first().and_then(|n| n.data()) {
+                            Some(Raw(Str(name))) => {
+                                hash_name_tag(name).map(|tag| Type(format!("crypto.hash.{}", tag)))
+                            }
+                            _ => None,
+                        }
+                    }
+                    // [SHA256]::HashData($bytes)
+                    (Some(Type(typename)), "::", "hashdata") => {
+                        match (hash_type_tag(typename), &arguments[..]) {
+                            (Some(tag), [data]) => data
+                                .data()
+                                .and_then(bytes_from_data)
+                                .and_then(|data| compute_hash(tag, &data))
+                                .map(Bytes),
+                            _ => None,
+                        }
+                    }
+                    (Some(Type(typename)), ".", "computehash") => {
+                        let tag = typename.strip_prefix("crypto.hash.");
+                        let data = arguments
+                            .first()
+                            .and_then(|n| n.data())
+                            .and_then(bytes_from_data);
+                        let range = match (arguments.get(1), arguments.get(2)) {
+                            (None, None) => data.as_ref().map(|data| 0..data.len()),
+                            (Some(offset), Some(count)) => match (offset.data(), count.data()) {
+                                (Some(Raw(Num(offset))), Some(Raw(Num(count))))
+                                    if *offset >= 0 && *count >= 0 =>
+                                {
+                                    offset
+                                        .checked_add(*count)
+                                        .map(|end| *offset as usize..end as usize)
+                                }
+                                _ => None,
+                            },
+                            _ => None,
+                        };
+                        match (tag, data, range) {
+                            (Some(tag), Some(data), Some(range)) => data
+                                .get(range)
+                                .and_then(|data| compute_hash(tag, data))
+                                .map(Bytes),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+
+                if let Some(result) = result {
+                    trace!("ComputeHash (L): Setting node with {:?}", result);
+                    node.set(result);
+                }
+            }
+            // New-Object System.Security.Cryptography.SHA256Managed
+            "command" => {
+                if let Some(command_name) = view.named_child("command_name")
+                    && resolved_command_name(&command_name)? == "new-object"
+                    && let Some(mut parameters) = BoundParameters::bind(
+                        &view,
+                        &[("typename", false), ("argumentlist", false)],
+                    )?
+                    && let (Some(typename), None, None) = (
+                        parameters.take("typename"),
+                        parameters.take("argumentlist"),
+                        parameters.positional.pop_front(),
+                    )
+                    && let Some(tag) = hash_type_tag(&typename.text()?.to_lowercase())
+                {
+                    trace!("ComputeHash (L): Setting node with New-Object hash algorithm");
+                    node.set(Type(format!("crypto.hash.{}", tag)));
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+/// Key derivation functions of .NET
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyDerivationFunction {
+    /// `Rfc2898DeriveBytes`, PBKDF2 over HMAC
+    Pbkdf2,
+    /// `PasswordDeriveBytes`, PBKDF1 with the Microsoft extension for longer keys
+    Pbkdf1,
+}
+
+/// A `Rfc2898DeriveBytes` or `PasswordDeriveBytes` object
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyDerivationState {
+    pub function: KeyDerivationFunction,
+    pub password: Vec<u8>,
+    pub salt: Vec<u8>,
+    pub iterations: u32,
+    /// lowercase name of the hash algorithm
+    pub hash: String,
+    /// Counts of the previous `GetBytes` calls, the next bytes depend on them
+    pub requested: Vec<usize>,
+}
+
+impl KeyDerivationState {
+    /// Bytes returned by the next call to `GetBytes(count)`
+    ///
+    /// None when the iterations or the bytes requested
+    /// are over the budgets, see [`crate::profile::Limits`],
+    /// or without any iteration, which .NET refuses
+    ///
+    /// # Example
+    /// ```
+    /// use minusone::ps::digest::{KeyDerivationFunction, KeyDerivationState};
+    ///
+    /// let mut state = KeyDerivationState {
+    ///     function: KeyDerivationFunction::Pbkdf2,
+    ///     password: b"password".to_vec(),
+    ///     salt: b"salt".to_vec(),
+    ///     iterations: 1,
+    ///     hash: "sha1".to_string(),
+    ///     requested: vec![],
+    /// };
+    /// // RFC 6070 test vector
+    /// assert_eq!(
+    ///     state.get_bytes(20).unwrap()[..4],
+    ///     [0x0c, 0x60, 0xc8, 0x0f]
+    /// );
+    /// state.requested.push(4);
+    /// assert_eq!(state.get_bytes(4).unwrap(), [0x96, 0x1f, 0x0e, 0x71]);
+    /// ```
+    pub fn get_bytes(&self, count: usize) -> Option<Vec<u8>> {
+        let limits = limits();
+        let start = self
+            .requested
+            .iter()
+            .try_fold(0usize, |start, requested| start.checked_add(*requested))?;
+        let end = start.checked_add(count)?;
+        if self.iterations < 1 {
+            trace!("KeyDerivation: the iteration count must be positive");
+            return None;
+        }
+        if self.iterations as usize > limits.kdf_iterations || end > limits.derived_bytes {
+            trace!(
+                "KeyDerivation: {} iterations for {} bytes is over the budget",
+                self.iterations, end
+            );
+            return None;
+        }
+
+        match self.function {
+            KeyDerivationFunction::Pbkdf2 => {
+                let mut output = vec![0u8; end];
+                match self.hash.as_str() {
+                    "md5" => pbkdf2::pbkdf2_hmac::<Md5>(
+                        &self.password,
+                        &self.salt,
+                        self.iterations,
+                        &mut output,
+                    ),
+                    "sha1" => pbkdf2::pbkdf2_hmac::<Sha1>(
+                        &self.password,
+                        &self.salt,
+                        self.iterations,
+                        &mut output,
+                    ),
+                    "sha256" => pbkdf2::pbkdf2_hmac::<Sha256>(
+                        &self.password,
+                        &self.salt,
+                        self.iterations,
+                        &mut output,
+                    ),
+                    "sha384" => pbkdf2::pbkdf2_hmac::<Sha384>(
+                        &self.password,
+                        &self.salt,
+                        self.iterations,
+                        &mut output,
+                    ),
+                    "sha512" => pbkdf2::pbkdf2_hmac::<Sha512>(
+                        &self.password,
+                        &self.salt,
+                        self.iterations,
+                        &mut output,
+                    ),
+                    _ => return None,
+                }
+                Some(output.split_off(start))
+            }
+            KeyDerivationFunction::Pbkdf1 => {
+                let mut stream = PasswordDeriveBytes::default();
+                for requested in &self.requested {
+                    stream.get_bytes(self, *requested)?;
+                }
+                stream.get_bytes(self, count)
+            }
+        }
+    }
+}
+
+/// Replay of the .NET `PasswordDeriveBytes.GetBytes`, bugs included
+#[derive(Default)]
+struct PasswordDeriveBytes {
+    base_value: Option<Vec<u8>>,
+    prefix: usize,
+    // last hashes computed, and how many of their bytes were returned
+    extra: Option<(Vec<u8>, usize)>,
+}
+
+impl PasswordDeriveBytes {
+    fn base_value(state: &KeyDerivationState) -> Option<Vec<u8>> {
+        let mut value = compute_hash(
+            &state.hash,
+            &[&state.password[..], &state.salt[..]].concat(),
+        )?;
+        for _ in 1..state.iterations.saturating_sub(1) {
+            value = compute_hash(&state.hash, &value)?;
+        }
+        Some(value)
+    }
+
+    fn compute_bytes(&mut self, state: &KeyDerivationState, count: usize) -> Option<Vec<u8>> {
+        let base_value = self.base_value.as_ref()?;
+        let mut result = vec![];
+        loop {
+            // .NET numbers the hashes with at most 3 digits
+            if self.prefix > 999 {
+                return None;
+            }
+            let prefix = match self.prefix {
+                0 => String::new(),
+                prefix => prefix.to_string(),
+            };
+            self.prefix += 1;
+            result.extend(compute_hash(
+                &state.hash,
+                &[prefix.as_bytes(), &base_value[..]].concat(),
+            )?);
+            if result.len() >= count {
+                return Some(result);
+            }
+        }
+    }
+
+    fn get_bytes(&mut self, state: &KeyDerivationState, count: usize) -> Option<Vec<u8>> {
+        let mut output = vec![0u8; count];
+        let mut index = 0;
+        if self.base_value.is_none() {
+            self.base_value = Some(Self::base_value(state)?);
+        } else if let Some((extra, extra_count)) = self.extra.take() {
+            index = extra.len() - extra_count;
+            if index >= count {
+                output.copy_from_slice(&extra[extra_count..extra_count + count]);
+                if index > count {
+                    self.extra = Some((extra, extra_count + count));
+                }
+                return Some(output);
+            }
+            // .NET copies the remaining bytes from the wrong offset
+            output[..index].copy_from_slice(extra.get(index..2 * index)?);
+        }
+        let bytes = self.compute_bytes(state, count - index)?;
+        output[index..].copy_from_slice(&bytes[..count - index]);
+        if bytes.len() + index > count {
+            self.extra = Some((bytes, count - index));
+        }
+        Some(output)
+    }
+}
+
+fn kdf_function(typename: &str) -> Option<KeyDerivationFunction> {
+    match typename.strip_prefix("system.").unwrap_or(typename) {
+        "security.cryptography.rfc2898derivebytes" => Some(KeyDerivationFunction::Pbkdf2),
+        "security.cryptography.passwordderivebytes" => Some(KeyDerivationFunction::Pbkdf1),
+        _ => None,
+    }
+}
+
+/// Build a key derivation object from its constructor arguments
+///
+/// - `Rfc2898DeriveBytes(password, salt[, iterations[, hashAlgorithmName]])`
+/// - `PasswordDeriveBytes(password, salt[, hashName, iterations])`
+fn new_key_derivation(
+    function: KeyDerivationFunction,
+    arguments: &[Node<Powershell>],
+) -> Option<KeyDerivationState> {
+    let password = match arguments.first()?.data()? {
+        Raw(Str(password)) => password.as_bytes().to_vec(),
+        data => bytes_from_data(data)?,
+    };
+    let salt = bytes_from_data(arguments.get(1)?.data()?)?;
+    let number = |node: Option<&Node<Powershell>>| match node.map(|n| n.data()) {
+        Some(Some(Raw(Num(n)))) => u32::try_from(*n).ok(),
+        _ => None,
+    };
+    let hash_name = |node: Option<&Node<Powershell>>| match node.map(|n| n.data()) {
+        None => Some("sha1".to_string()),
+        Some(Some(Raw(Str(name)))) => hash_name_tag(name).map(str::to_string),
+        Some(Some(Type(typename))) => typename.strip_prefix("crypto.hash.").map(str::to_string),
+        _ => None,
+    };
+
+    let (iterations, hash) = match (function, arguments.len()) {
+        (KeyDerivationFunction::Pbkdf2, 2) => (1000, "sha1".to_string()),
+        (KeyDerivationFunction::Pbkdf2, 3 | 4) => {
+            (number(arguments.get(2))?, hash_name(arguments.get(3))?)
+        }
+        (KeyDerivationFunction::Pbkdf1, 2) => (100, "sha1".to_string()),
+        (KeyDerivationFunction::Pbkdf1, 4) => {
+            (number(arguments.get(3))?, hash_name(arguments.get(2))?)
+        }
+        _ => return None,
+    };
+
+    Some(KeyDerivationState {
+        function,
+        password,
+        salt,
+        iterations,
+        hash,
+        requested: vec![],
+    })
+}
+
+/// Key derivation folded over `GetBytes`
+///
+/// - `[System.Security.Cryptography.Rfc2898DeriveBytes]::new($password, $salt, 1000)`,
+///   `New-Object System.Security.Cryptography.PasswordDeriveBytes($password, $salt)`
+///   are tracked as [`Powershell::KeyDerivation`]
+/// - `.GetBytes(32)` returns the derived bytes, following the previous `GetBytes`
+///   calls on the same variable, which are counted by [`crate::ps::var::Var`]
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::integer::ParseInt;
+/// use minusone::ps::encoding::{EncodingType, EncodingGetBytes};
+/// use minusone::ps::digest::DeriveKey;
+///
+/// let mut tree = build_powershell_tree(
+///     "[Security.Cryptography.Rfc2898DeriveBytes]::new('password', [Text.Encoding]::UTF8.GetBytes('salt'), 1).GetBytes(4)"
+/// ).unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseType::default(),
+///     ParseString::default(),
+///     ParseInt::default(),
+///     EncodingType::default(),
+///     EncodingGetBytes::default(),
+///     DeriveKey::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "@(12, 96, 200, 15)");
+/// ```
+#[derive(Default)]
+pub struct DeriveKey;
+
+impl<'a> RuleMut<'a> for DeriveKey {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+
+        match view.kind() {
+            "invokation_expression" => {
+                let (Some(type_node), Some(op), Some(member_name), Some(args_list)) =
+                    (view.child(0), view.child(1), view.child(2), view.child(3))
+                else {
+                    return Ok(());
+                };
+                let member = member_name.text()?.to_string().normalize();
+                let arguments = arguments(&args_list);
+
+                match (type_node.data(), op.text()?, member.as_str()) {
+                    (Some(Type(typename)), "::", "new") => {
+                        if let Some(function) = kdf_function(typename)
+                            && let Some(state) = new_key_derivation(function, &arguments)
+                        {
+                            trace!("DeriveKey (L): Setting node with {:?} object", function);
+                            node.set(KeyDerivation(state));
+                        }
+                    }
+                    (Some(KeyDerivation(state)), ".", "getbytes") => {
+                        if let [count] = &arguments[..]
+                            && let Some(Raw(Num(count))) = count.data()
+                            && *count >= 0
+                            && let Some(bytes) = state.get_bytes(*count as usize)
+                        {
+                            trace!("DeriveKey (L): Setting node with derived bytes");
+                            node.set(Bytes(bytes));
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            // New-Object System.Security.Cryptography.Rfc2898DeriveBytes($password, $salt, 1000)
+            "command" => {
+                if let Some(command_name) = view.named_child("command_name")
+                    && resolved_command_name(&command_name)? == "new-object"
+                    && let Some(mut parameters) = BoundParameters::bind(
+                        &view,
+                        &[("typename", false), ("argumentlist", false)],
+                    )?
+                    && let (Some(typename), Some(arguments), None) = (
+                        parameters.take("typename"),
+                        parameters.take("argumentlist"),
+                        parameters.positional.pop_front(),
+                    )
+                    && let Some(function) = kdf_function(&typename.text()?.to_lowercase())
+                    && let Some(state) =
+                        new_key_derivation(function, &new_object_arguments(&arguments))
+                {
+                    trace!(
+                        "DeriveKey (L): Setting node with New-Object {:?} object",
+                        function
+                    );
+                    node.set(KeyDerivation(state));
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+/// Count a `GetBytes` call on a key derivation object, or reset it
+pub fn call_key_derivation(
+    state: &mut KeyDerivationState,
+    member: &str,
+    arguments: &[Powershell],
+) -> bool {
+    match (member, arguments) {
+        ("getbytes", [Raw(Num(count))]) => match usize::try_from(*count) {
+            Ok(count) if count <= limits().derived_bytes => {
+                state.requested.push(count);
+                true
+            }
+            _ => false,
+        },
+        ("reset", []) => {
+            state.requested.clear();
+            true
+        }
+        _ => false,
+    }
+}
@@ -1,11 +1,12 @@
 use crate::error::{Error, MinusOneResult};
 use crate::ps::Powershell;
-use crate::ps::Powershell::{Array, PSItem, Raw};
+use crate::ps::Powershell::{Array, MatchCollection, PSItem, Raw, RegexMatch};
+use crate::ps::Value::Str;
 use crate::rule::RuleMut;
 use crate::tree::{ControlFlow, Node, NodeMut};
 use log::trace;
 
-fn find_previous_expr<'a>(
+pub(crate) fn find_previous_expr<'a>(
     command: &Node<'a, Powershell>,
 ) -> MinusOneResult<Option<Node<'a, Powershell>>> {
     let pipeline_chain = command.parent().ok_or(Error::invalid_child())?;
@@ -38,6 +39,22 @@ fn is_foreach_command<'a>(command: &Node<'a, Powershell>) -> bool {
         }
 }
 
+/// The expression piped into the foreach command in which the `$_` variable is used
+pub(crate) fn pipeline_input<'a>(
+    variable: &Node<'a, Powershell>,
+) -> MinusOneResult<Option<Node<'a, Powershell>>> {
+    if variable.kind() == "variable"
+        && variable.text()? == "$_"
+        && let Some(script_block_expression) =
+            variable.get_parent_of_types(vec!["script_block_expression"])
+        && let Some(foreach_command) = script_block_expression.get_parent_of_types(vec!["command"])
+        && is_foreach_command(&foreach_command)
+    {
+        return find_previous_expr(&foreach_command);
+    }
+    Ok(None)
+}
+
 /// This rule will stop on the special var $_
 /// And check if it's used into a foreach command
 /// And then will infer the result of the previous command
@@ -94,15 +111,7 @@ impl<'a> RuleMut<'a> for PSItemInferrator {
     ) -> MinusOneResult<()> {
         let view = node.view();
         // find usage of magic variable
-        if view.kind() == "variable"
-            && view.text()? == "$_"
-            && let Some(script_block_expression) =
-                view.get_parent_of_types(vec!["script_block_expression"])
-            && let Some(foreach_command) =
-                script_block_expression.get_parent_of_types(vec!["command"])
-            && is_foreach_command(&foreach_command)
-            && let Some(previous) = find_previous_expr(&foreach_command)?
-        {
+        if let Some(previous) = pipeline_input(&view)? {
             // the previous in the pipeline
             match previous.data() {
                 Some(Array(values)) => {
@@ -119,6 +128,23 @@ impl<'a> RuleMut<'a> for PSItemInferrator {
                     );
                     node.set(PSItem(vec![value.clone()]));
                 }
+                // regex matches are enumerated by their value
+                Some(MatchCollection(matches)) => {
+                    trace!(
+                        "PSItemInferrator (L): Setting node with PSItem of matches: {:?}",
+                        matches
+                    );
+                    node.set(PSItem(
+                        matches.iter().map(|m| Str(m.value.clone())).collect(),
+                    ));
+                }
+                Some(RegexMatch(m)) => {
+                    trace!(
+                        "PSItemInferrator (L): Setting node with PSItem of match: {:?}",
+                        m
+                    );
+                    node.set(PSItem(vec![Str(m.value.clone())]));
+                }
                 _ => (),
             }
         }
@@ -196,6 +222,10 @@ impl<'a> RuleMut<'a> for ForEach {
                 Some(Array(values)) => previous_values.extend(values.clone()),
                 // array of size 1
                 Some(Raw(value)) => previous_values.push(value.clone()),
+                Some(MatchCollection(matches)) => {
+                    previous_values.extend(matches.iter().map(|m| Str(m.value.clone())))
+                }
+                Some(RegexMatch(m)) => previous_values.push(Str(m.value.clone())),
                 _ => (),
             }
             let script_block_body = script_block_expression
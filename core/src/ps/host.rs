@@ -0,0 +1,256 @@
+use crate::error::{Error, MinusOneErrorKind};
+use crate::ps::Powershell::{self, HashMap, Raw};
+use crate::ps::Value::{Bool, Num, Str};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Version of the Powershell host a script is emulated on
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum HostVersion {
+    /// Windows Powershell 5.1, shipped with Windows
+    #[default]
+    WindowsPowershell,
+    /// Powershell 7, also known as pwsh
+    Powershell7,
+}
+
+impl FromStr for HostVersion {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "5" | "5.1" | "windows-powershell" | "windowspowershell" | "desktop" => {
+                Ok(HostVersion::WindowsPowershell)
+            }
+            "7" | "pwsh" | "powershell7" | "powershell-7" | "core" => Ok(HostVersion::Powershell7),
+            _ => Err(Error::new(
+                MinusOneErrorKind::Unknown,
+                &format!("Unknown Powershell host version: {}", name),
+            )),
+        }
+    }
+}
+
+/// The machine a script is supposed to run on
+///
+/// It feeds the value of environment variables and automatic variables
+/// which are commonly indexed to rebuild command names: $env:ComSpec[4,15,25]
+///
+/// # Example
+/// ```
+/// use minusone::ps::host::{HostProfile, HostVersion};
+///
+/// let profile = HostProfile::new(HostVersion::WindowsPowershell).with_user_name("bob");
+///
+/// assert_eq!(profile.env("comspec").as_deref(), Some("C:\\WINDOWS\\system32\\cmd.exe"));
+/// assert_eq!(profile.env("UserProfile").as_deref(), Some("C:\\Users\\bob"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HostProfile {
+    version: HostVersion,
+    user_name: String,
+    computer_name: String,
+    // lowercase name -> (name, value), set on top of the default env block
+    env: BTreeMap<String, (String, String)>,
+}
+
+impl Default for HostProfile {
+    fn default() -> Self {
+        Self::new(HostVersion::default())
+    }
+}
+
+impl HostProfile {
+    pub fn new(version: HostVersion) -> Self {
+        HostProfile {
+            version,
+            user_name: String::from("user"),
+            computer_name: String::from("DESKTOP-1NK4F2G"),
+            env: BTreeMap::new(),
+        }
+    }
+
+    pub fn version(&self) -> HostVersion {
+        self.version
+    }
+
+    pub fn with_user_name(mut self, user_name: &str) -> Self {
+        self.user_name = user_name.to_string();
+        self
+    }
+
+    pub fn with_computer_name(mut self, computer_name: &str) -> Self {
+        self.computer_name = computer_name.to_string();
+        self
+    }
+
+    /// Set or override an environment variable
+    pub fn with_env(mut self, name: &str, value: &str) -> Self {
+        self.env
+            .insert(name.to_lowercase(), (name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set environment variables from the output of the `set` command
+    /// One NAME=VALUE per line, other lines are ignored
+    pub fn with_env_block(self, block: &str) -> Self {
+        block
+            .lines()
+            .fold(self, |profile, line| match line.split_once('=') {
+                Some((name, value)) if !name.trim().is_empty() => {
+                    profile.with_env(name.trim(), value)
+                }
+                _ => profile,
+            })
+    }
+
+    fn pshome(&self) -> &'static str {
+        match self.version {
+            HostVersion::WindowsPowershell => "C:\\Windows\\System32\\WindowsPowerShell\\v1.0",
+            HostVersion::Powershell7 => "C:\\Program Files\\PowerShell\\7",
+        }
+    }
+
+    fn default_env(&self) -> Vec<(&'static str, String)> {
+        let profile = format!("C:\\Users\\{}", self.user_name);
+        let module_path = match self.version {
+            HostVersion::WindowsPowershell => format!(
+                "{}\\Documents\\WindowsPowerShell\\Modules;C:\\Program Files\\WindowsPowerShell\\Modules;C:\\WINDOWS\\system32\\WindowsPowerShell\\v1.0\\Modules",
+                profile
+            ),
+            HostVersion::Powershell7 => format!(
+                "{}\\Documents\\PowerShell\\Modules;C:\\Program Files\\PowerShell\\Modules;c:\\program files\\powershell\\7\\Modules;C:\\Program Files\\WindowsPowerShell\\Modules;C:\\WINDOWS\\system32\\WindowsPowerShell\\v1.0\\Modules",
+                profile
+            ),
+        };
+
+        vec![
+            ("ALLUSERSPROFILE", "C:\\ProgramData".to_string()),
+            ("APPDATA", format!("{}\\AppData\\Roaming", profile)),
+            ("CommonProgramFiles", "C:\\Program Files\\Common Files".to_string()),
+            ("CommonProgramFiles(x86)", "C:\\Program Files (x86)\\Common Files".to_string()),
+            ("CommonProgramW6432", "C:\\Program Files\\Common Files".to_string()),
+            ("COMPUTERNAME", self.computer_name.clone()),
+            ("ComSpec", "C:\\WINDOWS\\system32\\cmd.exe".to_string()),
+            ("DriverData", "C:\\Windows\\System32\\Drivers\\DriverData".to_string()),
+            ("HOMEDRIVE", "C:".to_string()),
+            ("HOMEPATH", format!("\\Users\\{}", self.user_name)),
+            ("LOCALAPPDATA", format!("{}\\AppData\\Local", profile)),
+            ("LOGONSERVER", format!("\\\\{}", self.computer_name)),
+            ("NUMBER_OF_PROCESSORS", "4".to_string()),
+            ("OS", "Windows_NT".to_string()),
+            ("Path", "C:\\WINDOWS\\system32;C:\\WINDOWS;C:\\WINDOWS\\System32\\Wbem;C:\\WINDOWS\\System32\\WindowsPowerShell\\v1.0\\;C:\\WINDOWS\\System32\\OpenSSH\\".to_string()),
+            ("PATHEXT", ".COM;.EXE;.BAT;.CMD;.VBS;.VBE;.JS;.JSE;.WSF;.WSH;.MSC;.CPL".to_string()),
+            ("PROCESSOR_ARCHITECTURE", "AMD64".to_string()),
+            ("ProgramData", "C:\\ProgramData".to_string()),
+            ("ProgramFiles", "C:\\Program Files".to_string()),
+            ("ProgramFiles(x86)", "C:\\Program Files (x86)".to_string()),
+            ("ProgramW6432", "C:\\Program Files".to_string()),
+            ("PSModulePath", module_path),
+            ("PUBLIC", "C:\\Users\\Public".to_string()),
+            ("SystemDrive", "C:".to_string()),
+            ("SystemRoot", "C:\\WINDOWS".to_string()),
+            ("TEMP", format!("{}\\AppData\\Local\\Temp", profile)),
+            ("TMP", format!("{}\\AppData\\Local\\Temp", profile)),
+            ("USERDOMAIN", self.computer_name.clone()),
+            ("USERNAME", self.user_name.clone()),
+            ("USERPROFILE", profile),
+            ("windir", "C:\\WINDOWS".to_string()),
+        ]
+    }
+
+    /// The whole env block, as (name, value)
+    pub fn env_vars(&self) -> Vec<(String, String)> {
+        let mut vars: BTreeMap<String, (String, String)> = self
+            .default_env()
+            .into_iter()
+            .map(|(name, value)| (name.to_lowercase(), (name.to_string(), value)))
+            .collect();
+        vars.extend(self.env.clone());
+        vars.into_values().collect()
+    }
+
+    /// Value of an environment variable, case insensitive
+    pub fn env(&self, name: &str) -> Option<String> {
+        let name = name.to_lowercase();
+        if let Some((_, value)) = self.env.get(&name) {
+            return Some(value.clone());
+        }
+        self.default_env()
+            .into_iter()
+            .find(|(default_name, _)| default_name.to_lowercase() == name)
+            .map(|(_, value)| value)
+    }
+
+    fn version_table(&self) -> Powershell {
+        let (version, edition, build) = match self.version {
+            HostVersion::WindowsPowershell => ("5.1.19041.5247", "Desktop", "10.0.19041.5247"),
+            HostVersion::Powershell7 => ("7.4.6", "Core", "10.0.19045"),
+        };
+        HashMap(BTreeMap::from([
+            (Str("psversion".to_string()), Str(version.to_string())),
+            (Str("psedition".to_string()), Str(edition.to_string())),
+            (Str("buildversion".to_string()), Str(build.to_string())),
+            (
+                Str("clrversion".to_string()),
+                Str("4.0.30319.42000".to_string()),
+            ),
+            (Str("wsmanstackversion".to_string()), Str("3.0".to_string())),
+            (
+                Str("psremotingprotocolversion".to_string()),
+                Str("2.3".to_string()),
+            ),
+            (
+                Str("serializationversion".to_string()),
+                Str("1.1.0.1".to_string()),
+            ),
+        ]))
+    }
+
+    /// Value of an automatic or preference variable, by its lowercase name
+    pub fn automatic_variable(&self, name: &str) -> Option<Powershell> {
+        let windows_powershell = self.version == HostVersion::WindowsPowershell;
+        let value = match name {
+            "shellid" => Str("Microsoft.Powershell".to_string()),
+            "pshome" => Str(self.pshome().to_string()),
+            "home" => Str(format!("C:\\Users\\{}", self.user_name)),
+            "psedition" => Str(if windows_powershell {
+                "Desktop"
+            } else {
+                "Core"
+            }
+            .to_string()),
+            "psculture" | "psuiculture" => Str("en-US".to_string()),
+            "psversiontable" => return Some(self.version_table()),
+            "confirmpreference" => Str("High".to_string()),
+            "debugpreference" | "informationpreference" | "verbosepreference" => {
+                Str("SilentlyContinue".to_string())
+            }
+            "erroractionpreference" | "progresspreference" | "warningpreference" => {
+                Str("Continue".to_string())
+            }
+            "whatifpreference" => Bool(false),
+            "errorview" => Str(if windows_powershell {
+                "NormalView"
+            } else {
+                "ConciseView"
+            }
+            .to_string()),
+            "formatenumerationlimit" => Num(4),
+            "nestedpromptlevel" => Num(0),
+            // removed from Powershell 7
+            "maximumaliascount"
+            | "maximumdrivecount"
+            | "maximumfunctioncount"
+            | "maximumvariablecount"
+                if windows_powershell =>
+            {
+                Num(4096)
+            }
+            "maximumerrorcount" if windows_powershell => Num(256),
+            "maximumhistorycount" => Num(4096),
+            _ => return None,
+        };
+        Some(Raw(value))
+    }
+}
@@ -1,11 +1,33 @@
 use crate::error::MinusOneResult;
 use crate::ps::Powershell;
-use crate::ps::Powershell::Raw;
-use crate::ps::Value::Num;
+use crate::ps::Powershell::{PSItem, Raw};
+use crate::ps::Value::{self, Float, Num};
+use crate::ps::math::{arithmetic, as_double, parse_real_literal};
 use crate::rule::RuleMut;
 use crate::tree::{ControlFlow, NodeMut};
 use log::{trace, warn};
 
+/// Arithmetic on each value of a pipeline item : % { [char]($_ / 2) }
+fn arithmetic_on_items(
+    left: &Powershell,
+    operator: &str,
+    right: &Powershell,
+) -> Option<Powershell> {
+    match (left, right) {
+        (PSItem(values), Raw(right)) => values
+            .iter()
+            .map(|left| arithmetic(left, operator, right))
+            .collect::<Option<Vec<Value>>>()
+            .map(PSItem),
+        (Raw(left), PSItem(values)) => values
+            .iter()
+            .map(|right| arithmetic(left, operator, right))
+            .collect::<Option<Vec<Value>>>()
+            .map(PSItem),
+        _ => None,
+    }
+}
+
 /// Parse int will interpret integer node into Rust world
 /// as decimal
 ///
@@ -55,6 +77,16 @@ impl<'a> RuleMut<'a> for ParseInt {
             "decimal_integer_literal" => {
                 if let Ok(number) = token.parse::<i64>() {
                     node.reduce(Raw(Num(number)));
+                } else if token.chars().all(|c| c.is_ascii_digit()) {
+                    // too big for a long, Powershell parses it as a double
+                    if let Ok(number) = token.parse::<f64>() {
+                        node.reduce(Raw(Float(number)));
+                    }
+                }
+            }
+            "real_literal" => {
+                if let Some(number) = parse_real_literal(token) {
+                    node.reduce(Raw(Float(number)));
                 }
             }
             "expression_with_unary_operator" => {
@@ -68,6 +100,14 @@ impl<'a> RuleMut<'a> for ParseInt {
                             trace!("ParseInt (L): Setting node with positive number: +{}", num);
                             node.set(Raw(Num(*num)))
                         }
+                        ("-", Some(Raw(Float(num)))) => {
+                            trace!("ParseInt (L): Setting node with negative number: -{}", num);
+                            node.set(Raw(Float(-num)))
+                        }
+                        ("+", Some(Raw(Float(num)))) => {
+                            trace!("ParseInt (L): Setting node with positive number: +{}", num);
+                            node.set(Raw(Float(*num)))
+                        }
                         _ => (),
                     }
                 }
@@ -126,18 +166,15 @@ impl<'a> RuleMut<'a> for AddInt {
                 (node_view.child(0), node_view.child(1), node_view.child(2))
         {
             match (left_op.data(), operator.text()?, right_op.data()) {
-                (Some(Raw(Num(number_left))), "+", Some(Raw(Num(number_right)))) => {
-                    if let Some(result) = number_left.checked_add(*number_right) {
-                        node.reduce(Raw(Num(result)))
-                    } else {
-                        warn!("Addition overflow: {} + {}", number_left, number_right);
+                (Some(Raw(left)), operator @ ("+" | "-"), Some(Raw(right))) => {
+                    if let Some(result) = arithmetic(left, operator, right) {
+                        node.reduce(Raw(result))
                     }
                 }
-                (Some(Raw(Num(number_left))), "-", Some(Raw(Num(number_right)))) => {
-                    if let Some(result) = number_left.checked_sub(*number_right) {
-                        node.reduce(Raw(Num(result)))
-                    } else {
-                        warn!("Subtraction overflow: {} - {}", number_left, number_right);
+                (Some(left), operator @ ("+" | "-"), Some(right)) => {
+                    if let Some(result) = arithmetic_on_items(left, operator, right) {
+                        trace!("AddInt (L): Setting node with pipeline items: {:?}", result);
+                        node.set(result)
                     }
                 }
                 _ => {}
@@ -148,7 +185,9 @@ impl<'a> RuleMut<'a> for AddInt {
 }
 
 /// This rule will infer integer operation
-/// of type add (+) and minus(-)
+/// of type multiply (*), divide (/) and modulo (%)
+///
+/// A division that is not exact produces a double : 5 / 2 => 2.5
 ///
 /// # Example
 /// ```
@@ -194,21 +233,22 @@ impl<'a> RuleMut<'a> for MultInt {
                 (node_view.child(0), node_view.child(1), node_view.child(2))
         {
             match (left_op.data(), operator.text()?, right_op.data()) {
-                (Some(Raw(Num(number_left))), "*", Some(Raw(Num(number_right)))) => {
-                    if let Some(result) = number_left.checked_mul(*number_right) {
-                        node.reduce(Raw(Num(result)))
+                (Some(Raw(left)), operator @ ("*" | "/" | "%"), Some(Raw(right)))
+                    if as_double(left).is_some() && as_double(right).is_some() =>
+                {
+                    if let Some(result) = arithmetic(left, operator, right) {
+                        node.reduce(Raw(result))
                     } else {
-                        warn!(
-                            "Multiplication overflow: {} * {}",
-                            number_left, number_right
-                        );
+                        warn!("Division by zero: {} {} {}", left, operator, right);
                     }
                 }
-                (Some(Raw(Num(number_left))), "/", Some(Raw(Num(number_right)))) => {
-                    if let Some(result) = number_left.checked_div(*number_right) {
-                        node.reduce(Raw(Num(result)))
-                    } else {
-                        warn!("Division by zero: {} / {}", number_left, number_right);
+                (Some(left), operator @ ("*" | "/" | "%"), Some(right)) => {
+                    if let Some(result) = arithmetic_on_items(left, operator, right) {
+                        trace!(
+                            "MultInt (L): Setting node with pipeline items: {:?}",
+                            result
+                        );
+                        node.set(result)
                     }
                 }
                 _ => {}
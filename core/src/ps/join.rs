@@ -1,11 +1,22 @@
 use crate::error::MinusOneResult;
 use crate::ps::Powershell;
-use crate::ps::Powershell::{Array, Raw, Type};
-use crate::ps::Value::Str;
+use crate::ps::Powershell::{Array, MatchCollection, Raw, Type};
+use crate::ps::Value::{self, Str};
 use crate::rule::RuleMut;
 use crate::tree::{ControlFlow, NodeMut};
 use log::trace;
 
+/// Values joined by the -join operator, regex matches are joined by their value
+fn joinable(data: Option<&Powershell>) -> Option<Vec<Value>> {
+    match data {
+        Some(Array(values)) => Some(values.clone()),
+        Some(MatchCollection(matches)) => {
+            Some(matches.iter().map(|m| Str(m.value.clone())).collect())
+        }
+        _ => None,
+    }
+}
+
 /// This rule will infer the -join opoerator
 /// in the context of comparison operator
 ///
@@ -61,8 +72,8 @@ impl<'a> RuleMut<'a> for JoinComparison {
         if view.kind() == "comparison_expression"
             && let (Some(left_expression), Some(operator), Some(right_expression)) =
                 (view.child(0), view.child(1), view.child(2))
-            && let (Some(Array(src_array)), "-join", Some(Raw(Str(join_token)))) = (
-                left_expression.data(),
+            && let (Some(src_array), "-join", Some(Raw(Str(join_token)))) = (
+                joinable(left_expression.data()),
                 operator.text()?.to_lowercase().as_str(),
                 right_expression.data(),
             )
@@ -238,9 +249,9 @@ impl<'a> RuleMut<'a> for JoinOperator {
         let view = node.view();
         if view.kind() == "expression_with_unary_operator"
             && let (Some(operator), Some(unary_expression)) = (view.child(0), view.child(1))
-            && let ("-join", Some(Array(values))) = (
+            && let ("-join", Some(values)) = (
                 operator.text()?.to_lowercase().as_str(),
-                unary_expression.data(),
+                joinable(unary_expression.data()),
             )
         {
             let result = values
@@ -1,6 +1,11 @@
+use crate::artefact::Artefact;
 use crate::error::MinusOneResult;
-use crate::ps::Powershell::{Bytes, Raw};
-use crate::ps::Value::{Bool, Num, Str};
+use crate::ps::Powershell::{Bytes, Raw, ScriptBlock};
+use crate::ps::Value::{Bool, Float, Num, Str};
+use crate::ps::cmdlets::{
+    BoundParameters, canonical_command_name, canonical_parameter_name, resolved_command_name,
+};
+use crate::ps::math::double_literal;
 use crate::ps::tool::StringTool;
 use crate::ps::utils::string::escape_string;
 use crate::ps::var::{UnusedVar, Var, find_variable_node};
@@ -8,9 +13,185 @@ use crate::ps::{LoopStatus, Powershell};
 use crate::regex::Regex;
 use crate::rule::Rule;
 use crate::tree::Node;
-
+use std::collections::HashSet;
+
+// tokens printed as they are written, ticks are part of their value
+const LITERAL_KINDS: [&str; 5] = [
+    "verbatim_string_characters",
+    "verbatim_here_string_characters",
+    "expandable_string_literal",
+    "expandable_here_string_literal",
+    "comment",
+];
+
+/// Remove escaping ticks, except the ones of special characters : `n, `t, `0...
 fn remove_useless_token(src: &str) -> String {
-    src.replace("`", "")
+    let mut result = String::with_capacity(src.len());
+    let mut chars = src.chars();
+    while let Some(c) = chars.next() {
+        if c != '`' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(escaped) if "0abefnrtv`".contains(escaped) => {
+                result.push('`');
+                result.push(escaped);
+            }
+            Some(escaped) => result.push(escaped),
+            None => (),
+        }
+    }
+    result
+}
+
+/// ${name} is written $name when braces are not needed
+fn normalize_variable(src: &str) -> String {
+    let Some(inner) = src.strip_prefix("${").and_then(|s| s.strip_suffix('}')) else {
+        return src.to_string();
+    };
+    // in a braced name, a tick escapes any character
+    let mut name = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '`' => name.extend(chars.next()),
+            c => name.push(c),
+        }
+    }
+    let (scope, simple_name) = name.split_once(':').unwrap_or(("", &name));
+    let is_word = |s: &str| s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !simple_name.is_empty()
+        && is_word(simple_name)
+        && is_word(scope)
+        && !scope.starts_with(|c: char| c.is_ascii_digit())
+    {
+        format!("${}", name)
+    } else {
+        src.to_string()
+    }
+}
+
+/// Name of the command run by a command node :
+/// its command name, or a constant string invoked with & or .
+fn invoked_command_name(command: &Node<Powershell>) -> MinusOneResult<Option<String>> {
+    Ok(match (command.child(0), command.child(1)) {
+        (Some(name), _) if name.kind() == "command_name" => {
+            Some(resolved_command_name(&name)?.replace('`', ""))
+        }
+        (Some(operator), Some(name)) if operator.kind() == "command_invokation_operator" => {
+            match name.data() {
+                Some(Raw(Str(name))) => Some(name.clone()),
+                _ => None,
+            }
+        }
+        _ => None,
+    })
+}
+
+// parameters of Set-Alias and New-Alias, the switches are flagged
+const ALIAS_PARAMETERS: [(&str, bool); 9] = [
+    ("name", false),
+    ("value", false),
+    ("description", false),
+    ("option", false),
+    ("scope", false),
+    ("passthru", true),
+    ("force", true),
+    ("whatif", true),
+    ("confirm", true),
+];
+
+/// Collect the lowercase names of the functions and aliases defined by a script
+fn collect_defined_commands(
+    node: &Node<Powershell>,
+    defined: &mut HashSet<String>,
+) -> MinusOneResult<()> {
+    match node.kind() {
+        "function_statement" => {
+            if let Some(name) = node.child(1) {
+                defined.insert(name.text()?.replace('`', "").to_lowercase());
+            }
+        }
+        "command" => {
+            if let Some(name) = invoked_command_name(node)?
+                && matches!(
+                    canonical_command_name(&name),
+                    Some("Set-Alias") | Some("New-Alias")
+                )
+                && let Some(mut parameters) = BoundParameters::bind(node, &ALIAS_PARAMETERS)?
+                && let Some(alias) = parameters.take("name")
+            {
+                let alias = match alias.data() {
+                    Some(Raw(Str(alias))) => alias.clone(),
+                    _ => alias.text()?.trim_matches(['\'', '"']).to_string(),
+                };
+                defined.insert(alias.replace('`', "").to_lowercase());
+            }
+        }
+        _ => (),
+    }
+    for child in node.iter() {
+        collect_defined_commands(&child, defined)?;
+    }
+    Ok(())
+}
+
+/// Canonical name of a command, unless the script defines its own command with this name
+fn canonical_known_command(name: &str, defined: &HashSet<String>) -> Option<&'static str> {
+    if defined.contains(&name.to_lowercase()) {
+        return None;
+    }
+    canonical_command_name(name)
+}
+
+/// Known command invoked through a constant string : & ("{1}{0}" -f 'ex','i')
+fn invoked_by_string(
+    command: &Node<Powershell>,
+    defined: &HashSet<String>,
+) -> MinusOneResult<Option<&'static str>> {
+    if command
+        .child(0)
+        .is_some_and(|operator| operator.kind() == "command_invokation_operator")
+        && let Some(name) = invoked_command_name(command)?
+    {
+        return Ok(canonical_known_command(&name, defined));
+    }
+    Ok(None)
+}
+
+/// Parameter with its full name and canonical case, when the command is known
+fn canonical_parameter(
+    node: &Node<Powershell>,
+    defined: &HashSet<String>,
+) -> MinusOneResult<Option<String>> {
+    let Some(command) = node.get_parent_of_types(vec!["command"]) else {
+        return Ok(None);
+    };
+    let Some(name) = invoked_command_name(&command)? else {
+        return Ok(None);
+    };
+    if defined.contains(&name.to_lowercase()) {
+        return Ok(None);
+    }
+    let given = node.text()?.replace('`', "");
+    let colon = if given.ends_with(':') { ":" } else { "" };
+    let parameter = given.trim_start_matches('-').trim_end_matches(':');
+    Ok(canonical_parameter_name(&name, parameter)
+        .map(|parameter| format!("-{}{}", parameter, colon)))
+}
+
+/// Text of a token, normalized except for literals
+fn lint_token(node: &Node<Powershell>, defined: &HashSet<String>) -> MinusOneResult<String> {
+    let text = node.text()?;
+    Ok(match node.kind() {
+        kind if LITERAL_KINDS.contains(&kind) => text.to_string(),
+        "variable" => normalize_variable(text),
+        "command_parameter" => {
+            canonical_parameter(node, defined)?.unwrap_or_else(|| remove_useless_token(text))
+        }
+        _ => remove_useless_token(text),
+    })
 }
 
 fn uppercase_first(src: &str) -> String {
@@ -35,6 +216,10 @@ pub struct Linter {
     is_param_block: bool,
     statement_block_tab: Vec<bool>,
     is_multiline: bool,
+    // byte values from this size are replaced by a reference to their artefact
+    artefact_threshold: Option<usize>,
+    // lowercase names of the functions and aliases defined by the script
+    defined_commands: HashSet<String>,
 }
 
 impl<'a> Rule<'a> for Linter {
@@ -47,6 +232,8 @@ impl<'a> Rule<'a> for Linter {
 
         // depending on what am i
         match node.kind() {
+            // commands the script defines are not expanded
+            "program" => collect_defined_commands(node, &mut self.defined_commands)?,
             "script_block" => {
                 if !is_inline(node) {
                     self.tab();
@@ -57,10 +244,29 @@ impl<'a> Rule<'a> for Linter {
             // ignore comment only if it was requested
             "comment" => return Ok(self.comment),
             "command_invokation_operator" => {
+                // dot sourcing runs in the current scope, it is kept
+                if node.text()? == "." {
+                    self.write(". ");
+                    return Ok(false);
+                }
+                // a known command invoked by its name is written as is
+                if let Some(command) = node.parent()
+                    && invoked_by_string(&command, &self.defined_commands)?.is_some()
+                {
+                    return Ok(false);
+                }
                 // normalize operator
                 self.write("& ");
                 return Ok(false);
             }
+            "command_name_expr" => {
+                if let Some(command) = node.parent()
+                    && let Some(name) = invoked_by_string(&command, &self.defined_commands)?
+                {
+                    self.write(name);
+                    return Ok(false);
+                }
+            }
             // add a new line space before special statement
             "while_statement" | "if_statement" | "function_statement" | "switch_statement"
             | "switch_clause" => {
@@ -85,13 +291,18 @@ impl<'a> Rule<'a> for Linter {
             "statement_block" => self.statement_block_tab.push(true),
             // Normalize command name
             // If it's a Verb-Action name parse it and print it normalize
+            // Aliases are expanded, unless the script defines a command with the same name
             "command_name" => {
+                let name = resolved_command_name(node)?.replace('`', "");
+                if let Some(canonical) = canonical_known_command(&name, &self.defined_commands) {
+                    self.write(canonical);
+                    return Ok(false);
+                }
                 if let Some(Raw(Str(resolved))) = node.data() {
                     self.write(resolved.as_str());
                     return Ok(false);
                 }
                 let re = Regex::new(r"([a-z]+)-([a-z]+)").unwrap();
-                let name = crate::ps::cmdlets::resolved_command_name(node)?;
                 if let Some(m) = re.captures(name.as_str()) {
                     if let (Some(verb), Some(action)) = (m.get(1), m.get(2)) {
                         self.write(uppercase_first(verb.as_str()).as_str());
@@ -253,6 +464,12 @@ impl<'a> Rule<'a> for Linter {
             }
         }
 
+        // keep what the parser failed on as is
+        if node.kind() == "ERROR" {
+            self.write(node.text()?);
+            return Ok(false);
+        }
+
         // Special token
         if node.child_count() == 0 {
             match node.text()?.to_lowercase().as_str() {
@@ -314,6 +531,10 @@ impl<'a> Rule<'a> for Linter {
                     self.write(number.to_string().as_str());
                     return Ok(false);
                 }
+                Raw(Float(number)) => {
+                    self.write(&double_literal(*number));
+                    return Ok(false);
+                }
                 Raw(Bool(true)) => {
                     self.write("$true".to_string().as_str());
                     return Ok(false);
@@ -322,6 +543,16 @@ impl<'a> Rule<'a> for Linter {
                     self.write("$false".to_string().as_str());
                     return Ok(false);
                 }
+                Bytes(bytes)
+                    if self
+                        .artefact_threshold
+                        .is_some_and(|threshold| bytes.len() >= threshold) =>
+                {
+                    self.write("@(<# ");
+                    self.write(&Artefact::new(bytes, node.start_abs(), node.end_abs()).reference());
+                    self.write(" #>)");
+                    return Ok(false);
+                }
                 Bytes(bytes) => {
                     let joined = bytes
                         .iter()
@@ -333,6 +564,13 @@ impl<'a> Rule<'a> for Linter {
                     self.write(")");
                     return Ok(false);
                 }
+                // a literal is printed as is, other script blocks are inlined
+                ScriptBlock(layer, _)
+                    if node.smallest_child().kind() != "script_block_expression" =>
+                {
+                    self.write_script_block(layer);
+                    return Ok(false);
+                }
                 _ => (),
             }
         }
@@ -343,7 +581,7 @@ impl<'a> Rule<'a> for Linter {
     fn leave(&mut self, node: &Node<'a, Self::Language>) -> MinusOneResult<()> {
         // leaf node => just print the token
         if node.child_count() == 0 {
-            self.write(&remove_useless_token(node.text()?));
+            self.write(&lint_token(node, &self.defined_commands)?);
         }
 
         // depending on what my parent are
@@ -414,6 +652,8 @@ impl Default for Linter {
             is_param_block: false,
             statement_block_tab: vec![],
             is_multiline: true,
+            artefact_threshold: None,
+            defined_commands: HashSet::new(),
         }
     }
 }
@@ -447,6 +687,25 @@ impl Linter {
         self.output += new;
     }
 
+    /// Write a script block from its linted layer
+    pub fn write_script_block(&mut self, layer: &str) {
+        self.write("{");
+        if layer.contains('\n') {
+            self.tab();
+            for line in layer.lines() {
+                self.enter();
+                self.write(line);
+            }
+            self.untab();
+            self.enter();
+        } else if !layer.is_empty() {
+            self.write(" ");
+            self.write(layer);
+            self.write(" ");
+        }
+        self.write("}");
+    }
+
     pub fn set_tab(mut self, tab_chr: &str) -> Self {
         self.tab_char = tab_chr.to_string();
         self
@@ -456,32 +715,43 @@ impl Linter {
         self.comment = comment;
         self
     }
+
+    /// Replace the byte values from this size by a comment
+    /// with the kind, the size and the SHA-256 of their artefact
+    pub fn set_artefact_threshold(mut self, threshold: usize) -> Self {
+        self.artefact_threshold = Some(threshold);
+        self
+    }
 }
 
 #[derive(Default)]
 pub struct RemoveCode {
     source: String,
     pub output: String,
+    // offset of the program in the source
+    base: usize,
     last_index: usize,
 }
 
 impl RemoveCode {
     pub fn start_program<T>(&mut self, root: &Node<T>) -> MinusOneResult<()> {
         self.source = root.text()?.to_string();
+        self.base = root.start_abs();
+        self.last_index = root.start_abs();
         Ok(())
     }
 
     pub fn end_program(&mut self) -> MinusOneResult<()> {
-        self.output += &self.source[self.last_index..];
+        self.output += &self.source[(self.last_index - self.base)..];
         Ok(())
     }
 
     pub fn remove_node<T>(&mut self, node: &Node<T>) -> MinusOneResult<()> {
-        while self.source.chars().nth(self.last_index) == Some('\n') {
+        while self.source.chars().nth(self.last_index - self.base) == Some('\n') {
             self.last_index += 1;
         }
 
-        self.output += &self.source[self.last_index..node.start_abs()];
+        self.output += &self.source[(self.last_index - self.base)..(node.start_abs() - self.base)];
         self.last_index = node.end_abs();
         Ok(())
     }
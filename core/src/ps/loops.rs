@@ -250,8 +250,8 @@ impl<'a> RuleMut<'a> for ForStatementFlowControl {
                 }
             }
             "statement_block" => {
-                let parent = view.parent().unwrap();
-                if matches!(parent.kind(), "while_statement" | "for_statement")
+                if let Some(parent) = view.parent()
+                    && matches!(parent.kind(), "while_statement" | "for_statement")
                     && self.loop_id == Some(parent.id())
                     && parent.data().is_none()
                     && self.statment_count == 1
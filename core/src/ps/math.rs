@@ -0,0 +1,389 @@
+use crate::error::MinusOneResult;
+use crate::ps::Powershell::{self, PSItem, Raw, Type};
+use crate::ps::Value::{self, Float, Num, Str};
+use crate::ps::convert::{IntegerType, arguments};
+use crate::ps::tool::StringTool;
+use crate::rule::RuleMut;
+use crate::tree::{ControlFlow, NodeMut};
+use log::trace;
+
+/// Render a double as .NET does : shortest round trip representation,
+/// with an exponent from 1E+15 and under 1E-05
+///
+/// # Example
+/// ```
+/// use minusone::ps::math::format_double;
+///
+/// assert_eq!(format_double(2.5), "2.5");
+/// assert_eq!(format_double(100.0), "100");
+/// assert_eq!(format_double(1e20), "1E+20");
+/// assert_eq!(format_double(0.00001), "1E-05");
+/// ```
+pub fn format_double(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    let scientific = format!("{:e}", value);
+    let Some((mantissa, exponent)) = scientific.split_once('e') else {
+        return value.to_string();
+    };
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    if value != 0.0 && !(-5..15).contains(&exponent) {
+        format!(
+            "{}E{}{:02}",
+            mantissa,
+            if exponent < 0 { '-' } else { '+' },
+            exponent.abs()
+        )
+    } else {
+        value.to_string()
+    }
+}
+
+/// Powershell source of a double, special values are taken from [double]
+pub fn double_literal(value: f64) -> String {
+    if value.is_nan() {
+        "[double]::NaN".to_string()
+    } else if value == f64::INFINITY {
+        "[double]::PositiveInfinity".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "[double]::NegativeInfinity".to_string()
+    } else {
+        format_double(value)
+    }
+}
+
+/// Parse a real literal : 1.5, .5, 1e2, 1.5kb
+///
+/// Decimal (d) and long (l) suffixes are not handled
+pub fn parse_real_literal(token: &str) -> Option<f64> {
+    let token = token.to_lowercase();
+    let (digits, multiplier) = match ["kb", "mb", "gb", "tb", "pb"]
+        .iter()
+        .position(|suffix| token.ends_with(suffix))
+    {
+        Some(index) => (&token[..token.len() - 2], 1024f64.powi(index as i32 + 1)),
+        None => (token.as_str(), 1.0),
+    };
+    // only digits, point and exponent, rust would accept inf and nan
+    if !digits
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | '+' | '-'))
+    {
+        return None;
+    }
+    digits.parse::<f64>().ok().map(|value| value * multiplier)
+}
+
+/// Numeric value of an integer or a double
+pub(crate) fn as_double(value: &Value) -> Option<f64> {
+    match value {
+        Num(n) => Some(*n as f64),
+        Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+/// Conversion of a double to an integer type, rounded half to even as .NET does
+///
+/// # Example
+/// ```
+/// use minusone::ps::convert::IntegerType;
+/// use minusone::ps::math::double_to_integer;
+///
+/// assert_eq!(double_to_integer(65.5, IntegerType::Int32), Some(66));
+/// assert_eq!(double_to_integer(66.5, IntegerType::Int32), Some(66));
+/// assert_eq!(double_to_integer(255.6, IntegerType::Byte), None);
+/// ```
+pub fn double_to_integer(value: f64, integer_type: IntegerType) -> Option<i64> {
+    let rounded = value.round_ties_even();
+    // i128 covers all integer types, out of range values are rejected by check
+    if !rounded.is_finite() || rounded.abs() >= 2f64.powi(100) {
+        return None;
+    }
+    integer_type.check(rounded as i128)
+}
+
+/// Arithmetic operation between two numbers, with the Powershell promotion rules
+///
+/// - integers stay integers unless the result overflows, then it becomes a double
+/// - a division that is not exact produces a double
+/// - any double operand produces a double
+/// - a division by zero is an error, so no result is inferred
+///
+/// # Example
+/// ```
+/// use minusone::ps::Value::{Num, Float};
+/// use minusone::ps::math::arithmetic;
+///
+/// assert_eq!(arithmetic(&Num(130), "/", &Num(2)), Some(Num(65)));
+/// assert_eq!(arithmetic(&Num(5), "/", &Num(2)), Some(Float(2.5)));
+/// assert_eq!(arithmetic(&Float(0.5), "+", &Num(1)), Some(Float(1.5)));
+/// assert_eq!(arithmetic(&Num(1), "/", &Num(0)), None);
+/// ```
+pub fn arithmetic(left: &Value, operator: &str, right: &Value) -> Option<Value> {
+    if let (Num(left), Num(right)) = (left, right) {
+        let (left, right) = (*left, *right);
+        return match operator {
+            "+" => Some(
+                left.checked_add(right)
+                    .map(Num)
+                    .unwrap_or(Float(left as f64 + right as f64)),
+            ),
+            "-" => Some(
+                left.checked_sub(right)
+                    .map(Num)
+                    .unwrap_or(Float(left as f64 - right as f64)),
+            ),
+            "*" => Some(
+                left.checked_mul(right)
+                    .map(Num)
+                    .unwrap_or(Float(left as f64 * right as f64)),
+            ),
+            "/" if right == 0 => None,
+            "/" => match (left.checked_rem(right), left.checked_div(right)) {
+                (Some(0), Some(result)) => Some(Num(result)),
+                _ => Some(Float(left as f64 / right as f64)),
+            },
+            "%" => left.checked_rem(right).map(Num),
+            _ => None,
+        };
+    }
+
+    let (left, right) = (as_double(left)?, as_double(right)?);
+    match operator {
+        "+" => Some(Float(left + right)),
+        "-" => Some(Float(left - right)),
+        "*" => Some(Float(left * right)),
+        "/" | "%" if right == 0.0 => None,
+        "/" => Some(Float(left / right)),
+        "%" => Some(Float(left % right)),
+        _ => None,
+    }
+}
+
+pub(crate) fn is_double_typename(typename: &str) -> bool {
+    typename.strip_prefix("system.").unwrap_or(typename) == "double"
+}
+
+pub(crate) fn is_decimal_typename(typename: &str) -> bool {
+    typename.strip_prefix("system.").unwrap_or(typename) == "decimal"
+}
+
+fn is_math_typename(typename: &str) -> bool {
+    typename.strip_prefix("system.").unwrap_or(typename) == "math"
+}
+
+/// Integral result of Floor, Ceiling... on an integer
+fn integral(value: &Value, f: impl Fn(f64) -> f64) -> Option<Value> {
+    match value {
+        Num(n) => Some(Num(*n)),
+        Float(x) => Some(Float(f(*x))),
+        _ => None,
+    }
+}
+
+/// [Math]::Round(x, digits, mode), half to even unless AwayFromZero is given
+fn round(value: &Value, digits: i64, away_from_zero: bool) -> Option<Value> {
+    let x = match value {
+        Num(n) => return Some(Num(*n)),
+        Float(x) => *x,
+        _ => return None,
+    };
+    if !(0..=15).contains(&digits) {
+        return None;
+    }
+    let power = 10f64.powi(digits as i32);
+    let scaled = x * power;
+    let rounded = if away_from_zero {
+        scaled.round()
+    } else {
+        scaled.round_ties_even()
+    };
+    Some(Float(if digits == 0 {
+        rounded
+    } else {
+        rounded / power
+    }))
+}
+
+/// Midpoint rounding mode, as a name or as its value
+fn is_away_from_zero(value: &Value) -> Option<bool> {
+    match value {
+        Num(0) => Some(false),
+        Num(1) => Some(true),
+        Str(s) => match s.to_lowercase().as_str() {
+            "toeven" => Some(false),
+            "awayfromzero" => Some(true),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Integer result when both operands are integers, a double otherwise
+fn min_max(left: &Value, right: &Value, max: bool) -> Option<Value> {
+    match (left, right) {
+        (Num(l), Num(r)) => Some(Num(if max { *l.max(r) } else { *l.min(r) })),
+        _ => {
+            let (l, r) = (as_double(left)?, as_double(right)?);
+            Some(Float(if max { l.max(r) } else { l.min(r) }))
+        }
+    }
+}
+
+/// Dispatch a [Math] static method on known arguments
+fn math_method(method: &str, value: &Value, others: &[Value]) -> Option<Value> {
+    match (method, others) {
+        ("floor", []) => integral(value, f64::floor),
+        ("ceiling", []) => integral(value, f64::ceil),
+        ("truncate", []) => integral(value, f64::trunc),
+        ("round", []) => round(value, 0, false),
+        ("round", [Num(digits)]) => round(value, *digits, false),
+        ("round", [mode @ Str(_)]) => round(value, 0, is_away_from_zero(mode)?),
+        ("round", [Num(digits), mode]) => round(value, *digits, is_away_from_zero(mode)?),
+        ("abs", []) => match value {
+            Num(n) => n.checked_abs().map(Num),
+            _ => Some(Float(as_double(value)?.abs())),
+        },
+        ("sign", []) => match value {
+            Num(n) => Some(Num(n.signum())),
+            _ => {
+                let x = as_double(value)?;
+                // Math.Sign throws on NaN
+                if x.is_nan() {
+                    None
+                } else if x == 0.0 {
+                    Some(Num(0))
+                } else {
+                    Some(Num(x.signum() as i64))
+                }
+            }
+        },
+        ("sqrt", []) => Some(Float(as_double(value)?.sqrt())),
+        ("exp", []) => Some(Float(as_double(value)?.exp())),
+        ("log", []) => Some(Float(as_double(value)?.ln())),
+        ("log", [base]) => Some(Float(as_double(value)?.log(as_double(base)?))),
+        ("log10", []) => Some(Float(as_double(value)?.log10())),
+        ("log2", []) => Some(Float(as_double(value)?.log2())),
+        ("pow", [exponent]) => Some(Float(as_double(value)?.powf(as_double(exponent)?))),
+        ("min", [other]) => min_max(value, other, false),
+        ("max", [other]) => min_max(value, other, true),
+        _ => None,
+    }
+}
+
+/// This rule infers [Math] static methods and constants
+///
+/// - `[Math]::Floor`, `Ceiling`, `Truncate` and `Round`, with digits and midpoint mode
+/// - `[Math]::Abs`, `Sign`, `Min`, `Max`
+/// - `[Math]::Pow`, `Sqrt`, `Exp`, `Log`, `Log10`, `Log2`
+/// - `[Math]::PI` and `[Math]::E`
+///
+/// Methods are applied on each value of a pipeline item too,
+/// so `% { [char][Math]::Floor($_ / 2) }` folds.
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::integer::{ParseInt, AddInt};
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::cast::Cast;
+/// use minusone::ps::math::MathMethod;
+///
+/// let mut tree = build_powershell_tree("[char]([Math]::Pow(2, 6) + 1)").unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseInt::default(),
+///     AddInt::default(),
+///     ParseType::default(),
+///     Cast::default(),
+///     MathMethod::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "\"A\"");
+/// ```
+#[derive(Default)]
+pub struct MathMethod;
+
+impl<'a> RuleMut<'a> for MathMethod {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+
+        if view.kind() == "member_access"
+            && let (Some(type_node), Some(op), Some(member_name)) =
+                (view.child(0), view.child(1), view.child(2))
+            && op.text()? == "::"
+            && let Some(Type(typename)) = type_node.data()
+            && is_math_typename(typename)
+        {
+            let constant = match member_name.text()?.to_string().normalize().as_str() {
+                "pi" => std::f64::consts::PI,
+                "e" => std::f64::consts::E,
+                _ => return Ok(()),
+            };
+            trace!("MathMethod (L): Setting node with constant: {}", constant);
+            node.set(Raw(Float(constant)));
+        } else if view.kind() == "invokation_expression"
+            && let (Some(type_node), Some(op), Some(member_name), Some(args_list)) =
+                (view.child(0), view.child(1), view.child(2), view.child(3))
+            && op.text()? == "::"
+            && let Some(Type(typename)) = type_node.data()
+            && is_math_typename(typename)
+        {
+            let method = member_name.text()?.to_string().normalize();
+            let arguments = arguments(&args_list);
+            let Some((first, others)) = arguments.split_first() else {
+                return Ok(());
+            };
+            let Some(others) = others
+                .iter()
+                .map(|argument| match argument.data() {
+                    Some(Raw(value)) => Some(value.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<Value>>>()
+            else {
+                return Ok(());
+            };
+
+            let result = match first.data() {
+                Some(Raw(value)) => math_method(&method, value, &others).map(Raw),
+                Some(PSItem(values)) => values
+                    .iter()
+                    .map(|value| math_method(&method, value, &others))
+                    .collect::<Option<Vec<Value>>>()
+                    .map(PSItem),
+                _ => None,
+            };
+
+            if let Some(result) = result {
+                trace!("MathMethod (L): Setting node with result: {:?}", result);
+                node.set(result);
+            }
+        }
+        Ok(())
+    }
+}
@@ -4,7 +4,9 @@ use self::bool::*;
 use self::cast::*;
 use self::cmdlets::*;
 use self::compression::*;
+use self::convert::*;
 use self::crypto::*;
+use self::digest::*;
 use self::encoding::*;
 use self::foreach::*;
 use self::forward::*;
@@ -13,14 +15,24 @@ use self::integer::*;
 use self::join::*;
 use self::linter::*;
 use self::loops::*;
+use self::math::*;
 use self::method::*;
+use self::object::*;
+use self::pipeline::*;
+use self::regex::*;
+use self::scriptblock::*;
+use self::securestring::*;
 use self::string::*;
 use self::switch::*;
 use self::typing::*;
 use self::var::*;
+use crate::artefact::ArtefactLanguage;
 use crate::error::{Error, MinusOneResult};
-use crate::rule::{RuleMut, RuleSet, RuleSetBuilderType};
+use crate::ps::host::HostProfile;
+use crate::query::{QueryLanguage, QueryRule, QueryValue};
+use crate::rule::{RuleCategory, RuleInfo, RuleMut, RuleSafety, RuleSet, RuleSetBuilderType};
 use crate::tree::{HashMapStorage, Storage, Tree};
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::fmt::Display;
 use tree_sitter_powershell::LANGUAGE as powershell_language;
@@ -28,21 +40,33 @@ use tree_sitter_powershell::LANGUAGE as powershell_language;
 pub mod access;
 pub mod array;
 pub mod backend;
+pub mod behaviour;
 pub mod bool;
 pub mod cast;
 pub mod cmdlets;
+pub mod cmdline;
 pub mod comparison;
 pub mod compression;
+pub mod convert;
 pub mod crypto;
+pub mod digest;
 pub mod encoding;
 pub mod foreach;
 pub mod forward;
 pub mod hash;
+pub mod host;
 pub mod integer;
 pub mod join;
 pub mod linter;
 pub mod loops;
+pub mod math;
 pub mod method;
+pub mod object;
+pub mod pipeline;
+pub mod regex;
+pub mod scriptblock;
+pub mod securestring;
+pub mod r#static;
 pub mod step;
 pub mod strategy;
 pub mod string;
@@ -54,15 +78,54 @@ pub mod trace;
 pub mod typing;
 pub mod utils;
 pub mod var;
-//todo: add : mod r#static;
 
-#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Debug, Clone)]
 pub enum Value {
     Num(i64),
     Str(String),
     Bool(bool),
+    Float(f64),
+}
+
+impl Value {
+    /// Order of the variants, used to compare values of different types
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Num(_) => 0,
+            Value::Str(_) => 1,
+            Value::Bool(_) => 2,
+            Value::Float(_) => 3,
+        }
+    }
+}
+
+// Doubles are totally ordered to be used as hashtable keys
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Num(l), Value::Num(r)) => l.cmp(r),
+            (Value::Str(l), Value::Str(r)) => l.cmp(r),
+            (Value::Bool(l), Value::Bool(r)) => l.cmp(r),
+            (Value::Float(l), Value::Float(r)) => l.total_cmp(r),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value {}
+
 impl Value {
     fn normalize(&self) -> Value {
         match self {
@@ -82,6 +145,7 @@ impl Display for Value {
                 Value::Str(s) => s.clone(),
                 Value::Bool(true) => "True".to_string(),
                 Value::Bool(false) => "False".to_string(),
+                Value::Float(f) => format_double(*f),
             }
         )
     }
@@ -93,6 +157,8 @@ impl Value {
             Value::Str(s) => {
                 if let Ok(number) = s.parse::<i64>() {
                     Some(number)
+                } else if let Some(number) = parse_real_literal(s.trim()) {
+                    double_to_integer(number, IntegerType::Int64)
                 } else if s.len() > 2 {
                     u32::from_str_radix(&s[2..], 16).map(|e| e as i64).ok()
                 } else {
@@ -100,6 +166,7 @@ impl Value {
                 }
             }
             Value::Num(i) => Some(*i),
+            Value::Float(f) => double_to_integer(*f, IntegerType::Int64),
             Value::Bool(_) => None,
         }
     }
@@ -125,35 +192,160 @@ pub enum Powershell {
     HashEntry(Value, Value),
     Type(String), // Will infer type
     Bytes(Vec<u8>),
-    Crypto(AesState), // Tracks a partially/fully configured AES algorithm or transform object
-    Stream(Vec<u8>),  // Tracks a Stream/StreamReader object backed by a known byte buffer
+    Crypto(CipherState), // Tracks a partially/fully configured symmetric algorithm or transform object
+    KeyDerivation(KeyDerivationState), // Tracks a Rfc2898DeriveBytes/PasswordDeriveBytes object and its consumed bytes
+    Stream(Vec<u8>), // Tracks a Stream/StreamReader/BinaryReader object by the bytes left to read
+    ZipArchive(Vec<ArchiveEntry>), // Tracks an in-memory ZipArchive, or its Entries, by its entries
+    ZipEntry(ArchiveEntry), // Tracks a ZipArchiveEntry
+    SecureString(String), // Tracks a SecureString, or an unmanaged copy of it, by its plaintext
+    Credential(CredentialKind, String), // Tracks a PSCredential/NetworkCredential by its password
+    ScriptBlock(String, Option<Box<Powershell>>), // Tracks a script block by its source and value
+    Object(Properties), // Tracks a PSCustomObject/PSObject by its properties
+    Regex(String, RegexOptions), // Tracks a .NET regex object by its pattern and options
+    RegexMatch(Match), // Tracks a .NET regex Match
+    MatchCollection(Vec<Match>), // Tracks a .NET regex MatchCollection
     Unknown,
 }
 
+impl From<&Value> for QueryValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Num(n) => QueryValue::Int(*n),
+            Value::Str(s) => QueryValue::Str(s.clone()),
+            Value::Bool(b) => QueryValue::Bool(*b),
+            Value::Float(f) => QueryValue::Float(*f),
+        }
+    }
+}
+
+fn value_from_query_value(value: QueryValue) -> Option<Value> {
+    match value {
+        QueryValue::Int(n) => Some(Value::Num(n)),
+        QueryValue::Str(s) => Some(Value::Str(s)),
+        QueryValue::Bool(b) => Some(Value::Bool(b)),
+        QueryValue::Float(f) => Some(Value::Float(f)),
+        _ => None,
+    }
+}
+
+impl ArtefactLanguage for Powershell {
+    fn artefact_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Powershell::Bytes(bytes) | Powershell::Stream(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+}
+
+impl QueryLanguage for Powershell {
+    fn language() -> tree_sitter::Language {
+        powershell_language.into()
+    }
+
+    fn builtin_rules() -> Vec<RuleInfo> {
+        PowershellRuleSet::rules()
+    }
+
+    fn to_query_value(&self) -> Option<QueryValue> {
+        match self {
+            Powershell::Raw(v) => Some(v.into()),
+            Powershell::Array(values) => Some(QueryValue::Array(
+                values.iter().map(QueryValue::from).collect(),
+            )),
+            Powershell::Bytes(bytes) => Some(QueryValue::Bytes(bytes.clone())),
+            Powershell::Type(t) => Some(QueryValue::Type(t.clone())),
+            Powershell::Null => Some(QueryValue::Null),
+            _ => None,
+        }
+    }
+
+    fn from_query_value(value: QueryValue) -> Option<Self> {
+        match value {
+            QueryValue::Array(values) => values
+                .into_iter()
+                .map(value_from_query_value)
+                .collect::<Option<Vec<Value>>>()
+                .map(Powershell::Array),
+            QueryValue::Bytes(bytes) => Some(Powershell::Bytes(bytes)),
+            QueryValue::Type(t) => Some(Powershell::Type(t.to_lowercase())),
+            QueryValue::Null => Some(Powershell::Null),
+            value => value_from_query_value(value).map(Powershell::Raw),
+        }
+    }
+}
+
 pub struct PowershellRuleSet<'a> {
     ruleset: RuleSet<'a, Powershell>,
 }
 
 macro_rules! impl_powershell_ruleset {
-    ( $($ty:ident),* ) => {
+    ( $( $ty:ident : $category:ident, $safety:ident, $default_enabled:literal, [ $( $kind:literal ),* ], $description:literal );* ) => {
         /// This is the rule set use to perform
         /// inferred type in Powershell deobfuscation
         pub type PowershellDefaultRuleSet = ( $($ty,)* );
 
         impl<'a> PowershellRuleSet<'a> {
             pub fn new(ctx: RuleSetBuilderType) -> Self {
+                Self::with_query_rules(ctx, &[])
+            }
+
+            /// Build the rule set with additional query rules,
+            /// applied after the built-in ones and filtered the same way
+            pub fn with_query_rules(ctx: RuleSetBuilderType, query_rules: &'a [QueryRule<Powershell>]) -> Self {
+                Self::with_host_profile(ctx, query_rules, &HostProfile::default())
+            }
+
+            /// Build the rule set emulating a specific host
+            /// for environment and automatic variables
+            pub fn with_host_profile(ctx: RuleSetBuilderType, query_rules: &'a [QueryRule<Powershell>], profile: &HostProfile) -> Self {
+                let ctx = ctx.expand_categories(&Self::rules());
+                let layer = LayerContext::new(&ctx, &Self::rules(), profile);
+                Self::with_layer_context(ctx, query_rules, &layer)
+            }
+
+            /// Build the rule set of a script layer,
+            /// with the rules and host of the script it comes from
+            pub fn for_layer(layer: &LayerContext, query_rules: &'a [QueryRule<Powershell>]) -> Self {
+                Self::with_layer_context(layer.rule_set(), query_rules, layer)
+            }
+
+            fn with_layer_context(ctx: RuleSetBuilderType, query_rules: &'a [QueryRule<Powershell>], layer: &LayerContext) -> Self {
+                let profile = layer.host_profile();
+                let mut rules: Vec<(&'a str, Box<dyn RuleMut<'a, Language = Powershell> + 'a>)> = vec![
+                    $( (stringify!($ty), Box::new($ty::default())), )*
+                ];
+                for (name, rule) in rules.iter_mut() {
+                    match *name {
+                        "StaticVar" => *rule = Box::new(StaticVar::with_host_profile(profile.clone())),
+                        "Var" => *rule = Box::new(Var::with_host_profile(profile.clone())),
+                        "ParseScriptBlock" => *rule = Box::new(ParseScriptBlock::with_layer_context(layer.clone())),
+                        "ScriptBlockCreate" => *rule = Box::new(ScriptBlockCreate::with_layer_context(layer.clone())),
+                        "ScriptBlockInvoke" => *rule = Box::new(ScriptBlockInvoke::with_layer_context(layer.clone())),
+                        "PipelineStage" => *rule = Box::new(PipelineStage::with_layer_context(layer.clone())),
+                        _ => (),
+                    }
+                }
+                rules.extend(query_rules.iter().map(|r| {
+                    (r.name(), Box::new(r) as Box<dyn RuleMut<'a, Language = Powershell> + 'a>)
+                }));
+
                 Self {
-                    ruleset: RuleSet::new(
-                        vec![
-                            $( (stringify!($ty), Box::new($ty::default())), )*
-                        ],
-                        ctx
-                    )
+                    ruleset: RuleSet::new(rules, ctx)
                 }
             }
 
-            pub fn names(self) -> Vec<&'a str> {
-                vec![ $( stringify!($ty), )* ]
+            /// Built-in rules, in the order they are applied
+            pub fn rules() -> Vec<RuleInfo> {
+                vec![ $(
+                    RuleInfo {
+                        name: stringify!($ty),
+                        description: $description,
+                        category: RuleCategory::$category,
+                        node_kinds: &[ $( $kind ),* ],
+                        safety: RuleSafety::$safety,
+                        default_enabled: $default_enabled,
+                    },
+                )* ]
             }
 
         }
@@ -161,53 +353,72 @@ macro_rules! impl_powershell_ruleset {
 }
 
 impl_powershell_ruleset!(
-    Forward, // Special rule that will forward inferred value in case the node is transparent
-    WildcardCmdlet, // Resolve wildcarded cmdlet/function/alias names (I*-Ex*) to their canonical form
-    ParseInt,       // Parse integer
-    AddInt,         // +, - operations on integer
-    MultInt,        // *, / operations on integer
-    ParseString,    // Parse string token, including multiline strings
-    ConcatString,   // String concatenation operation
-    Cast,           // cast operation, like [char]0x65
-    ParseArrayLiteral, // It will parse array declared using separate value (integer or string) by a comma
-    ParseRange,        // It will parse .. operator and generate an array
-    AccessString,      // The access operator [] apply to a string : "foo"[0] => "f"
-    JoinComparison, // It will infer join string operation using the -join operator : @('a', 'b', 'c') -join '' => "abc"
-    JoinStringMethod, // It will infer join string operation using the [string]::join method : [string]::join('', @('a', 'b', 'c'))
-    JoinOperator, // It will infer join string operation using the -join unary operator -join @('a', 'b', 'c')
-    PSItemInferrator, // PsItem is used to inferred commandlet pattern like % { [char] $_ }
-    ForEach,      // It will used PSItem rules to inferred foreach-object command
-    StringBuiltins, // Centralized dispatcher for string builtins : ToLower, ToUpper, Replace, ...
-    ComputeArrayExpr, // It will infer array that start with @
-    NewObjectArray, // Infers arrays constructed via New-Object cmdlet
-    StringReplaceOp, // It will infer replace method apply to a string by using the -replace operator
-    StaticVar,       // It will infer value of known variable : $pshome, $shellid
-    CastNull,        // It will infer value of +$() or -$() which will produce 0
-    ParseHash,       // Parse hashtable
-    FormatString, // It will infer string when format operator is used ; "{1}-{0}" -f "Debug", "Write"
-    ParseBool,    // It will infer boolean operator
-    Comparison,   // It will infer comparison when it's possible
-    Not,          // It will infer the ! operator
-    ParseType,    // Parse type
-    DecodeBase64, // Decode calls to FromBase64
-    AesType,      // Resolve AES algorithm objects and CreateDecryptor/CreateEncryptor(key, iv)
-    AesTransformFinalBlock, // Decode/encode calls to TransformFinalBlock(bytes, offset, count)
-    StreamType,   // Resolve MemoryStream/GzipStream/DeflateStream/ZLibStream/StreamReader objects
-    StreamReadToEnd, // Decode calls to StreamReader.ReadToEnd()
-    EncodingType, // Resolve [System.Text.Encoding] statics, constructors and GetEncoding(...)
-    EncodingGetString, // Decode calls to Encoding.GetString(byte[])
-    EncodingGetBytes, // Encode calls to Encoding.GetBytes(string)
-    NewStringMethod, // Infer [System.String]::new(@(char codes)) constructor
-    Length,       // Decode attribute length of string and array
-    BoolAlgebra,  // Add support to boolean algebra (or and)
-    Var,          // Variable replacement in case of predictable flow
-    AddArray,     // Array concat using +, operator
-    StringSplitMethod, // Handle split method
-    AccessArray,  // Handle static array element access
-    AccessHashMap, // Handle hashmap access
-    Switch,       // Handle switch predictible branches
-    ForStatementCondition, // Infer for condition to remove fake loops
-    ForStatementFlowControl  // Simplify for statment based on flow control
+    Forward: Parsing, Pure, true, ["unary_expression", "array_literal_expression", "range_expression", "format_expression", "multiplicative_expression", "additive_expression", "comparison_expression", "bitwise_expression", "string_literal", "logical_expression", "integer_literal", "argument_expression", "range_argument_expression", "format_argument_expression", "multiplicative_argument_expression", "additive_argument_expression", "comparison_argument_expression", "bitwise_argument_expression", "logical_argument_expression", "command_name_expr", "expression_with_unary_operator", "while_condition", "for_condition", "member_name", "sub_expression", "parenthesized_expression", "pipeline", "pipeline_chain", "command", "type_literal", "key_expression", "hash_literal_expression"], "Forward the inferred value of transparent nodes, like parenthesis";
+    WildcardCmdlet: Commands, Pure, true, ["command_name"], "Resolve wildcarded cmdlet/function/alias names (I*-Ex*) to their canonical form";
+    ParseInt: Parsing, Pure, true, ["hexadecimal_integer_literal", "decimal_integer_literal", "real_literal", "expression_with_unary_operator"], "Parse integer literals";
+    AddInt: Arithmetic, Pure, true, ["additive_expression", "additive_argument_expression"], "+ and - operations on integers and doubles";
+    MultInt: Arithmetic, Pure, true, ["multiplicative_expression", "multiplicative_argument_expression"], "*, / and % operations on integers and doubles";
+    ParseString: Parsing, Pure, true, ["verbatim_string_characters", "expandable_string_literal"], "Parse string tokens, including multiline strings";
+    ConcatString: Strings, Pure, true, ["additive_expression", "additive_argument_expression"], "String concatenation with the + operator";
+    Cast: Objects, Pure, true, ["cast_expression", "expression_with_unary_operator"], "Cast operations, like [char]0x65";
+    ParseArrayLiteral: Arrays, Pure, true, ["array_literal_expression"], "Arrays of values separated by commas";
+    ParseRange: Arrays, Pure, true, ["range_expression"], "Arrays generated by the .. operator";
+    AccessString: Strings, Pure, true, ["element_access"], "The [] operator on a string : \"foo\"[0] => \"f\"";
+    JoinComparison: Strings, Pure, true, ["comparison_expression"], "-join operator : @('a', 'b', 'c') -join '' => \"abc\"";
+    JoinStringMethod: Strings, Pure, true, ["invokation_expression"], "[string]::join method : [string]::join('', @('a', 'b', 'c'))";
+    JoinOperator: Strings, Pure, true, ["expression_with_unary_operator"], "-join unary operator : -join @('a', 'b', 'c')";
+    PSItemInferrator: Commands, Pure, true, ["command"], "Values of $_ in a pipeline, like % { [char] $_ }";
+    ForEach: Commands, Evaluates, true, ["command"], "ForEach-Object commands on known items, using the values of $_";
+    PipelineStage: Commands, Evaluates, true, ["command"], "Where-Object, Select-Object, Sort-Object, Get-Unique and Out-String on known items";
+    StringBuiltins: Strings, Pure, true, ["invokation_expression", "array_literal_expression"], "Centralized dispatcher for string builtins : ToLower, ToUpper, Replace, ...";
+    ComputeArrayExpr: Arrays, Pure, true, ["array_expression"], "Array expressions starting with @";
+    NewObjectArray: Arrays, Pure, true, ["command"], "Arrays constructed with the New-Object cmdlet";
+    StringReplaceOp: Strings, Pure, true, ["comparison_expression"], "-replace operator on strings";
+    ParseRegex: Regex, Pure, true, ["member_access", "cast_expression", "invokation_expression", "command"], "[regex]'pattern', [regex]::new(...), New-Object regex and [RegexOptions] values";
+    RegexMethod: Regex, Pure, true, ["invokation_expression"], "[regex]::Replace/Matches/Match/IsMatch/Split/Escape/Unescape, and on regex objects";
+    RegexMatchMember: Regex, Pure, true, ["member_access", "element_access"], "$m.Value, $m.Groups[1].Value, $_.Value over matches";
+    MatchOperator: Regex, Pure, true, ["comparison_expression"], "-match, -cmatch, -notmatch on scalars and arrays";
+    StaticVar: Variables, Pure, true, ["variable"], "Automatic variables with a known value : $pshome, $shellid, $env:comspec";
+    CastNull: Arithmetic, Pure, true, ["expression_with_unary_operator"], "+$() and -$(), which produce 0";
+    ParseHash: Objects, Pure, true, ["hash_entry", "hash_literal_body"], "Parse hashtables";
+    FormatString: Strings, Pure, true, ["format_expression"], "-f format operator : \"{1}-{0}\" -f \"Debug\", \"Write\"";
+    ParseBool: Parsing, Pure, true, ["variable"], "Parse $true and $false";
+    Comparison: Logic, Pure, true, ["comparison_expression"], "Comparison operators on known values";
+    Not: Logic, Pure, true, ["expression_with_unary_operator"], "The ! and -not operators";
+    ParseType: Parsing, Pure, true, ["type_name", "type_spec", "array_type_name"], "Parse type literals";
+    DecodeBase64: Encoding, Pure, true, ["member_access", "invokation_expression"], "Decode calls to FromBase64String";
+    ConvertNumber: Encoding, Pure, true, ["member_access", "invokation_expression"], "[Convert]::ToInt32('1F', 16), [Convert]::ToString(5, 2), [int]::Parse('ff', 'HexNumber')";
+    MathMethod: Arithmetic, Pure, true, ["member_access", "invokation_expression"], "[Math]::Floor, Round, Pow, Sqrt... and [Math]::PI";
+    ConvertBytes: Encoding, Pure, true, ["invokation_expression"], "[Convert]::ToBase64String, [Convert]::FromHexString, [BitConverter]::ToString/GetBytes";
+    AesType: Crypto, Pure, true, ["member_access", "invokation_expression", "command"], "Resolve AES/DES/TripleDES/RC2 algorithm objects and CreateDecryptor/CreateEncryptor(key, iv)";
+    AesTransformFinalBlock: Crypto, Pure, true, ["invokation_expression"], "Decode/encode calls to TransformFinalBlock(bytes, offset, count)";
+    ComputeHash: Crypto, Pure, true, ["member_access", "invokation_expression", "command"], "Resolve MD5/SHA1/SHA256/SHA384/SHA512 objects and fold ComputeHash/HashData";
+    DeriveKey: Crypto, Pure, true, ["invokation_expression", "command"], "Rfc2898DeriveBytes/PasswordDeriveBytes(password, salt, ...).GetBytes(n)";
+    ConvertToSecureString: Crypto, Pure, true, ["command"], "Decrypt ConvertTo-SecureString payloads exported with a key";
+    SecureStringUnwrap: Crypto, Pure, true, ["member_access", "invokation_expression", "command"], "Fold SecureString back to plain text through Marshal BSTR or credentials";
+    ParseScriptBlock: Functions, Pure, true, ["script_block_expression"], "Script block literals, deobfuscated as their own layer : { ... }";
+    ScriptBlockCreate: Functions, Pure, true, ["invokation_expression"], "[ScriptBlock]::Create($s) and $ExecutionContext.InvokeCommand.NewScriptBlock($s)";
+    ScriptBlockInvoke: Functions, Evaluates, true, ["command", "invokation_expression"], "& $sb, $sb.Invoke(), Invoke-Command -ScriptBlock $sb, InvokeScript($s)";
+    StreamType: Compression, Pure, true, ["member_access", "command", "invokation_expression"], "Resolve MemoryStream/GzipStream/DeflateStream/ZLibStream/StreamReader/BinaryReader/ZipArchive objects";
+    StreamReadToEnd: Compression, Pure, true, ["invokation_expression"], "Decode calls to StreamReader.ReadToEnd()";
+    StreamRead: Compression, Pure, true, ["member_access", "invokation_expression"], "ToArray/GetBuffer, BinaryReader.ReadBytes/ReadInt32... and Stream.Length";
+    ZipArchiveRead: Compression, Pure, true, ["member_access", "element_access", "invokation_expression"], "ZipArchive.GetEntry/Entries[n] and ZipArchiveEntry.Open()";
+    EncodingType: Encoding, Pure, true, ["member_access", "invokation_expression", "command"], "Resolve [System.Text.Encoding] statics, constructors and GetEncoding(...)";
+    EncodingGetString: Encoding, Pure, true, ["member_access", "invokation_expression"], "Decode calls to Encoding.GetString(byte[])";
+    EncodingGetBytes: Encoding, Pure, true, ["member_access", "invokation_expression"], "Encode calls to Encoding.GetBytes(string)";
+    NewStringMethod: Strings, Pure, true, ["invokation_expression"], "[System.String]::new(@(char codes)) constructor";
+    Length: Objects, Pure, true, ["member_access"], "Length of strings and arrays";
+    BoolAlgebra: Logic, Pure, true, ["logical_expression"], "-and and -or operators";
+    Var: Variables, Pure, true, ["program", "function_statement", "while_statement", "statement_block", "assignment_expression", "comparison_expression", "variable", "pre_increment_expression", "pre_decrement_expression", "post_increment_expression", "post_decrement_expression", "invokation_expression", "command"], "Variable replacement, when the flow is predictable";
+    AddArray: Arrays, Pure, true, ["additive_expression", "additive_argument_expression"], "Array concatenation with the + operator";
+    StringSplitMethod: Strings, Pure, true, ["invokation_expression"], "Split method on strings";
+    AccessArray: Arrays, Pure, true, ["element_access"], "Access to an element of a known array";
+    AccessHashMap: Objects, Pure, true, ["element_access", "member_access"], "Access to an entry of a known hashtable";
+    ParseObject: Objects, Pure, true, ["cast_expression", "command"], "[PSCustomObject]@{...}, New-Object PSObject -Property @{...}, Select-Object calculated properties";
+    AccessObject: Objects, Pure, true, ["member_access"], "Access to a property of a known object";
+    Switch: ControlFlow, Pure, true, ["switch_statement", "switch_clause", "switch_clause_condition", "switch_condition", "statement_block", "pipeline"], "Switch statements with a predictable branch";
+    ForStatementCondition: ControlFlow, Pure, true, ["while_condition", "for_condition"], "Remove fake loops whose condition is known";
+    ForStatementFlowControl: ControlFlow, Pure, true, ["flow_control_statement", "variable", "statement_block"], "Simplify loops left by their flow control statements"
 );
 
 impl<'a> RuleMut<'a> for PowershellRuleSet<'a> {
@@ -261,9 +472,7 @@ impl<'a> PowershellRuleSet<'a> {
 
 pub fn remove_powershell_extra(source: &str) -> MinusOneResult<String> {
     let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&powershell_language.into())
-        .expect("Error loading powershell grammar");
+    parser.set_language(&powershell_language.into())?;
 
     // Trim to assert program is at the beginning
     let source = source.trim();
@@ -272,12 +481,14 @@ pub fn remove_powershell_extra(source: &str) -> MinusOneResult<String> {
     let tree_sitter_remove_extra = parser.parse(source, None).unwrap();
     let root = Tree::<HashMapStorage<Powershell>>::new(source.as_bytes(), tree_sitter_remove_extra);
 
-    let root_node = root.root().or(Err(Error::invalid_program()))?;
-    if root_node.kind() != "program" {
-        return Err(Error::invalid_program());
+    if !root.is_processable() {
+        return Err(Error::unprocessable_source());
     }
-    if root_node.start_abs() != 0 {
-        return Err(Error::invalid_program_index(root_node.start_abs()));
+
+    // The whole source is an error region, keep it as is
+    let root_node = root.root()?;
+    if root_node.kind() != "program" {
+        return Ok(source.to_string());
     }
 
     let mut source_without_extra = RemoveComment::default();
@@ -293,9 +504,7 @@ pub fn build_powershell_tree_for_storage<T: Storage + Default>(
     source: &str,
 ) -> MinusOneResult<Tree<'_, T>> {
     let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&powershell_language.into())
-        .expect("Error loading powershell grammar");
+    parser.set_language(&powershell_language.into())?;
 
     // And the grammar is specified in lowercase
     let tree_sitter = parser.parse(source, None).unwrap();
@@ -0,0 +1,525 @@
+use crate::error::MinusOneResult;
+use crate::ps::Powershell::{self, Array, HashMap, Object, Raw, ScriptBlock, Type, Unknown};
+use crate::ps::Value::Str;
+use crate::ps::cmdlets::{BoundParameters, resolved_command_name};
+use crate::ps::foreach::find_previous_expr;
+use crate::ps::tool::StringTool;
+use crate::ps::var::Var;
+use crate::rule::RuleMut;
+use crate::tree::{ControlFlow, Node, NodeMut};
+use log::trace;
+use std::collections::BTreeMap;
+
+/// Properties of an object, keyed by their lowercase name
+///
+/// Properties with a value that was not inferred are kept as [`Powershell::Unknown`]
+pub type Properties = BTreeMap<String, Powershell>;
+
+fn is_custom_object_typename(typename: &str) -> bool {
+    matches!(
+        typename.strip_prefix("system.").unwrap_or(typename),
+        "pscustomobject" | "management.automation.pscustomobject"
+    )
+}
+
+fn is_psobject_typename(typename: &str) -> bool {
+    matches!(
+        typename.strip_prefix("system.").unwrap_or(typename),
+        "psobject" | "pscustomobject" | "management.automation.psobject"
+    )
+}
+
+/// Name of a property, from a literal or an inferred string
+fn property_name(node: &Node<Powershell>) -> MinusOneResult<Option<String>> {
+    Ok(match node.data() {
+        Some(Raw(name)) => Some(name.to_string().to_lowercase()),
+        _ if node.smallest_child().kind() == "generic_token"
+            || node.smallest_child().kind() == "simple_name" =>
+        {
+            Some(node.text()?.to_lowercase())
+        }
+        _ => None,
+    })
+}
+
+/// Properties declared by a hashtable literal : @{ K = 'v'; ... }
+///
+/// Contrary to `ParseHash`, values are not limited to raw values
+fn hash_literal_properties(node: &Node<Powershell>) -> MinusOneResult<Option<Properties>> {
+    let hash_literal = node.smallest_child();
+    if hash_literal.kind() != "hash_literal_expression" {
+        return Ok(None);
+    }
+
+    let mut properties = Properties::new();
+    let Some(hash_literal_body) = hash_literal
+        .child(1)
+        .filter(|body| body.kind() == "hash_literal_body")
+    else {
+        // @{}
+        return Ok(Some(properties));
+    };
+
+    for hash_entry in hash_literal_body.iter() {
+        if hash_entry.kind() != "hash_entry" {
+            continue;
+        }
+        let (Some(key_expression), Some(pipeline)) = (hash_entry.child(0), hash_entry.child(2))
+        else {
+            return Ok(None);
+        };
+        let Some(name) = property_name(&key_expression)? else {
+            return Ok(None);
+        };
+        properties.insert(name, pipeline.data().cloned().unwrap_or(Unknown));
+    }
+    Ok(Some(properties))
+}
+
+/// Properties from an inferred hashtable, or a hashtable literal
+fn properties_of(node: &Node<Powershell>) -> MinusOneResult<Option<Properties>> {
+    if let Some(properties) = hash_literal_properties(node)? {
+        return Ok(Some(properties));
+    }
+    Ok(match node.data() {
+        Some(HashMap(map)) => Some(
+            map.iter()
+                .map(|(k, v)| (k.to_string().to_lowercase(), Raw(v.clone())))
+                .collect(),
+        ),
+        _ => None,
+    })
+}
+
+/// Value of a calculated property of Select-Object : @{ n = 'K'; e = { ... } }
+fn calculated_property(
+    input: Option<&Powershell>,
+    specification: &Properties,
+) -> Option<(String, Powershell)> {
+    let name = ["name", "n", "label", "l"]
+        .iter()
+        .find_map(|key| specification.get(*key))?;
+    let Raw(name) = name else {
+        return None;
+    };
+
+    let expression = ["expression", "e"]
+        .iter()
+        .find_map(|key| specification.get(*key))?;
+    let value = match expression {
+        // a script block that does not depend on the input object
+        ScriptBlock(layer, value)
+            if !layer.contains("$_") && !layer.to_lowercase().contains("$psitem") =>
+        {
+            value.as_deref().cloned().unwrap_or(Unknown)
+        }
+        Raw(Str(source_name)) => input_property(input, source_name),
+        _ => Unknown,
+    };
+    Some((name.to_string().to_lowercase(), value))
+}
+
+fn input_property(input: Option<&Powershell>, name: &str) -> Powershell {
+    match input {
+        Some(Object(properties)) => properties
+            .get(&name.to_lowercase())
+            .cloned()
+            // Select-Object creates missing properties
+            .unwrap_or(Powershell::Null),
+        _ => Unknown,
+    }
+}
+
+/// Assign the property of an object, $o.Prop = $value
+///
+/// Returns false if the object has no such property,
+/// as Powershell throws in that case
+pub fn assign_object_property(
+    properties: &mut Properties,
+    member: &str,
+    value: &Powershell,
+) -> bool {
+    match properties.get_mut(member) {
+        Some(property) => {
+            *property = value.clone();
+            true
+        }
+        None => false,
+    }
+}
+
+/// A call to Add-Member, bound to its parameters
+///
+/// ```powershell
+/// Add-Member -InputObject $o -NotePropertyName K -NotePropertyValue 'v'
+/// $o | Add-Member -MemberType NoteProperty -Name K -Value 'v'
+/// $o | Add-Member -NotePropertyMembers @{ K = 'v' }
+/// ```
+pub struct AddMember<'a> {
+    /// Node of the variable holding the object
+    pub input: Node<'a, Powershell>,
+    pub members: Vec<(String, Powershell)>,
+    pub force: bool,
+    pub passthru: bool,
+}
+
+impl<'a> AddMember<'a> {
+    /// Bind an Add-Member command to its parameters
+    ///
+    /// Returns None for other commands, or member types other than NoteProperty
+    pub fn parse(command: &Node<'a, Powershell>) -> MinusOneResult<Option<Self>> {
+        let Some(command_name) = command.named_child("command_name") else {
+            return Ok(None);
+        };
+        if resolved_command_name(&command_name)? != "add-member" {
+            return Ok(None);
+        }
+
+        let Some(mut parameters) = BoundParameters::bind(
+            command,
+            &[
+                ("inputobject", false),
+                ("membertype", false),
+                ("name", false),
+                ("value", false),
+                ("notepropertyname", false),
+                ("notepropertyvalue", false),
+                ("notepropertymembers", false),
+                ("typename", false),
+                ("force", true),
+                ("passthru", true),
+            ],
+        )?
+        else {
+            return Ok(None);
+        };
+
+        let input = match parameters.named.remove("inputobject") {
+            Some(input) => input,
+            None => find_previous_expr(command)?,
+        };
+        let Some(input) = input.filter(|input| input.smallest_child().kind() == "variable") else {
+            return Ok(None);
+        };
+
+        let members = if let Some(Some(members)) = parameters.named.remove("notepropertymembers") {
+            let Some(members) = properties_of(&members)? else {
+                return Ok(None);
+            };
+            members.into_iter().collect()
+        } else if parameters.is_set("notepropertyname")
+            || (!parameters.is_set("membertype") && parameters.positional.len() == 2)
+        {
+            let (Some(name), Some(value)) = (
+                parameters.take("notepropertyname"),
+                parameters.take("notepropertyvalue"),
+            ) else {
+                return Ok(None);
+            };
+            let Some(name) = property_name(&name)? else {
+                return Ok(None);
+            };
+            vec![(name, value.data().cloned().unwrap_or(Unknown))]
+        } else {
+            let (Some(member_type), Some(name), Some(value)) = (
+                parameters.take("membertype"),
+                parameters.take("name"),
+                parameters.take("value"),
+            ) else {
+                return Ok(None);
+            };
+            if property_name(&member_type)?.as_deref() != Some("noteproperty") {
+                return Ok(None);
+            }
+            let Some(name) = property_name(&name)? else {
+                return Ok(None);
+            };
+            vec![(name, value.data().cloned().unwrap_or(Unknown))]
+        };
+
+        if !parameters.positional.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(AddMember {
+            input,
+            members,
+            force: parameters.is_set("force"),
+            passthru: parameters.is_set("passthru"),
+        }))
+    }
+
+    /// Name of the variable holding the object
+    pub fn variable_name(&self) -> MinusOneResult<Option<String>> {
+        Ok(Var::extract(self.input.text()?))
+    }
+
+    /// Add the members to the properties of the object
+    ///
+    /// Returns false when a member already exists without -Force,
+    /// as Powershell throws in that case
+    pub fn apply(&self, properties: &mut Properties) -> bool {
+        if !self.force
+            && self
+                .members
+                .iter()
+                .any(|(name, _)| properties.contains_key(name))
+        {
+            return false;
+        }
+        properties.extend(self.members.iter().cloned());
+        true
+    }
+}
+
+/// This rule infers custom objects from their constructors
+///
+/// ```powershell
+/// [PSCustomObject]@{ K = 'v' }
+/// New-Object PSObject -Property @{ K = 'v' }
+/// $o | Select-Object K, @{ n = 'V'; e = { 'v' } }
+/// ```
+///
+/// Properties are tracked in a [`Powershell::Object`],
+/// that [`AccessObject`] reads back.
+/// Members added by `Add-Member` and property assignments
+/// are tracked by `Var`.
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::object::{AccessObject, ParseObject};
+///
+/// let mut tree = build_powershell_tree("([PSCustomObject]@{ Key = 'minusone' }).kEy").unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseString::default(),
+///     ParseType::default(),
+///     ParseObject::default(),
+///     AccessObject::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "\"minusone\"");
+/// ```
+#[derive(Default)]
+pub struct ParseObject;
+
+impl<'a> RuleMut<'a> for ParseObject {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        match view.kind() {
+            // [PSCustomObject]@{ ... }
+            "cast_expression" => {
+                if let (Some(type_literal), Some(expression)) = (view.child(0), view.child(1))
+                    && let Some(Type(typename)) = type_literal.data()
+                    && is_custom_object_typename(typename)
+                    && let Some(properties) = hash_literal_properties(&expression)?
+                {
+                    trace!(
+                        "ParseObject (L): Setting node with custom object: {:?}",
+                        properties
+                    );
+                    node.set(Object(properties));
+                }
+            }
+            "command" => {
+                let Some(command_name) = view.named_child("command_name") else {
+                    return Ok(());
+                };
+                match resolved_command_name(&command_name)?.as_str() {
+                    // New-Object PSObject -Property @{ ... }
+                    "new-object" => {
+                        let Some(mut parameters) = BoundParameters::bind(
+                            &view,
+                            &[
+                                ("typename", false),
+                                ("argumentlist", false),
+                                ("property", false),
+                            ],
+                        )?
+                        else {
+                            return Ok(());
+                        };
+                        let Some(typename) = parameters.take("typename") else {
+                            return Ok(());
+                        };
+                        if !is_psobject_typename(&typename.text()?.to_lowercase())
+                            || parameters.is_set("argumentlist")
+                            || !parameters.positional.is_empty()
+                        {
+                            return Ok(());
+                        }
+
+                        let properties = match parameters.named.remove("property") {
+                            Some(Some(property)) => properties_of(&property)?,
+                            _ => Some(Properties::new()),
+                        };
+                        if let Some(properties) = properties {
+                            trace!(
+                                "ParseObject (L): Setting node with New-Object object: {:?}",
+                                properties
+                            );
+                            node.set(Object(properties));
+                        }
+                    }
+                    // $o | Select-Object K, @{ n = 'V'; e = { 'v' } }
+                    "select-object" | "select" => {
+                        let Some(mut parameters) =
+                            BoundParameters::bind(&view, &[("property", false)])?
+                        else {
+                            return Ok(());
+                        };
+                        let (Some(property), true) = (
+                            parameters.take("property"),
+                            parameters.positional.is_empty(),
+                        ) else {
+                            return Ok(());
+                        };
+                        let Some(previous) = find_previous_expr(&view)? else {
+                            return Ok(());
+                        };
+                        let input = previous.data();
+                        if matches!(input, Some(Array(_))) {
+                            return Ok(());
+                        }
+
+                        let specifications: Vec<Node<Powershell>> =
+                            if property.smallest_child().kind() == "array_literal_expression" {
+                                property
+                                    .smallest_child()
+                                    .iter()
+                                    .filter(|element| element.kind() != ",")
+                                    .collect()
+                            } else {
+                                vec![property]
+                            };
+
+                        let mut properties = Properties::new();
+                        for specification in specifications {
+                            if let Some(calculated) = hash_literal_properties(&specification)? {
+                                let Some((name, value)) = calculated_property(input, &calculated)
+                                else {
+                                    return Ok(());
+                                };
+                                properties.insert(name, value);
+                            } else if let Some(name) = property_name(&specification)? {
+                                // wildcards select an unknown set of properties
+                                if name.contains('*') {
+                                    return Ok(());
+                                }
+                                let value = input_property(input, &name);
+                                properties.insert(name, value);
+                            } else {
+                                return Ok(());
+                            }
+                        }
+
+                        trace!(
+                            "ParseObject (L): Setting node with Select-Object object: {:?}",
+                            properties
+                        );
+                        node.set(Object(properties));
+                    }
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+/// This rule infers the read of a known object property
+///
+/// ```powershell
+/// $o.Key
+/// $o.'Key'
+/// ```
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::object::{AccessObject, ParseObject};
+///
+/// let mut tree = build_powershell_tree("(New-Object PSObject -Property @{ Key = 'minusone' }).'KEY'").unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseString::default(),
+///     ParseType::default(),
+///     ParseObject::default(),
+///     AccessObject::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "\"minusone\"");
+/// ```
+#[derive(Default)]
+pub struct AccessObject;
+
+impl<'a> RuleMut<'a> for AccessObject {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() == "member_access"
+            && let (Some(object), Some(op), Some(member_name)) =
+                (view.child(0), view.child(1), view.child(2))
+            && op.text()? == "."
+            && let Some(Object(properties)) = object.data()
+        {
+            let member = match member_name.data() {
+                Some(Raw(member)) => member.to_string().to_lowercase(),
+                _ => member_name.text()?.to_string().normalize(),
+            };
+            if let Some(value) = properties.get(&member)
+                && *value != Unknown
+            {
+                trace!(
+                    "AccessObject (L): Setting node with property {}: {:?}",
+                    member, value
+                );
+                node.set(value.clone());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,543 @@
+use crate::error::MinusOneResult;
+use crate::profile::limits;
+use crate::ps::Powershell::{self, Array, Null, Raw};
+use crate::ps::Value::{self, Bool, Float, Num, Str};
+use crate::ps::cmdlets::{BoundParameters, resolved_command_name};
+use crate::ps::foreach::find_previous_expr;
+use crate::ps::math::double_literal;
+use crate::ps::scriptblock::LayerContext;
+use crate::ps::utils::string::escape_string;
+use crate::rule::RuleMut;
+use crate::tree::{ControlFlow, Node, NodeMut};
+use log::trace;
+use std::cmp::Ordering;
+
+/// Out-String renders each item on its own line, with the newline of the Windows host
+const OUT_STRING_NEWLINE: &str = "\r\n";
+
+enum TemplatePart {
+    Text(String),
+    Item,
+}
+
+/// Source of an expression on a pipeline item, where `$_` is replaced by the item
+struct ItemTemplate(Vec<TemplatePart>);
+
+impl ItemTemplate {
+    /// The body of a script block, `$_` and `$PSItem` of nested script blocks are left as is
+    fn from_block(block: &Node<Powershell>) -> MinusOneResult<Option<Self>> {
+        let Some(body) = block.child(1).filter(|body| body.kind() == "script_block") else {
+            return Ok(None);
+        };
+        let mut items = vec![];
+        Self::find_items(&body, block, &mut items)?;
+
+        let source = body.text()?;
+        let mut parts = vec![];
+        let mut last = body.start_abs();
+        for (start, end) in items {
+            parts.push(TemplatePart::Text(
+                source[last - body.start_abs()..start - body.start_abs()].to_string(),
+            ));
+            parts.push(TemplatePart::Item);
+            last = end;
+        }
+        parts.push(TemplatePart::Text(
+            source[last - body.start_abs()..].to_string(),
+        ));
+        Ok(Some(ItemTemplate(parts)))
+    }
+
+    fn find_items(
+        node: &Node<Powershell>,
+        block: &Node<Powershell>,
+        items: &mut Vec<(usize, usize)>,
+    ) -> MinusOneResult<()> {
+        for child in node.iter() {
+            if child.kind() == "variable"
+                && matches!(child.text()?.to_lowercase().as_str(), "$_" | "$psitem")
+            {
+                if child
+                    .get_parent_of_types(vec!["script_block_expression"])
+                    .as_ref()
+                    == Some(block)
+                {
+                    items.push((child.start_abs(), child.end_abs()));
+                }
+            } else {
+                Self::find_items(&child, block, items)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// A property of the item : Sort-Object Length
+    fn property(name: &str) -> Self {
+        ItemTemplate(vec![
+            TemplatePart::Item,
+            TemplatePart::Text(format!(".'{}'", name.replace('\'', "''"))),
+        ])
+    }
+
+    fn render(&self, item: &Value) -> String {
+        let literal = match item {
+            Num(n) => n.to_string(),
+            Str(s) => format!("\"{}\"", escape_string(s)),
+            Bool(true) => "$true".to_string(),
+            Bool(false) => "$false".to_string(),
+            Float(f) => double_literal(*f),
+        };
+        self.0
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Text(text) => text.clone(),
+                TemplatePart::Item => format!("({})", literal),
+            })
+            .collect()
+    }
+
+    /// The value of the expression for each item, or None if one is unknown
+    fn evaluate(&self, items: &[Value], layer: &LayerContext) -> Option<Vec<Powershell>> {
+        // script blocks are evaluated once per item, larger inputs are left as is
+        if items.len() > limits().pipeline_items {
+            return None;
+        }
+        items
+            .iter()
+            .map(|item| match layer.deobfuscate(&self.render(item)) {
+                Ok(Some((_, value))) => value,
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Template of a script block parameter
+fn block_template(node: &Node<Powershell>) -> MinusOneResult<Option<ItemTemplate>> {
+    let block = node.smallest_child();
+    if block.kind() != "script_block_expression" {
+        return Ok(None);
+    }
+    ItemTemplate::from_block(&block)
+}
+
+/// Boolean conversion of a value, as Where-Object does with its filter result
+fn is_true(value: &Powershell) -> Option<bool> {
+    match value {
+        Raw(Bool(b)) => Some(*b),
+        Raw(Num(n)) => Some(*n != 0),
+        Raw(Float(f)) => Some(*f != 0.0),
+        Raw(Str(s)) => Some(!s.is_empty()),
+        Array(values) => match values.as_slice() {
+            [] => Some(false),
+            [value] => is_true(&Raw(value.clone())),
+            _ => Some(true),
+        },
+        Null => Some(false),
+        _ => None,
+    }
+}
+
+/// Compare strings as the invariant culture does : case insensitive first, then lowercase before uppercase
+fn compare_strings(left: &str, right: &str, case_sensitive: bool) -> Ordering {
+    let ordering = left.to_lowercase().cmp(&right.to_lowercase());
+    if ordering != Ordering::Equal || !case_sensitive {
+        return ordering;
+    }
+    left.chars()
+        .zip(right.chars())
+        .map(|(l, r)| r.is_lowercase().cmp(&l.is_lowercase()))
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Sort-Object comparison, only values of the same type are compared
+fn compare_values(left: &Value, right: &Value, case_sensitive: bool) -> Ordering {
+    match (left, right) {
+        (Num(l), Num(r)) => l.cmp(r),
+        (Float(l), Float(r)) => l.total_cmp(r),
+        (Bool(l), Bool(r)) => l.cmp(r),
+        (Str(l), Str(r)) => compare_strings(l, r, case_sensitive),
+        _ => Ordering::Equal,
+    }
+}
+
+/// A count parameter : Some(None) when unset, None when its value is unknown
+fn count_parameter(parameters: &mut BoundParameters, name: &str) -> Option<Option<usize>> {
+    match parameters.named.remove(name) {
+        None => Some(None),
+        Some(value) => match value.as_ref().and_then(|v| v.data()) {
+            Some(Raw(Num(n))) => usize::try_from(*n).ok().map(Some),
+            _ => None,
+        },
+    }
+}
+
+/// Where-Object { ... }
+fn where_object(
+    command: &Node<Powershell>,
+    items: Vec<Value>,
+    layer: &LayerContext,
+) -> MinusOneResult<Option<Vec<Value>>> {
+    let Some(mut parameters) = BoundParameters::bind(command, &[("filterscript", false)])? else {
+        return Ok(None);
+    };
+    let (Some(filter), true) = (
+        parameters.take("filterscript"),
+        parameters.positional.is_empty(),
+    ) else {
+        return Ok(None);
+    };
+    let Some(template) = block_template(&filter)? else {
+        return Ok(None);
+    };
+    let Some(results) = template.evaluate(&items, layer) else {
+        return Ok(None);
+    };
+
+    let mut kept = vec![];
+    for (item, result) in items.into_iter().zip(results.iter()) {
+        match is_true(result) {
+            Some(true) => kept.push(item),
+            Some(false) => (),
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(kept))
+}
+
+/// Select-Object -First/-Last/-Skip/-SkipLast/-Index/-Unique
+///
+/// Properties are handled by `ParseObject`
+fn select_object(
+    command: &Node<Powershell>,
+    items: Vec<Value>,
+) -> MinusOneResult<Option<Vec<Value>>> {
+    let Some(mut parameters) = BoundParameters::bind(
+        command,
+        &[
+            ("first", false),
+            ("last", false),
+            ("skip", false),
+            ("skiplast", false),
+            ("index", false),
+            ("unique", true),
+            ("wait", true),
+            ("property", false),
+            ("excludeproperty", false),
+            ("expandproperty", false),
+        ],
+    )?
+    else {
+        return Ok(None);
+    };
+    if !parameters.positional.is_empty()
+        || ["property", "excludeproperty", "expandproperty"]
+            .iter()
+            .any(|name| parameters.is_set(name))
+    {
+        return Ok(None);
+    }
+    let unique = parameters.is_set("unique");
+
+    let mut items = match parameters.named.remove("index") {
+        Some(index) => {
+            let indexes = match index.as_ref().and_then(|index| index.data()) {
+                Some(Raw(Num(index))) => vec![*index],
+                Some(Array(indexes)) => {
+                    let Some(indexes) = indexes
+                        .iter()
+                        .map(|index| match index {
+                            Num(index) => Some(*index),
+                            _ => None,
+                        })
+                        .collect::<Option<Vec<i64>>>()
+                    else {
+                        return Ok(None);
+                    };
+                    indexes
+                }
+                _ => return Ok(None),
+            };
+            // items are selected in the input order
+            items
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| indexes.contains(&(*i as i64)))
+                .map(|(_, item)| item)
+                .collect()
+        }
+        None => {
+            let (Some(first), Some(last), Some(skip), Some(skip_last)) = (
+                count_parameter(&mut parameters, "first"),
+                count_parameter(&mut parameters, "last"),
+                count_parameter(&mut parameters, "skip"),
+                count_parameter(&mut parameters, "skiplast"),
+            ) else {
+                return Ok(None);
+            };
+            let skip = skip.unwrap_or(0);
+            match (first, last) {
+                (Some(_), Some(_)) => return Ok(None),
+                // -Skip counts from the end with -Last
+                (None, Some(last)) => {
+                    let end = items.len().saturating_sub(skip);
+                    items[end.saturating_sub(last)..end].to_vec()
+                }
+                (first, None) => {
+                    let mut items: Vec<Value> = items.into_iter().skip(skip).collect();
+                    items.truncate(items.len().saturating_sub(skip_last.unwrap_or(0)));
+                    if let Some(first) = first {
+                        items.truncate(first);
+                    }
+                    items
+                }
+            }
+        }
+    };
+
+    if unique {
+        let mut seen = vec![];
+        items.retain(|item| {
+            if seen.contains(item) {
+                false
+            } else {
+                seen.push(item.clone());
+                true
+            }
+        });
+    }
+    Ok(Some(items))
+}
+
+/// Sort-Object [-Descending] [-Unique] [-CaseSensitive] [{ key } | Property]
+fn sort_object(
+    command: &Node<Powershell>,
+    items: Vec<Value>,
+    layer: &LayerContext,
+) -> MinusOneResult<Option<Vec<Value>>> {
+    let Some(mut parameters) = BoundParameters::bind(
+        command,
+        &[
+            ("property", false),
+            ("descending", true),
+            ("unique", true),
+            ("casesensitive", true),
+            ("stable", true),
+            ("top", false),
+            ("bottom", false),
+        ],
+    )?
+    else {
+        return Ok(None);
+    };
+    let descending = parameters.is_set("descending");
+    let unique = parameters.is_set("unique");
+    let case_sensitive = parameters.is_set("casesensitive");
+    let (Some(top), Some(bottom)) = (
+        count_parameter(&mut parameters, "top"),
+        count_parameter(&mut parameters, "bottom"),
+    ) else {
+        return Ok(None);
+    };
+
+    // the sort key of each item
+    let keys = match parameters.take("property") {
+        None => items.clone(),
+        Some(property) => {
+            let template = match property.data() {
+                Some(Raw(Str(name))) => Some(ItemTemplate::property(name)),
+                _ if property.smallest_child().kind() == "generic_token" => {
+                    Some(ItemTemplate::property(property.text()?))
+                }
+                _ => block_template(&property)?,
+            };
+            let Some(keys) = template.and_then(|template| template.evaluate(&items, layer)) else {
+                return Ok(None);
+            };
+            let Some(keys) = keys
+                .into_iter()
+                .map(|key| match key {
+                    Raw(key) => Some(key),
+                    _ => None,
+                })
+                .collect::<Option<Vec<Value>>>()
+            else {
+                return Ok(None);
+            };
+            keys
+        }
+    };
+    if !parameters.positional.is_empty()
+        || keys
+            .windows(2)
+            .any(|pair| std::mem::discriminant(&pair[0]) != std::mem::discriminant(&pair[1]))
+    {
+        return Ok(None);
+    }
+
+    let mut sorted: Vec<(Value, Value)> = keys.into_iter().zip(items).collect();
+    // the sort is stable, equal keys keep the input order
+    sorted.sort_by(|(left, _), (right, _)| {
+        let ordering = compare_values(left, right, case_sensitive);
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    if unique {
+        sorted.dedup_by(|(right, _), (left, _)| {
+            compare_values(left, right, case_sensitive) == Ordering::Equal
+        });
+    }
+
+    let mut items: Vec<Value> = sorted.into_iter().map(|(_, item)| item).collect();
+    match (top, bottom) {
+        (Some(_), Some(_)) => return Ok(None),
+        (Some(top), None) => items.truncate(top),
+        (None, Some(bottom)) => {
+            items.drain(..items.len().saturating_sub(bottom));
+        }
+        (None, None) => (),
+    }
+    Ok(Some(items))
+}
+
+/// Get-Unique removes adjacent duplicates
+fn get_unique(
+    command: &Node<Powershell>,
+    mut items: Vec<Value>,
+) -> MinusOneResult<Option<Vec<Value>>> {
+    let Some(parameters) = BoundParameters::bind(command, &[("asstring", true), ("ontype", true)])?
+    else {
+        return Ok(None);
+    };
+    if !parameters.positional.is_empty() || parameters.is_set("ontype") {
+        return Ok(None);
+    }
+    if parameters.is_set("asstring") {
+        items = items.iter().map(|item| Str(item.to_string())).collect();
+    }
+    items.dedup();
+    Ok(Some(items))
+}
+
+/// Out-String [-Stream] [-NoNewline]
+fn out_string(command: &Node<Powershell>, items: Vec<Value>) -> MinusOneResult<Option<Powershell>> {
+    let Some(parameters) = BoundParameters::bind(
+        command,
+        &[("stream", true), ("nonewline", true), ("width", false)],
+    )?
+    else {
+        return Ok(None);
+    };
+    if !parameters.positional.is_empty() {
+        return Ok(None);
+    }
+    let lines = items.iter().map(|item| item.to_string());
+    Ok(Some(if parameters.is_set("stream") {
+        Array(lines.map(Str).collect())
+    } else if parameters.is_set("nonewline") {
+        Raw(Str(lines.collect()))
+    } else {
+        Raw(Str(lines.map(|line| line + OUT_STRING_NEWLINE).collect()))
+    }))
+}
+
+/// This rule evaluates pipeline stages on known values
+///
+/// Each stage transforms the items of the previous one:
+/// - `Where-Object { $_ % 2 }`, the filter is evaluated for each item
+/// - `Select-Object -First 2`, `-Last`, `-Skip`, `-SkipLast`, `-Index 0, 2`, `-Unique`
+/// - `Sort-Object`, `-Descending`, `-Unique`, `-CaseSensitive`, with a property or a key script block.
+///   A key that is not predictable, like `{ Get-Random }`, leaves the pipeline unknown.
+/// - `Get-Unique`
+/// - `Out-String`
+///
+/// Aliases (`?`, `where`, `select`, `sort`, `gu`) and wildcarded names are resolved.
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::integer::ParseInt;
+/// use minusone::ps::array::ParseArrayLiteral;
+/// use minusone::ps::join::JoinOperator;
+/// use minusone::ps::pipeline::PipelineStage;
+///
+/// let mut tree = build_powershell_tree("-join (3, 1, 2 | Sort-Object -Descending | Select-Object -First 2)").unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseInt::default(),
+///     ParseArrayLiteral::default(),
+///     PipelineStage::default(),
+///     JoinOperator::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "\"32\"");
+/// ```
+#[derive(Default)]
+pub struct PipelineStage {
+    layer: LayerContext,
+}
+
+impl PipelineStage {
+    /// Evaluate the script blocks with the rules and host of the script
+    pub fn with_layer_context(layer: LayerContext) -> Self {
+        PipelineStage { layer }
+    }
+}
+
+impl<'a> RuleMut<'a> for PipelineStage {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() != "command" {
+            return Ok(());
+        }
+        let Some(command_name) = view.named_child("command_name") else {
+            return Ok(());
+        };
+        let Some(previous) = find_previous_expr(&view)? else {
+            return Ok(());
+        };
+        let items = match previous.data() {
+            Some(Array(values)) => values.clone(),
+            Some(Raw(value)) => vec![value.clone()],
+            _ => return Ok(()),
+        };
+
+        let result = match resolved_command_name(&command_name)?.as_str() {
+            "where-object" | "where" | "?" => where_object(&view, items, &self.layer)?.map(Array),
+            "select-object" | "select" => select_object(&view, items)?.map(Array),
+            "sort-object" | "sort" => sort_object(&view, items, &self.layer)?.map(Array),
+            "get-unique" | "gu" => get_unique(&view, items)?.map(Array),
+            "out-string" => out_string(&view, items)?,
+            _ => None,
+        };
+
+        if let Some(result) = result {
+            trace!("PipelineStage (L): Setting node with result: {:?}", result);
+            node.set(result);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1173 @@
+use crate::error::MinusOneResult;
+use crate::ps::Powershell::{
+    self, Array, MatchCollection, PSItem, Raw, Regex as RegexObject, RegexMatch, Type,
+};
+use crate::ps::Value::{self, Bool, Num, Str};
+use crate::ps::cmdlets::{BoundParameters, resolved_command_name};
+use crate::ps::convert::arguments;
+use crate::ps::foreach::pipeline_input;
+use crate::ps::tool::StringTool;
+use crate::rule::RuleMut;
+use crate::tree::{ControlFlow, Node, NodeMut};
+use log::trace;
+use regex::{Captures, Regex};
+use std::collections::BTreeMap;
+use std::ops::BitOr;
+
+/// [System.Text.RegularExpressions.RegexOptions] flags
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RegexOptions(u32);
+
+impl RegexOptions {
+    pub const NONE: RegexOptions = RegexOptions(0);
+    pub const IGNORE_CASE: RegexOptions = RegexOptions(1);
+    pub const MULTILINE: RegexOptions = RegexOptions(2);
+    pub const EXPLICIT_CAPTURE: RegexOptions = RegexOptions(4);
+    pub const COMPILED: RegexOptions = RegexOptions(8);
+    pub const SINGLELINE: RegexOptions = RegexOptions(16);
+    pub const IGNORE_PATTERN_WHITESPACE: RegexOptions = RegexOptions(32);
+    pub const RIGHT_TO_LEFT: RegexOptions = RegexOptions(64);
+    pub const ECMA_SCRIPT: RegexOptions = RegexOptions(256);
+    pub const CULTURE_INVARIANT: RegexOptions = RegexOptions(512);
+    pub const NON_BACKTRACKING: RegexOptions = RegexOptions(1024);
+
+    pub fn contains(self, other: RegexOptions) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// From a single flag name : IgnoreCase, RightToLeft...
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "none" => Some(RegexOptions::NONE),
+            "ignorecase" => Some(RegexOptions::IGNORE_CASE),
+            "multiline" => Some(RegexOptions::MULTILINE),
+            "explicitcapture" => Some(RegexOptions::EXPLICIT_CAPTURE),
+            "compiled" => Some(RegexOptions::COMPILED),
+            "singleline" => Some(RegexOptions::SINGLELINE),
+            "ignorepatternwhitespace" => Some(RegexOptions::IGNORE_PATTERN_WHITESPACE),
+            "righttoleft" => Some(RegexOptions::RIGHT_TO_LEFT),
+            "ecmascript" => Some(RegexOptions::ECMA_SCRIPT),
+            "cultureinvariant" => Some(RegexOptions::CULTURE_INVARIANT),
+            "nonbacktracking" => Some(RegexOptions::NON_BACKTRACKING),
+            _ => None,
+        }
+    }
+
+    /// From an inferred value : `'IgnoreCase, RightToLeft'`, `65` or `[RegexOptions]::RightToLeft`
+    pub fn from_value(value: &Powershell) -> Option<Self> {
+        match value {
+            Raw(Num(bits)) => u32::try_from(*bits).ok().map(RegexOptions),
+            Raw(Str(names)) => names
+                .split(',')
+                .map(RegexOptions::from_name)
+                .try_fold(RegexOptions::NONE, |options, flag| Some(options | flag?)),
+            Type(typename) => RegexOptions::from_name(typename.strip_prefix("regexoptions.")?),
+            _ => None,
+        }
+    }
+
+    /// Inline flags of the regex crate for these options
+    fn inline_flags(self) -> String {
+        [
+            (RegexOptions::IGNORE_CASE, 'i'),
+            (RegexOptions::MULTILINE, 'm'),
+            (RegexOptions::SINGLELINE, 's'),
+            (RegexOptions::IGNORE_PATTERN_WHITESPACE, 'x'),
+        ]
+        .iter()
+        .filter(|(flag, _)| self.contains(*flag))
+        .map(|(_, c)| *c)
+        .collect()
+    }
+}
+
+impl BitOr for RegexOptions {
+    type Output = RegexOptions;
+
+    fn bitor(self, rhs: RegexOptions) -> RegexOptions {
+        RegexOptions(self.0 | rhs.0)
+    }
+}
+
+/// A group of a [`Match`], as found in `$m.Groups`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    pub name: String,
+    pub index: usize,
+    pub value: Option<String>,
+}
+
+/// A .NET [System.Text.RegularExpressions.Match]
+///
+/// Index and length are counted in UTF-16 code units, as .NET does
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub success: bool,
+    pub value: String,
+    pub index: usize,
+    pub groups: Vec<Group>,
+    // byte range in the input
+    start: usize,
+    end: usize,
+}
+
+impl Match {
+    /// The failed match returned by `[regex]::Match`, or a group that did not participate
+    fn empty() -> Self {
+        Match {
+            success: false,
+            value: String::new(),
+            index: 0,
+            groups: vec![],
+            start: 0,
+            end: 0,
+        }
+    }
+
+    /// `$m.Groups[key]`, by number or by name
+    pub fn group(&self, key: &Value) -> Match {
+        let name = match key {
+            Str(name) => name.clone(),
+            key => key.to_string(),
+        };
+        match self.groups.iter().find(|group| group.name == name) {
+            Some(Group {
+                index,
+                value: Some(value),
+                ..
+            }) => Match {
+                success: true,
+                value: value.clone(),
+                index: *index,
+                groups: vec![],
+                start: 0,
+                end: 0,
+            },
+            _ => Match::empty(),
+        }
+    }
+
+    /// Members of a match or a group
+    pub fn member(&self, member: &str) -> Option<Value> {
+        match member {
+            "value" => Some(Str(self.value.clone())),
+            "index" => Some(Num(self.index as i64)),
+            "length" => Some(Num(self.value.encode_utf16().count() as i64)),
+            "success" => Some(Bool(self.success)),
+            _ => None,
+        }
+    }
+
+    /// The `$Matches` hashtable set by the `-match` operator
+    ///
+    /// Numbered groups are keyed by number, named groups by their name
+    pub fn to_hashtable(&self) -> BTreeMap<Value, Value> {
+        self.groups
+            .iter()
+            .filter_map(|group| {
+                let key = match group.name.parse::<i64>() {
+                    Ok(number) => Num(number),
+                    Err(_) => Str(group.name.to_lowercase()),
+                };
+                group.value.clone().map(|value| (key, Str(value)))
+            })
+            .collect()
+    }
+}
+
+/// Rewrite a .NET pattern for the regex crate
+///
+/// Inline options and named groups are translated,
+/// constructs without equivalent, like backreferences, are rejected.
+fn translate_pattern(pattern: &str, options: RegexOptions) -> Option<String> {
+    let mut explicit_capture = options.contains(RegexOptions::EXPLICIT_CAPTURE);
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut result = String::new();
+    let mut in_class = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                match chars.get(i + 1)? {
+                    'Z' => result.push_str(r"\z"),
+                    'e' => result.push_str(r"\x1B"),
+                    // backreferences and \G are not supported by the regex crate
+                    '1'..='9' | 'k' | 'G' if !in_class => return None,
+                    c => {
+                        result.push('\\');
+                        result.push(*c);
+                    }
+                }
+                i += 2;
+                continue;
+            }
+            '[' if !in_class => {
+                in_class = true;
+                result.push('[');
+                if chars.get(i + 1) == Some(&'^') {
+                    result.push('^');
+                    i += 1;
+                }
+                // a leading ']' is a literal
+                if chars.get(i + 1) == Some(&']') {
+                    result.push_str(r"\]");
+                    i += 1;
+                }
+            }
+            ']' if in_class => {
+                in_class = false;
+                result.push(']');
+            }
+            // set operations of the regex crate are literals in .NET
+            c @ ('[' | '&' | '~') if in_class => {
+                result.push('\\');
+                result.push(c);
+            }
+            '(' if !in_class => {
+                if chars.get(i + 1) != Some(&'?') {
+                    result.push_str(if explicit_capture { "(?:" } else { "(" });
+                    i += 1;
+                    continue;
+                }
+
+                let rest: String = chars[i + 2..].iter().collect();
+                // (?'name'...)
+                if let Some(named) = rest.strip_prefix('\'') {
+                    let end = named.find('\'')?;
+                    result.push_str(&format!("(?<{}>", &named[..end]));
+                    i += 3 + named[..end].chars().count() + 1;
+                    continue;
+                }
+                // (?#comment)
+                if rest.starts_with('#') {
+                    let end = rest.find(')')?;
+                    i += 2 + rest[..end].chars().count() + 1;
+                    continue;
+                }
+                // (?imnsx-imnsx) and (?imnsx-imnsx:...)
+                let flags: String = rest
+                    .chars()
+                    .take_while(|c| matches!(c, 'i' | 'm' | 'n' | 's' | 'x' | '-'))
+                    .collect();
+                let terminator = rest[flags.len()..].chars().next();
+                if !flags.is_empty() && matches!(terminator, Some(')' | ':')) {
+                    i += 2 + flags.len() + 1;
+                    let (on, off) = flags.split_once('-').unwrap_or((flags.as_str(), ""));
+                    if on.contains('n') {
+                        explicit_capture = true;
+                    } else if off.contains('n') {
+                        explicit_capture = false;
+                    }
+                    let on = on.replace('n', "");
+                    let off = off.replace('n', "");
+                    let flags = if off.is_empty() {
+                        on
+                    } else {
+                        format!("{}-{}", on, off)
+                    };
+                    match (flags.is_empty(), terminator) {
+                        (true, Some(')')) => (),
+                        (true, _) => result.push_str("(?:"),
+                        (false, Some(')')) => result.push_str(&format!("(?{})", flags)),
+                        (false, _) => result.push_str(&format!("(?{}:", flags)),
+                    }
+                    continue;
+                }
+                result.push_str("(?");
+                i += 2;
+                continue;
+            }
+            c => result.push(c),
+        }
+        i += 1;
+    }
+
+    Some(result)
+}
+
+/// A .NET regular expression emulated on top of the regex crate
+///
+/// # Example
+/// ```
+/// use minusone::ps::regex::{DotNetRegex, RegexOptions};
+///
+/// let re = DotNetRegex::new("(?<a>.)(.)", RegexOptions::NONE).unwrap();
+/// assert_eq!(re.replace("abcd", "$1${a}"), "baadc");
+///
+/// let re = DotNetRegex::new(".", RegexOptions::RIGHT_TO_LEFT).unwrap();
+/// let reversed: String = re.matches("abc").iter().map(|m| m.value.as_str()).collect();
+/// assert_eq!(reversed, "cba");
+/// ```
+#[derive(Debug)]
+pub struct DotNetRegex {
+    regex: Regex,
+    // the same pattern anchored at the end of the input, to match from right to left
+    right_to_left: Option<Regex>,
+    // .NET numbering of groups : unnamed groups first, then named ones
+    groups: Vec<(usize, String)>,
+}
+
+impl DotNetRegex {
+    pub fn new(pattern: &str, options: RegexOptions) -> Option<Self> {
+        let translated = translate_pattern(pattern, options)?;
+        let flags = match options.inline_flags() {
+            flags if flags.is_empty() => String::new(),
+            flags => format!("(?{})", flags),
+        };
+        let regex = Regex::new(&format!("{}{}", flags, translated)).ok()?;
+        let right_to_left = if options.contains(RegexOptions::RIGHT_TO_LEFT) {
+            Some(Regex::new(&format!(r"{}(?:{})\z", flags, translated)).ok()?)
+        } else {
+            None
+        };
+
+        let names: Vec<Option<&str>> = regex.capture_names().collect();
+        let mut groups = vec![(0, "0".to_string())];
+        let unnamed = names
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, n)| n.is_none());
+        for (number, (index, _)) in unnamed.enumerate() {
+            groups.push((index, (number + 1).to_string()));
+        }
+        for (index, name) in names.iter().enumerate() {
+            if let Some(name) = name {
+                groups.push((index, name.to_string()));
+            }
+        }
+
+        Some(DotNetRegex {
+            regex,
+            right_to_left,
+            groups,
+        })
+    }
+
+    fn build_match(&self, input: &str, captures: &Captures) -> Option<Match> {
+        let whole = captures.get(0)?;
+        let utf16_index = |offset: usize| input[..offset].encode_utf16().count();
+        Some(Match {
+            success: true,
+            value: whole.as_str().to_string(),
+            index: utf16_index(whole.start()),
+            groups: self
+                .groups
+                .iter()
+                .map(|(index, name)| {
+                    let capture = captures.get(*index);
+                    Group {
+                        name: name.clone(),
+                        index: capture.map(|c| utf16_index(c.start())).unwrap_or(0),
+                        value: capture.map(|c| c.as_str().to_string()),
+                    }
+                })
+                .collect(),
+            start: whole.start(),
+            end: whole.end(),
+        })
+    }
+
+    /// All matches, in the order .NET finds them
+    ///
+    /// With RightToLeft, matches are searched backward from the end of the input
+    pub fn matches(&self, input: &str) -> Vec<Match> {
+        let Some(anchored) = &self.right_to_left else {
+            return self
+                .regex
+                .captures_iter(input)
+                .filter_map(|captures| self.build_match(input, &captures))
+                .collect();
+        };
+
+        let mut result = vec![];
+        let mut end = input.len();
+        loop {
+            if let Some(captures) = anchored.captures(&input[..end])
+                && let Some(m) = self.build_match(input, &captures)
+            {
+                let start = m.start;
+                result.push(m);
+                if start < end {
+                    end = start;
+                    continue;
+                }
+            }
+            match input[..end].chars().next_back() {
+                Some(c) => end -= c.len_utf8(),
+                None => break,
+            }
+        }
+        result
+    }
+
+    /// The first match, or the failed match
+    pub fn first_match(&self, input: &str) -> Match {
+        self.matches(input)
+            .into_iter()
+            .next()
+            .unwrap_or_else(Match::empty)
+    }
+
+    pub fn is_match(&self, input: &str) -> bool {
+        match self.right_to_left {
+            Some(_) => !self.matches(input).is_empty(),
+            None => self.regex.is_match(input),
+        }
+    }
+
+    /// Replace all matches, with .NET substitutions : `$1`, `${name}`, `$&`, `$$`...
+    pub fn replace(&self, input: &str, replacement: &str) -> String {
+        let mut matches = self.matches(input);
+        matches.sort_by_key(|m| m.start);
+
+        let mut result = String::new();
+        let mut last = 0;
+        for m in matches.iter() {
+            result.push_str(&input[last..m.start]);
+            result.push_str(&substitute(replacement, m, input));
+            last = m.end;
+        }
+        result.push_str(&input[last..]);
+        result
+    }
+
+    /// Split the input on matches, captured groups are part of the result
+    pub fn split(&self, input: &str) -> Vec<String> {
+        let mut matches = self.matches(input);
+        matches.sort_by_key(|m| m.start);
+
+        let mut result = vec![];
+        let mut last = 0;
+        for m in matches.iter() {
+            result.push(input[last..m.start].to_string());
+            result.extend(m.groups.iter().skip(1).filter_map(|g| g.value.clone()));
+            last = m.end;
+        }
+        result.push(input[last..].to_string());
+        result
+    }
+}
+
+/// Expand a .NET replacement pattern for a match
+fn substitute(replacement: &str, m: &Match, input: &str) -> String {
+    let group = |name: &str| {
+        m.groups
+            .iter()
+            .find(|group| group.name == name)
+            .map(|group| group.value.clone().unwrap_or_default())
+    };
+
+    let chars: Vec<char> = replacement.chars().collect();
+    let mut result = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '$' || i + 1 == chars.len() {
+            result.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        match chars[i + 1] {
+            '$' => result.push('$'),
+            '&' => result.push_str(&m.value),
+            '`' => result.push_str(&input[..m.start]),
+            '\'' => result.push_str(&input[m.end..]),
+            '_' => result.push_str(input),
+            '+' => result.push_str(
+                &m.groups
+                    .last()
+                    .and_then(|group| group.value.clone())
+                    .unwrap_or_default(),
+            ),
+            '{' => {
+                let name: String = chars[i + 2..].iter().take_while(|c| **c != '}').collect();
+                let end = i + 2 + name.chars().count();
+                match group(&name) {
+                    Some(value) if end < chars.len() => {
+                        result.push_str(&value);
+                        i = end + 1;
+                    }
+                    _ => {
+                        result.push('$');
+                        i += 1;
+                    }
+                }
+                continue;
+            }
+            c if c.is_ascii_digit() => {
+                let digits: String = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit())
+                    .collect();
+                let number = digits.trim_start_matches('0');
+                match group(if number.is_empty() { "0" } else { number }) {
+                    Some(value) => result.push_str(&value),
+                    None => {
+                        result.push('$');
+                        result.push_str(&digits);
+                    }
+                }
+                i += 1 + digits.len();
+                continue;
+            }
+            c => {
+                result.push('$');
+                result.push(c);
+            }
+        }
+        i += 2;
+    }
+    result
+}
+
+/// `[regex]::Escape`
+pub fn escape(input: &str) -> String {
+    let mut result = String::new();
+    for c in input.chars() {
+        match c {
+            '\\' | '*' | '+' | '?' | '|' | '{' | '[' | '(' | ')' | '^' | '$' | '.' | '#' | ' ' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '\t' => result.push_str(r"\t"),
+            '\n' => result.push_str(r"\n"),
+            '\r' => result.push_str(r"\r"),
+            '\x0C' => result.push_str(r"\f"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// `[regex]::Unescape`
+pub fn unescape(input: &str) -> Option<String> {
+    let mut result = String::new();
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        let escaped = match chars.next()? {
+            't' => '\t',
+            'n' => '\n',
+            'r' => '\r',
+            'f' => '\x0C',
+            'v' => '\x0B',
+            'a' => '\x07',
+            'e' => '\x1B',
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+            }
+            'u' => {
+                let hex: String = chars.by_ref().take(4).collect();
+                char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+            }
+            c => c,
+        };
+        result.push(escaped);
+    }
+    Some(result)
+}
+
+/// Operators that match a regex : -match, -cmatch, -notmatch...
+pub fn is_match_operator(operator: &str) -> bool {
+    matches!(
+        operator.to_lowercase().as_str(),
+        "-match" | "-imatch" | "-cmatch" | "-notmatch" | "-inotmatch" | "-cnotmatch"
+    )
+}
+
+/// Result of a match operator
+pub enum MatchOperation {
+    /// On a scalar, the result and the match to populate `$Matches` with
+    Scalar(bool, Option<Match>),
+    /// On an array, the elements that are kept
+    Filter(Vec<Value>),
+}
+
+/// Infer `$s -match 'pattern'` and its friends
+///
+/// `-match` is case insensitive, `-cmatch` is case sensitive
+pub fn infer_match_operator(
+    left: &Node<Powershell>,
+    operator: &Node<Powershell>,
+    right: &Node<Powershell>,
+) -> MinusOneResult<Option<MatchOperation>> {
+    let operator = operator.text()?.to_lowercase();
+    if !is_match_operator(&operator) {
+        return Ok(None);
+    }
+    let Some(Raw(pattern)) = right.data() else {
+        return Ok(None);
+    };
+    let options = if operator.starts_with("-c") {
+        RegexOptions::NONE
+    } else {
+        RegexOptions::IGNORE_CASE
+    };
+    let Some(regex) = DotNetRegex::new(&pattern.to_string(), options) else {
+        return Ok(None);
+    };
+    let negate = operator.contains("not");
+
+    Ok(match left.data() {
+        Some(Raw(value)) => {
+            let m = regex.first_match(&value.to_string());
+            Some(MatchOperation::Scalar(
+                m.success != negate,
+                m.success.then_some(m),
+            ))
+        }
+        Some(Array(values)) => Some(MatchOperation::Filter(
+            values
+                .iter()
+                .filter(|value| regex.is_match(&value.to_string()) != negate)
+                .cloned()
+                .collect(),
+        )),
+        _ => None,
+    })
+}
+
+fn is_regex_typename(typename: &str) -> bool {
+    let typename = typename.strip_prefix("system.").unwrap_or(typename);
+    typename
+        .strip_prefix("text.regularexpressions.")
+        .unwrap_or(typename)
+        == "regex"
+}
+
+fn is_regex_options_typename(typename: &str) -> bool {
+    let typename = typename.strip_prefix("system.").unwrap_or(typename);
+    typename
+        .strip_prefix("text.regularexpressions.")
+        .unwrap_or(typename)
+        == "regexoptions"
+}
+
+/// A regex object from its constructor arguments : pattern and options
+fn new_regex(pattern: &Powershell, options: Option<&Powershell>) -> Option<Powershell> {
+    let Raw(pattern) = pattern else {
+        return None;
+    };
+    let options = match options {
+        Some(options) => RegexOptions::from_value(options)?,
+        None => RegexOptions::NONE,
+    };
+    // only patterns we know how to run are tracked
+    DotNetRegex::new(&pattern.to_string(), options)?;
+    Some(RegexObject(pattern.to_string(), options))
+}
+
+/// This rule tracks .NET regex objects and their options
+///
+/// - `[regex]'pattern'`, `[regex]::new('pattern', 'IgnoreCase')`
+/// - `New-Object Text.RegularExpressions.Regex 'pattern', 'RightToLeft'`
+/// - `[Text.RegularExpressions.RegexOptions]::RightToLeft`
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::regex::{ParseRegex, RegexMethod};
+///
+/// let mut tree = build_powershell_tree("([regex]'(.)(.)').Replace('badc', '$2$1')").unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseString::default(),
+///     ParseType::default(),
+///     ParseRegex::default(),
+///     RegexMethod::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "\"abcd\"");
+/// ```
+#[derive(Default)]
+pub struct ParseRegex;
+
+impl<'a> RuleMut<'a> for ParseRegex {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        let regex = match view.kind() {
+            // [RegexOptions]::RightToLeft
+            "member_access" => {
+                if let (Some(type_node), Some(op), Some(member_name)) =
+                    (view.child(0), view.child(1), view.child(2))
+                    && op.text()? == "::"
+                    && let Some(Type(typename)) = type_node.data()
+                    && is_regex_options_typename(typename)
+                {
+                    let member = member_name.text()?.to_string().normalize();
+                    if RegexOptions::from_name(&member).is_some() {
+                        trace!(
+                            "ParseRegex (L): Setting node with regex options: {}",
+                            member
+                        );
+                        node.set(Type(format!("regexoptions.{}", member)));
+                    }
+                }
+                None
+            }
+            // [regex]'pattern'
+            "cast_expression" => match (
+                view.child(0).and_then(|t| t.data().cloned()),
+                view.child(1).and_then(|e| e.data().cloned()),
+            ) {
+                (Some(Type(typename)), Some(pattern)) if is_regex_typename(&typename) => {
+                    new_regex(&pattern, None)
+                }
+                _ => None,
+            },
+            // [regex]::new('pattern', 'options')
+            "invokation_expression" => {
+                if let (Some(type_node), Some(op), Some(member_name), Some(args_list)) =
+                    (view.child(0), view.child(1), view.child(2), view.child(3))
+                    && op.text()? == "::"
+                    && let Some(Type(typename)) = type_node.data()
+                    && is_regex_typename(typename)
+                    && member_name.text()?.to_string().normalize() == "new"
+                {
+                    match arguments(&args_list)
+                        .iter()
+                        .map(|argument| argument.data())
+                        .collect::<Option<Vec<&Powershell>>>()
+                        .as_deref()
+                    {
+                        Some([pattern]) => new_regex(pattern, None),
+                        Some([pattern, options]) => new_regex(pattern, Some(*options)),
+                        _ => None,
+                    }
+                } else {
+                    None
+                }
+            }
+            // New-Object regex 'pattern', 'options'
+            "command" => {
+                let Some(command_name) = view.child(0) else {
+                    return Ok(());
+                };
+                if resolved_command_name(&command_name)? != "new-object" {
+                    return Ok(());
+                }
+                let Some(mut parameters) =
+                    BoundParameters::bind(&view, &[("typename", false), ("argumentlist", false)])?
+                else {
+                    return Ok(());
+                };
+                let (Some(typename), Some(argument_list)) =
+                    (parameters.take("typename"), parameters.take("argumentlist"))
+                else {
+                    return Ok(());
+                };
+                if !is_regex_typename(&typename.text()?.to_lowercase())
+                    || !parameters.positional.is_empty()
+                {
+                    return Ok(());
+                }
+                match argument_list.data() {
+                    Some(Array(values)) if values.len() == 2 => {
+                        new_regex(&Raw(values[0].clone()), Some(&Raw(values[1].clone())))
+                    }
+                    Some(pattern @ Raw(_)) => new_regex(pattern, None),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        if let Some(regex) = regex {
+            trace!("ParseRegex (L): Setting node with regex: {:?}", regex);
+            node.set(regex);
+        }
+        Ok(())
+    }
+}
+
+/// Run a method of a regex on inferred arguments
+fn regex_method(regex: &DotNetRegex, method: &str, args: &[&Powershell]) -> Option<Powershell> {
+    let input = match args.first()? {
+        Raw(input) => input.to_string(),
+        _ => return None,
+    };
+    match (method, args.len()) {
+        ("ismatch", 1) => Some(Raw(Bool(regex.is_match(&input)))),
+        ("match", 1) => Some(RegexMatch(regex.first_match(&input))),
+        ("matches", 1) => Some(MatchCollection(regex.matches(&input))),
+        ("split", 1) => Some(Array(regex.split(&input).into_iter().map(Str).collect())),
+        ("replace", 2) => match args[1] {
+            Raw(replacement) => Some(Raw(Str(regex.replace(&input, &replacement.to_string())))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// This rule infers the .NET regex API
+///
+/// - `[regex]::Replace($s, '(.)(.)', '$2$1')` with `$1`, `${name}`, `$&`... substitutions
+/// - `[regex]::Matches($s, '..')` and `[regex]::Match($s, '(?<n>\d+)')`
+/// - `[regex]::IsMatch`, `[regex]::Split`, `[regex]::Escape`, `[regex]::Unescape`
+/// - the same methods on a regex object : `$r.Replace($s, '$1')`
+///
+/// Options are honoured, `RightToLeft` included, so
+/// `[regex]::Matches($s, '.', 'RightToLeft')` enumerates the input backward.
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::regex::RegexMethod;
+///
+/// let mut tree = build_powershell_tree("[regex]::Replace('olleh', '(?<c>.)', '${c}-')").unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseString::default(),
+///     ParseType::default(),
+///     RegexMethod::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "\"o-l-l-e-h-\"");
+/// ```
+#[derive(Default)]
+pub struct RegexMethod;
+
+impl<'a> RuleMut<'a> for RegexMethod {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() != "invokation_expression" {
+            return Ok(());
+        }
+        let (Some(object), Some(op), Some(member_name), Some(args_list)) =
+            (view.child(0), view.child(1), view.child(2), view.child(3))
+        else {
+            return Ok(());
+        };
+        let method = member_name.text()?.to_string().normalize();
+        let arguments = arguments(&args_list);
+        let Some(args) = arguments
+            .iter()
+            .map(|argument| argument.data())
+            .collect::<Option<Vec<&Powershell>>>()
+        else {
+            return Ok(());
+        };
+
+        let result = match (object.data(), op.text()?) {
+            (Some(Type(typename)), "::") if is_regex_typename(typename) => {
+                match (method.as_str(), args.as_slice()) {
+                    ("escape", [Raw(input)]) => Some(Raw(Str(escape(&input.to_string())))),
+                    ("unescape", [Raw(input)]) => unescape(&input.to_string()).map(|s| Raw(Str(s))),
+                    _ => {
+                        // the pattern is the second argument, options are the last one
+                        let arity = if method == "replace" { 3 } else { 2 };
+                        let options = match args.get(arity) {
+                            Some(options) if args.len() == arity + 1 => {
+                                RegexOptions::from_value(options)
+                            }
+                            None => Some(RegexOptions::NONE),
+                            _ => None,
+                        };
+                        match (args.get(1), options) {
+                            (Some(Raw(pattern)), Some(options)) if args.len() >= arity => {
+                                let mut args = args.clone();
+                                args.truncate(arity);
+                                args.remove(1);
+                                DotNetRegex::new(&pattern.to_string(), options)
+                                    .and_then(|regex| regex_method(&regex, &method, &args))
+                            }
+                            _ => None,
+                        }
+                    }
+                }
+            }
+            (Some(RegexObject(pattern, options)), ".") => DotNetRegex::new(pattern, *options)
+                .and_then(|regex| regex_method(&regex, &method, &args)),
+            _ => None,
+        };
+
+        if let Some(result) = result {
+            trace!("RegexMethod (L): Setting node with result: {:?}", result);
+            node.set(result);
+        }
+        Ok(())
+    }
+}
+
+/// How matches are reached by an expression
+enum MatchSource {
+    Single(Match),
+    Collection(Vec<Match>),
+    // `$_` in a foreach over matches
+    PipelineItem(Vec<Match>),
+}
+
+impl MatchSource {
+    fn map(self, f: impl Fn(&Match) -> Match) -> MatchSource {
+        match self {
+            MatchSource::Single(m) => MatchSource::Single(f(&m)),
+            MatchSource::Collection(matches) => {
+                MatchSource::Collection(matches.iter().map(f).collect())
+            }
+            MatchSource::PipelineItem(matches) => {
+                MatchSource::PipelineItem(matches.iter().map(f).collect())
+            }
+        }
+    }
+}
+
+/// Matches referred by an expression, including `$_` and `.Groups[key]` on it
+fn match_source(node: &Node<Powershell>) -> MinusOneResult<Option<MatchSource>> {
+    match node.data() {
+        Some(RegexMatch(m)) => return Ok(Some(MatchSource::Single(m.clone()))),
+        Some(MatchCollection(matches)) => {
+            return Ok(Some(MatchSource::Collection(matches.clone())));
+        }
+        _ => (),
+    }
+
+    let node = node.smallest_child();
+    match node.kind() {
+        "variable" => Ok(pipeline_input(&node)?.and_then(|input| match input.data() {
+            Some(MatchCollection(matches)) => Some(MatchSource::PipelineItem(matches.clone())),
+            Some(RegexMatch(m)) => Some(MatchSource::PipelineItem(vec![m.clone()])),
+            _ => None,
+        })),
+        // $_.Groups[1]
+        "element_access" => {
+            if let (Some(groups), Some(key)) = (node.child(0), node.child(2))
+                && groups.kind() == "member_access"
+                && let (Some(object), Some(member_name), Some(Raw(key))) =
+                    (groups.child(0), groups.child(2), key.data())
+                && member_name.text()?.to_string().normalize() == "groups"
+            {
+                Ok(match_source(&object)?.map(|source| source.map(|m| m.group(key))))
+            } else {
+                Ok(None)
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+/// This rule infers members of regex matches
+///
+/// - `$m.Value`, `$m.Index`, `$m.Length`, `$m.Success`
+/// - `$m.Groups[1].Value`, `$m.Groups['name'].Value`
+/// - `$matches.Count`, `$matches[0]`, and member enumeration like `$matches.Value`
+/// - `$_.Value` in a foreach over matches
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::regex::{RegexMethod, RegexMatchMember};
+///
+/// let mut tree = build_powershell_tree("[regex]::Match('id=42', '(?<n>\\d+)').Groups['n'].Value").unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseString::default(),
+///     ParseType::default(),
+///     RegexMethod::default(),
+///     RegexMatchMember::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "\"42\"");
+/// ```
+#[derive(Default)]
+pub struct RegexMatchMember;
+
+impl<'a> RuleMut<'a> for RegexMatchMember {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        let result = match view.kind() {
+            "member_access" => {
+                let (Some(object), Some(op), Some(member_name)) =
+                    (view.child(0), view.child(1), view.child(2))
+                else {
+                    return Ok(());
+                };
+                if op.text()? != "." {
+                    return Ok(());
+                }
+                let member = member_name.text()?.to_string().normalize();
+                match (match_source(&object)?, member.as_str()) {
+                    (Some(MatchSource::Collection(matches)), "count") => {
+                        Some(Raw(Num(matches.len() as i64)))
+                    }
+                    (Some(MatchSource::Single(m)), member) => m.member(member).map(Raw),
+                    // member enumeration
+                    (Some(MatchSource::Collection(matches)), member) => matches
+                        .iter()
+                        .map(|m| m.member(member))
+                        .collect::<Option<Vec<Value>>>()
+                        .map(Array),
+                    (Some(MatchSource::PipelineItem(matches)), member) => matches
+                        .iter()
+                        .map(|m| m.member(member))
+                        .collect::<Option<Vec<Value>>>()
+                        .map(PSItem),
+                    _ => None,
+                }
+            }
+            "element_access" => match (
+                view.child(0).and_then(|n| n.data().cloned()),
+                view.child(2).and_then(|n| n.data().cloned()),
+            ) {
+                (Some(MatchCollection(matches)), Some(Raw(Num(index)))) => {
+                    let index = if index < 0 {
+                        matches.len() as i64 + index
+                    } else {
+                        index
+                    };
+                    usize::try_from(index)
+                        .ok()
+                        .and_then(|index| matches.get(index))
+                        .map(|m| RegexMatch(m.clone()))
+                }
+                _ => match match_source(&view)? {
+                    Some(MatchSource::Single(group)) => Some(RegexMatch(group)),
+                    _ => None,
+                },
+            },
+            _ => None,
+        };
+
+        if let Some(result) = result {
+            trace!(
+                "RegexMatchMember (L): Setting node with value: {:?}",
+                result
+            );
+            node.set(result);
+        }
+        Ok(())
+    }
+}
+
+/// This rule infers the `-match` operator and its variants
+///
+/// On a scalar, the result is a boolean and `$Matches` is tracked by `Var`.
+/// On an array, the matching elements are kept.
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::regex::MatchOperator;
+///
+/// let mut tree = build_powershell_tree("'MinusOne' -match '^(?i)minus'").unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseString::default(),
+///     MatchOperator::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "$true");
+/// ```
+#[derive(Default)]
+pub struct MatchOperator;
+
+impl<'a> RuleMut<'a> for MatchOperator {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() == "comparison_expression"
+            && let (Some(left), Some(operator), Some(right)) =
+                (view.child(0), view.child(1), view.child(2))
+        {
+            match infer_match_operator(&left, &operator, &right)? {
+                Some(MatchOperation::Scalar(result, _)) => {
+                    trace!("MatchOperator (L): Setting node with result: {}", result);
+                    node.set(Raw(Bool(result)));
+                }
+                Some(MatchOperation::Filter(values)) => {
+                    trace!("MatchOperator (L): Setting node with values: {:?}", values);
+                    node.set(Array(values));
+                }
+                None => (),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,536 @@
+use crate::depth::DepthCounter;
+use crate::engine::DeobfuscationBackend;
+use crate::error::{Error, MinusOneResult};
+use crate::profile::limits;
+use crate::ps::Powershell::{self, Array, Raw, ScriptBlock, Type};
+use crate::ps::Value::Str;
+use crate::ps::backend::PowershellBackend;
+use crate::ps::cmdlets::{BoundParameters, resolved_command_name};
+use crate::ps::convert::arguments;
+use crate::ps::host::HostProfile;
+use crate::ps::strategy::PowershellStrategy;
+use crate::ps::tool::StringTool;
+use crate::ps::{PowershellRuleSet, build_powershell_tree, remove_powershell_extra};
+use crate::rule::{RuleInfo, RuleMut, RuleSetBuilderType};
+use crate::tree::{ControlFlow, Node, NodeMut};
+use log::{trace, warn};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+thread_local! {
+    static LAYER_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+fn layer_counter() -> DepthCounter {
+    DepthCounter::new(&LAYER_DEPTH, limits().layer_depth, "ScriptBlock")
+}
+
+/// A deobfuscated layer, and its value when the layer
+/// is a single statement inferred as a value
+pub type Layer = (String, Option<Powershell>);
+
+/// Rules and host a layer is deobfuscated with, the ones of the script it comes from
+///
+/// The budgets are the ones of the running deobfuscation, see [`crate::profile::limits`].
+/// Layers are cached by source, so a layer met again, like a
+/// block nested in other blocks, is only deobfuscated once
+#[derive(Clone, Default)]
+pub struct LayerContext {
+    /// Keep only `rules` when set, skip them otherwise
+    with_rules: bool,
+    rules: Vec<String>,
+    profile: HostProfile,
+    cache: Rc<RefCell<HashMap<String, Layer>>>,
+}
+
+impl LayerContext {
+    /// Context of the layers of a script deobfuscated with these rules,
+    /// unknown rule names are left out
+    pub fn new(ctx: &RuleSetBuilderType, rules: &[RuleInfo], profile: &HostProfile) -> Self {
+        let (with_rules, names) = match ctx {
+            RuleSetBuilderType::WithRules(names) => (true, names),
+            RuleSetBuilderType::WithoutRules(names) => (false, names),
+        };
+        Self {
+            with_rules,
+            rules: names
+                .iter()
+                .filter(|name| {
+                    rules
+                        .iter()
+                        .any(|rule| rule.name.eq_ignore_ascii_case(name))
+                })
+                .map(|name| name.to_string())
+                .collect(),
+            profile: profile.clone(),
+            cache: Rc::default(),
+        }
+    }
+
+    pub fn rule_set(&self) -> RuleSetBuilderType<'_> {
+        let names = self.rules.iter().map(String::as_str).collect();
+        if self.with_rules {
+            RuleSetBuilderType::WithRules(names)
+        } else {
+            RuleSetBuilderType::WithoutRules(names)
+        }
+    }
+
+    pub fn host_profile(&self) -> &HostProfile {
+        &self.profile
+    }
+
+    /// Deobfuscate a script layer on its own, as a new program
+    ///
+    /// Returns `None` once the layer depth budget is spent
+    pub fn deobfuscate(&self, source: &str) -> MinusOneResult<Option<Layer>> {
+        if source.trim().is_empty() {
+            return Ok(Some((String::new(), None)));
+        }
+        if let Some(layer) = self.cache.borrow().get(source) {
+            return Ok(Some(layer.clone()));
+        }
+        let Some(_guard) = layer_counter().enter() else {
+            return Ok(None);
+        };
+
+        let cleaned = remove_powershell_extra(source)?;
+        let mut tree = build_powershell_tree(&cleaned)?;
+        if !tree.is_processable() {
+            return Err(Error::unprocessable_source());
+        }
+        tree.apply_mut_with_strategy(
+            &mut PowershellRuleSet::for_layer(self, &[]),
+            PowershellStrategy,
+        )?;
+
+        let root = tree.root()?;
+        let statements: Vec<Node<Powershell>> = root
+            .iter()
+            .find(|child| child.kind() == "statement_list")
+            .map(|statement_list| {
+                statement_list
+                    .iter()
+                    .filter(|statement| statement.kind() != "empty_statement")
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let value = match (statements.len(), statements.first().and_then(|s| s.data())) {
+            (1, Some(value @ (Raw(_) | Array(_)))) => Some(value.clone()),
+            _ => None,
+        };
+
+        let layer = PowershellBackend::lint_tree(&tree, "    ", false)?;
+        let layer = (layer.trim().to_string(), value);
+        self.cache
+            .borrow_mut()
+            .insert(source.to_string(), layer.clone());
+        Ok(Some(layer))
+    }
+
+    /// Script block built from a layer, and the value it returns
+    fn script_block(&self, source: &str) -> Option<Powershell> {
+        match self.deobfuscate(source) {
+            Ok(Some((layer, value))) => Some(ScriptBlock(layer, value.map(Box::new))),
+            Ok(None) => None,
+            Err(e) => {
+                warn!("ScriptBlock: unable to deobfuscate layer: {}: {:?}", e, e);
+                None
+            }
+        }
+    }
+}
+
+/// Deobfuscate a script layer on its own, as a new program, with the default rule set
+///
+/// Returns the linted layer, and its value when the layer
+/// is a single statement inferred as a value
+///
+/// # Example
+/// ```
+/// use minusone::ps::Powershell::Raw;
+/// use minusone::ps::Value::Str;
+/// use minusone::ps::scriptblock::deobfuscate_layer;
+///
+/// let (layer, value) = deobfuscate_layer("'Write-'+'Host'").unwrap();
+/// assert_eq!(layer, "\"Write-Host\"");
+/// assert_eq!(value, Some(Raw(Str("Write-Host".to_string()))));
+/// ```
+pub fn deobfuscate_layer(source: &str) -> MinusOneResult<Layer> {
+    Ok(LayerContext::default()
+        .deobfuscate(source)?
+        .unwrap_or_else(|| (source.trim().to_string(), None)))
+}
+
+fn is_scriptblock_typename(typename: &str) -> bool {
+    matches!(
+        typename.strip_prefix("system.").unwrap_or(typename),
+        "scriptblock" | "management.automation.scriptblock"
+    )
+}
+
+/// $ExecutionContext.InvokeCommand
+fn is_invoke_command_intrinsics(node: &Node<Powershell>) -> MinusOneResult<bool> {
+    Ok(node.kind() == "member_access"
+        && node.text()?.replace('`', "").to_lowercase() == "$executioncontext.invokecommand")
+}
+
+/// Value returned by a script block invoked without arguments,
+/// as inferred when its layer was deobfuscated
+fn invoke_script_block(script_block: &Powershell) -> Option<Powershell> {
+    match script_block {
+        ScriptBlock(_, Some(value)) => Some(*value.clone()),
+        _ => None,
+    }
+}
+
+/// This rule infers script block literals
+///
+/// The body is deobfuscated as its own layer,
+/// so the block can be inlined where it is invoked
+///
+/// # Example
+/// ```
+/// use minusone::ps::Powershell::ScriptBlock;
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::scriptblock::ParseScriptBlock;
+///
+/// let mut tree = build_powershell_tree("{ Write-Host ('mi'+'nusone') }").unwrap();
+/// tree.apply_mut(&mut ParseScriptBlock::default()).unwrap();
+///
+/// let root = tree.root().unwrap();
+/// let script_block = root.child(0).unwrap().child(0).unwrap().smallest_child();
+/// assert_eq!(
+///     script_block.data(),
+///     Some(&ScriptBlock("Write-Host \"minusone\"".to_string(), None))
+/// );
+/// ```
+#[derive(Default)]
+pub struct ParseScriptBlock {
+    layer: LayerContext,
+}
+
+impl ParseScriptBlock {
+    /// Deobfuscate the layers with the rules and host of the script
+    pub fn with_layer_context(layer: LayerContext) -> Self {
+        ParseScriptBlock { layer }
+    }
+}
+
+impl<'a> RuleMut<'a> for ParseScriptBlock {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() != "script_block_expression" {
+            return Ok(());
+        }
+
+        // { } has no script_block child
+        let body = match view.child(1) {
+            Some(script_block) if script_block.kind() == "script_block" => script_block.text()?,
+            _ => "",
+        };
+
+        if let Some(script_block) = self.layer.script_block(body) {
+            trace!(
+                "ParseScriptBlock (L): Setting node with script block: {:?}",
+                script_block
+            );
+            node.set(script_block);
+        }
+        Ok(())
+    }
+}
+
+/// This rule infers script blocks created from a string
+///
+/// ```powershell
+/// [ScriptBlock]::Create("Write-Host 'minusone'")
+/// $ExecutionContext.InvokeCommand.NewScriptBlock("Write-Host 'minusone'")
+/// ```
+///
+/// The string is parsed and deobfuscated as a new layer
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::string::{ConcatString, ParseString};
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::scriptblock::ScriptBlockCreate;
+///
+/// let mut tree = build_powershell_tree("& ([ScriptBlock]::Create('Write-Host '+'minusone'))").unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseString::default(),
+///     ConcatString::default(),
+///     ParseType::default(),
+///     ScriptBlockCreate::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "& { Write-Host minusone }");
+/// ```
+#[derive(Default)]
+pub struct ScriptBlockCreate {
+    layer: LayerContext,
+}
+
+impl ScriptBlockCreate {
+    /// Deobfuscate the layers with the rules and host of the script
+    pub fn with_layer_context(layer: LayerContext) -> Self {
+        ScriptBlockCreate { layer }
+    }
+}
+
+impl<'a> RuleMut<'a> for ScriptBlockCreate {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() != "invokation_expression" {
+            return Ok(());
+        }
+
+        let (Some(object), Some(op), Some(member_name), Some(args_list)) =
+            (view.child(0), view.child(1), view.child(2), view.child(3))
+        else {
+            return Ok(());
+        };
+        let member = member_name.text()?.to_string().normalize();
+
+        let is_create = match (object.data(), op.text()?) {
+            (Some(Type(typename)), "::") => is_scriptblock_typename(typename) && member == "create",
+            (_, ".") => is_invoke_command_intrinsics(&object)? && member == "newscriptblock",
+            _ => false,
+        };
+        if !is_create {
+            return Ok(());
+        }
+
+        if let [argument] = &arguments(&args_list)[..]
+            && let Some(Raw(Str(source))) = argument.data()
+            && let Some(script_block) = self.layer.script_block(source)
+        {
+            trace!(
+                "ScriptBlockCreate (L): Setting node with script block: {:?}",
+                script_block
+            );
+            node.set(script_block);
+        }
+        Ok(())
+    }
+}
+
+/// This rule reduces the invocation of a known script block
+///
+/// ```powershell
+/// & $sb
+/// . ([ScriptBlock]::Create($payload))
+/// $sb.Invoke()
+/// Invoke-Command -ScriptBlock $sb
+/// $ExecutionContext.InvokeCommand.InvokeScript($payload)
+/// ```
+///
+/// When the script block is a single expression, the invocation
+/// is replaced by its value. Otherwise the invoked script block
+/// is inlined by the linter, and a string given to `InvokeScript`
+/// is deobfuscated as a new layer.
+/// Invocations with arguments are left as is.
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::scriptblock::{ParseScriptBlock, ScriptBlockInvoke};
+///
+/// let mut tree = build_powershell_tree("& {'mi'+'nusone'}").unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseScriptBlock::default(),
+///     ScriptBlockInvoke::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "\"minusone\"");
+/// ```
+#[derive(Default)]
+pub struct ScriptBlockInvoke {
+    layer: LayerContext,
+}
+
+impl ScriptBlockInvoke {
+    /// Deobfuscate the layers with the rules and host of the script
+    pub fn with_layer_context(layer: LayerContext) -> Self {
+        ScriptBlockInvoke { layer }
+    }
+}
+
+impl<'a> RuleMut<'a> for ScriptBlockInvoke {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        match view.kind() {
+            "command" => {
+                let script_block = match (view.child(0), view.child(1)) {
+                    // & $sb
+                    (Some(operator), Some(command_name_expr))
+                        if operator.kind() == "command_invokation_operator" =>
+                    {
+                        let has_arguments = view
+                            .named_child("command_elements")
+                            .map(|elements| {
+                                elements
+                                    .iter()
+                                    .any(|element| element.kind() != "command_argument_sep")
+                            })
+                            .unwrap_or(false);
+                        if has_arguments {
+                            return Ok(());
+                        }
+                        command_name_expr.data().cloned()
+                    }
+                    // Invoke-Command -ScriptBlock $sb
+                    (Some(command_name), _) if command_name.kind() == "command_name" => {
+                        if !matches!(
+                            resolved_command_name(&command_name)?.as_str(),
+                            "invoke-command" | "icm"
+                        ) {
+                            return Ok(());
+                        }
+                        let Some(mut parameters) = BoundParameters::bind(
+                            &view,
+                            &[
+                                ("scriptblock", false),
+                                ("argumentlist", false),
+                                ("nonewscope", true),
+                            ],
+                        )?
+                        else {
+                            return Ok(());
+                        };
+                        let script_block = parameters.take("scriptblock");
+                        if parameters.is_set("argumentlist") || !parameters.positional.is_empty() {
+                            return Ok(());
+                        }
+                        script_block.and_then(|n| n.data().cloned())
+                    }
+                    _ => return Ok(()),
+                };
+
+                if let Some(value) = script_block.as_ref().and_then(invoke_script_block) {
+                    trace!(
+                        "ScriptBlockInvoke (L): Setting node with script block value: {:?}",
+                        value
+                    );
+                    node.set(value);
+                }
+            }
+            "invokation_expression" => {
+                let (Some(object), Some(op), Some(member_name), Some(args_list)) =
+                    (view.child(0), view.child(1), view.child(2), view.child(3))
+                else {
+                    return Ok(());
+                };
+                if op.text()? != "." {
+                    return Ok(());
+                }
+                let member = member_name.text()?.to_string().normalize();
+                let arguments = arguments(&args_list);
+
+                match (object.data(), member.as_str(), &arguments[..]) {
+                    // $sb.Invoke()
+                    (Some(script_block @ ScriptBlock(..)), "invoke" | "invokereturnasis", []) => {
+                        if let Some(value) = invoke_script_block(script_block) {
+                            trace!(
+                                "ScriptBlockInvoke (L): Setting node with script block value: {:?}",
+                                value
+                            );
+                            node.set(value);
+                        }
+                    }
+                    // $ExecutionContext.InvokeCommand.InvokeScript($payload)
+                    (_, "invokescript", [argument]) if is_invoke_command_intrinsics(&object)? => {
+                        let Some(Raw(Str(source))) = argument.data() else {
+                            return Ok(());
+                        };
+                        match self.layer.deobfuscate(source) {
+                            Ok(Some((_, Some(value)))) => {
+                                trace!(
+                                    "ScriptBlockInvoke (L): Setting node with script value: {:?}",
+                                    value
+                                );
+                                node.set(value);
+                            }
+                            // InvokeScript also accepts a script block
+                            Ok(Some((layer, None))) => {
+                                let argument_id = argument.id();
+                                trace!(
+                                    "ScriptBlockInvoke (L): Setting InvokeScript argument with script block: {:?}",
+                                    layer
+                                );
+                                node.set_by_node_id(argument_id, ScriptBlock(layer, None));
+                            }
+                            Ok(None) => (),
+                            Err(e) => {
+                                warn!(
+                                    "ScriptBlockInvoke (L): unable to deobfuscate layer: {}: {:?}",
+                                    e, e
+                                )
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,459 @@
+use crate::error::{Error, MinusOneErrorKind, MinusOneResult};
+use crate::ps::Powershell::{self, Credential, Raw, SecureString, Type};
+use crate::ps::Value::Str;
+use crate::ps::cmdlets::{BoundParameters, resolved_command_name};
+use crate::ps::crypto::{CipherMode, CipherPadding, CipherState, cipher_transform};
+use crate::ps::tool::StringTool;
+use crate::ps::utils::bytes::*;
+use crate::ps::utils::string::{decode, encode};
+use crate::rule::RuleMut;
+use crate::tree::{ControlFlow, Node, NodeMut};
+use base64::{Engine as _, engine::general_purpose};
+use log::{trace, warn};
+
+/// Header of a SecureString exported with a key by `ConvertFrom-SecureString -Key`
+pub const SECURE_STRING_HEADER: &str = "76492d1116743f0423413b16050a5345";
+
+/// Credential objects, tracked with their known password
+#[derive(Debug, Clone, PartialEq)]
+pub enum CredentialKind {
+    /// System.Management.Automation.PSCredential, its Password is a SecureString
+    PSCredential,
+    /// System.Net.NetworkCredential, its Password is a plain string
+    NetworkCredential,
+}
+
+fn invalid_secure_string(reason: &str) -> Error {
+    Error::new(
+        MinusOneErrorKind::Decryption,
+        &format!("Invalid encrypted SecureString: {}", reason),
+    )
+}
+
+/// Decrypt a SecureString exported with a key
+///
+/// The format is the header followed by the base64 of the UTF-16
+/// string `2|<base64 IV>|<hex AES-CBC ciphertext>`
+///
+/// # Example
+/// ```
+/// use minusone::ps::securestring::decrypt_secure_string;
+///
+/// let key: Vec<u8> = (1..=16).collect();
+/// assert_eq!(
+///     decrypt_secure_string(
+///         "76492d1116743f0423413b16050a5345MgB8AEUAQgBFAFMARQB4AFEAVgBGAGgAYwBZAEcAUgBvAGIASABCADAAZQBIAHcAPQA9AHwAYgBhAGEAOQBlAGIAZgA5ADgANwAzADAANABhADgAMQA2ADAANAA4AGMANgAzADYAMAAxADQANAA5ADEAMgBlAA==",
+///         &key
+///     ).unwrap(),
+///     "calc"
+/// );
+/// ```
+pub fn decrypt_secure_string(encrypted: &str, key: &[u8]) -> MinusOneResult<String> {
+    let payload = encrypted
+        .trim()
+        .strip_prefix(SECURE_STRING_HEADER)
+        .ok_or_else(|| invalid_secure_string("missing header"))?;
+    let payload = general_purpose::STANDARD
+        .decode(payload)
+        .map_err(|_| invalid_secure_string("payload is not base64"))?;
+    let payload = decode("unicode", &payload)
+        .ok_or_else(|| invalid_secure_string("payload is not UTF-16"))?;
+
+    let fields: Vec<&str> = payload.split('|').collect();
+    let [version, iv, ciphertext] = fields[..] else {
+        return Err(invalid_secure_string("unexpected number of fields"));
+    };
+    if version != "2" {
+        return Err(invalid_secure_string(&format!(
+            "unsupported version {}",
+            version
+        )));
+    }
+
+    let state = CipherState {
+        key: Some(key.to_vec()),
+        iv: Some(
+            general_purpose::STANDARD
+                .decode(iv)
+                .map_err(|_| invalid_secure_string("IV is not base64"))?,
+        ),
+        mode: Some(CipherMode::Cbc),
+        padding: Some(CipherPadding::Pkcs7),
+        is_decrypt: Some(true),
+        ..CipherState::default()
+    };
+    let ciphertext =
+        from_hex(ciphertext).ok_or_else(|| invalid_secure_string("ciphertext is not hex"))?;
+    let plaintext = cipher_transform(&state, true, &ciphertext)?;
+    decode("unicode", &plaintext).ok_or_else(|| invalid_secure_string("plaintext is not UTF-16"))
+}
+
+/// First two arguments of a constructor, from `(a, b)` or `a, b`
+fn constructor_arguments<'a>(
+    node: &Node<'a, Powershell>,
+) -> Option<(Node<'a, Powershell>, Node<'a, Powershell>)> {
+    let list = if node.kind() == "argument_list" {
+        node.named_child("argument_expression_list")?
+    } else {
+        node.smallest_child()
+    };
+
+    if (list.kind() == "argument_expression_list" || list.kind() == "array_literal_expression")
+        && list.child_count() >= 3
+    {
+        Some((list.child(0)?, list.child(2)?))
+    } else {
+        None
+    }
+}
+
+fn credential_kind_of(typename: &str) -> Option<CredentialKind> {
+    match typename.strip_prefix("system.").unwrap_or(typename) {
+        "pscredential" | "management.automation.pscredential" => Some(CredentialKind::PSCredential),
+        "net.networkcredential" => Some(CredentialKind::NetworkCredential),
+        _ => None,
+    }
+}
+
+fn is_marshal_typename(typename: &str) -> bool {
+    typename.strip_prefix("system.").unwrap_or(typename) == "runtime.interopservices.marshal"
+}
+
+/// Build a credential object from its (user, password) constructor arguments
+fn new_credential(kind: CredentialKind, password: &Powershell) -> Option<Powershell> {
+    match (kind, password) {
+        (kind, SecureString(password)) => Some(Credential(kind, password.clone())),
+        // NetworkCredential also accepts a plain string password
+        (CredentialKind::NetworkCredential, Raw(Str(password))) => Some(Credential(
+            CredentialKind::NetworkCredential,
+            password.clone(),
+        )),
+        _ => None,
+    }
+}
+
+/// This rule infers the SecureString built by `ConvertTo-SecureString`
+///
+/// - `ConvertTo-SecureString '76492d1116743f0423413b16050a5345...' -Key (1..32)`
+///   decrypts the payload with the key bytes
+/// - `-SecureKey $k` uses the UTF-16 bytes of another SecureString as key
+/// - `-AsPlainText -Force` wraps the string as is
+///
+/// The plaintext is tracked as a [`Powershell::SecureString`], that
+/// [`SecureStringUnwrap`] folds back to a string.
+/// `ConvertFrom-SecureString -AsPlainText` from Powershell 7 is also handled.
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::securestring::ConvertToSecureString;
+///
+/// let mut tree = build_powershell_tree(
+///     "ConvertFrom-SecureString -AsPlainText (ConvertTo-SecureString 'calc' -AsPlainText -Force)"
+/// ).unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseString::default(),
+///     ConvertToSecureString::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "\"calc\"");
+/// ```
+#[derive(Default)]
+pub struct ConvertToSecureString;
+
+impl<'a> RuleMut<'a> for ConvertToSecureString {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() != "command" {
+            return Ok(());
+        }
+        let Some(command_name) = view.named_child("command_name") else {
+            return Ok(());
+        };
+
+        match resolved_command_name(&command_name)?.as_str() {
+            "convertto-securestring" => {
+                let Some(mut parameters) = BoundParameters::bind(
+                    &view,
+                    &[
+                        ("string", false),
+                        ("securekey", false),
+                        ("key", false),
+                        ("asplaintext", true),
+                        ("force", true),
+                    ],
+                )?
+                else {
+                    return Ok(());
+                };
+
+                let Some(Raw(Str(string))) =
+                    parameters.take("string").and_then(|n| n.data().cloned())
+                else {
+                    return Ok(());
+                };
+
+                let key = match (
+                    parameters.named.get("key"),
+                    parameters.named.get("securekey"),
+                ) {
+                    (Some(Some(key)), None) => key.data().and_then(bytes_from_data),
+                    (None, Some(Some(secure_key))) => match secure_key.data() {
+                        Some(SecureString(secure_key)) => encode("unicode", secure_key.as_str()),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+
+                if let Some(key) = key {
+                    match decrypt_secure_string(&string, &key) {
+                        Ok(plaintext) => {
+                            trace!(
+                                "ConvertToSecureString (L): Setting node with decrypted SecureString: {:?}",
+                                plaintext
+                            );
+                            node.set(SecureString(plaintext));
+                        }
+                        Err(e) => warn!("ConvertToSecureString (L): {}: {:?}", e, e),
+                    }
+                } else if parameters.is_set("asplaintext")
+                    && !parameters.is_set("key")
+                    && !parameters.is_set("securekey")
+                {
+                    trace!(
+                        "ConvertToSecureString (L): Setting node with plain text SecureString: {:?}",
+                        string
+                    );
+                    node.set(SecureString(string));
+                }
+            }
+            "convertfrom-securestring" => {
+                let Some(mut parameters) = BoundParameters::bind(
+                    &view,
+                    &[
+                        ("securestring", false),
+                        ("securekey", false),
+                        ("key", false),
+                        ("asplaintext", true),
+                    ],
+                )?
+                else {
+                    return Ok(());
+                };
+
+                if parameters.is_set("asplaintext")
+                    && !parameters.is_set("key")
+                    && !parameters.is_set("securekey")
+                    && let Some(SecureString(plaintext)) = parameters
+                        .take("securestring")
+                        .and_then(|n| n.data().cloned())
+                {
+                    trace!(
+                        "ConvertToSecureString (L): Setting node with SecureString plain text: {:?}",
+                        plaintext
+                    );
+                    node.set(Raw(Str(plaintext)));
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+/// This rule folds the ways a SecureString is read back as plain text
+///
+/// ```powershell
+/// [Runtime.InteropServices.Marshal]::PtrToStringAuto([Runtime.InteropServices.Marshal]::SecureStringToBSTR($s))
+/// (New-Object PSCredential 'x',$s).GetNetworkCredential().Password
+/// ```
+///
+/// - `[Marshal]::SecureStringToBSTR` and friends keep the [`Powershell::SecureString`],
+///   as the unmanaged copy holds the same plaintext
+/// - `[Marshal]::PtrToStringAuto`, `PtrToStringBSTR`, `PtrToStringUni` and `PtrToStringAnsi`
+///   read it as a string
+/// - `PSCredential` and `NetworkCredential`, built with `New-Object` or `::new`,
+///   are tracked as [`Powershell::Credential`] until their `Password` is read
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree;
+/// use minusone::ps::forward::Forward;
+/// use minusone::ps::linter::Linter;
+/// use minusone::ps::string::ParseString;
+/// use minusone::ps::typing::ParseType;
+/// use minusone::ps::securestring::{ConvertToSecureString, SecureStringUnwrap};
+///
+/// let mut tree = build_powershell_tree(
+///     "[Runtime.InteropServices.Marshal]::PtrToStringAuto([Runtime.InteropServices.Marshal]::SecureStringToBSTR((ConvertTo-SecureString 'calc' -AsPlainText -Force)))"
+/// ).unwrap();
+/// tree.apply_mut(&mut (
+///     Forward::default(),
+///     ParseString::default(),
+///     ParseType::default(),
+///     ConvertToSecureString::default(),
+///     SecureStringUnwrap::default(),
+/// )).unwrap();
+///
+/// let mut ps_litter_view = Linter::default();
+/// tree.apply(&mut ps_litter_view).unwrap();
+///
+/// assert_eq!(ps_litter_view.output, "\"calc\"");
+/// ```
+#[derive(Default)]
+pub struct SecureStringUnwrap;
+
+impl<'a> RuleMut<'a> for SecureStringUnwrap {
+    type Language = Powershell;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        match view.kind() {
+            // $cred.Password
+            "member_access" => {
+                if let (Some(object), Some(op), Some(member_name)) =
+                    (view.child(0), view.child(1), view.child(2))
+                    && op.text()? == "."
+                    && member_name.text()?.to_string().normalize() == "password"
+                    && let Some(Credential(kind, password)) = object.data()
+                {
+                    trace!(
+                        "SecureStringUnwrap (L): Setting node with credential password: {:?}",
+                        password
+                    );
+                    match kind {
+                        CredentialKind::PSCredential => node.set(SecureString(password.clone())),
+                        CredentialKind::NetworkCredential => node.set(Raw(Str(password.clone()))),
+                    }
+                }
+            }
+            "invokation_expression" => {
+                let (Some(object), Some(op), Some(member_name), Some(args_list)) =
+                    (view.child(0), view.child(1), view.child(2), view.child(3))
+                else {
+                    return Ok(());
+                };
+                let member = member_name.text()?.to_string().normalize();
+                let argument_expression_list = args_list.named_child("argument_expression_list");
+
+                match (object.data(), op.text()?) {
+                    (Some(Type(typename)), "::") if is_marshal_typename(typename) => {
+                        let Some(Some(argument)) = argument_expression_list
+                            .filter(|list| list.child_count() == 1)
+                            .map(|list| list.child(0))
+                        else {
+                            return Ok(());
+                        };
+                        let Some(SecureString(plaintext)) = argument.data() else {
+                            return Ok(());
+                        };
+                        match member.as_str() {
+                            "securestringtobstr"
+                            | "securestringtoglobalallocunicode"
+                            | "securestringtoglobalallocansi"
+                            | "securestringtocotaskmemunicode"
+                            | "securestringtocotaskmemansi" => {
+                                trace!(
+                                    "SecureStringUnwrap (L): Setting node with unmanaged SecureString copy"
+                                );
+                                node.set(SecureString(plaintext.clone()));
+                            }
+                            "ptrtostringauto" | "ptrtostringbstr" | "ptrtostringuni"
+                            | "ptrtostringansi" => {
+                                trace!(
+                                    "SecureStringUnwrap (L): Setting node with SecureString plain text: {:?}",
+                                    plaintext
+                                );
+                                node.set(Raw(Str(plaintext.clone())));
+                            }
+                            _ => (),
+                        }
+                    }
+                    // [PSCredential]::new('user', $s)
+                    (Some(Type(typename)), "::") if member == "new" => {
+                        if let Some(kind) = credential_kind_of(typename)
+                            && let Some((_, password)) = constructor_arguments(&args_list)
+                            && let Some(credential) =
+                                password.data().and_then(|p| new_credential(kind, p))
+                        {
+                            trace!("SecureStringUnwrap (L): Setting node with credential object");
+                            node.set(credential);
+                        }
+                    }
+                    (Some(Credential(CredentialKind::PSCredential, password)), ".")
+                        if member == "getnetworkcredential"
+                            && argument_expression_list.is_none() =>
+                    {
+                        trace!("SecureStringUnwrap (L): Setting node with network credential");
+                        node.set(Credential(
+                            CredentialKind::NetworkCredential,
+                            password.clone(),
+                        ));
+                    }
+                    _ => (),
+                }
+            }
+            // New-Object PSCredential 'user', $s
+            "command" => {
+                if let Some(command_name) = view.named_child("command_name")
+                    && resolved_command_name(&command_name)? == "new-object"
+                    && let Some(mut parameters) = BoundParameters::bind(
+                        &view,
+                        &[("typename", false), ("argumentlist", false)],
+                    )?
+                {
+                    let typename = parameters.take("typename");
+                    let arguments = parameters.take("argumentlist");
+
+                    if let (Some(typename), Some(arguments), None) =
+                        (typename, arguments, parameters.positional.pop_front())
+                        && let Some(kind) = credential_kind_of(&typename.text()?.to_lowercase())
+                        && let Some((_, password)) = constructor_arguments(&arguments)
+                        && let Some(credential) =
+                            password.data().and_then(|p| new_credential(kind, p))
+                    {
+                        trace!(
+                            "SecureStringUnwrap (L): Setting node with New-Object credential object"
+                        );
+                        node.set(credential);
+                    }
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
@@ -1,183 +1,904 @@
-use log::trace;
-use crate::rule::RuleMut;
-use crate::tree::{NodeMut, ControlFlow};
-use crate::error::{MinusOneResult, Error};
-
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub enum PowershellDetect {
-    Static(bool),
-    StaticCast(String)
-}
-
-pub struct Pattern {
-    pub start_offset : usize,
-    pub end_offset : usize
-}
-
-impl Pattern {
-    pub fn new(start_offset: usize, end_offset: usize) -> Self {
-        Pattern {
-            start_offset,
-            end_offset
-        }
-    }
-}
-
-pub trait Detection {
-    fn get_nodes(&self) -> &Vec<Pattern>;
-}
-
-#[derive(Default)]
-pub struct Static;
-
-impl<'a> RuleMut<'a> for Static {
-    type Language = PowershellDetect;
-
-    fn enter(&mut self, _node: &mut NodeMut<'a, Self::Language>, _flow: ControlFlow) -> MinusOneResult<()>{
-        Ok(())
-    }
-
-    fn leave(&mut self, node: &mut NodeMut<'a, Self::Language>, _flow: ControlFlow) -> MinusOneResult<()>{
-        let view = node.view();
-        match view.kind() {
-            "decimal_integer_literal" | "hexadecimal_integer_literal" => {
-                trace!("Static (L): Setting node with static: true");
-                node.set(PowershellDetect::Static(true))
-            },
-            // Forward static on simple node
-            "unary_expression" | "range_expression" |
-            "format_expression" | "comparison_expression" |
-            "bitwise_expression" | "string_literal" |
-            "logical_expression" | "integer_literal" |
-            "argument_expression" | "range_argument_expression" |
-            "format_argument_expression" | "comparison_argument_expression" |
-            "bitwise_argument_expression" | "logical_argument_expression" |
-            "command_name_expr" | "pipeline" |
-            "statement_list" | "expression_with_unary_operator" => {
-                if view.child_count() == 1 {
-                    if let Some(PowershellDetect::Static(data)) = view.child(0).ok_or(Error::invalid_child())?.data() {
-                        trace!("Static (L): Setting node with static: {}", data);
-                        node.set(PowershellDetect::Static(data.clone()))
-                    }
-                }
-            },
-            // complex expression
-            "parenthesized_expression" | "sub_expression" => {
-                if view.child_count() == 3 {
-                    if let Some(PowershellDetect::Static(data)) = view.child(1).ok_or(Error::invalid_child())?.data() {
-                        trace!("Static (L): Setting node with static: {}", data);
-                        node.set(PowershellDetect::Static(data.clone()))
-                    }
-                }
-            },
-
-            "additive_argument_expression" | "additive_expression" |
-            "multiplicative_expression" | "multiplicative_argument_expression" | "array_literal_expression" => {
-                match view.child_count() {
-                    1 => {
-                        if let Some(PowershellDetect::Static(data)) = view.child(0).ok_or(Error::invalid_child())?.data() {
-                                trace!("Static (L): Setting node with static: {}", data);
-                            node.set(PowershellDetect::Static(data.clone()))
-                        }
-                    },
-                    3 => {
-                        if let (Some(PowershellDetect::Static(left)), Some(PowershellDetect::Static(right))) =
-                            (
-                                view.child(0).ok_or(Error::invalid_child())?.data(),
-                                view.child(2).ok_or(Error::invalid_child())?.data()
-                            ) {
-                                trace!("Static (L): Setting node with static: {} && {}", left, right);
-                            node.set(PowershellDetect::Static(*left && *right))
-                        }
-                    },
-                    _ => ()
-                }
-            },
-            "expandable_string_literal" => {
-                if view.child_count() == 0 {
-                    trace!("Static (L): Setting node with static: true");
-                    node.set(PowershellDetect::Static(true))
-                }
-            },
-            "cast_expression" => {
-                if let (Some(_type_literal), Some(unary_expression)) = (view.child(0), view.child(1)) {
-                    if unary_expression.data() == Some(&PowershellDetect::Static(true)) {
-                        trace!("Static (L): Setting node with static: true");
-                        node.set(PowershellDetect::Static(true))
-                    }
-                }
-            }
-            _ => ()
-        }
-        Ok(())
-    }
-}
-
-#[derive(Default)]
-pub struct StaticArray {
-    detected_nodes : Vec<Pattern>
-}
-
-impl<'a> RuleMut<'a> for StaticArray {
-    type Language = PowershellDetect;
-
-    fn enter(&mut self, _node: &mut NodeMut<'a, Self::Language>, _flow: ControlFlow) -> MinusOneResult<()>{
-        Ok(())
-    }
-
-    fn leave(&mut self, node: &mut NodeMut<'a, Self::Language>, _flow: ControlFlow) -> MinusOneResult<()>{
-        let view = node.view();
-        if view.kind() == "array_literal_expression" && view.parent().ok_or(Error::invalid_child())?.kind() != "array_literal_expression" && view.child_count() > 1 {
-            if let Some(PowershellDetect::Static(true)) = view.data() {
-                self.detected_nodes.push(Pattern::new(view.start_abs(), view.end_abs()));
-            }
-        }
-        Ok(())
-    }
-}
-
-impl Detection for StaticArray {
-    fn get_nodes(&self) -> &Vec<Pattern> {
-        &self.detected_nodes
-    }
-}
-
-#[derive(Default)]
-pub struct StaticFormat {
-    detected_nodes : Vec<Pattern>
-}
-
-impl<'a> RuleMut<'a> for StaticFormat {
-    type Language = PowershellDetect;
-
-    fn enter(&mut self, _node: &mut NodeMut<'a, Self::Language>, _flow: ControlFlow) -> MinusOneResult<()>{
-        Ok(())
-    }
-
-    fn leave(&mut self, node: &mut NodeMut<'a, Self::Language>, _flow: ControlFlow) -> MinusOneResult<()>{
-        let view = node.view();
-        if view.kind() == "format_expression"  {
-            if let (Some(expression), Some(range_expression )) = (view.child(0), view.child(2)) {
-                match (expression.data(), range_expression.data()) {
-                    (Some(PowershellDetect::Static(true)), Some(PowershellDetect::Static(true))) => {
-                        self.detected_nodes.push(Pattern::new(view.start_abs(), view.end_abs()))
-                    }
-                    _ => ()
-                }
-            }
-        }
-        Ok(())
-    }
-}
-
-impl Detection for StaticFormat {
-    fn get_nodes(&self) -> &Vec<Pattern> {
-        &self.detected_nodes
-    }
-}
-
-pub type RuleSet = (
-    Static,
-    StaticArray,
-    StaticFormat,
-);
+use crate::detect::{Detection, Pattern, Report, Technique, looks_like_base64, push_outermost};
+use crate::error::{Error, MinusOneResult};
+use crate::init::Init;
+use crate::ps::build_powershell_tree_for_storage;
+use crate::ps::cmdlets::{canonical_cmdlet_name, resolve_wildcard_cmdlet};
+use crate::rule::RuleMut;
+use crate::tree::{ControlFlow, HashMapStorage, Node, NodeMut};
+use log::trace;
+
+/// Inferred component of the static analysis
+/// A node is static when its value doesn't depend on the runtime
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PowershellDetect {
+    Static(bool),
+    StaticCast(String),
+}
+
+impl PowershellDetect {
+    fn is_static(&self) -> bool {
+        match self {
+            PowershellDetect::Static(value) => *value,
+            PowershellDetect::StaticCast(_) => true,
+        }
+    }
+}
+
+const STRING_KINDS: [&str; 4] = [
+    "verbatim_string_characters",
+    "verbatim_here_string_characters",
+    "expandable_string_literal",
+    "expandable_here_string_literal",
+];
+
+/// Follow single child nodes down to the node that carries the value
+fn innermost(node: Node<PowershellDetect>) -> Node<PowershellDetect> {
+    let mut current = node;
+    while current.child_count() == 1
+        && let Some(child) = current.child(0)
+    {
+        current = child;
+    }
+    current
+}
+
+/// Flatten a chain of binary operations using the same operator
+fn operands<'a>(
+    node: Node<'a, PowershellDetect>,
+    kinds: &[&str],
+    operator: &str,
+    result: &mut Vec<Node<'a, PowershellDetect>>,
+) -> MinusOneResult<()> {
+    let node = innermost(node);
+    if node.kind() == "parenthesized_expression" && node.child_count() == 3 {
+        operands(
+            node.child(1).ok_or(Error::invalid_child())?,
+            kinds,
+            operator,
+            result,
+        )?;
+    } else if kinds.contains(&node.kind())
+        && node.child_count() == 3
+        && node.child(1).ok_or(Error::invalid_child())?.text()? == operator
+    {
+        operands(
+            node.child(0).ok_or(Error::invalid_child())?,
+            kinds,
+            operator,
+            result,
+        )?;
+        operands(
+            node.child(2).ok_or(Error::invalid_child())?,
+            kinds,
+            operator,
+            result,
+        )?;
+    } else {
+        result.push(node);
+    }
+    Ok(())
+}
+
+fn is_char_cast(node: &Node<PowershellDetect>) -> bool {
+    matches!(node.data(), Some(PowershellDetect::StaticCast(t)) if t == "char" || t == "char[]")
+}
+
+/// Dotted type name as lowercase, without ticks nor the system namespace
+pub(crate) fn type_name(text: &str) -> String {
+    let name = text.replace('`', "").to_lowercase();
+    name.strip_prefix("system.").unwrap_or(&name).to_string()
+}
+
+/// This rule will flag static expressions
+/// Those expressions can be computed without running the script
+///
+/// # Example
+/// ```
+/// use minusone::ps::build_powershell_tree_for_storage;
+/// use minusone::ps::r#static::{PowershellDetect, Static};
+/// use minusone::tree::HashMapStorage;
+///
+/// let mut tree = build_powershell_tree_for_storage::<HashMapStorage<PowershellDetect>>("'a'+'b'").unwrap();
+/// tree.apply_mut(&mut Static::default()).unwrap();
+///
+/// let statement_list = tree.root().unwrap().child(0).unwrap();
+/// assert_eq!(statement_list.data(), Some(&PowershellDetect::Static(true)));
+/// ```
+#[derive(Default)]
+pub struct Static;
+
+impl<'a> RuleMut<'a> for Static {
+    type Language = PowershellDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        match view.kind() {
+            "decimal_integer_literal"
+            | "hexadecimal_integer_literal"
+            | "real_literal"
+            | "verbatim_string_characters"
+            | "verbatim_here_string_characters" => {
+                trace!("Static (L): Setting node with static: true");
+                node.set(PowershellDetect::Static(true))
+            }
+            // Forward static on simple node
+            "unary_expression"
+            | "range_expression"
+            | "comparison_expression"
+            | "bitwise_expression"
+            | "string_literal"
+            | "logical_expression"
+            | "integer_literal"
+            | "argument_expression"
+            | "range_argument_expression"
+            | "comparison_argument_expression"
+            | "bitwise_argument_expression"
+            | "logical_argument_expression"
+            | "command_name_expr"
+            | "pipeline"
+            | "pipeline_chain"
+            | "statement_list"
+            | "expression_with_unary_operator" => {
+                if view.child_count() == 1
+                    && let Some(data) = view.child(0).ok_or(Error::invalid_child())?.data()
+                {
+                    trace!("Static (L): Setting node with static: {}", data.is_static());
+                    node.set(PowershellDetect::Static(data.is_static()))
+                }
+            }
+            // complex expression
+            "parenthesized_expression" | "sub_expression" => {
+                if view.child_count() == 3
+                    && let Some(data) = view.child(1).ok_or(Error::invalid_child())?.data()
+                {
+                    trace!("Static (L): Setting node with static: {}", data.is_static());
+                    node.set(PowershellDetect::Static(data.is_static()))
+                }
+            }
+
+            "additive_argument_expression"
+            | "additive_expression"
+            | "multiplicative_expression"
+            | "multiplicative_argument_expression"
+            | "format_expression"
+            | "format_argument_expression" => match view.child_count() {
+                1 => {
+                    if let Some(data) = view.child(0).ok_or(Error::invalid_child())?.data() {
+                        trace!("Static (L): Setting node with static: {}", data.is_static());
+                        node.set(PowershellDetect::Static(data.is_static()))
+                    }
+                }
+                3 => {
+                    if let (Some(left), Some(right)) = (
+                        view.child(0).ok_or(Error::invalid_child())?.data(),
+                        view.child(2).ok_or(Error::invalid_child())?.data(),
+                    ) {
+                        let data = left.is_static() && right.is_static();
+                        trace!("Static (L): Setting node with static: {}", data);
+                        node.set(PowershellDetect::Static(data))
+                    }
+                }
+                _ => (),
+            },
+            // elements are separated by commas : 1, 2, 3
+            "array_literal_expression" => {
+                let mut data = Some(true);
+                for element in view.iter().filter(|child| child.kind() != ",") {
+                    data = match (data, element.data()) {
+                        (Some(left), Some(right)) => Some(left && right.is_static()),
+                        _ => None,
+                    };
+                }
+                if let Some(data) = data {
+                    trace!("Static (L): Setting node with static: {}", data);
+                    node.set(PowershellDetect::Static(data))
+                }
+            }
+            "expandable_string_literal" | "expandable_here_string_literal" => {
+                if view.child_count() == 0 {
+                    trace!("Static (L): Setting node with static: true");
+                    node.set(PowershellDetect::Static(true))
+                }
+            }
+            "cast_expression" => {
+                if let (Some(type_literal), Some(unary_expression)) = (view.child(0), view.child(1))
+                    && unary_expression.data().map(PowershellDetect::is_static) == Some(true)
+                {
+                    let cast = type_name(type_literal.text()?.trim_matches(['[', ']']));
+                    trace!("Static (L): Setting node with static cast: {}", cast);
+                    node.set(PowershellDetect::StaticCast(cast))
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+/// Static array of at least 4 integers, usually char codes
+#[derive(Default)]
+pub struct StaticArray {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for StaticArray {
+    type Language = PowershellDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() == "array_literal_expression"
+            && view.parent().ok_or(Error::invalid_child())?.kind() != "array_literal_expression"
+            && view.child_count() > 1
+            && let Some(PowershellDetect::Static(true)) = view.data()
+        {
+            let elements: Vec<_> = view
+                .iter()
+                .filter(|child| child.kind() != ",")
+                .map(innermost)
+                .collect();
+            if elements.len() >= 4
+                && elements.iter().all(|element| {
+                    matches!(
+                        element.kind(),
+                        "decimal_integer_literal" | "hexadecimal_integer_literal"
+                    )
+                })
+            {
+                self.detected_nodes
+                    .push(Pattern::new(view.start_abs(), view.end_abs()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Detection for StaticArray {
+    fn technique(&self) -> Technique {
+        Technique::CharCode
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// Static cast of an integer to a char : [char]0x41
+#[derive(Default)]
+pub struct StaticChar {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for StaticChar {
+    type Language = PowershellDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() == "cast_expression" && is_char_cast(&view) {
+            push_outermost(
+                &mut self.detected_nodes,
+                Pattern::new(view.start_abs(), view.end_abs()),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Detection for StaticChar {
+    fn technique(&self) -> Technique {
+        Technique::CharCode
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// Static string split into at least two concatenated parts
+#[derive(Default)]
+pub struct StaticConcat {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for StaticConcat {
+    type Language = PowershellDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if matches!(
+            view.kind(),
+            "additive_expression" | "additive_argument_expression"
+        ) && view.child_count() == 3
+            && let Some(PowershellDetect::Static(true)) = view.data()
+        {
+            let mut parts = vec![];
+            operands(
+                node.view(),
+                &["additive_expression", "additive_argument_expression"],
+                "+",
+                &mut parts,
+            )?;
+            let strings = parts
+                .iter()
+                .filter(|part| STRING_KINDS.contains(&part.kind()) || is_char_cast(part))
+                .count();
+            if strings >= 2 {
+                push_outermost(
+                    &mut self.detected_nodes,
+                    Pattern::new(view.start_abs(), view.end_abs()),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Detection for StaticConcat {
+    fn technique(&self) -> Technique {
+        Technique::StringConcat
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// Format operator applied on static operands : "{1}{0}" -f 'lo','hel'
+#[derive(Default)]
+pub struct StaticFormat {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for StaticFormat {
+    type Language = PowershellDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() == "format_expression"
+            && let (Some(expression), Some(range_expression)) = (view.child(0), view.child(2))
+            && let (Some(left), Some(right)) = (expression.data(), range_expression.data())
+            && left.is_static()
+            && right.is_static()
+        {
+            push_outermost(
+                &mut self.detected_nodes,
+                Pattern::new(view.start_abs(), view.end_abs()),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Detection for StaticFormat {
+    fn technique(&self) -> Technique {
+        Technique::FormatString
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// Backtick inserted into a name or before a regular character of a string : I`nv`oke
+#[derive(Default)]
+pub struct Tick {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for Tick {
+    type Language = PowershellDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        let ticked = match view.kind() {
+            "command_name" | "simple_name" | "type_name" | "generic_token" | "variable" => {
+                view.text()?.contains('`')
+            }
+            "expandable_string_literal" | "expandable_here_string_literal" => {
+                let text = view.text()?;
+                text.char_indices().any(|(index, c)| {
+                    c == '`'
+                        && text[index + 1..]
+                            .chars()
+                            .next()
+                            .is_some_and(|next| !"0abefnrtuv`$\"'\r\n".contains(next))
+                })
+            }
+            _ => false,
+        };
+
+        if ticked {
+            push_outermost(
+                &mut self.detected_nodes,
+                Pattern::new(view.start_abs(), view.end_abs()),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Detection for Tick {
+    fn technique(&self) -> Technique {
+        Technique::TickInsertion
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// Known command name written with an unusual case : InVoKe-ExPrEsSiOn
+#[derive(Default)]
+pub struct RandomCase {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for RandomCase {
+    type Language = PowershellDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() == "command_name" {
+            let name = view.text()?.replace('`', "");
+            if let Some(canonical) = canonical_cmdlet_name(&name)
+                && name != canonical
+                && name != name.to_lowercase()
+                && name != name.to_uppercase()
+            {
+                self.detected_nodes
+                    .push(Pattern::new(view.start_abs(), view.end_abs()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Detection for RandomCase {
+    fn technique(&self) -> Technique {
+        Technique::RandomCase
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// Command name resolved through a wildcard : I*-Ex*
+#[derive(Default)]
+pub struct WildcardName {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for WildcardName {
+    type Language = PowershellDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if view.kind() == "command_name" && resolve_wildcard_cmdlet(view.text()?).is_some() {
+            self.detected_nodes
+                .push(Pattern::new(view.start_abs(), view.end_abs()));
+        }
+        Ok(())
+    }
+}
+
+impl Detection for WildcardName {
+    fn technique(&self) -> Technique {
+        Technique::WildcardCmdlet
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// FromBase64String calls, -EncodedCommand parameters and long base64 strings
+#[derive(Default)]
+pub struct Base64Layer {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for Base64Layer {
+    type Language = PowershellDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        let detected = match view.kind() {
+            "member_name" => {
+                view.text()?.replace('`', "").to_lowercase() == "frombase64string"
+                    && view.parent().map(|parent| parent.kind()) == Some("invokation_expression")
+            }
+            "command_parameter" => {
+                let parameter = view.text()?.trim_start_matches('-').to_lowercase();
+                let command_name = view
+                    .get_parent_of_types(vec!["command"])
+                    .and_then(|command| command.named_child("command_name"))
+                    .filter(|command_name| command_name.kind() == "command_name")
+                    .map(|command_name| command_name.text().map(str::to_lowercase))
+                    .transpose()?;
+                matches!(
+                    command_name.as_deref(),
+                    Some("powershell" | "powershell.exe" | "pwsh" | "pwsh.exe")
+                ) && (parameter == "ec"
+                    || (parameter.starts_with('e') && "encodedcommand".starts_with(&parameter)))
+            }
+            "verbatim_string_characters" | "expandable_string_literal" => {
+                view.child_count() == 0 && looks_like_base64(view.text()?.trim_matches(['\'', '"']))
+            }
+            _ => false,
+        };
+
+        if detected {
+            // the whole call is reported for a method name
+            let located = if view.kind() == "member_name" {
+                view.parent().ok_or(Error::invalid_child())?
+            } else {
+                view
+            };
+            self.detected_nodes
+                .push(Pattern::new(located.start_abs(), located.end_abs()));
+        }
+        Ok(())
+    }
+}
+
+impl Detection for Base64Layer {
+    fn technique(&self) -> Technique {
+        Technique::Base64
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+const ENCRYPTION_TYPES: [&str; 12] = [
+    "aes",
+    "aesmanaged",
+    "aescryptoserviceprovider",
+    "aescng",
+    "rijndael",
+    "rijndaelmanaged",
+    "tripledes",
+    "tripledescryptoserviceprovider",
+    "des",
+    "descryptoserviceprovider",
+    "rc2",
+    "rc2cryptoserviceprovider",
+];
+
+const COMPRESSION_TYPES: [&str; 4] = ["gzipstream", "deflatestream", "zlibstream", "brotlistream"];
+
+/// .Net type from a namespace, used as a type literal or a New-Object argument
+fn is_dotnet_type(view: &Node<PowershellDetect>, namespace: &str, types: &[&str]) -> bool {
+    if !matches!(view.kind(), "type_name" | "generic_token") {
+        return false;
+    }
+
+    let name = match view.text() {
+        Ok(text) => type_name(text),
+        Err(_) => return false,
+    };
+
+    match name.rsplit_once('.') {
+        Some((prefix, last)) => {
+            (prefix == namespace || prefix.ends_with(&format!(".{}", namespace)))
+                && types.contains(&last)
+        }
+        None => false,
+    }
+}
+
+/// AES, DES or RC2 algorithm instantiation and CreateDecryptor calls
+#[derive(Default)]
+pub struct EncryptionLayer {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for EncryptionLayer {
+    type Language = PowershellDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if is_dotnet_type(&view, "security.cryptography", &ENCRYPTION_TYPES) {
+            push_outermost(
+                &mut self.detected_nodes,
+                Pattern::new(view.start_abs(), view.end_abs()),
+            );
+        } else if view.kind() == "member_name"
+            && view.text()?.replace('`', "").to_lowercase() == "createdecryptor"
+            && let Some(invokation) = view.parent()
+        {
+            self.detected_nodes
+                .push(Pattern::new(invokation.start_abs(), invokation.end_abs()));
+        }
+        Ok(())
+    }
+}
+
+impl Detection for EncryptionLayer {
+    fn technique(&self) -> Technique {
+        Technique::Encryption
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// GZip, Deflate, ZLib or Brotli stream instantiation
+#[derive(Default)]
+pub struct CompressionLayer {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for CompressionLayer {
+    type Language = PowershellDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        if is_dotnet_type(&view, "io.compression", &COMPRESSION_TYPES) {
+            push_outermost(
+                &mut self.detected_nodes,
+                Pattern::new(view.start_abs(), view.end_abs()),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Detection for CompressionLayer {
+    fn technique(&self) -> Technique {
+        Technique::Compression
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// Invoke-Expression, computed command names : &('ie'+'x')
+/// and script blocks created from strings
+#[derive(Default)]
+pub struct DynamicInvocation {
+    detected_nodes: Vec<Pattern>,
+}
+
+impl<'a> RuleMut<'a> for DynamicInvocation {
+    type Language = PowershellDetect;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        let view = node.view();
+        let detected = match view.kind() {
+            "command" => match (view.child(0), view.child(1)) {
+                (Some(command_name), _) if command_name.kind() == "command_name" => {
+                    let name = command_name.text()?.replace('`', "").to_lowercase();
+                    name == "iex"
+                        || name == "invoke-expression"
+                        || resolve_wildcard_cmdlet(&name).as_deref() == Some("Invoke-Expression")
+                }
+                (Some(operator), Some(command_name_expr))
+                    if operator.kind() == "command_invokation_operator" =>
+                {
+                    let name = innermost(command_name_expr);
+                    !matches!(name.kind(), "command_name" | "variable")
+                        && !STRING_KINDS.contains(&name.kind())
+                }
+                _ => false,
+            },
+            "invokation_expression" => match (view.child(0), view.child(2)) {
+                (Some(target), Some(member_name)) => {
+                    let target = type_name(target.text()?.trim_matches(['[', ']']));
+                    let member = member_name.text()?.replace('`', "").to_lowercase();
+                    matches!(member.as_str(), "invokescript" | "newscriptblock")
+                        || (member == "create"
+                            && (target == "scriptblock" || target.ends_with(".scriptblock")))
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+
+        if detected {
+            push_outermost(
+                &mut self.detected_nodes,
+                Pattern::new(view.start_abs(), view.end_abs()),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Detection for DynamicInvocation {
+    fn technique(&self) -> Technique {
+        Technique::DynamicInvocation
+    }
+
+    fn get_nodes(&self) -> &Vec<Pattern> {
+        &self.detected_nodes
+    }
+}
+
+/// The static analysis rule set
+/// Static has to be first, as other rules use the static flag it sets
+pub type PowershellDetectRuleSet = (
+    Static,
+    StaticArray,
+    StaticChar,
+    StaticConcat,
+    StaticFormat,
+    Tick,
+    RandomCase,
+    WildcardName,
+    Base64Layer,
+    EncryptionLayer,
+    CompressionLayer,
+    DynamicInvocation,
+);
+
+/// Run the static analysis on a Powershell script
+/// The source is parsed again and never modified
+///
+/// # Example
+/// ```
+/// use minusone::detect::Technique;
+/// use minusone::ps::r#static::analyze_powershell;
+///
+/// let report = analyze_powershell("$a = 'Write-Ho'+'st'").unwrap();
+/// assert_eq!(report.techniques(), vec![(Technique::StringConcat, 1)]);
+/// assert_eq!(report.findings[0].start, 5);
+/// assert_eq!(report.findings[0].end, 20);
+/// ```
+pub fn analyze_powershell(source: &str) -> MinusOneResult<Report> {
+    let mut tree = build_powershell_tree_for_storage::<HashMapStorage<PowershellDetect>>(source)?;
+    let mut rules = PowershellDetectRuleSet::init();
+    tree.apply_mut(&mut rules)?;
+
+    Ok(Report::new(&[
+        &rules.1, &rules.2, &rules.3, &rules.4, &rules.5, &rules.6, &rules.7, &rules.8, &rules.9,
+        &rules.10, &rules.11,
+    ]))
+}
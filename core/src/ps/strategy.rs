@@ -18,7 +18,10 @@ impl Strategy<Powershell> for PowershellStrategy {
 
         let predictability = match node.kind() {
             "statement_block" => {
-                let parent = node.parent().unwrap();
+                // a block parsed on its own has no statement to depend on
+                let Some(parent) = node.parent() else {
+                    return Ok(Continue(Predictable));
+                };
                 match parent.kind() {
                     "for_statement" | "while_statement" => match parent.data() {
                         Some(Powershell::Loop(LoopStatus::Dead)) => Ok(Break),
@@ -40,7 +43,9 @@ impl Strategy<Powershell> for PowershellStrategy {
                     "elseif_clause" => {
                         // elseif_clause is evaluated only if the if statement if false or unpredictable
                         // We have to check if previous clause are predictable or not
-                        let elseif_clauses = parent.parent().unwrap();
+                        let Some(elseif_clauses) = parent.parent() else {
+                            return Ok(Continue(Unpredictable));
+                        };
 
                         // loop over all elseif clauses
                         for elseif_clause in elseif_clauses.iter() {
@@ -73,8 +78,9 @@ impl Strategy<Powershell> for PowershellStrategy {
                     "else_clause" => {
                         // else clause is visited only if the main if is false
                         // but we have to check elseif_clause
-                        if let Some(elseif_clauses) =
-                            parent.parent().unwrap().named_child("elseif_clauses")
+                        if let Some(elseif_clauses) = parent
+                            .parent()
+                            .and_then(|if_statement| if_statement.named_child("elseif_clauses"))
                         {
                             for elseif_clause in elseif_clauses.iter() {
                                 if let Some(condition) = elseif_clause.named_child("condition") {
@@ -97,8 +103,10 @@ impl Strategy<Powershell> for PowershellStrategy {
             }
             // We can add condition to visit these node depending on the main if condition
             "elseif_clauses" | "else_clause" => {
-                let if_statement = node.parent().unwrap();
-                if let Some(condition) = if_statement.named_child("condition") {
+                if let Some(condition) = node
+                    .parent()
+                    .and_then(|if_statement| if_statement.named_child("condition"))
+                {
                     return match condition.data() {
                         // don't visit elseif_clauses if the main if is true
                         Some(Raw(Bool(true))) => Ok(Break),
@@ -2,10 +2,11 @@ use crate::error::MinusOneResult;
 use crate::ps::Powershell;
 use crate::ps::Powershell::{Array, Raw, Type};
 use crate::ps::Value::{Bool, Num, Str};
+use crate::ps::convert::format_composite;
+use crate::ps::regex::{DotNetRegex, RegexOptions};
 use crate::ps::tool::StringTool;
 use crate::ps::utils::conversion::*;
 use crate::ps::utils::string::*;
-use crate::regex::RegexBuilder;
 use crate::rule::RuleMut;
 use crate::tree::{ControlFlow, NodeMut};
 use log::trace;
@@ -744,13 +745,24 @@ impl<'a> RuleMut<'a> for StringReplaceOp {
                 operator.text()?.to_lowercase().as_str(),
                 right_expression.data(),
             ) {
-                (Some(Raw(Str(src))), op @ ("-replace" | "-creplace"), Some(Array(params))) => {
-                    if let (Some(Str(pattern)), Some(Str(new))) = (params.first(), params.get(1))
-                        && let Ok(re) = RegexBuilder::new(pattern)
-                            .case_insensitive(op == "-replace")
-                            .build()
-                    {
-                        node.reduce(Raw(Str(re.replace_all(src, new.as_str()).into_owned())));
+                (
+                    Some(Raw(Str(src))),
+                    op @ ("-replace" | "-ireplace" | "-creplace"),
+                    Some(params),
+                ) => {
+                    let options = if op == "-creplace" {
+                        RegexOptions::NONE
+                    } else {
+                        RegexOptions::IGNORE_CASE
+                    };
+                    // without a substitution, matches are removed
+                    let (pattern, new) = match params {
+                        Array(params) if params.len() == 2 => (&params[0], params[1].to_string()),
+                        Raw(pattern) => (pattern, String::new()),
+                        _ => return Ok(()),
+                    };
+                    if let Some(re) = DotNetRegex::new(&pattern.to_string(), options) {
+                        node.reduce(Raw(Str(re.replace(src, &new))));
                     }
                 }
                 _ => (),
@@ -762,6 +774,8 @@ impl<'a> RuleMut<'a> for StringReplaceOp {
 
 /// This rule will infer format operator
 ///
+/// Format items like `{0:X2}` or `{1,-8}` are handled by [`format_composite`]
+///
 /// # Example
 /// ```
 /// use minusone::tree::{HashMapStorage, Tree};
@@ -807,21 +821,17 @@ impl<'a> RuleMut<'a> for FormatString {
         if view.kind() == "format_expression"
             && let (Some(format_str_node), Some(format_args_node)) = (view.child(0), view.child(2))
         {
-            match (format_str_node.data(), format_args_node.data()) {
+            let result = match (format_str_node.data(), format_args_node.data()) {
                 (Some(Raw(Str(format_str))), Some(Array(format_args))) => {
-                    let mut result = format_str.clone();
-                    for (index, new) in format_args.iter().enumerate() {
-                        result = result
-                            .replace(format!("{{{index}}}").as_str(), new.to_string().as_str());
-                    }
-                    node.reduce(Raw(Str(result)));
+                    format_composite(format_str, format_args)
                 }
                 (Some(Raw(Str(format_str))), Some(Raw(format_arg))) => {
-                    node.reduce(Raw(Str(
-                        format_str.replace("{0}", format_arg.to_string().as_str())
-                    )));
+                    format_composite(format_str, std::slice::from_ref(format_arg))
                 }
-                _ => (),
+                _ => None,
+            };
+            if let Some(result) = result {
+                node.reduce(Raw(Str(result)));
             }
         }
         Ok(())
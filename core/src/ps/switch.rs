@@ -62,7 +62,10 @@ impl<'a> RuleMut<'a> for Switch {
         // find usage of magic variable
         match view.kind() {
             "switch_statement" => {
-                let ctx = self.ctx.pop().unwrap();
+                // a switch without condition, as parsed from a malformed script
+                let Some(ctx) = self.ctx.pop() else {
+                    return Ok(());
+                };
                 if let Some(data) = ctx.matching.or(ctx.default.filter(|_| ctx.predictable)) {
                     trace!(
                         "SwitchCtx (L): Setting predictable switch statement {} as {:?}",
@@ -0,0 +1,151 @@
+#[cfg(test)]
+mod tests_ps_artefact {
+    use crate::artefact::{ArtefactKind, classify, dump_artefacts};
+    use crate::engine::DeobfuscateEngine;
+    use crate::ps::backend::PowershellBackend;
+    use base64::{Engine as _, engine::general_purpose};
+
+    // Headers of a PE32+, with a CLR runtime header when it is a .NET assembly
+    fn pe(is_dotnet: bool) -> Vec<u8> {
+        let mut pe = vec![0u8; 328];
+        pe[..2].copy_from_slice(b"MZ");
+        pe[0x3c] = 0x40;
+        pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+        // optional header magic and number of data directories
+        pe[0x58..0x5a].copy_from_slice(&0x20bu16.to_le_bytes());
+        pe[0xc4..0xc8].copy_from_slice(&16u32.to_le_bytes());
+        if is_dotnet {
+            pe[0x138..0x13c].copy_from_slice(&0x2008u32.to_le_bytes());
+        }
+        pe
+    }
+
+    fn shellcode() -> Vec<u8> {
+        b"\xfc\x48\x83\xe4\xf0\xe8\xc0\x00\x00\x00".repeat(30)
+    }
+
+    #[test]
+    fn test_classify_pe() {
+        assert_eq!(classify(&pe(false)), ArtefactKind::Pe);
+        assert_eq!(classify(&pe(true)), ArtefactKind::DotNetAssembly);
+    }
+
+    #[test]
+    fn test_classify_mz_without_pe_header() {
+        assert_eq!(classify(b"MZ is not enough"), ArtefactKind::Data);
+    }
+
+    #[test]
+    fn test_classify_archives_and_documents() {
+        assert_eq!(
+            classify(b"PK\x03\x04\x14\x00\x00\x00\x08\x00a.ps1"),
+            ArtefactKind::Zip
+        );
+        assert_eq!(
+            classify(
+                b"PK\x03\x04\x14\x00\x06\x00\x08\x00[Content_Types].xmlPK\x03\x04word/document.xml"
+            ),
+            ArtefactKind::OfficeOpenXml
+        );
+        assert_eq!(ArtefactKind::OfficeOpenXml.extension(), "docx");
+        assert_eq!(
+            classify(
+                b"PK\x03\x04\x14\x00\x06\x00\x08\x00[Content_Types].xmlPK\x03\x04xl/workbook.xml"
+            ),
+            ArtefactKind::Zip
+        );
+        assert_eq!(
+            classify(b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1\x00\x00"),
+            ArtefactKind::Office
+        );
+    }
+
+    #[test]
+    fn test_classify_shellcode() {
+        assert_eq!(classify(&shellcode()), ArtefactKind::Shellcode);
+        assert_eq!(
+            classify(b"\xda\xc1\xd9\x74\x24\xf4\x5b\x31\xc9"),
+            ArtefactKind::Shellcode
+        );
+    }
+
+    #[test]
+    fn test_assembly_load_is_collected_once() {
+        let source = format!(
+            "$b = [Convert]::FromBase64String('{}')\n[Reflection.Assembly]::Load($b)\n[Reflection.Assembly]::Load($b)",
+            general_purpose::STANDARD.encode(pe(true))
+        );
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(&source).unwrap();
+        engine.deobfuscate().unwrap();
+
+        let artefacts = engine.artefacts(256).unwrap();
+        assert_eq!(artefacts.len(), 1);
+        assert_eq!(artefacts[0].kind, ArtefactKind::DotNetAssembly);
+        assert_eq!(artefacts[0].data, pe(true));
+        assert_eq!(
+            artefacts[0].sha256,
+            "bea4dd014baada4335169156df2ce5c7edad5c23c4949289461759cd8768f804"
+        );
+    }
+
+    #[test]
+    fn test_small_values_are_not_collected() {
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(
+            "[Convert]::FromBase64String('/EiD5PDowAAAAA==')",
+        )
+        .unwrap();
+        engine.deobfuscate().unwrap();
+
+        assert!(engine.artefacts(256).unwrap().is_empty());
+        assert_eq!(
+            engine.artefacts(8).unwrap()[0].kind,
+            ArtefactKind::Shellcode
+        );
+    }
+
+    #[test]
+    fn test_lint_with_artefacts() {
+        let source = format!(
+            "[Reflection.Assembly]::Load([Convert]::FromBase64String('{}'))",
+            general_purpose::STANDARD.encode(shellcode())
+        );
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(&source).unwrap();
+        engine.deobfuscate().unwrap();
+
+        assert!(
+            engine
+                .lint_with_artefacts("    ", false, 256)
+                .unwrap()
+                .ends_with("::Load(@(<# shellcode, 300 bytes, sha256:d0b6fa3176b5113a03beb961a80287a9514bb81916f5957b13f9f02a48de0dba #>))")
+        );
+        assert!(
+            engine
+                .lint(false)
+                .unwrap()
+                .contains("@(252, 72, 131, 228, 240")
+        );
+    }
+
+    #[test]
+    fn test_dump_artefacts() {
+        let source = format!(
+            "[IO.File]::WriteAllBytes('C:\\a.exe', [Convert]::FromBase64String('{}'))",
+            general_purpose::STANDARD.encode(pe(false))
+        );
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(&source).unwrap();
+        engine.deobfuscate().unwrap();
+
+        let directory = std::env::temp_dir().join("minusone_tests_ps_artefact");
+        let artefacts = engine.artefacts(256).unwrap();
+        let paths = dump_artefacts(&artefacts, &directory).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                directory
+                    .join("561f8d9c72e6bde1100818bfd45a1255e27e37e1ed18fa070e11c764e5f25c62.exe")
+            ]
+        );
+        assert_eq!(std::fs::read(&paths[0]).unwrap(), pe(false));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
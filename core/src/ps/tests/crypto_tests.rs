@@ -1,7 +1,10 @@
 #[cfg(test)]
 mod tests_ps_crypto {
+    use crate::error::{Error, MinusOneErrorKind};
     use crate::ps::build_powershell_tree;
-    use crate::ps::crypto::{AesState, AesTransformFinalBlock, AesType};
+    use crate::ps::crypto::{
+        AesTransformFinalBlock, AesType, CipherAlgorithm, CipherMode, CipherPadding, CipherState,
+    };
     use crate::ps::encoding::{EncodingGetBytes, EncodingType};
     use crate::ps::forward::Forward;
     use crate::ps::integer::ParseInt;
@@ -73,13 +76,14 @@ mod tests_ps_crypto {
 
         let ciphertext = parse_bytes_literal(&output);
 
-        let plaintext = crate::ps::crypto::aes_transform(
-            &AesState {
+        let plaintext = crate::ps::crypto::cipher_transform(
+            &CipherState {
                 key: Some(b"0123456789abcdef".to_vec()),
                 iv: Some(b"abcdef0123456789".to_vec()),
                 mode: None,
                 padding: None,
                 is_decrypt: Some(true),
+                ..CipherState::default()
             },
             true,
             &ciphertext,
@@ -115,4 +119,138 @@ $decryptor.TransformFinalBlock([Convert]::FromBase64String("{}"), 0, {})
 
         assert_eq!(parse_bytes_literal(last_line), plaintext.to_vec());
     }
+
+    #[test]
+    fn test_aes_invalid_padding_is_reported() {
+        let error = crate::ps::crypto::cipher_transform(
+            &CipherState {
+                key: Some(b"0123456789abcdef".to_vec()),
+                iv: Some(b"abcdef0123456789".to_vec()),
+                mode: None,
+                padding: None,
+                is_decrypt: Some(true),
+                ..CipherState::default()
+            },
+            true,
+            &[0u8; 16],
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), MinusOneErrorKind::Decryption);
+    }
+
+    #[test]
+    fn test_aes_invalid_key_length_is_reported() {
+        let error = crate::ps::crypto::cipher_transform(
+            &CipherState {
+                key: Some(b"0123".to_vec()),
+                iv: Some(b"abcdef0123456789".to_vec()),
+                mode: None,
+                padding: None,
+                is_decrypt: Some(true),
+                ..CipherState::default()
+            },
+            true,
+            &[0u8; 16],
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::InvalidLength(_)));
+    }
+
+    #[test]
+    fn test_triple_des_decrypt_via_property_assignment() {
+        let output = deobfuscate(
+            r#"
+$des = New-Object System.Security.Cryptography.TripleDESCryptoServiceProvider
+$des.Key = [System.text.encoding]::UTF8.GetBytes("0123456789abcdefghijklmn")
+$des.IV = [System.text.encoding]::UTF8.GetBytes("abcdefgh")
+$des.CreateDecryptor().TransformFinalBlock([Convert]::FromBase64String("LG5YfcE/afZjIIBQ2V1JNoY0Ra4fwvs+"), 0, 24)
+"#,
+        );
+        let last_line = output.lines().next_back().unwrap();
+
+        assert_eq!(parse_bytes_literal(last_line), b"Hello, minusone!");
+    }
+
+    #[test]
+    fn test_triple_des_ansi_x923_padding() {
+        let output = deobfuscate(
+            r#"
+$des = [System.Security.Cryptography.TripleDES]::Create()
+$des.Padding = [System.Security.Cryptography.PaddingMode]::ANSIX923
+$des.CreateDecryptor([System.text.encoding]::UTF8.GetBytes("0123456789abcdefghijklmn"), [System.text.encoding]::UTF8.GetBytes("abcdefgh")).TransformFinalBlock([Convert]::FromBase64String("1g5N6f+tl1MMOztznPmWHA=="), 0, 16)
+"#,
+        );
+        let last_line = output.lines().next_back().unwrap();
+
+        assert_eq!(parse_bytes_literal(last_line), b"minusone!");
+    }
+
+    #[test]
+    fn test_des_decrypt() {
+        let plaintext = crate::ps::crypto::cipher_transform(
+            &CipherState {
+                algorithm: CipherAlgorithm::Des,
+                key: Some(b"01234567".to_vec()),
+                iv: Some(b"abcdefgh".to_vec()),
+                ..CipherState::default()
+            },
+            true,
+            &general_purpose::STANDARD
+                .decode("f1zBaFK+Fvfpod7GflE2XN8VfhrKotFU")
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(plaintext, b"Hello, minusone!");
+    }
+
+    #[test]
+    fn test_rc2_round_trip() {
+        let state = CipherState {
+            algorithm: CipherAlgorithm::Rc2,
+            key: Some(b"0123456789abcdef".to_vec()),
+            iv: Some(b"abcdefgh".to_vec()),
+            mode: Some(CipherMode::Ecb),
+            ..CipherState::default()
+        };
+        let ciphertext =
+            crate::ps::crypto::cipher_transform(&state, false, b"Hello, minusone!").unwrap();
+        assert_eq!(ciphertext.len(), 24);
+        assert_eq!(
+            crate::ps::crypto::cipher_transform(&state, true, &ciphertext).unwrap(),
+            b"Hello, minusone!"
+        );
+    }
+
+    #[test]
+    fn test_zeros_padding_is_kept_on_decrypt() {
+        let state = CipherState {
+            algorithm: CipherAlgorithm::TripleDes,
+            key: Some(b"0123456789abcdef".to_vec()),
+            iv: Some(b"abcdefgh".to_vec()),
+            padding: Some(CipherPadding::Zeros),
+            ..CipherState::default()
+        };
+        let ciphertext = crate::ps::crypto::cipher_transform(&state, false, b"minusone!").unwrap();
+        assert_eq!(ciphertext.len(), 16);
+        assert_eq!(
+            crate::ps::crypto::cipher_transform(&state, true, &ciphertext).unwrap(),
+            b"minusone!\0\0\0\0\0\0\0"
+        );
+    }
+
+    #[test]
+    fn test_des_invalid_key_length_is_reported() {
+        let error = crate::ps::crypto::cipher_transform(
+            &CipherState {
+                algorithm: CipherAlgorithm::Des,
+                key: Some(b"0123456789abcdef".to_vec()),
+                iv: Some(b"abcdefgh".to_vec()),
+                ..CipherState::default()
+            },
+            true,
+            &[0u8; 8],
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::InvalidLength(_)));
+    }
 }
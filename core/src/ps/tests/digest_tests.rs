@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod tests_ps_digest {
+    use crate::ps::build_powershell_tree;
+    use crate::ps::convert::ConvertBytes;
+    use crate::ps::crypto::{AesTransformFinalBlock, AesType};
+    use crate::ps::digest::{ComputeHash, DeriveKey};
+    use crate::ps::encoding::{EncodingGetBytes, EncodingType};
+    use crate::ps::forward::Forward;
+    use crate::ps::integer::ParseInt;
+    use crate::ps::linter::Linter;
+    use crate::ps::method::DecodeBase64;
+    use crate::ps::strategy::PowershellStrategy;
+    use crate::ps::string::ParseString;
+    use crate::ps::typing::ParseType;
+    use crate::ps::var::Var;
+
+    fn deobfuscate(input: &str) -> String {
+        let mut tree = build_powershell_tree(input).unwrap();
+        tree.apply_mut_with_strategy(
+            &mut (
+                Forward::default(),
+                ParseType::default(),
+                ParseString::default(),
+                ParseInt::default(),
+                EncodingType::default(),
+                EncodingGetBytes::default(),
+                DecodeBase64::default(),
+                ConvertBytes::default(),
+                AesType::default(),
+                AesTransformFinalBlock::default(),
+                ComputeHash::default(),
+                DeriveKey::default(),
+                Var::default(),
+            ),
+            PowershellStrategy::default(),
+        )
+        .unwrap();
+
+        let mut linter = Linter::default();
+        tree.apply(&mut linter).unwrap();
+        linter.output
+    }
+
+    fn last_line(output: &str) -> &str {
+        output.lines().next_back().unwrap()
+    }
+
+    #[test]
+    fn test_md5_create_compute_hash() {
+        assert_eq!(
+            deobfuscate(
+                "[BitConverter]::ToString([Security.Cryptography.MD5]::Create().ComputeHash([Text.Encoding]::UTF8.GetBytes('abc')))"
+            ),
+            "\"90-01-50-98-3C-D2-4F-B0-D6-96-3F-7D-28-E1-7F-72\""
+        );
+    }
+
+    #[test]
+    fn test_sha256_new_object_compute_hash() {
+        assert_eq!(
+            deobfuscate(
+                "(New-Object System.Security.Cryptography.SHA256Managed).ComputeHash([Text.Encoding]::UTF8.GetBytes('abc'))"
+            ),
+            "@(186, 120, 22, 191, 143, 1, 207, 234, 65, 65, 64, 222, 93, 174, 34, 35, 176, 3, 97, 163, 150, 23, 122, 156, 180, 16, 255, 97, 242, 0, 21, 173)"
+        );
+    }
+
+    #[test]
+    fn test_hash_algorithm_create_by_name_with_range() {
+        let output = deobfuscate(
+            "$h = [Security.Cryptography.HashAlgorithm]::Create('SHA256')\n$h.ComputeHash([Text.Encoding]::UTF8.GetBytes('abcdef'), 0, 3)",
+        );
+        assert_eq!(
+            last_line(&output),
+            "@(186, 120, 22, 191, 143, 1, 207, 234, 65, 65, 64, 222, 93, 174, 34, 35, 176, 3, 97, 163, 150, 23, 122, 156, 180, 16, 255, 97, 242, 0, 21, 173)"
+        );
+    }
+
+    #[test]
+    fn test_hash_data() {
+        assert_eq!(
+            deobfuscate(
+                "[BitConverter]::ToString([Security.Cryptography.SHA256]::HashData([Text.Encoding]::UTF8.GetBytes('minusone')))"
+            ),
+            "\"3C-0A-C5-C1-85-42-42-BE-9B-BB-1C-2B-3A-A6-C7-78-16-66-39-58-FF-C4-FB-77-A3-FB-F1-04-FF-A4-98-5A\""
+        );
+    }
+
+    #[test]
+    fn test_rfc2898_sha256_get_bytes() {
+        let output = deobfuscate(
+            "$salt = [Text.Encoding]::UTF8.GetBytes('saltsalt')
+$kdf = [Security.Cryptography.Rfc2898DeriveBytes]::new('P@ssw0rd', $salt, 1000, [Security.Cryptography.HashAlgorithmName]::SHA256)
+$kdf.GetBytes(48)",
+        );
+        assert_eq!(
+            last_line(&output),
+            "@(163, 189, 87, 113, 19, 41, 106, 4, 53, 59, 28, 7, 58, 101, 82, 205, 126, 210, 169, 199, 51, 197, 86, 125, 62, 206, 56, 60, 231, 171, 113, 194, 93, 97, 135, 220, 246, 173, 71, 232, 236, 7, 208, 73, 4, 91, 70, 206)"
+        );
+    }
+
+    #[test]
+    fn test_rfc2898_successive_get_bytes() {
+        let output = deobfuscate(
+            "$kdf = New-Object Security.Cryptography.Rfc2898DeriveBytes 'P@ssw0rd', ([Text.Encoding]::UTF8.GetBytes('saltsalt')), 1000
+$k = $kdf.GetBytes(16)
+$kdf.GetBytes(16)",
+        );
+        assert_eq!(
+            last_line(&output),
+            "@(55, 80, 4, 241, 17, 233, 255, 229, 16, 35, 212, 181, 82, 52, 156, 40)"
+        );
+    }
+
+    #[test]
+    fn test_password_derive_bytes_extra_bytes() {
+        // .NET returns the leftover bytes of the previous call from the wrong offset
+        let output = deobfuscate(
+            "$kdf = New-Object Security.Cryptography.PasswordDeriveBytes('P@ssw0rd', [Text.Encoding]::UTF8.GetBytes('saltsalt'), 'SHA1', 100)
+$k = $kdf.GetBytes(32)
+$kdf.GetBytes(16)",
+        );
+        assert_eq!(
+            last_line(&output),
+            "@(64, 69, 212, 44, 248, 50, 82, 74, 26, 249, 203, 21, 136, 193, 3, 206)"
+        );
+    }
+
+    #[test]
+    fn test_decrypt_with_derived_key() {
+        let output = deobfuscate(
+            "$kdf = [Security.Cryptography.Rfc2898DeriveBytes]::new('P@ssw0rd', [Text.Encoding]::UTF8.GetBytes('saltsalt'), 1000)
+$aes = [Security.Cryptography.Aes]::Create()
+$aes.Key = $kdf.GetBytes(32)
+$aes.IV = $kdf.GetBytes(16)
+$aes.CreateDecryptor().TransformFinalBlock([Convert]::FromBase64String('eUN0rGEzuBmYxEX53tOenq/F9gyLCTUETjG22qhz5vg='), 0, 32)",
+        );
+        let plaintext: Vec<u8> = last_line(&output)
+            .trim_start_matches("@(")
+            .trim_end_matches(')')
+            .split(',')
+            .map(|s| s.trim().parse().unwrap())
+            .collect();
+        assert_eq!(plaintext, b"Write-Host 'minusone'");
+    }
+
+    #[test]
+    fn test_get_bytes_in_unpredictable_branch_is_forgotten() {
+        let output = deobfuscate(
+            "$kdf = [Security.Cryptography.Rfc2898DeriveBytes]::new('P@ssw0rd', [Text.Encoding]::UTF8.GetBytes('saltsalt'), 1000)
+if ($x) { $k = $kdf.GetBytes(16) }
+$kdf.GetBytes(16)",
+        );
+        assert_eq!(last_line(&output), "$kdf.GetBytes(16)");
+    }
+
+    #[test]
+    fn test_iterations_over_budget_are_left_as_is() {
+        let output = deobfuscate(
+            "$kdf = [Security.Cryptography.Rfc2898DeriveBytes]::new('P@ssw0rd', [Text.Encoding]::UTF8.GetBytes('saltsalt'), 2147483647)
+$kdf.GetBytes(16)",
+        );
+        assert_eq!(last_line(&output), "$kdf.GetBytes(16)");
+    }
+
+    #[test]
+    fn test_zero_iterations_are_left_as_is() {
+        let output = deobfuscate(
+            "$kdf = [Security.Cryptography.Rfc2898DeriveBytes]::new('P@ssw0rd', [Text.Encoding]::UTF8.GetBytes('saltsalt'), 0)
+$kdf.GetBytes(16)",
+        );
+        assert_eq!(last_line(&output), "$kdf.GetBytes(16)");
+    }
+
+    #[test]
+    fn test_get_bytes_over_budget_are_left_as_is() {
+        let output = deobfuscate(
+            "$kdf = [Security.Cryptography.Rfc2898DeriveBytes]::new('P@ssw0rd', [Text.Encoding]::UTF8.GetBytes('saltsalt'), 1000)
+$k = $kdf.GetBytes(9223372036854775807)
+$kdf.GetBytes(16)",
+        );
+        assert_eq!(last_line(&output), "$kdf.GetBytes(16)");
+    }
+}
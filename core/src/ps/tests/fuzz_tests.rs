@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests_ps_fuzz {
+    //! Malformed trees, as the fuzz targets produce them: every crash
+    //! found by fuzzing ends up here as a regression test
+    use crate::engine::DeobfuscateEngine;
+    use crate::ps::backend::PowershellBackend;
+
+    /// Errors are expected on such inputs, panics are not
+    fn deobfuscate(input: &str) {
+        let Ok(cleaned) = DeobfuscateEngine::<PowershellBackend>::remove_extra(input, false) else {
+            return;
+        };
+        let Ok(mut engine) = DeobfuscateEngine::<PowershellBackend>::from_powershell(&cleaned)
+        else {
+            return;
+        };
+        if engine.deobfuscate().is_ok() {
+            let _ = engine.lint(false);
+        }
+    }
+
+    #[test]
+    fn test_else_without_if() {
+        deobfuscate("else { $a = 1 }\n$a");
+        deobfuscate("$a = 1\n} else { $a = 2 }\n$a");
+    }
+
+    #[test]
+    fn test_elseif_without_if() {
+        deobfuscate("elseif ($true) { $a = 1 }\n$a");
+        deobfuscate("$a = 1\n} elseif ($a -eq 1) { $a = 2 } else { $a = 3 }\n$a");
+    }
+
+    #[test]
+    fn test_unbalanced_blocks() {
+        deobfuscate("$a = 1 } } }\n$a");
+        deobfuscate("function f { $b = 2 } }\n{ $a = 1\n$a");
+        deobfuscate("& { $a = 1 } }\n} { $a");
+    }
+
+    #[test]
+    fn test_switch_without_condition() {
+        deobfuscate("switch { 1 { 'a' } default { 'b' } }");
+        deobfuscate("switch (1) { 1 { switch { 2 { 'a' } } } }");
+        deobfuscate("switch");
+    }
+
+    #[test]
+    fn test_truncated_if() {
+        deobfuscate("if ($a -eq 1) { $b = 1 } elseif");
+        deobfuscate("if ($a -eq 1) { $b = 1 } else");
+        deobfuscate("if (");
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests_ps_host {
+    use crate::engine::DeobfuscateEngine;
+    use crate::ps::backend::PowershellBackend;
+    use crate::ps::host::{HostProfile, HostVersion};
+    use crate::rule::RuleSetBuilderType;
+
+    fn deobfuscate_on(input: &str, profile: &HostProfile) -> String {
+        let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(input, false).unwrap();
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(&cleaned).unwrap();
+        engine
+            .deobfuscate_with_host_profile(RuleSetBuilderType::WithoutRules(vec![]), &[], profile)
+            .unwrap();
+        engine.lint(false).unwrap()
+    }
+
+    fn deobfuscate(input: &str) -> String {
+        deobfuscate_on(input, &HostProfile::default())
+    }
+
+    #[test]
+    fn test_env_comspec_index() {
+        assert_eq!(
+            deobfuscate("& ($env:ComSpec[4,15,25]-join'') 'Write-Host 1'"),
+            "Invoke-Expression 'Write-Host 1'"
+        );
+    }
+
+    #[test]
+    fn test_braced_env_variable() {
+        assert_eq!(deobfuscate("${env:windir}"), "\"C:\\WINDOWS\"");
+    }
+
+    #[test]
+    fn test_pshome_index() {
+        assert_eq!(deobfuscate("$PSHome[21]+$PSHome[30]"), "\"ie\"");
+    }
+
+    #[test]
+    fn test_shellid_index() {
+        assert_eq!(deobfuscate("$ShellId[1]+$ShellId[13]"), "\"ie\"");
+    }
+
+    #[test]
+    fn test_get_variable_wildcard() {
+        assert_eq!(deobfuscate("(gv '*mdr*').Name[3,11,2]-join''"), "\"iex\"");
+    }
+
+    #[test]
+    fn test_get_childitem_env() {
+        assert_eq!(
+            deobfuscate("(Get-ChildItem env:public).Value"),
+            "\"C:\\Users\\Public\""
+        );
+    }
+
+    #[test]
+    fn test_powershell7_profile() {
+        let profile = HostProfile::new(HostVersion::Powershell7);
+        assert_eq!(
+            deobfuscate_on("$PSHome", &profile),
+            "\"C:\\Program Files\\PowerShell\\7\""
+        );
+        assert_eq!(deobfuscate_on("$PSEdition", &profile), "\"Core\"");
+    }
+
+    #[test]
+    fn test_maximum_drive_count_removed_from_powershell7() {
+        let profile = HostProfile::new(HostVersion::Powershell7);
+        assert_eq!(
+            deobfuscate_on("$MaximumDriveCount", &profile),
+            "$MaximumDriveCount"
+        );
+    }
+
+    #[test]
+    fn test_user_profile() {
+        let profile = HostProfile::default()
+            .with_user_name("bob")
+            .with_env_block("COMPUTERNAME=WS-01\r\n=C:=C:\\\r\nnot an assignment");
+        assert_eq!(
+            deobfuscate_on("$env:USERPROFILE", &profile),
+            "\"C:\\Users\\bob\""
+        );
+        assert_eq!(deobfuscate_on("$env:computername", &profile), "\"WS-01\"");
+    }
+
+    #[test]
+    fn test_assigned_env_is_not_inferred() {
+        assert_eq!(deobfuscate("$env:TEMP = 'x'"), "$env:TEMP = 'x'");
+    }
+
+    #[test]
+    fn test_env_assigned_by_the_script_is_kept() {
+        assert!(!deobfuscate("$env:ComSpec = 'x'\n${env:comspec}").contains("cmd.exe"));
+        assert!(!deobfuscate("Remove-Item env:ComSpec\n$env:ComSpec").contains("cmd.exe"));
+    }
+
+    #[test]
+    fn test_automatic_variable_assigned_by_the_script_is_kept() {
+        assert!(!deobfuscate("Set-Variable PSHome 'x'\n$PSHome").contains("WindowsPowerShell"));
+        assert!(!deobfuscate("$ShellId = 'x'\n$ShellId").contains("Microsoft"));
+    }
+
+    #[test]
+    fn test_env_block() {
+        let profile = HostProfile::default().with_env_block("Path=C:\\bin\n=C:=C:\\\nFOO=bar=baz");
+        assert_eq!(profile.env("path").as_deref(), Some("C:\\bin"));
+        assert_eq!(profile.env("foo").as_deref(), Some("bar=baz"));
+        assert_eq!(profile.env("=C:"), None);
+    }
+
+    #[test]
+    fn test_host_version_parsing() {
+        assert_eq!(
+            "5.1".parse::<HostVersion>().unwrap(),
+            HostVersion::WindowsPowershell
+        );
+        assert_eq!(
+            "pwsh".parse::<HostVersion>().unwrap(),
+            HostVersion::Powershell7
+        );
+        assert!("6".parse::<HostVersion>().is_err());
+    }
+}
@@ -34,4 +34,78 @@ mod tests_ps_linter {
     fn test_escape_control_chars_on_output() {
         assert_eq!(deobfuscate("\"a`tb`0c\""), "\"a`tb`0c\"");
     }
+
+    #[test]
+    fn test_remove_ticks_in_command_name() {
+        assert_eq!(
+            deobfuscate("I`N`V`oKe-eX`pReSSIon 'Write-Host 1'"),
+            "Invoke-Expression 'Write-Host 1'"
+        );
+    }
+
+    #[test]
+    fn test_keep_ticks_in_verbatim_string() {
+        assert_eq!(deobfuscate("Write-Host 'a`b'"), "Write-Host 'a`b'");
+    }
+
+    #[test]
+    fn test_expand_aliases() {
+        assert_eq!(deobfuscate("iex 'a'"), "Invoke-Expression 'a'");
+        assert_eq!(deobfuscate("gci C:\\"), "Get-ChildItem C:\\");
+        assert_eq!(
+            deobfuscate("$a | % { $_ } | ? { $_ }"),
+            "$a | ForEach-Object { $_ } | Where-Object { $_ }"
+        );
+        assert_eq!(deobfuscate("sal x iex"), "Set-Alias x iex");
+    }
+
+    #[test]
+    fn test_keep_commands_defined_by_script() {
+        let output = deobfuscate("function gci { 'mine' }\ngci -r");
+        assert!(output.ends_with("\ngci -r"));
+        assert!(!output.contains("Get-ChildItem"));
+
+        let output = deobfuscate("Set-Alias -Name iex -Value Write-Host\niex 'a'");
+        assert!(output.ends_with("\niex 'a'"));
+        assert!(!output.contains("Invoke-Expression"));
+
+        assert_eq!(
+            deobfuscate("nal gc iex\ngc 'a'"),
+            "New-Alias gc iex\ngc 'a'"
+        );
+    }
+
+    #[test]
+    fn test_keep_dot_sourcing() {
+        assert_eq!(deobfuscate(". 'iex' 'a'"), ". Invoke-Expression 'a'");
+        assert_eq!(deobfuscate(". $profile"), ". $profile");
+        assert_eq!(deobfuscate("& $sb"), "& $sb");
+    }
+
+    #[test]
+    fn test_expand_parameters() {
+        assert_eq!(
+            deobfuscate("powershell -nop -w hidden -e abc"),
+            "powershell -NoProfile -WindowStyle hidden -EncodedCommand abc"
+        );
+        assert_eq!(
+            deobfuscate("Get-ChildItem -pA C:\\ -rec"),
+            "Get-ChildItem -Path C:\\ -Recurse"
+        );
+    }
+
+    #[test]
+    fn test_keep_ambiguous_parameter() {
+        assert_eq!(
+            deobfuscate("Start-Process calc -w"),
+            "Start-Process calc -w"
+        );
+    }
+
+    #[test]
+    fn test_remove_braces_of_variable() {
+        assert_eq!(deobfuscate("${foo} = 1"), "$foo = 1");
+        assert_eq!(deobfuscate("${env:Path}"), "$env:Path");
+        assert_eq!(deobfuscate("${a-b} = 1"), "${a-b} = 1");
+    }
 }
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests_ps_math {
+    use crate::engine::DeobfuscateEngine;
+    use crate::ps::backend::PowershellBackend;
+
+    fn deobfuscate(input: &str) -> String {
+        let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(input, false).unwrap();
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(&cleaned).unwrap();
+        engine.deobfuscate().unwrap();
+        engine.lint(false).unwrap()
+    }
+
+    #[test]
+    fn test_real_literal() {
+        assert_eq!(deobfuscate("1.5 + 1"), "2.5");
+        assert_eq!(deobfuscate("1e2"), "100");
+        assert_eq!(deobfuscate("-0.5 * 4"), "-2");
+    }
+
+    #[test]
+    fn test_division_produces_double() {
+        assert_eq!(deobfuscate("5 / 2"), "2.5");
+        assert_eq!(deobfuscate("0x41 / 1"), "65");
+    }
+
+    #[test]
+    fn test_division_by_zero_is_not_inferred() {
+        assert!(deobfuscate("1 / 0").contains('/'));
+        assert!(deobfuscate("1.5 % 0").contains('%'));
+    }
+
+    #[test]
+    fn test_double_precision() {
+        assert_eq!(deobfuscate("0.1 + 0.2"), "0.30000000000000004");
+    }
+
+    #[test]
+    fn test_integer_overflow_produces_double() {
+        assert_eq!(
+            deobfuscate("9223372036854775807 + 1"),
+            "9.223372036854776E+18"
+        );
+    }
+
+    #[test]
+    fn test_cast_int_rounds_half_to_even() {
+        assert_eq!(deobfuscate("[int](65.5)"), "66");
+        assert_eq!(deobfuscate("[int]66.5"), "66");
+        assert_eq!(deobfuscate("[int]('3.7')"), "4");
+    }
+
+    #[test]
+    fn test_cast_byte_out_of_range() {
+        assert!(deobfuscate("[byte]255.6").contains("[byte]"));
+    }
+
+    #[test]
+    fn test_cast_char_from_division() {
+        assert_eq!(deobfuscate("[char](130 / 2)"), "\"A\"");
+        assert_eq!(
+            deobfuscate("-join ((130, 132) | % { [char]($_ / 2) })"),
+            "\"AB\""
+        );
+    }
+
+    #[test]
+    fn test_cast_double() {
+        assert_eq!(deobfuscate("[double]'1.5' * 2"), "3");
+        assert_eq!(deobfuscate("[double]7 / 2"), "3.5");
+    }
+
+    #[test]
+    fn test_math_floor_ceiling() {
+        assert_eq!(deobfuscate("[math]::Floor(3.7)"), "3");
+        assert_eq!(deobfuscate("[System.Math]::Ceiling(3.2)"), "4");
+    }
+
+    #[test]
+    fn test_math_round() {
+        assert_eq!(deobfuscate("[math]::Round(2.5)"), "2");
+        assert_eq!(deobfuscate("[math]::Round(2.5, 'AwayFromZero')"), "3");
+        assert_eq!(deobfuscate("[math]::Round(3.14159, 2)"), "3.14");
+    }
+
+    #[test]
+    fn test_math_pow_sqrt() {
+        assert_eq!(deobfuscate("[math]::Pow(2, 10)"), "1024");
+        assert_eq!(deobfuscate("[char][math]::Sqrt(4225)"), "\"A\"");
+    }
+
+    #[test]
+    fn test_math_constant() {
+        assert_eq!(deobfuscate("[math]::PI"), "3.141592653589793");
+    }
+
+    #[test]
+    fn test_math_in_foreach() {
+        assert_eq!(
+            deobfuscate("-join ((131, 133) | % { [char][math]::Floor($_ / 2) })"),
+            "\"AB\""
+        );
+    }
+
+    #[test]
+    fn test_double_comparison() {
+        assert_eq!(deobfuscate("1.5 -gt 1"), "$true");
+        assert_eq!(deobfuscate("2 -eq 2.0"), "$true");
+    }
+}
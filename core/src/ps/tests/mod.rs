@@ -1,18 +1,37 @@
 mod access_tests;
 mod array_tests;
+mod artefact_tests;
+mod behaviour_tests;
 mod bool_tests;
 mod cast_tests;
 mod cmdlets_tests;
+mod cmdline_tests;
 mod comparison_tests;
 mod compression_tests;
+mod convert_tests;
 mod crypto_tests;
+mod digest_tests;
 mod encoding_tests;
 mod foreach_tests;
+mod fuzz_tests;
+mod host_tests;
 mod integer_tests;
 mod join_tests;
 mod linter_tests;
 mod loops_tests;
+mod math_tests;
 mod method_tests;
+mod object_tests;
+mod oracle_tests;
+mod parse_error_tests;
+mod pipeline_tests;
+mod profile_tests;
+mod query_tests;
+mod regex_tests;
+mod ruleset_tests;
+mod scriptblock_tests;
+mod securestring_tests;
+mod static_tests;
 mod string_tests;
 mod switch_tests;
 mod var_tests;
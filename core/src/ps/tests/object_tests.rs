@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests_ps_object {
+    use crate::engine::DeobfuscateEngine;
+    use crate::ps::backend::PowershellBackend;
+
+    fn deobfuscate(input: &str) -> String {
+        let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(input, false).unwrap();
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(&cleaned).unwrap();
+        engine.deobfuscate().unwrap();
+        engine.lint(false).unwrap()
+    }
+
+    #[test]
+    fn test_pscustomobject_property() {
+        assert!(
+            deobfuscate(
+                "$o = [PSCustomObject]@{ K = 'Write-'; V = 'Host' }\nWrite-Host ($o.k + $o.V)"
+            )
+            .ends_with("Write-Host \"Write-Host\"")
+        );
+    }
+
+    #[test]
+    fn test_pscustomobject_unknown_property() {
+        assert!(
+            deobfuscate("$o = [PSCustomObject]@{ K = $env:unknown }\nWrite-Host $o.K")
+                .ends_with("Write-Host $o.K")
+        );
+    }
+
+    #[test]
+    fn test_new_object_psobject() {
+        assert!(
+            deobfuscate(
+                "$o = New-Object PSObject -Property @{ Key = 'mi'+'nusone' }\nWrite-Host $o.Key"
+            )
+            .ends_with("Write-Host \"minusone\"")
+        );
+    }
+
+    #[test]
+    fn test_new_object_psobject_from_hashtable() {
+        assert!(
+            deobfuscate("$h = @{ Key = 'minusone' }\n$o = New-Object -TypeName PSObject -Property $h\nWrite-Host $o.key")
+                .ends_with("Write-Host \"minusone\"")
+        );
+    }
+
+    #[test]
+    fn test_add_member_note_property() {
+        assert!(
+            deobfuscate("$o = New-Object PSObject\nAdd-Member -InputObject $o -NotePropertyName Key -NotePropertyValue 'minusone'\nWrite-Host $o.Key")
+                .ends_with("Write-Host \"minusone\"")
+        );
+    }
+
+    #[test]
+    fn test_add_member_pipeline() {
+        assert!(
+            deobfuscate("$o = [PSCustomObject]@{}\n$o | Add-Member -MemberType NoteProperty -Name Key -Value 'minusone'\nWrite-Host $o.Key")
+                .ends_with("Write-Host \"minusone\"")
+        );
+        assert!(
+            deobfuscate("$o = [PSCustomObject]@{}\n$o | Add-Member NoteProperty Key 'minusone'\nWrite-Host $o.Key")
+                .ends_with("Write-Host \"minusone\"")
+        );
+    }
+
+    #[test]
+    fn test_add_member_note_property_members() {
+        assert!(
+            deobfuscate("$o = [PSCustomObject]@{}\n$o | Add-Member -NotePropertyMembers @{ Key = 'minusone' }\nWrite-Host $o.Key")
+                .ends_with("Write-Host \"minusone\"")
+        );
+    }
+
+    #[test]
+    fn test_add_member_existing_without_force() {
+        assert!(
+            deobfuscate("$o = [PSCustomObject]@{ Key = 'a' }\n$o | Add-Member -NotePropertyName Key -NotePropertyValue 'b'\nWrite-Host $o.Key")
+                .ends_with("Write-Host $o.Key")
+        );
+        assert!(
+            deobfuscate("$o = [PSCustomObject]@{ Key = 'a' }\n$o | Add-Member -NotePropertyName Key -NotePropertyValue 'b' -Force\nWrite-Host $o.Key")
+                .ends_with("Write-Host \"b\"")
+        );
+    }
+
+    #[test]
+    fn test_property_assignment() {
+        assert!(
+            deobfuscate(
+                "$o = [PSCustomObject]@{ Key = 'a' }\n$o.Key = 'mi'+'nusone'\nWrite-Host $o.Key"
+            )
+            .ends_with("Write-Host \"minusone\"")
+        );
+    }
+
+    #[test]
+    fn test_property_assignment_missing_property() {
+        assert!(
+            deobfuscate("$o = [PSCustomObject]@{ Key = 'a' }\n$o.Other = 'b'\nWrite-Host $o.Key")
+                .ends_with("Write-Host $o.Key")
+        );
+    }
+
+    #[test]
+    fn test_select_object_calculated_property() {
+        assert!(
+            deobfuscate("$o = 1 | Select-Object @{n='Key';e={'mi'+'nusone'}}\nWrite-Host $o.Key")
+                .ends_with("Write-Host \"minusone\"")
+        );
+    }
+
+    #[test]
+    fn test_select_object_properties() {
+        assert!(
+            deobfuscate("$o = [PSCustomObject]@{ A = 'mi'; B = 'nusone'; C = 'x' } | Select-Object A, @{ Name = 'B'; Expression = 'B' }\nWrite-Host ($o.A + $o.B)")
+                .ends_with("Write-Host \"minusone\"")
+        );
+    }
+}
@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod tests_ps_oracle {
+    use crate::oracle::{Recorded, check_oracles};
+    use crate::ps::Powershell::{Array, Raw};
+    use crate::ps::Value::{self, Bool, Float, Num, Str};
+    use crate::ps::scriptblock::deobfuscate_layer;
+
+    fn recorded(value: &Value) -> Recorded {
+        match value {
+            Num(n) => Recorded::Int(*n),
+            Float(x) => Recorded::Double(*x),
+            Str(s) => Recorded::Str(s.clone()),
+            Bool(b) => Recorded::Bool(*b),
+        }
+    }
+
+    /// Value of the expression, alone in its layer
+    fn infer(expression: &str) -> Option<Recorded> {
+        match deobfuscate_layer(expression).ok()?.1? {
+            Raw(value) => Some(recorded(&value)),
+            Array(values) => Some(Recorded::Array(values.iter().map(recorded).collect())),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_recorded_pwsh_values() {
+        check_oracles(
+            "pwsh",
+            include_str!("../../../oracles/powershell.json"),
+            infer,
+        );
+    }
+}
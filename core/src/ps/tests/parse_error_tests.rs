@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests_ps_parse_error {
+    use crate::engine::DeobfuscateEngine;
+    use crate::error::{Error, MinusOneErrorKind, ParseError};
+    use crate::ps::backend::PowershellBackend;
+
+    fn deobfuscate(input: &str) -> (String, Vec<ParseError>) {
+        let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(input, true).unwrap();
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(&cleaned).unwrap();
+        engine.deobfuscate().unwrap();
+        (engine.lint(true).unwrap(), engine.parse_errors())
+    }
+
+    #[test]
+    fn test_no_parse_error() {
+        let (output, errors) = deobfuscate("$a = \"he\" + \"llo\"");
+        assert_eq!(output, "$a = \"hello\"");
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_truncated_source() {
+        let (output, errors) = deobfuscate("$a = \"mi\" + \"nus\"\nif ($a -eq ");
+        assert!(output.starts_with("$a = \"minus\""));
+        assert!(!errors.is_empty());
+        assert!(errors.iter().all(|error| error.start >= 18));
+    }
+
+    #[test]
+    fn test_error_region_kept_as_is() {
+        let (output, errors) = deobfuscate("$a = \"mi\" + \"nus\"\n$b = @(1, ))");
+        assert!(output.starts_with("$a = \"minus\""));
+        assert!(!errors.is_empty());
+        for error in errors {
+            let region = &"$a = \"mi\" + \"nus\"\n$b = @(1, ))"[error.start..error.end];
+            assert!(output.contains(region));
+        }
+    }
+
+    #[test]
+    fn test_nothing_to_process() {
+        let error = DeobfuscateEngine::<PowershellBackend>::from_powershell(")))")
+            .err()
+            .unwrap();
+        let Error::MinusOneError(error) = error else {
+            panic!("a parsing error was expected");
+        };
+        assert_eq!(error.kind(), MinusOneErrorKind::Parsing);
+    }
+}
@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod tests_ps_pipeline {
+    use crate::engine::DeobfuscateEngine;
+    use crate::ps::backend::PowershellBackend;
+
+    fn deobfuscate(input: &str) -> String {
+        let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(input, false).unwrap();
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(&cleaned).unwrap();
+        engine.deobfuscate().unwrap();
+        engine.lint(false).unwrap()
+    }
+
+    #[test]
+    fn test_where_object_filter() {
+        assert_eq!(deobfuscate("-join (1..6 | ? { $_ % 2 })"), "\"135\"");
+    }
+
+    #[test]
+    fn test_where_object_comparison() {
+        assert_eq!(
+            deobfuscate("-join ('a', 'x', 'b', 'x' | Where-Object { $_ -ne 'x' })"),
+            "\"ab\""
+        );
+    }
+
+    #[test]
+    fn test_where_object_then_foreach() {
+        assert_eq!(
+            deobfuscate("-join (0x61, 0x00, 0x62 | where { $_ } | % { [char]$_ })"),
+            "\"ab\""
+        );
+    }
+
+    #[test]
+    fn test_select_object_first_last_skip() {
+        assert_eq!(deobfuscate("-join (1..5 | select -First 2)"), "\"12\"");
+        assert_eq!(
+            deobfuscate("-join (1..5 | Select-Object -Last 2)"),
+            "\"45\""
+        );
+        assert_eq!(
+            deobfuscate("-join (1..5 | Select-Object -Skip 1 -First 2)"),
+            "\"23\""
+        );
+        assert_eq!(
+            deobfuscate("-join (1..5 | Select-Object -SkipLast 3)"),
+            "\"12\""
+        );
+    }
+
+    #[test]
+    fn test_select_object_index() {
+        assert_eq!(
+            deobfuscate("-join ('w', 'x', 'h', 'o' | Select-Object -Index 3, 0)"),
+            "\"wo\""
+        );
+    }
+
+    #[test]
+    fn test_select_object_unique() {
+        assert_eq!(
+            deobfuscate("-join ('a', 'b', 'a', 'A' | Select-Object -Unique)"),
+            "\"abA\""
+        );
+    }
+
+    #[test]
+    fn test_sort_object() {
+        assert_eq!(deobfuscate("-join (3, 1, 2 | Sort-Object)"), "\"123\"");
+        assert_eq!(
+            deobfuscate("-join ('b', 'C', 'a' | sort -Descending)"),
+            "\"Cba\""
+        );
+    }
+
+    #[test]
+    fn test_sort_object_unique() {
+        assert_eq!(
+            deobfuscate("-join ('b', 'a', 'B', 'a' | Sort-Object -Unique)"),
+            "\"ab\""
+        );
+    }
+
+    #[test]
+    fn test_sort_object_key_script_block() {
+        assert_eq!(
+            deobfuscate("-join ('ccc', 'a', 'bb' | Sort-Object { $_.Length })"),
+            "\"abbccc\""
+        );
+    }
+
+    #[test]
+    fn test_sort_object_property() {
+        assert_eq!(
+            deobfuscate("-join ('ccc', 'a', 'bb' | Sort-Object Length -Descending)"),
+            "\"cccbba\""
+        );
+    }
+
+    #[test]
+    fn test_sort_object_random_is_unknown() {
+        assert!(
+            deobfuscate("-join ('a', 'b', 'c' | Sort-Object { Get-Random })")
+                .contains("Get-Random")
+        );
+    }
+
+    #[test]
+    fn test_get_unique() {
+        assert_eq!(deobfuscate("-join (1, 1, 2, 2, 1 | Get-Unique)"), "\"121\"");
+    }
+
+    #[test]
+    fn test_out_string() {
+        assert_eq!(deobfuscate("'a', 'b' | Out-String"), "\"a`r`nb`r`n\"");
+    }
+
+    #[test]
+    fn test_wildcarded_stage() {
+        assert_eq!(deobfuscate("-join (3, 1, 2 | So*-Object)"), "\"123\"");
+    }
+
+    #[test]
+    fn test_pipeline_assigned_and_reversed() {
+        assert!(
+            deobfuscate("$a = 'c', 'b', 'a' | sort\n[array]::Reverse($a)\nWrite-Host (-join $a)")
+                .ends_with("Write-Host \"cba\"")
+        );
+    }
+}
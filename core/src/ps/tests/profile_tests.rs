@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests_ps_profile {
+    use crate::engine::DeobfuscateEngine;
+    use crate::profile::{Limits, RuleProfile, limits, with_limits};
+    use crate::ps::backend::PowershellBackend;
+    use crate::rule::RuleSetBuilderType;
+
+    fn deobfuscate_with(input: &str, profile: RuleProfile, include: &[&str]) -> String {
+        let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(input, false).unwrap();
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_source(&cleaned).unwrap();
+        engine
+            .deobfuscate_with_profile(profile, include, &[])
+            .unwrap();
+        engine.lint(false).unwrap()
+    }
+
+    #[test]
+    fn test_profile_names() {
+        assert_eq!(
+            "Aggressive".parse::<RuleProfile>(),
+            Ok(RuleProfile::Aggressive)
+        );
+        assert!("reckless".parse::<RuleProfile>().is_err());
+        assert_eq!(RuleProfile::default(), RuleProfile::Default);
+    }
+
+    #[test]
+    fn test_default_profile_matches_no_profile() {
+        let rules = DeobfuscateEngine::<PowershellBackend>::language_rules();
+        let RuleSetBuilderType::WithRules(names) = RuleProfile::Default.rule_set(&rules, &[], &[])
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            names,
+            rules.iter().map(|rule| rule.name).collect::<Vec<_>>()
+        );
+
+        let src = "$sb = { -join (1..6 | ? { $_ % 2 }) }\n& $sb";
+        let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(src, false).unwrap();
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_source(&cleaned).unwrap();
+        engine.deobfuscate().unwrap();
+        assert_eq!(
+            deobfuscate_with(src, RuleProfile::Default, &[]),
+            engine.lint(false).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_safe_profile_does_not_evaluate_pipelines() {
+        let src = "-join (1..6 | ? { $_ % 2 })";
+        assert_eq!(deobfuscate_with(src, RuleProfile::Default, &[]), "\"135\"");
+        assert_ne!(deobfuscate_with(src, RuleProfile::Safe, &[]), "\"135\"");
+        assert_eq!(
+            deobfuscate_with(src, RuleProfile::Safe, &["PipelineStage"]),
+            "\"135\""
+        );
+    }
+
+    #[test]
+    fn test_safe_profile_does_not_evaluate_script_block_layers() {
+        let src = "$sb = { -join (1..6 | ? { $_ % 2 }) }";
+        assert!(deobfuscate_with(src, RuleProfile::Default, &[]).contains("\"135\""));
+        let output = deobfuscate_with(src, RuleProfile::Safe, &[]);
+        assert!(!output.contains("\"135\""));
+        assert!(output.contains("$_ % 2"));
+    }
+
+    #[test]
+    fn test_safe_profile_does_not_invoke_script_blocks() {
+        let src = "& {'mi'+'nusone'}";
+        assert_eq!(
+            deobfuscate_with(src, RuleProfile::Default, &[]),
+            "\"minusone\""
+        );
+        assert!(deobfuscate_with(src, RuleProfile::Safe, &[]).starts_with("&"));
+        assert_eq!(
+            deobfuscate_with(src, RuleProfile::Safe, &["ScriptBlockInvoke"]),
+            "\"minusone\""
+        );
+    }
+
+    #[test]
+    fn test_pipeline_item_budget() {
+        let _limits = with_limits(Limits {
+            pipeline_items: 4,
+            ..Limits::DEFAULT
+        });
+        assert_ne!(
+            deobfuscate_with("-join (1..6 | ? { $_ % 2 })", RuleProfile::Default, &[]),
+            "\"135\""
+        );
+    }
+
+    #[test]
+    fn test_profile_limits_are_restored() {
+        {
+            let mut engine = DeobfuscateEngine::<PowershellBackend>::from_source("1..6").unwrap();
+            engine
+                .deobfuscate_with_profile(RuleProfile::Aggressive, &[], &[])
+                .unwrap();
+        }
+        assert_eq!(limits(), Limits::DEFAULT);
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests_ps_query {
+    use crate::ps::Powershell;
+    use crate::ps::build_powershell_tree;
+    use crate::ps::forward::Forward;
+    use crate::ps::integer::ParseInt;
+    use crate::ps::linter::Linter;
+    use crate::ps::string::ParseString;
+    use crate::ps::typing::ParseType;
+    use crate::query::parse_query_rules;
+
+    const RULES: &str = r#"
+# .NET 5+ only, not handled by the built-in rules
+rule FromHexString
+match ((invokation_expression
+         (type_literal) @type
+         "::"
+         (member_name) @method
+         (argument_list (argument_expression_list . (_) @arg .)))
+       (#match? @method "^(?i)fromhexstring$"))
+where or(ieq(@type, "convert"), ieq(@type, "system.convert"))
+emit bytes(hex(@arg))
+
+rule ReverseString
+match ((invokation_expression
+         (_) @str
+         "."
+         (member_name) @method
+         (argument_list))
+       (#match? @method "^(?i)reverse$"))
+where is(@str, "str")
+emit reverse(@str)
+"#;
+
+    fn deobfuscate(input: &str) -> String {
+        let rules = parse_query_rules::<Powershell>(RULES).unwrap();
+        let mut tree = build_powershell_tree(input).unwrap();
+        tree.apply_mut(&mut (
+            ParseInt::default(),
+            ParseString::default(),
+            ParseType::default(),
+            Forward::default(),
+            &rules[0],
+            &rules[1],
+        ))
+        .unwrap();
+
+        let mut linter = Linter::default();
+        tree.apply(&mut linter).unwrap();
+        linter.output
+    }
+
+    #[test]
+    fn test_query_rule_type_condition() {
+        assert_eq!(
+            deobfuscate("[System.Convert]::FromHexString('deadbeef')"),
+            "@(222, 173, 190, 239)"
+        );
+    }
+
+    #[test]
+    fn test_query_rule_wrong_type() {
+        assert_eq!(
+            deobfuscate("[Foo]::FromHexString('deadbeef')"),
+            "[Foo]::FromHexString(\"deadbeef\")"
+        );
+    }
+
+    #[test]
+    fn test_query_rule_case_insensitive_predicate() {
+        assert_eq!(deobfuscate("'olleh'.ReVeRsE()"), "\"hello\"");
+    }
+}
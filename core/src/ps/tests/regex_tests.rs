@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod tests_ps_regex {
+    use crate::engine::DeobfuscateEngine;
+    use crate::ps::backend::PowershellBackend;
+
+    fn deobfuscate(input: &str) -> String {
+        let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(input, false).unwrap();
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(&cleaned).unwrap();
+        engine.deobfuscate().unwrap();
+        engine.lint(false).unwrap()
+    }
+
+    #[test]
+    fn test_regex_replace_numbered_groups() {
+        assert_eq!(
+            deobfuscate("[regex]::Replace('badc', '(.)(.)', '$2$1')"),
+            "\"abcd\""
+        );
+    }
+
+    #[test]
+    fn test_regex_replace_named_groups() {
+        assert_eq!(
+            deobfuscate(
+                "[regex]::Replace('john smith', '(?<first>\\w+) (?<last>\\w+)', '${last} ${first}')"
+            ),
+            "\"smith john\""
+        );
+    }
+
+    #[test]
+    fn test_regex_replace_with_options() {
+        assert_eq!(
+            deobfuscate(
+                "[regex]::Replace('aAa', 'a', 'b', [Text.RegularExpressions.RegexOptions]::IgnoreCase)"
+            ),
+            "\"bbb\""
+        );
+    }
+
+    #[test]
+    fn test_regex_inline_options() {
+        assert_eq!(deobfuscate("[regex]::IsMatch('ABC', '(?i)abc')"), "$true");
+        assert_eq!(deobfuscate("[regex]::IsMatch('ABC', 'abc')"), "$false");
+    }
+
+    #[test]
+    fn test_regex_matches_right_to_left_foreach() {
+        assert_eq!(
+            deobfuscate("-join ([regex]::Matches('olleh', '.', 'RightToLeft') | % { $_.Value })"),
+            "\"hello\""
+        );
+    }
+
+    #[test]
+    fn test_regex_matches_right_to_left_join() {
+        assert_eq!(
+            deobfuscate("-join [regex]::Matches('olleh', '.', 'RightToLeft')"),
+            "\"hello\""
+        );
+    }
+
+    #[test]
+    fn test_regex_match_right_to_left() {
+        assert_eq!(
+            deobfuscate("[regex]::Match('1 22 333', '\\d+', 'RightToLeft').Value"),
+            "\"333\""
+        );
+    }
+
+    #[test]
+    fn test_regex_matches_hex_decoding() {
+        assert_eq!(
+            deobfuscate(
+                "-join ([regex]::Matches('414243', '..') | % { [char][convert]::ToInt32($_.Value, 16) })"
+            ),
+            "\"ABC\""
+        );
+    }
+
+    #[test]
+    fn test_regex_matches_groups_in_foreach() {
+        assert_eq!(
+            deobfuscate(
+                "-join ([regex]::Matches('a1b2', '[a-z](\\d)') | % { $_.Groups[1].Value })"
+            ),
+            "\"12\""
+        );
+    }
+
+    #[test]
+    fn test_regex_matches_count() {
+        assert_eq!(deobfuscate("[regex]::Matches('aaa', 'a').Count"), "3");
+    }
+
+    #[test]
+    fn test_regex_split() {
+        assert_eq!(
+            deobfuscate("[regex]::Split('a1b22c', '\\d+') -join ','"),
+            "\"a,b,c\""
+        );
+    }
+
+    #[test]
+    fn test_regex_escape() {
+        assert_eq!(deobfuscate("[regex]::Escape('a.b*c')"), "\"a\\.b\\*c\"");
+    }
+
+    #[test]
+    fn test_regex_object() {
+        assert!(
+            deobfuscate("$r = [regex]'(\\d)'\nWrite-Host $r.Replace('a1', '<$1>')")
+                .ends_with("Write-Host \"a<1>\"")
+        );
+    }
+
+    #[test]
+    fn test_regex_new_object_right_to_left() {
+        assert!(
+            deobfuscate(
+                "$r = New-Object Text.RegularExpressions.Regex '\\d+', 'RightToLeft'\nWrite-Host $r.Match('1 22 333').Value"
+            )
+            .ends_with("Write-Host \"333\"")
+        );
+    }
+
+    #[test]
+    fn test_regex_backreference_not_folded() {
+        assert!(deobfuscate("[regex]::Replace('aa', '(a)\\1', 'b')").contains("Replace"));
+    }
+
+    #[test]
+    fn test_match_operator_populates_matches() {
+        assert!(
+            deobfuscate("'id-42' -match '(?<id>\\d+)'\nWrite-Host $Matches[0] $Matches.id")
+                .ends_with("Write-Host \"42\" \"42\"")
+        );
+    }
+
+    #[test]
+    fn test_match_operator_array_filter() {
+        assert_eq!(
+            deobfuscate("(('abc', 'xyz', 'abd') -match '^ab') -join ','"),
+            "\"abc,abd\""
+        );
+    }
+
+    #[test]
+    fn test_replace_operator_named_groups() {
+        assert_eq!(
+            deobfuscate("'hello world' -replace '(?<a>\\w+) (?<b>\\w+)', '${b} ${a}'"),
+            "\"world hello\""
+        );
+    }
+
+    #[test]
+    fn test_replace_operator_whole_match() {
+        assert_eq!(deobfuscate("'abc' -replace 'b', '[$&]'"), "\"a[b]c\"");
+    }
+
+    #[test]
+    fn test_replace_operator_without_substitution() {
+        assert_eq!(deobfuscate("'a1b2c3' -replace '\\d'"), "\"abc\"");
+    }
+}
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests_ps_ruleset {
+    use crate::engine::DeobfuscateEngine;
+    use crate::ps::backend::PowershellBackend;
+    use crate::rule::{RuleCategory, RuleSafety};
+    use std::collections::HashSet;
+
+    fn deobfuscate_without(input: &str, rules: Vec<&str>) -> String {
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_source(input).unwrap();
+        engine.deobfuscate_without_custom_ruleset(rules).unwrap();
+        engine.lint(false).unwrap()
+    }
+
+    #[test]
+    fn test_rules_are_described() {
+        let rules = DeobfuscateEngine::<PowershellBackend>::language_rules();
+        let names: HashSet<&str> = rules.iter().map(|rule| rule.name).collect();
+        assert_eq!(names.len(), rules.len());
+        assert!(rules.iter().all(|rule| !rule.description.is_empty()));
+        assert!(rules.iter().all(|rule| !rule.node_kinds.is_empty()));
+        assert!(rules.iter().all(|rule| rule.default_enabled));
+    }
+
+    #[test]
+    fn test_rule_metadata() {
+        let rules = DeobfuscateEngine::<PowershellBackend>::language_rules();
+        let rule = |name| rules.iter().find(|rule| rule.name == name).unwrap();
+        assert_eq!(rule("DecodeBase64").category, RuleCategory::Encoding);
+        assert_eq!(rule("AddInt").safety, RuleSafety::Pure);
+        assert!(rule("ScriptBlockInvoke").executes_code());
+        assert!(rule("Switch").node_kinds.contains(&"switch_statement"));
+    }
+
+    #[test]
+    fn test_skip_rule_category() {
+        let input = "[Text.Encoding]::UTF8.GetString([Convert]::FromBase64String('bWludXNvbmU='))";
+        assert_eq!(deobfuscate_without(input, vec![]), "\"minusone\"");
+        let output = deobfuscate_without(input, vec!["@encoding"]);
+        assert!(output.contains("FromBase64String"));
+        assert!(output.contains("GetString"));
+    }
+
+    #[test]
+    fn test_unknown_rule_category_is_ignored() {
+        assert_eq!(deobfuscate_without("1+2", vec!["@unknown"]), "3");
+    }
+}
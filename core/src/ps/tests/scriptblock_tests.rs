@@ -0,0 +1,191 @@
+#[cfg(test)]
+mod tests_ps_scriptblock {
+    use crate::engine::DeobfuscateEngine;
+    use crate::profile::{Limits, with_limits};
+    use crate::ps::Powershell::Raw;
+    use crate::ps::Value::Str;
+    use crate::ps::backend::PowershellBackend;
+    use crate::ps::host::HostProfile;
+    use crate::ps::scriptblock::deobfuscate_layer;
+    use crate::rule::RuleSetBuilderType;
+
+    fn deobfuscate(input: &str) -> String {
+        let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(input, false).unwrap();
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(&cleaned).unwrap();
+        engine.deobfuscate().unwrap();
+        engine.lint(false).unwrap()
+    }
+
+    fn deobfuscate_with(input: &str, ctx: RuleSetBuilderType, profile: &HostProfile) -> String {
+        let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(input, false).unwrap();
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(&cleaned).unwrap();
+        engine
+            .deobfuscate_with_host_profile(ctx, &[], profile)
+            .unwrap();
+        engine.lint(false).unwrap()
+    }
+
+    #[test]
+    fn test_deobfuscate_layer() {
+        let (layer, value) = deobfuscate_layer("Write-Host ('mi'+'nusone')").unwrap();
+        assert_eq!(layer, "Write-Host \"minusone\"");
+        assert_eq!(value, None);
+
+        let (layer, value) = deobfuscate_layer("'mi'+'nusone'").unwrap();
+        assert_eq!(layer, "\"minusone\"");
+        assert_eq!(value, Some(Raw(Str("minusone".to_string()))));
+    }
+
+    #[test]
+    fn test_scriptblock_create() {
+        assert_eq!(
+            deobfuscate("& ([ScriptBlock]::Create('Write-Host '+'minusone'))"),
+            "& { Write-Host minusone }"
+        );
+    }
+
+    #[test]
+    fn test_scriptblock_create_full_typename() {
+        assert_eq!(
+            deobfuscate(
+                "& ([System.Management.Automation.ScriptBlock]::Create(\"Write-Host ('mi'+'nusone')\"))"
+            ),
+            "& { Write-Host \"minusone\" }"
+        );
+    }
+
+    #[test]
+    fn test_new_script_block() {
+        assert_eq!(
+            deobfuscate(
+                ". ($ExecutionContext.InvokeCommand.NewScriptBlock('Write-Host '+'minusone'))"
+            ),
+            ". { Write-Host minusone }"
+        );
+    }
+
+    #[test]
+    fn test_invoke_literal() {
+        assert_eq!(deobfuscate("& {'mi'+'nusone'}"), "\"minusone\"");
+    }
+
+    #[test]
+    fn test_invoke_variable() {
+        assert!(
+            deobfuscate("$sb = {'mi'+'nusone'}\n& $sb | Write-Host")
+                .ends_with("\"minusone\" | Write-Host")
+        );
+    }
+
+    #[test]
+    fn test_invoke_variable_inlined() {
+        assert!(
+            deobfuscate("$sb = [ScriptBlock]::Create(\"Write-Host ('mi'+'nusone')\")\n& $sb")
+                .ends_with("& { Write-Host \"minusone\" }")
+        );
+    }
+
+    #[test]
+    fn test_invoke_method() {
+        assert_eq!(
+            deobfuscate("[ScriptBlock]::Create(\"'mi'+'nusone'\").Invoke()"),
+            "\"minusone\""
+        );
+    }
+
+    #[test]
+    fn test_invoke_method_with_arguments() {
+        assert!(deobfuscate("{'mi'+'nusone'}.Invoke(1)").ends_with(".Invoke(1)"));
+    }
+
+    #[test]
+    fn test_invoke_command() {
+        assert_eq!(
+            deobfuscate("Invoke-Command -ScriptBlock {'mi'+'nusone'}"),
+            "\"minusone\""
+        );
+        assert_eq!(deobfuscate("icm {'mi'+'nusone'}"), "\"minusone\"");
+    }
+
+    #[test]
+    fn test_invoke_command_with_arguments() {
+        assert!(
+            deobfuscate("Invoke-Command {'mi'+'nusone'} -ArgumentList 1")
+                .starts_with("Invoke-Command")
+        );
+    }
+
+    #[test]
+    fn test_invoke_script() {
+        assert_eq!(
+            deobfuscate("$ExecutionContext.InvokeCommand.InvokeScript(\"'mi'+'nusone'\")"),
+            "\"minusone\""
+        );
+        assert!(
+            deobfuscate("$ExecutionContext.InvokeCommand.InvokeScript('Write-Host '+'minusone')")
+                .ends_with("InvokeScript({ Write-Host minusone })")
+        );
+    }
+
+    #[test]
+    fn test_nested_layers() {
+        assert_eq!(
+            deobfuscate(
+                "& ([ScriptBlock]::Create(\"& ([ScriptBlock]::Create('Write-Host '+'minusone'))\"))"
+            ),
+            "& { & { Write-Host minusone } }"
+        );
+    }
+
+    #[test]
+    fn test_layer_depth_budget() {
+        let _limits = with_limits(Limits {
+            layer_depth: 1,
+            ..Limits::DEFAULT
+        });
+        let output = deobfuscate(
+            "& ([ScriptBlock]::Create(\"& ([ScriptBlock]::Create('Write-Host '+'minusone'))\"))",
+        );
+        assert!(output.starts_with("& { & ([ScriptBlock]::Create("));
+    }
+
+    #[test]
+    fn test_deeply_nested_literals() {
+        let source = format!("& {}'minusone'{}", "{".repeat(64), "}".repeat(64));
+        assert!(deobfuscate(&source).contains("minusone"));
+    }
+
+    #[test]
+    fn test_layer_with_skipped_rules() {
+        let source = "[ScriptBlock]::Create(\"'mi'+'nusone'\").Invoke()";
+        assert_eq!(
+            deobfuscate_with(
+                source,
+                RuleSetBuilderType::WithoutRules(vec![]),
+                &HostProfile::default()
+            ),
+            "\"minusone\""
+        );
+        assert_ne!(
+            deobfuscate_with(
+                source,
+                RuleSetBuilderType::WithoutRules(vec!["ConcatString"]),
+                &HostProfile::default()
+            ),
+            "\"minusone\""
+        );
+    }
+
+    #[test]
+    fn test_layer_with_host_profile() {
+        let profile = HostProfile::default().with_user_name("bob");
+        assert_eq!(
+            deobfuscate_with(
+                "[ScriptBlock]::Create('$env:USERNAME').Invoke()",
+                RuleSetBuilderType::WithoutRules(vec![]),
+                &profile
+            ),
+            "\"bob\""
+        );
+    }
+}
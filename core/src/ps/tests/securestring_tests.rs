@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod tests_ps_securestring {
+    use crate::engine::DeobfuscateEngine;
+    use crate::error::MinusOneErrorKind;
+    use crate::ps::backend::PowershellBackend;
+    use crate::ps::securestring::decrypt_secure_string;
+
+    // "Write-Host minusone" encrypted with -Key (1..32)
+    const PAYLOAD_KEY_32: &str = "76492d1116743f0423413b16050a5345MgB8AEUAQgBFAFMARQB4AFEAVgBGAGgAYwBZAEcAUgBvAGIASABCADAAZQBIAHcAPQA9AHwAMgA4ADIAOQA2AGMAYwA1AGEAOABjAGMAMQA4ADYAOABiADkAYgA0ADkAYgAwAGQANQBkADQAYQBmADYAMQA3AGYAYgAwAGMAZQA0ADgAYwAxAGQANwBiAGEAMQA0ADMANQBjADcAZgBkAGUAMQA4ADIAMwBlADkAZgA4AGEAYgAxAGUAYwAyADkANAA0ADEAZAA3AGQAYwBkADkAMwA2ADMANQAzAGYAYwA4AGYAMABhADEAMQAyADYAMwA2ADQA";
+
+    // "secret" encrypted with -SecureKey (ConvertTo-SecureString '0123456789abcdef' -AsPlainText -Force)
+    const PAYLOAD_SECURE_KEY: &str = "76492d1116743f0423413b16050a5345MgB8AEUAQgBFAFMARQB4AFEAVgBGAGgAYwBZAEcAUgBvAGIASABCADAAZQBIAHcAPQA9AHwAMQBhAGQAMgAxADUANQAwADMANwA1ADUANgA0ADkAYQAzADUAMABkADEAMwA4ADgAZQBhADAANQA5AGMAZgBhAA==";
+
+    fn deobfuscate(input: &str) -> String {
+        let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(input, false).unwrap();
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_powershell(&cleaned).unwrap();
+        engine.deobfuscate().unwrap();
+        engine.lint(false).unwrap()
+    }
+
+    #[test]
+    fn test_decrypt_secure_string() {
+        let key: Vec<u8> = (1..=32).collect();
+        assert_eq!(
+            decrypt_secure_string(PAYLOAD_KEY_32, &key).unwrap(),
+            "Write-Host minusone"
+        );
+    }
+
+    #[test]
+    fn test_decrypt_secure_string_wrong_key() {
+        let key: Vec<u8> = (2..=33).collect();
+        assert_eq!(
+            decrypt_secure_string(PAYLOAD_KEY_32, &key)
+                .unwrap_err()
+                .kind(),
+            MinusOneErrorKind::Decryption
+        );
+    }
+
+    #[test]
+    fn test_decrypt_secure_string_without_header() {
+        let key: Vec<u8> = (1..=32).collect();
+        assert!(decrypt_secure_string("01000000d08c9ddf0115d1118c7a00c04fc297eb", &key).is_err());
+    }
+
+    #[test]
+    fn test_marshal_bstr() {
+        let src = format!(
+            "$s = ConvertTo-SecureString '{}' -Key (1..32)\n[Runtime.InteropServices.Marshal]::PtrToStringAuto([Runtime.InteropServices.Marshal]::SecureStringToBSTR($s)) | iex",
+            PAYLOAD_KEY_32
+        );
+        assert!(deobfuscate(&src).ends_with("\"Write-Host minusone\" | Invoke-Expression"));
+    }
+
+    #[test]
+    fn test_abbreviated_key_parameter() {
+        let src = format!(
+            "$s = ConvertTo-SecureString -k (1..32) -str '{}'\n[Runtime.InteropServices.Marshal]::PtrToStringBSTR([Runtime.InteropServices.Marshal]::SecureStringToBSTR($s))",
+            PAYLOAD_KEY_32
+        );
+        assert!(deobfuscate(&src).ends_with("\"Write-Host minusone\""));
+    }
+
+    #[test]
+    fn test_secure_key() {
+        let src = format!(
+            "$k = ConvertTo-SecureString '0123456789abcdef' -AsPlainText -Force\n$s = ConvertTo-SecureString '{}' -SecureKey $k\n[Runtime.InteropServices.Marshal]::PtrToStringUni([Runtime.InteropServices.Marshal]::SecureStringToGlobalAllocUnicode($s))",
+            PAYLOAD_SECURE_KEY
+        );
+        assert!(deobfuscate(&src).ends_with("\"secret\""));
+    }
+
+    #[test]
+    fn test_pscredential_network_credential() {
+        let src = format!(
+            "$s = ConvertTo-SecureString '{}' -Key (1..32)\n(New-Object PSCredential 'x',$s).GetNetworkCredential().Password",
+            PAYLOAD_KEY_32
+        );
+        assert!(deobfuscate(&src).ends_with("\"Write-Host minusone\""));
+    }
+
+    #[test]
+    fn test_pscredential_constructor() {
+        assert_eq!(
+            deobfuscate(
+                "[System.Management.Automation.PSCredential]::new('x', (ConvertTo-SecureString 'calc' -AsPlainText -Force)).GetNetworkCredential().Password"
+            ),
+            "\"calc\""
+        );
+    }
+
+    #[test]
+    fn test_network_credential() {
+        assert_eq!(
+            deobfuscate(
+                "(New-Object -TypeName Net.NetworkCredential -ArgumentList ('', (ConvertTo-SecureString 'calc' -AsPlainText -Force))).Password"
+            ),
+            "\"calc\""
+        );
+    }
+
+    #[test]
+    fn test_pscredential_password_is_secure() {
+        assert_eq!(
+            deobfuscate(
+                "[Runtime.InteropServices.Marshal]::PtrToStringAuto([Runtime.InteropServices.Marshal]::SecureStringToBSTR([PSCredential]::new('x', (ConvertTo-SecureString 'calc' -AsPlainText -Force)).Password))"
+            ),
+            "\"calc\""
+        );
+    }
+
+    #[test]
+    fn test_convertfrom_securestring_as_plain_text() {
+        assert_eq!(
+            deobfuscate(
+                "ConvertFrom-SecureString -SecureString (ConvertTo-SecureString 'calc' -AsPlainText -Force) -AsPlainText"
+            ),
+            "\"calc\""
+        );
+    }
+
+    #[test]
+    fn test_wrong_key_is_kept() {
+        let src = format!(
+            "$s = ConvertTo-SecureString '{}' -Key (2..33)\n[Runtime.InteropServices.Marshal]::PtrToStringAuto([Runtime.InteropServices.Marshal]::SecureStringToBSTR($s))",
+            PAYLOAD_KEY_32
+        );
+        assert!(deobfuscate(&src).ends_with("::SecureStringToBSTR($s))"));
+    }
+}
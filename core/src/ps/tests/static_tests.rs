@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests_ps_static {
+    use crate::detect::Technique;
+    use crate::detect::Technique::*;
+    use crate::ps::r#static::analyze_powershell;
+
+    fn techniques(input: &str) -> Vec<(Technique, usize)> {
+        analyze_powershell(input).unwrap().techniques()
+    }
+
+    #[test]
+    fn test_static_concat() {
+        let report = analyze_powershell("$a = 'Write-Ho'+\"st\"").unwrap();
+        assert_eq!(report.techniques(), vec![(StringConcat, 1)]);
+        assert_eq!(report.findings[0].start, 5);
+        assert_eq!(report.findings[0].end, 20);
+    }
+
+    #[test]
+    fn test_static_concat_outermost_only() {
+        assert_eq!(
+            techniques("$a = 'a' + ('b' + 'c') + 'd'"),
+            vec![(StringConcat, 1)]
+        );
+    }
+
+    #[test]
+    fn test_dynamic_concat_is_not_static() {
+        assert_eq!(techniques("$a = 'a' + $b + 'c'"), vec![]);
+        assert_eq!(techniques("$a = 1 + 2"), vec![]);
+    }
+
+    #[test]
+    fn test_char_code_array() {
+        assert_eq!(
+            techniques("$c = @(72, 0x65, 108, 108, 111)"),
+            vec![(CharCode, 1)]
+        );
+        assert_eq!(techniques("$c = 1, 2"), vec![]);
+    }
+
+    #[test]
+    fn test_char_cast_concat() {
+        assert_eq!(
+            techniques("$c = [char]72 + [char]0x69"),
+            vec![(StringConcat, 1), (CharCode, 2)]
+        );
+    }
+
+    #[test]
+    fn test_format_string() {
+        assert_eq!(
+            techniques("$f = \"{1}{0}\" -f 'lo', 'hel'"),
+            vec![(FormatString, 1)]
+        );
+        assert_eq!(techniques("$f = \"{0}\" -f $name"), vec![]);
+    }
+
+    #[test]
+    fn test_tick_insertion() {
+        assert_eq!(
+            techniques("I`nv`oke-Expression $a"),
+            vec![(TickInsertion, 1), (DynamicInvocation, 1)]
+        );
+        assert_eq!(techniques("$a = \"he`llo\""), vec![(TickInsertion, 1)]);
+        assert_eq!(techniques("$a = \"hello`n`$world\""), vec![]);
+    }
+
+    #[test]
+    fn test_random_case() {
+        assert_eq!(techniques("wRiTe-HoSt 1"), vec![(RandomCase, 1)]);
+        assert_eq!(techniques("write-host 1"), vec![]);
+        assert_eq!(techniques("Write-Host 1"), vec![]);
+    }
+
+    #[test]
+    fn test_wildcard_cmdlet() {
+        assert_eq!(
+            techniques("g*t-ch*ditem -Path C:\\foo"),
+            vec![(WildcardCmdlet, 1)]
+        );
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(
+            techniques("$b = [Convert]::FromBase64String('aGVsbG8=')"),
+            vec![(Base64, 1)]
+        );
+        assert_eq!(
+            techniques("powershell -nop -enc SQBFAFgA"),
+            vec![(Base64, 1)]
+        );
+        assert_eq!(techniques("powershell -ex bypass"), vec![]);
+    }
+
+    #[test]
+    fn test_layers() {
+        assert_eq!(
+            techniques(
+                "$aes = [Security.Cryptography.AesManaged]::new()\n$d = $aes.CreateDecryptor()\n$g = New-Object IO.Compression.GzipStream($s, [IO.Compression.CompressionMode]::Decompress)"
+            ),
+            vec![(Encryption, 2), (Compression, 1)]
+        );
+    }
+
+    #[test]
+    fn test_dynamic_invocation() {
+        assert_eq!(
+            techniques("&('ie'+'x') $a"),
+            vec![(StringConcat, 1), (DynamicInvocation, 1)]
+        );
+        assert_eq!(
+            techniques("[ScriptBlock]::Create($a).Invoke()"),
+            vec![(DynamicInvocation, 1)]
+        );
+        assert_eq!(techniques("& $path"), vec![]);
+    }
+
+    #[test]
+    fn test_clean_script() {
+        let report = analyze_powershell("$a = Get-ChildItem -Path C:\\foo\nWrite-Host $a").unwrap();
+        assert_eq!(report.findings, vec![]);
+        assert_eq!(report.score(), 0);
+    }
+
+    #[test]
+    fn test_score() {
+        let report = analyze_powershell(
+            "I`Nv`oKe-ExPrEsSiOn ('Write-Ho'+'st ' + (\"{1}{0}\" -f 'lo','hel'))",
+        )
+        .unwrap();
+        assert_eq!(report.score(), 60);
+    }
+}
@@ -356,4 +356,16 @@ mod tests_ps_string {
             "\"hello world\""
         );
     }
+
+    #[test]
+    fn test_format_operator_invoked_command() {
+        assert_eq!(
+            deobfuscate(".(\"{1}{0}\" -f 'ex','i') 'Write-Host 1'"),
+            ". Invoke-Expression 'Write-Host 1'"
+        );
+        assert_eq!(
+            deobfuscate("&(\"{1}{0}\" -f 'ex','i') 'Write-Host 1'"),
+            "Invoke-Expression 'Write-Host 1'"
+        );
+    }
 }
@@ -152,20 +152,23 @@ mod tests_ps_var {
     fn test_wildcarded_variable() {
         assert_eq!(
             deobfuscate("sV my-var 1\n(varIable M*ar).vaLue"),
-            "sv my-var 1\n1"
+            "Set-Variable my-var 1\n1"
         );
     }
 
     #[test]
     fn test_wildcarded_getvariable() {
-        assert_eq!(deobfuscate("sV my-var 1\ngV M*ar -vaL"), "sv my-var 1\n1");
+        assert_eq!(
+            deobfuscate("sV my-var 1\ngV M*ar -vaL"),
+            "Set-Variable my-var 1\n1"
+        );
     }
 
     #[test]
     fn test_wildcarded_getsetitem() {
         assert_eq!(
             deobfuscate("sv mYVAr 1\nsi variable:/M*ar 2\n(ls variable:*y*ar).value"),
-            "sv mYVAr 1\nsi variable:/M*ar 2\n2"
+            "Set-Variable mYVAr 1\nSet-Item variable:/M*ar 2\n2"
         );
     }
 
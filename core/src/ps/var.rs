@@ -1,15 +1,27 @@
 use crate::error::{Error, MinusOneResult};
-use crate::ps::Powershell::{self, Array, Crypto, Null, Raw, Type};
+use crate::ps::Powershell::{
+    self, Array, Bytes, Crypto, KeyDerivation, Null, Object, Raw, Stream, Type,
+};
 use crate::ps::Value::{self, Bool, Num, Str};
-use crate::ps::crypto::assign_aes_property;
+use crate::ps::cmdlets::BoundParameters;
+use crate::ps::compression::{call_stream, read_zip};
+use crate::ps::convert::arguments;
+use crate::ps::crypto::assign_cipher_property;
+use crate::ps::digest::call_key_derivation;
+use crate::ps::host::HostProfile;
+use crate::ps::object::{AddMember, assign_object_property};
+use crate::ps::regex::{MatchOperation, infer_match_operator, is_match_operator};
 use crate::ps::tool::StringTool;
+use crate::ps::utils::bytes::bytes_from_data;
+use crate::ps::utils::string::decode;
 use crate::regex::Regex;
 use crate::rule::{Rule, RuleMut};
 use crate::scope::ScopeManager;
 use crate::tree::{BranchFlow, ControlFlow, Node, NodeMut};
 use log::trace;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ops::Add;
+use std::sync::LazyLock;
 
 /// Var is a variable manager that will try to track
 /// static var assignement and propagte it in the code
@@ -41,11 +53,18 @@ use std::ops::Add;
 /// ```
 pub struct Var {
     scope_manager: ScopeManager<Powershell>,
+    profile: HostProfile,
+    // content of the files written by the script, by normalized path
+    files: HashMap<String, Vec<u8>>,
+    // stream copy loops being visited, by node id : source, destination and copied bytes
+    stream_copies: HashMap<usize, (String, String, Vec<u8>)>,
 }
 
 impl Var {
     fn reset_scope_manager(&mut self) {
         self.scope_manager.reset();
+        self.files.clear();
+        self.stream_copies.clear();
         vec![
             "args",
             "ConfirmPreference",
@@ -95,9 +114,18 @@ impl Var {
         ]
         .iter()
         .for_each(|s| {
-            self.scope_manager
-                .current_mut()
-                .assign(s, Powershell::Unknown, false)
+            // variables known by the host profile are predictable
+            match self.profile.automatic_variable(&s.to_lowercase()) {
+                Some(value) => {
+                    self.scope_manager
+                        .current_mut()
+                        .assign(&s.to_lowercase(), value, false)
+                }
+                None => self
+                    .scope_manager
+                    .current_mut()
+                    .assign(s, Powershell::Unknown, false),
+            }
         });
     }
     fn forget_assigned_var<T>(
@@ -182,6 +210,27 @@ impl Var {
         }
     }
 
+    /// Find an environment variable of the host profile from a pattern
+    fn resolve_env(&self, pattern: &str) -> Option<(String, String)> {
+        let re = Regex::new(&format!(
+            "^{}$",
+            regex::escape(pattern).replace("\\*", ".*")
+        ))
+        .ok()?;
+        let matches: Vec<(String, String)> = self
+            .profile
+            .env_vars()
+            .into_iter()
+            .filter(|(name, _)| re.is_match(&name.to_lowercase()))
+            .collect();
+
+        if matches.len() == 1 {
+            matches.into_iter().next()
+        } else {
+            None
+        }
+    }
+
     fn hashmap(variable_name: String, data: &Value) -> Powershell {
         Powershell::HashMap(BTreeMap::from([
             (Str("name".to_string()), Str(variable_name)),
@@ -190,14 +239,280 @@ impl Var {
     }
 }
 
-impl Default for Var {
-    fn default() -> Self {
+impl Var {
+    /// Emulate the automatic variables of a specific host
+    pub fn with_host_profile(profile: HostProfile) -> Self {
         let mut new = Var {
             scope_manager: ScopeManager::default(),
+            profile,
+            files: HashMap::new(),
+            stream_copies: HashMap::new(),
         };
         new.reset_scope_manager();
         new
     }
+
+    /// Static methods of `[IO.File]` over the files written by the script
+    fn call_file(
+        &mut self,
+        method: &str,
+        arguments: Option<&[Powershell]>,
+        is_predictable: bool,
+    ) -> Option<Powershell> {
+        let content = match (method, arguments) {
+            ("writeallbytes", Some([_, data])) => bytes_from_data(data),
+            ("writealltext", Some([_, Raw(text)])) => Some(text.to_string().into_bytes()),
+            ("readallbytes", Some([Raw(Str(path))])) => {
+                return self.files.get(&normalize_path(path)).cloned().map(Bytes);
+            }
+            ("readalltext", Some([Raw(Str(path))])) => {
+                return self
+                    .files
+                    .get(&normalize_path(path))
+                    .and_then(|content| decode_text(content))
+                    .map(|text| Raw(Str(text)));
+            }
+            ("writeallbytes" | "writealltext" | "appendalltext", _) => None,
+            _ => return None,
+        };
+
+        match (arguments.and_then(|a| a.first()), content) {
+            (Some(Raw(Str(path))), Some(content)) if is_predictable => {
+                self.files.insert(normalize_path(path), content);
+            }
+            (Some(Raw(Str(path))), _) => {
+                self.files.remove(&normalize_path(path));
+            }
+            // anything could have been written
+            _ => self.files.clear(),
+        }
+        None
+    }
+
+    /// Expand-Archive of a zip written by the script
+    fn expand_archive(
+        &mut self,
+        command: &Node<Powershell>,
+        is_predictable: bool,
+    ) -> MinusOneResult<()> {
+        let Some(mut parameters) = BoundParameters::bind(
+            command,
+            &[
+                ("path", false),
+                ("literalpath", false),
+                ("destinationpath", false),
+                ("force", true),
+                ("passthru", true),
+            ],
+        )?
+        else {
+            return Ok(());
+        };
+        let path = match parameters.named.remove("literalpath") {
+            Some(path) => path,
+            None => parameters.take("path"),
+        };
+        let destination = parameters.take("destinationpath");
+
+        let (Some(Raw(Str(path))), Some(Raw(Str(destination)))) = (
+            path.as_ref().and_then(|n| n.data()),
+            destination.as_ref().and_then(|n| n.data()),
+        ) else {
+            return Ok(());
+        };
+        let destination = normalize_path(destination);
+
+        match self
+            .files
+            .get(&normalize_path(path))
+            .and_then(|zip| read_zip(zip).ok())
+        {
+            Some(entries) if is_predictable => {
+                for entry in entries {
+                    if !entry.name.ends_with('/') {
+                        trace!("Var (L): Expanding {} to {}", entry.name, destination);
+                        self.files.insert(
+                            normalize_path(&format!("{}\\{}", destination, entry.name)),
+                            entry.data,
+                        );
+                    }
+                }
+            }
+            _ => self
+                .files
+                .retain(|file, _| !file.starts_with(&format!("{}\\", destination))),
+        }
+        Ok(())
+    }
+
+    /// Get-Content of a file written by the script
+    fn get_content(&self, command: &Node<Powershell>) -> MinusOneResult<Option<Powershell>> {
+        let Some(mut parameters) = BoundParameters::bind(
+            command,
+            &[
+                ("path", false),
+                ("literalpath", false),
+                ("raw", true),
+                ("encoding", false),
+                ("asbytestream", true),
+            ],
+        )?
+        else {
+            return Ok(None);
+        };
+        let path = match parameters.named.remove("literalpath") {
+            Some(path) => path,
+            None => parameters.take("path"),
+        };
+        let Some(Raw(Str(path))) = path.as_ref().and_then(|n| n.data()) else {
+            return Ok(None);
+        };
+        let Some(content) = self.files.get(&normalize_path(path)) else {
+            return Ok(None);
+        };
+        if !parameters.positional.is_empty() {
+            return Ok(None);
+        }
+
+        let encoding = match parameters.take("encoding") {
+            Some(encoding) => Some(match encoding.data() {
+                Some(Raw(Str(encoding))) => encoding.to_lowercase(),
+                _ => encoding.text()?.to_lowercase(),
+            }),
+            None => None,
+        };
+        if parameters.is_set("asbytestream") || encoding.as_deref() == Some("byte") {
+            return Ok(Some(Bytes(content.clone())));
+        }
+
+        let text = match encoding {
+            Some(encoding) => decode(&encoding, content),
+            None => decode_text(content),
+        };
+        let Some(text) = text else {
+            return Ok(None);
+        };
+        if parameters.is_set("raw") {
+            return Ok(Some(Raw(Str(text))));
+        }
+
+        let mut lines: Vec<Value> = text
+            .split('\n')
+            .map(|line| Str(line.trim_end_matches('\r').to_string()))
+            .collect();
+        if text.ends_with('\n') {
+            lines.pop();
+        }
+        Ok(match lines.len() {
+            1 => lines.pop().map(Raw),
+            _ => Some(Array(lines)),
+        })
+    }
+}
+
+fn is_file_typename(typename: &str) -> bool {
+    typename.strip_prefix("system.").unwrap_or(typename) == "io.file"
+}
+
+/// Key of a file path : case insensitive, with `\` as separator
+fn normalize_path(path: &str) -> String {
+    path.replace('/', "\\")
+        .trim_end_matches('\\')
+        .to_lowercase()
+}
+
+/// Text of a file, as detected by .NET from its byte order mark
+fn decode_text(content: &[u8]) -> Option<String> {
+    if let Some(content) = content.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        decode("utf8", content)
+    } else if let Some(content) = content.strip_prefix(&[0xFF, 0xFE]) {
+        decode("unicode", content)
+    } else if let Some(content) = content.strip_prefix(&[0xFE, 0xFF]) {
+        decode("bigendianunicode", content)
+    } else {
+        decode("utf8", content)
+    }
+}
+
+/// Source and destination variables of a loop copying a stream through a buffer
+///
+/// `while (($n = $source.Read($buffer, 0, 1024)) -gt 0) { $destination.Write($buffer, 0, $n) }`
+fn stream_copy_loop(view: &Node<Powershell>) -> MinusOneResult<Option<(String, String)>> {
+    let (Some(condition), Some(body)) = (
+        view.iter().find(|child| child.kind() == "while_condition"),
+        view.iter().find(|child| child.kind() == "statement_block"),
+    ) else {
+        return Ok(None);
+    };
+    let (reads, writes, assignments) = (
+        descendants(&condition, "invokation_expression"),
+        descendants(&body, "invokation_expression"),
+        descendants(&condition, "assignment_expression"),
+    );
+    let ([read], [write], [assignment]) = (&reads[..], &writes[..], &assignments[..]) else {
+        return Ok(None);
+    };
+    if !descendants(&body, "assignment_expression").is_empty() {
+        return Ok(None);
+    }
+
+    let (Some((source, read_arguments)), Some((destination, write_arguments)), Some(count)) = (
+        method_call(read, "read")?,
+        method_call(write, "write")?,
+        assignment.child(0),
+    ) else {
+        return Ok(None);
+    };
+    let count = count.text()?.to_lowercase();
+    match (&read_arguments[..], &write_arguments[..]) {
+        ([read_buffer, read_offset, _], [write_buffer, write_offset, write_count])
+            if read_buffer == write_buffer
+                && read_offset == "0"
+                && write_offset == "0"
+                && *write_count == count =>
+        {
+            Ok(Some((source, destination)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Variable and arguments text of a `$var.method(...)` call
+fn method_call(
+    node: &Node<Powershell>,
+    method: &str,
+) -> MinusOneResult<Option<(String, Vec<String>)>> {
+    if let (Some(object), Some(op), Some(member_name), Some(args_list)) =
+        (node.child(0), node.child(1), node.child(2), node.child(3))
+        && object.kind() == "variable"
+        && op.text()? == "."
+        && member_name.text()?.to_string().normalize() == method
+        && let Some(var_name) = Var::extract(object.text()?)
+    {
+        let arguments = arguments(&args_list)
+            .iter()
+            .map(|argument| Ok(argument.text()?.to_lowercase()))
+            .collect::<MinusOneResult<Vec<String>>>()?;
+        return Ok(Some((var_name, arguments)));
+    }
+    Ok(None)
+}
+
+fn descendants<'a>(node: &Node<'a, Powershell>, kind: &str) -> Vec<Node<'a, Powershell>> {
+    let mut found = vec![];
+    for child in node.iter() {
+        found.extend(descendants(&child, kind));
+        if child.kind() == kind {
+            found.push(child);
+        }
+    }
+    found
+}
+
+impl Default for Var {
+    fn default() -> Self {
+        Self::with_host_profile(HostProfile::default())
+    }
 }
 
 pub fn find_variable_node<'a, T>(node: &Node<'a, T>) -> Option<Node<'a, T>> {
@@ -245,6 +560,19 @@ impl<'a> RuleMut<'a> for Var {
         match view.kind() {
             "program" => self.reset_scope_manager(),
             "function_statement" => self.scope_manager.enter(),
+            // the copy is done before the body forgets the streams
+            "while_statement" => {
+                if let Some((source, destination)) = stream_copy_loop(&view)? {
+                    let scope = self.scope_manager.current();
+                    if let (Some(Stream(source_bytes)), Some(Stream(destination_bytes))) =
+                        (scope.get_var(&source), scope.get_var(&destination))
+                    {
+                        let copied = [&destination_bytes[..], &source_bytes[..]].concat();
+                        self.stream_copies
+                            .insert(view.id(), (source, destination, copied));
+                    }
+                }
+            }
             "}" => {
                 if let Some(parent) = view.parent()
                     && (parent.kind() == "statement_block" || parent.kind() == "function_statement")
@@ -329,19 +657,66 @@ impl<'a> RuleMut<'a> for Var {
                         && let Some(new_value) = right.data()
                     {
                         // Property assignment on a tracked object, e.g. $aes.Key = $keyBytes
-                        let member = member_name.text()?.to_string().normalize();
+                        let member = match member_name.data() {
+                            Some(Raw(member)) => member.to_string().to_lowercase(),
+                            _ => member_name.text()?.to_string().normalize(),
+                        };
                         let scope = self.scope_manager.current_mut();
                         let is_local = scope.is_local(&var_name).unwrap_or(true);
                         if flow == ControlFlow::Continue(BranchFlow::Predictable) || is_local {
-                            if let Some(Crypto(state)) = scope.get_var_mut(&var_name)
-                                && !assign_aes_property(state, &member, new_value)
-                            {
+                            let assigned = match scope.get_var_mut(&var_name) {
+                                Some(Crypto(state)) => {
+                                    assign_cipher_property(state, &member, new_value)
+                                }
+                                Some(Object(properties)) => {
+                                    assign_object_property(properties, &member, new_value)
+                                }
+                                _ => true,
+                            };
+                            if !assigned {
                                 scope.forget(&var_name, node.is_ongoing_transaction());
                             }
                         }
                     }
                 }
             }
+            // while (($n = $source.Read($buffer, 0, 1024)) -gt 0) { $destination.Write($buffer, 0, $n) }
+            "while_statement" => {
+                if let Some((source, destination, copied)) = self.stream_copies.remove(&view.id()) {
+                    let scope = self.scope_manager.current_mut();
+                    let is_local = scope.is_local(&destination).unwrap_or(true);
+                    if flow == ControlFlow::Continue(BranchFlow::Predictable) || is_local {
+                        trace!("Var (L): Copying stream {} to {}", source, destination);
+                        scope.assign(&destination, Stream(copied), node.is_ongoing_transaction());
+                        scope.assign(&source, Stream(vec![]), node.is_ongoing_transaction());
+                    }
+                }
+            }
+            // A scalar match populates $Matches
+            "comparison_expression" => {
+                if let (Some(left), Some(operator), Some(right)) =
+                    (view.child(0), view.child(1), view.child(2))
+                    && is_match_operator(operator.text()?)
+                {
+                    let scope = self.scope_manager.current_mut();
+                    let is_local = scope.is_local("matches").unwrap_or(true);
+                    match infer_match_operator(&left, &operator, &right)? {
+                        Some(MatchOperation::Scalar(_, Some(m)))
+                            if flow == ControlFlow::Continue(BranchFlow::Predictable)
+                                || is_local =>
+                        {
+                            scope.assign(
+                                "matches",
+                                Powershell::HashMap(m.to_hashtable()),
+                                node.is_ongoing_transaction(),
+                            )
+                        }
+                        // a failed match, or a filter on an array, leaves $Matches untouched
+                        Some(MatchOperation::Scalar(_, None) | MatchOperation::Filter(_)) => (),
+                        _ => scope.forget("matches", node.is_ongoing_transaction()),
+                    }
+                }
+            }
             "variable" => {
                 if let Some(var_name) = Var::extract(view.text()?) {
                     // forget variable with [ref] operator
@@ -421,11 +796,76 @@ impl<'a> RuleMut<'a> for Var {
                 if let (Some(type_lit), Some(op), Some(member_name), Some(args_list)) =
                     (view.child(0), view.child(1), view.child(2), view.child(3))
                 {
+                    // Streams and key derivation objects move forward on each call : $s.Read(...), $kdf.GetBytes(n)
+                    if op.text()? == "."
+                        && type_lit.kind() == "variable"
+                        && let Some(var_name) = Var::extract(type_lit.text()?)
+                    {
+                        let member = member_name.text()?.to_string().normalize();
+                        let arguments = arguments(&args_list)
+                            .iter()
+                            .map(|argument| argument.data().cloned())
+                            .collect::<Option<Vec<Powershell>>>();
+                        let scope = self.scope_manager.current_mut();
+                        let is_local = scope.is_local(&var_name).unwrap_or(true);
+                        let is_predictable =
+                            flow == ControlFlow::Continue(BranchFlow::Predictable) || is_local;
+                        let called = match scope.get_var_mut(&var_name) {
+                            Some(KeyDerivation(state)) if is_predictable => {
+                                arguments.is_some_and(|a| call_key_derivation(state, &member, &a))
+                            }
+                            Some(Stream(bytes)) if is_predictable => {
+                                arguments.is_some_and(|a| call_stream(bytes, &member, &a))
+                            }
+                            Some(KeyDerivation(_) | Stream(_)) => false,
+                            _ => true,
+                        };
+                        if !called {
+                            scope.forget(&var_name, node.is_ongoing_transaction());
+                        }
+                    }
+
+                    // $source.CopyTo($destination) and $source.Read($buffer, ...) fill their argument
+                    if op.text()? == "."
+                        && let Some(Stream(source)) = type_lit.data()
+                        && let Some(target) = arguments(&args_list).first()
+                        && let Some(var_name) = Var::extract(target.text()?)
+                    {
+                        let member = member_name.text()?.to_string().normalize();
+                        let scope = self.scope_manager.current_mut();
+                        let is_local = scope.is_local(&var_name).unwrap_or(true);
+                        let is_predictable =
+                            flow == ControlFlow::Continue(BranchFlow::Predictable) || is_local;
+                        match (member.as_str(), scope.get_var_mut(&var_name)) {
+                            ("copyto", Some(Stream(bytes))) if is_predictable => {
+                                bytes.extend(source)
+                            }
+                            ("copyto" | "read", _) => {
+                                scope.forget(&var_name, node.is_ongoing_transaction())
+                            }
+                            _ => (),
+                        }
+                    }
+
                     match (
                         type_lit.data(),
                         op.text()?,
                         member_name.text()?.to_lowercase().as_str(),
                     ) {
+                        (Some(Type(typename)), "::", m) if is_file_typename(typename) => {
+                            let arguments = arguments(&args_list)
+                                .iter()
+                                .map(|argument| argument.data().cloned())
+                                .collect::<Option<Vec<Powershell>>>();
+                            if let Some(content) = self.call_file(
+                                m,
+                                arguments.as_deref(),
+                                flow == ControlFlow::Continue(BranchFlow::Predictable),
+                            ) {
+                                trace!("Var (L): Setting node with file content: {:?}", content);
+                                node.set(content);
+                            }
+                        }
                         (Some(Type(typename)), "::", m)
                             if (typename == "array" && m.to_lowercase() == "reverse") =>
                         {
@@ -461,8 +901,50 @@ impl<'a> RuleMut<'a> for Var {
                 }
             }
             "command" => {
+                // Add-Member on a tracked object
+                if let Some(add_member) = AddMember::parse(&view)?
+                    && let Some(var_name) = add_member.variable_name()?
+                {
+                    let scope = self.scope_manager.current_mut();
+                    let is_local = scope.is_local(&var_name).unwrap_or(true);
+                    let properties = match scope.get_var_mut(&var_name) {
+                        Some(Object(properties))
+                            if flow == ControlFlow::Continue(BranchFlow::Predictable)
+                                || is_local =>
+                        {
+                            add_member.apply(properties).then(|| properties.clone())
+                        }
+                        _ => None,
+                    };
+                    match properties {
+                        Some(properties) if add_member.passthru => {
+                            trace!(
+                                "Var (L): Setting node with Add-Member object: {:?}",
+                                properties
+                            );
+                            node.set(Object(properties));
+                        }
+                        Some(_) => (),
+                        None => scope.forget(&var_name, node.is_ongoing_transaction()),
+                    }
+                    return Ok(());
+                }
+
                 if let Some(command_name) = view.child(0) {
                     match crate::ps::cmdlets::resolved_command_name(&command_name)?.as_str() {
+                        "expand-archive" => self.expand_archive(
+                            &view,
+                            flow == ControlFlow::Continue(BranchFlow::Predictable),
+                        )?,
+                        "get-content" | "gc" | "cat" | "type" => {
+                            if let Some(content) = self.get_content(&view)? {
+                                trace!(
+                                    "Var (L): Setting node with Get-Content value: {:?}",
+                                    content
+                                );
+                                node.set(content);
+                            }
+                        }
                         "variable" => {
                             if let Some(command_elements) = view.child(1)
                                 && let Some(variable_name) = command_elements.child(1)
@@ -537,12 +1019,16 @@ impl<'a> RuleMut<'a> for Var {
                                 );
                             }
                         }
-                        "get-childitem" | "gci" | "ls" => {
+                        "get-childitem" | "gci" | "ls" | "dir" | "get-item" | "gi" => {
                             if let Some(command_elements) = view.child(1)
                                 && let Some(item_name_node) = command_elements.child(1)
                             {
-                                let item_name = item_name_node.text()?.to_lowercase();
+                                let item_name = match item_name_node.data() {
+                                    Some(Raw(Str(item_name))) => item_name.to_lowercase(),
+                                    _ => item_name_node.text()?.to_lowercase(),
+                                };
                                 let re = Regex::new(r"^variable:\/?(.*)$").unwrap();
+                                let re_env = Regex::new(r"^env:\/?(.+)$").unwrap();
                                 if let Some(variable_name) =
                                     re.captures(&item_name).and_then(|cap| cap.get(1))
                                     && let Some(variable_name) =
@@ -555,6 +1041,16 @@ impl<'a> RuleMut<'a> for Var {
                                         Var::hashmap(variable_name.clone(), data)
                                     );
                                     node.set(Var::hashmap(variable_name, data));
+                                } else if let Some(env_name) =
+                                    re_env.captures(&item_name).and_then(|cap| cap.get(1))
+                                    && let Some((env_name, value)) =
+                                        self.resolve_env(env_name.as_str())
+                                {
+                                    trace!(
+                                        "Var (L): Setting node with environment variable {}: {:?}",
+                                        env_name, value
+                                    );
+                                    node.set(Var::hashmap(env_name, &Str(value)));
                                 }
                             }
                         }
@@ -684,17 +1180,85 @@ fn assign_handler(
 /// Write-Debug 4\
 /// ");
 /// ```
-#[derive(Default)]
-pub struct StaticVar;
+static ENV_VARIABLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\$(\{env:(?<braced>[^}]+)\}|env:(?<name>.+))$").unwrap());
+
+/// Lowercase name of a variable the host may define :
+/// $env:Path and ${env:path} => env:path, $PSHome => pshome
+fn host_variable_name(var: &str) -> String {
+    let var = var.to_lowercase();
+    match ENV_VARIABLE
+        .captures(&var)
+        .and_then(|cap| cap.name("braced").or(cap.name("name")))
+    {
+        Some(name) => format!("env:{}", name.as_str()),
+        None => var
+            .trim_start_matches('$')
+            .trim_start_matches('{')
+            .trim_end_matches('}')
+            .to_string(),
+    }
+}
+
+/// Name of the variable or environment variable a command sets or removes :
+/// Set-Variable name, Set-Item env:name, Remove-Item env:name
+fn host_variable_set_by(command: &Node<Powershell>) -> MinusOneResult<Option<String>> {
+    let Some(command_name) = command.child(0).filter(|n| n.kind() == "command_name") else {
+        return Ok(None);
+    };
+    let Some(target) = command.child(1).and_then(|elements| elements.child(1)) else {
+        return Ok(None);
+    };
+    let target = match target.data() {
+        Some(Raw(Str(target))) => target.to_lowercase(),
+        _ => target.text()?.to_lowercase(),
+    };
+    Ok(
+        match crate::ps::cmdlets::resolved_command_name(&command_name)?.as_str() {
+            "set-variable" | "sv" | "new-variable" | "nv" | "remove-variable" | "rv"
+            | "clear-variable" | "clv" => Some(target),
+            "set-item" | "si" | "new-item" | "ni" | "remove-item" | "ri" | "rm" | "del"
+            | "erase" | "clear-item" | "cli" => target
+                .strip_prefix("env:")
+                .map(|name| format!("env:{}", name.trim_start_matches(['/', '\\']))),
+            _ => None,
+        },
+    )
+}
+
+pub struct StaticVar {
+    profile: HostProfile,
+    // variables and environment variables the script sets, they are not the host ones anymore
+    assigned: HashSet<String>,
+}
+
+impl StaticVar {
+    /// Infer environment and automatic variables from a specific host
+    pub fn with_host_profile(profile: HostProfile) -> Self {
+        StaticVar {
+            profile,
+            assigned: HashSet::new(),
+        }
+    }
+}
+
+impl Default for StaticVar {
+    fn default() -> Self {
+        Self::with_host_profile(HostProfile::default())
+    }
+}
 
 impl<'a> RuleMut<'a> for StaticVar {
     type Language = Powershell;
 
     fn enter(
         &mut self,
-        _node: &mut NodeMut<'a, Self::Language>,
+        node: &mut NodeMut<'a, Self::Language>,
         _flow: ControlFlow,
     ) -> MinusOneResult<()> {
+        if node.view().kind() == "program" {
+            self.assigned.clear();
+        }
         Ok(())
     }
 
@@ -704,12 +1268,22 @@ impl<'a> RuleMut<'a> for StaticVar {
         _flow: ControlFlow,
     ) -> MinusOneResult<()> {
         let view = node.view();
-        if view.kind() == "variable" {
-            match view.text()?.to_lowercase().as_str() {
-                "$shellid" => {
-                    trace!("Var (L): Setting node with special variable $shellid");
-                    node.set(Raw(Str(String::from("Microsoft.Powershell"))))
-                }
+        if view.kind() == "command"
+            && let Some(name) = host_variable_set_by(&view)?
+        {
+            self.assigned.insert(name);
+        }
+        if view.kind() != "variable" {
+            return Ok(());
+        }
+        if view
+            .get_parent_of_types(vec!["left_assignment_expression"])
+            .is_some()
+        {
+            self.assigned.insert(host_variable_name(view.text()?));
+        } else if !self.assigned.contains(&host_variable_name(view.text()?)) {
+            let var = view.text()?.to_lowercase();
+            match var.as_str() {
                 "$?" => {
                     trace!("Var (L): Setting node with special variable $?");
                     node.set(Raw(Bool(true)))
@@ -718,17 +1292,24 @@ impl<'a> RuleMut<'a> for StaticVar {
                     trace!("Var (L): Setting node with special variable $null");
                     node.set(Null)
                 }
-                "$pshome" => {
-                    trace!("Var (L): Setting node with special variable $pshome");
-                    node.set(Raw(Str(String::from(
-                        "C:\\Windows\\System32\\WindowsPowerShell\\v1.0",
-                    ))))
-                }
-                "$verbosepreference" => {
-                    trace!("Var (L): Setting node with special variable $verbosepreference");
-                    node.set(Raw(Str(String::from("SilentlyContinue"))))
+                _ => {
+                    if let Some(cap) = ENV_VARIABLE.captures(&var)
+                        && let Some(name) = cap.name("braced").or(cap.name("name"))
+                        && let Some(value) = self.profile.env(name.as_str())
+                    {
+                        trace!(
+                            "Var (L): Setting node with environment variable {}",
+                            name.as_str()
+                        );
+                        node.set(Raw(Str(value)))
+                    } else if let Some(value) = var
+                        .strip_prefix('$')
+                        .and_then(|name| self.profile.automatic_variable(name))
+                    {
+                        trace!("Var (L): Setting node with special variable {}", var);
+                        node.set(value)
+                    }
                 }
-                _ => (),
             }
         }
         Ok(())
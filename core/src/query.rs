@@ -0,0 +1,880 @@
+use crate::error::{Error, MinusOneErrorKind, MinusOneResult};
+use crate::rule::{RuleInfo, RuleMut};
+use crate::tree::{ControlFlow, Node, NodeMut};
+use base64::{Engine as _, engine::general_purpose};
+use log::trace;
+use regex::Regex;
+use std::marker::PhantomData;
+use tree_sitter::{Language, Query, QueryCursor, StreamingIterator};
+
+/// Language neutral value used by query rules
+///
+/// Captured nodes are converted into this representation before
+/// the conditions and the emit template are evaluated, and the
+/// result is converted back into the language inferred type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryValue {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    Array(Vec<QueryValue>),
+    Type(String),
+    Null,
+}
+
+impl QueryValue {
+    fn kind(&self) -> &'static str {
+        match self {
+            QueryValue::Str(_) => "str",
+            QueryValue::Int(_) => "int",
+            QueryValue::Float(_) => "float",
+            QueryValue::Bool(_) => "bool",
+            QueryValue::Bytes(_) => "bytes",
+            QueryValue::Array(_) => "array",
+            QueryValue::Type(_) => "type",
+            QueryValue::Null => "null",
+        }
+    }
+
+    fn as_string(&self) -> Option<String> {
+        match self {
+            QueryValue::Str(s) | QueryValue::Type(s) => Some(s.clone()),
+            QueryValue::Int(n) => Some(n.to_string()),
+            QueryValue::Float(f) => Some(f.to_string()),
+            _ => None,
+        }
+    }
+
+    fn as_int(&self) -> Option<i64> {
+        match self {
+            QueryValue::Int(n) => Some(*n),
+            QueryValue::Float(f) if f.fract() == 0.0 => Some(*f as i64),
+            _ => None,
+        }
+    }
+
+    fn as_bytes(&self) -> Option<Vec<u8>> {
+        match self {
+            QueryValue::Bytes(b) => Some(b.clone()),
+            QueryValue::Array(a) => a
+                .iter()
+                .map(|v| v.as_int().and_then(|n| u8::try_from(n).ok()))
+                .collect(),
+            QueryValue::Str(s) => Some(s.as_bytes().to_vec()),
+            _ => None,
+        }
+    }
+}
+
+/// A language that can be deobfuscated using query rules
+pub trait QueryLanguage: Sized {
+    /// The tree-sitter grammar used to compile the queries
+    fn language() -> Language;
+
+    /// Built-in rules of the language, query rules can't take their names
+    fn builtin_rules() -> Vec<RuleInfo>;
+
+    /// Convert an inferred value into a query value
+    fn to_query_value(&self) -> Option<QueryValue>;
+
+    /// Convert back a query value into an inferred value
+    /// None if the language can't represent it
+    fn from_query_value(value: QueryValue) -> Option<Self>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Capture(String),
+    Literal(QueryValue),
+    Call(String, Vec<Expr>),
+}
+
+/// name and arity (min, max) of the functions available in templates
+const FUNCTIONS: &[(&str, usize, usize)] = &[
+    ("text", 1, 1),
+    ("known", 1, 1),
+    ("str", 1, 1),
+    ("int", 1, 2),
+    ("type", 1, 1),
+    ("bytes", 1, 1),
+    ("concat", 1, usize::MAX),
+    ("lower", 1, 1),
+    ("upper", 1, 1),
+    ("reverse", 1, 1),
+    ("replace", 3, 3),
+    ("substr", 2, 3),
+    ("split", 2, 2),
+    ("join", 1, 2),
+    ("len", 1, 1),
+    ("char", 1, 1),
+    ("ord", 1, 1),
+    ("base64", 1, 1),
+    ("utf8", 1, 1),
+    ("utf16", 1, 1),
+    ("hex", 1, 1),
+    ("xor", 2, 2),
+    ("add", 2, 2),
+    ("sub", 2, 2),
+    ("mul", 2, 2),
+    ("div", 2, 2),
+    ("mod", 2, 2),
+    ("eq", 2, 2),
+    ("ieq", 2, 2),
+    ("ne", 2, 2),
+    ("not", 1, 1),
+    ("and", 1, usize::MAX),
+    ("or", 1, usize::MAX),
+    ("is", 2, 2),
+    ("matches", 2, 2),
+];
+
+fn parse_error(message: String) -> Error {
+    Error::new(MinusOneErrorKind::Parsing, &message)
+}
+
+fn skip_whitespace(chars: &[char], pos: &mut usize) {
+    while chars.get(*pos).is_some_and(|c| c.is_whitespace()) {
+        *pos += 1;
+    }
+}
+
+fn parse_identifier(chars: &[char], pos: &mut usize) -> String {
+    let start = *pos;
+    while chars
+        .get(*pos)
+        .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '-'))
+    {
+        *pos += 1;
+    }
+    chars[start..*pos].iter().collect()
+}
+
+fn parse_string(chars: &[char], pos: &mut usize) -> Result<Expr, String> {
+    // skip the opening quote
+    *pos += 1;
+    let mut result = String::new();
+    loop {
+        match chars.get(*pos) {
+            None => return Err("unterminated string literal".to_string()),
+            Some('"') => {
+                *pos += 1;
+                return Ok(Expr::Literal(QueryValue::Str(result)));
+            }
+            Some('\\') => {
+                *pos += 1;
+                match chars.get(*pos) {
+                    Some('n') => result.push('\n'),
+                    Some('r') => result.push('\r'),
+                    Some('t') => result.push('\t'),
+                    Some('0') => result.push('\0'),
+                    Some(c) => result.push(*c),
+                    None => return Err("unterminated string literal".to_string()),
+                }
+                *pos += 1;
+            }
+            Some(c) => {
+                result.push(*c);
+                *pos += 1;
+            }
+        }
+    }
+}
+
+fn parse_number(chars: &[char], pos: &mut usize) -> Result<Expr, String> {
+    let start = *pos;
+    if chars.get(*pos) == Some(&'-') {
+        *pos += 1;
+    }
+    while chars
+        .get(*pos)
+        .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '.')
+    {
+        *pos += 1;
+    }
+    let token: String = chars[start..*pos].iter().collect();
+    let (negative, digits) = match token.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, token.as_str()),
+    };
+
+    let value = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        i64::from_str_radix(hex, 16).ok().map(QueryValue::Int)
+    } else if digits.contains('.') {
+        digits.parse::<f64>().ok().map(QueryValue::Float)
+    } else {
+        digits.parse::<i64>().ok().map(QueryValue::Int)
+    };
+
+    match (value, negative) {
+        (Some(QueryValue::Int(n)), true) => Ok(Expr::Literal(QueryValue::Int(-n))),
+        (Some(QueryValue::Float(f)), true) => Ok(Expr::Literal(QueryValue::Float(-f))),
+        (Some(v), false) => Ok(Expr::Literal(v)),
+        _ => Err(format!("invalid number literal '{}'", token)),
+    }
+}
+
+fn parse_expr_at(chars: &[char], pos: &mut usize) -> Result<Expr, String> {
+    skip_whitespace(chars, pos);
+    match chars.get(*pos) {
+        None => Err("unexpected end of expression".to_string()),
+        Some('@') => {
+            *pos += 1;
+            let name = parse_identifier(chars, pos);
+            if name.is_empty() {
+                return Err("expected a capture name after '@'".to_string());
+            }
+            Ok(Expr::Capture(name))
+        }
+        Some('"') => parse_string(chars, pos),
+        Some(c) if c.is_ascii_digit() || *c == '-' => parse_number(chars, pos),
+        Some(c) if c.is_alphabetic() || *c == '_' => {
+            let name = parse_identifier(chars, pos);
+            match name.as_str() {
+                "true" => return Ok(Expr::Literal(QueryValue::Bool(true))),
+                "false" => return Ok(Expr::Literal(QueryValue::Bool(false))),
+                "null" => return Ok(Expr::Literal(QueryValue::Null)),
+                _ => (),
+            }
+
+            skip_whitespace(chars, pos);
+            if chars.get(*pos) != Some(&'(') {
+                return Err(format!("expected '(' after '{}'", name));
+            }
+            *pos += 1;
+
+            let mut args = vec![];
+            skip_whitespace(chars, pos);
+            if chars.get(*pos) == Some(&')') {
+                *pos += 1;
+            } else {
+                loop {
+                    args.push(parse_expr_at(chars, pos)?);
+                    skip_whitespace(chars, pos);
+                    match chars.get(*pos) {
+                        Some(',') => *pos += 1,
+                        Some(')') => {
+                            *pos += 1;
+                            break;
+                        }
+                        _ => return Err(format!("expected ',' or ')' in call to '{}'", name)),
+                    }
+                }
+            }
+
+            let Some((_, min, max)) = FUNCTIONS.iter().find(|(f, _, _)| *f == name) else {
+                return Err(format!("unknown function '{}'", name));
+            };
+            if args.len() < *min || args.len() > *max {
+                return Err(format!(
+                    "wrong number of arguments for '{}': {}",
+                    name,
+                    args.len()
+                ));
+            }
+            if (name == "text" || name == "known") && !matches!(args[0], Expr::Capture(_)) {
+                return Err(format!("'{}' expects a capture", name));
+            }
+
+            Ok(Expr::Call(name, args))
+        }
+        Some(c) => Err(format!("unexpected character '{}'", c)),
+    }
+}
+
+fn parse_expr(src: &str) -> Result<Expr, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut pos = 0;
+    let expr = parse_expr_at(&chars, &mut pos)?;
+    skip_whitespace(&chars, &mut pos);
+    if pos != chars.len() {
+        return Err(format!(
+            "unexpected trailing input '{}'",
+            chars[pos..].iter().collect::<String>()
+        ));
+    }
+    Ok(expr)
+}
+
+fn collect_captures<'e>(expr: &'e Expr, captures: &mut Vec<&'e str>) {
+    match expr {
+        Expr::Capture(name) => captures.push(name),
+        Expr::Call(_, args) => args.iter().for_each(|a| collect_captures(a, captures)),
+        Expr::Literal(_) => (),
+    }
+}
+
+/// Captures of a single query match
+struct Captures<'n, T> {
+    nodes: Vec<(&'n str, Node<'n, T>)>,
+}
+
+impl<'n, T: QueryLanguage> Captures<'n, T> {
+    fn node(&self, name: &str) -> Option<&Node<'n, T>> {
+        self.nodes.iter().find(|(n, _)| *n == name).map(|(_, n)| n)
+    }
+
+    fn eval(&self, expr: &Expr) -> Option<QueryValue> {
+        match expr {
+            Expr::Literal(value) => Some(value.clone()),
+            Expr::Capture(name) => self.node(name)?.data()?.to_query_value(),
+            Expr::Call(name, args) if name == "text" => match &args[0] {
+                Expr::Capture(c) => Some(QueryValue::Str(self.node(c)?.text().ok()?.to_string())),
+                _ => None,
+            },
+            Expr::Call(name, args) if name == "known" => {
+                Some(QueryValue::Bool(self.eval(&args[0]).is_some()))
+            }
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|a| self.eval(a))
+                    .collect::<Option<Vec<QueryValue>>>()?;
+                call(name, args)
+            }
+        }
+    }
+}
+
+fn values_equal(left: &QueryValue, right: &QueryValue, case_insensitive: bool) -> bool {
+    match (left, right) {
+        (QueryValue::Int(_) | QueryValue::Float(_), QueryValue::Int(_) | QueryValue::Float(_)) => {
+            match (left, right) {
+                (QueryValue::Int(a), QueryValue::Int(b)) => a == b,
+                (QueryValue::Int(a), QueryValue::Float(b))
+                | (QueryValue::Float(b), QueryValue::Int(a)) => *a as f64 == *b,
+                (QueryValue::Float(a), QueryValue::Float(b)) => a == b,
+                _ => false,
+            }
+        }
+        (QueryValue::Str(a) | QueryValue::Type(a), QueryValue::Str(b) | QueryValue::Type(b)) => {
+            if case_insensitive {
+                a.to_lowercase() == b.to_lowercase()
+            } else {
+                a == b
+            }
+        }
+        (QueryValue::Array(a), QueryValue::Array(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|(x, y)| values_equal(x, y, case_insensitive))
+        }
+        (a, b) => a == b,
+    }
+}
+
+fn call(name: &str, args: Vec<QueryValue>) -> Option<QueryValue> {
+    use QueryValue::*;
+
+    match (name, args.as_slice()) {
+        ("str", [value]) => value.as_string().map(Str),
+        ("int", [Int(n)]) => Some(Int(*n)),
+        ("int", [Float(f)]) => Some(Int(f.trunc() as i64)),
+        ("int", [Str(s)]) => s.trim().parse::<i64>().ok().map(Int),
+        ("int", [Str(s), base]) => {
+            let base = u32::try_from(base.as_int()?).ok()?;
+            if !(2..=36).contains(&base) {
+                return None;
+            }
+            let s = s.trim();
+            let s = if base == 16 {
+                s.strip_prefix("0x").unwrap_or(s)
+            } else {
+                s
+            };
+            i64::from_str_radix(s, base).ok().map(Int)
+        }
+        ("type", [value]) => value.as_string().map(|s| Type(s.to_lowercase())),
+        ("bytes", [value]) => value.as_bytes().map(Bytes),
+        ("concat", values) => match values.first()? {
+            Array(_) => values
+                .iter()
+                .map(|v| match v {
+                    Array(a) => Some(a.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(|parts| Array(parts.concat())),
+            Bytes(_) => values
+                .iter()
+                .map(|v| match v {
+                    Bytes(b) => Some(b.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(|parts| Bytes(parts.concat())),
+            _ => values
+                .iter()
+                .map(QueryValue::as_string)
+                .collect::<Option<Vec<_>>>()
+                .map(|parts| Str(parts.concat())),
+        },
+        ("lower", [Str(s)]) => Some(Str(s.to_lowercase())),
+        ("upper", [Str(s)]) => Some(Str(s.to_uppercase())),
+        ("reverse", [Str(s)]) => Some(Str(s.chars().rev().collect())),
+        ("reverse", [Array(a)]) => Some(Array(a.iter().rev().cloned().collect())),
+        ("reverse", [Bytes(b)]) => Some(Bytes(b.iter().rev().cloned().collect())),
+        ("replace", [Str(s), Str(from), Str(to)]) if !from.is_empty() => {
+            Some(Str(s.replace(from.as_str(), to)))
+        }
+        ("substr", [Str(s), start]) => {
+            let start = usize::try_from(start.as_int()?).ok()?;
+            if start > s.chars().count() {
+                return None;
+            }
+            Some(Str(s.chars().skip(start).collect()))
+        }
+        ("substr", [Str(s), start, length]) => {
+            let start = usize::try_from(start.as_int()?).ok()?;
+            let length = usize::try_from(length.as_int()?).ok()?;
+            if start + length > s.chars().count() {
+                return None;
+            }
+            Some(Str(s.chars().skip(start).take(length).collect()))
+        }
+        ("split", [Str(s), Str(separator)]) if !separator.is_empty() => Some(Array(
+            s.split(separator.as_str())
+                .map(|p| Str(p.to_string()))
+                .collect(),
+        )),
+        ("join", [Array(a)]) => a
+            .iter()
+            .map(QueryValue::as_string)
+            .collect::<Option<Vec<_>>>()
+            .map(|parts| Str(parts.concat())),
+        ("join", [Array(a), Str(separator)]) => a
+            .iter()
+            .map(QueryValue::as_string)
+            .collect::<Option<Vec<_>>>()
+            .map(|parts| Str(parts.join(separator))),
+        ("len", [Str(s)]) => Some(Int(s.chars().count() as i64)),
+        ("len", [Array(a)]) => Some(Int(a.len() as i64)),
+        ("len", [Bytes(b)]) => Some(Int(b.len() as i64)),
+        ("char", [Array(a)]) => a
+            .iter()
+            .map(|v| char::from_u32(u32::try_from(v.as_int()?).ok()?))
+            .collect::<Option<String>>()
+            .map(Str),
+        ("char", [Bytes(b)]) => Some(Str(b.iter().map(|c| *c as char).collect())),
+        ("char", [value]) => {
+            char::from_u32(u32::try_from(value.as_int()?).ok()?).map(|c| Str(c.to_string()))
+        }
+        ("ord", [Str(s)]) => Some(Array(s.chars().map(|c| Int(c as i64)).collect())),
+        ("base64", [Str(s)]) => general_purpose::STANDARD.decode(s.trim()).ok().map(Bytes),
+        ("utf8", [value]) => String::from_utf8(value.as_bytes()?).ok().map(Str),
+        ("utf16", [value]) => {
+            let bytes = value.as_bytes()?;
+            if bytes.len() % 2 != 0 {
+                return None;
+            }
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+            String::from_utf16(&units).ok().map(Str)
+        }
+        ("hex", [Str(s)]) => {
+            let s = s.trim();
+            if s.len() % 2 != 0 || !s.is_ascii() {
+                return None;
+            }
+            (0..s.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+                .collect::<Option<Vec<u8>>>()
+                .map(Bytes)
+        }
+        ("xor", [Int(a), Int(b)]) => Some(Int(a ^ b)),
+        ("xor", [data @ (Bytes(_) | Array(_)), key]) => {
+            let data = data.as_bytes()?;
+            let key = match key {
+                Int(n) => vec![u8::try_from(*n).ok()?],
+                key => key.as_bytes()?,
+            };
+            if key.is_empty() {
+                return None;
+            }
+            Some(Bytes(
+                data.iter()
+                    .zip(key.iter().cycle())
+                    .map(|(d, k)| d ^ k)
+                    .collect(),
+            ))
+        }
+        ("add", [Int(a), Int(b)]) => a.checked_add(*b).map(Int),
+        ("sub", [Int(a), Int(b)]) => a.checked_sub(*b).map(Int),
+        ("mul", [Int(a), Int(b)]) => a.checked_mul(*b).map(Int),
+        ("div", [Int(a), Int(b)]) => a.checked_div(*b).map(Int),
+        ("mod", [Int(a), Int(b)]) => a.checked_rem(*b).map(Int),
+        ("add", [a, b]) => Some(Float(as_float(a)? + as_float(b)?)),
+        ("sub", [a, b]) => Some(Float(as_float(a)? - as_float(b)?)),
+        ("mul", [a, b]) => Some(Float(as_float(a)? * as_float(b)?)),
+        ("div", [a, b]) => Some(Float(as_float(a)? / as_float(b)?)),
+        ("eq", [a, b]) => Some(Bool(values_equal(a, b, false))),
+        ("ieq", [a, b]) => Some(Bool(values_equal(a, b, true))),
+        ("ne", [a, b]) => Some(Bool(!values_equal(a, b, false))),
+        ("not", [Bool(b)]) => Some(Bool(!b)),
+        ("and", values) => Some(Bool(values.iter().all(|v| *v == Bool(true)))),
+        ("or", values) => Some(Bool(values.contains(&Bool(true)))),
+        ("is", [value, Str(kind)]) => Some(Bool(value.kind() == kind)),
+        ("matches", [value, Str(pattern)]) => {
+            let regex = Regex::new(pattern).ok()?;
+            Some(Bool(regex.is_match(&value.as_string()?)))
+        }
+        _ => None,
+    }
+}
+
+fn as_float(value: &QueryValue) -> Option<f64> {
+    match value {
+        QueryValue::Int(n) => Some(*n as f64),
+        QueryValue::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+/// A rule described by a tree-sitter query and a value template
+///
+/// The query is matched against each node when leaving it, the root
+/// of the pattern being the node itself. Every `where` condition must
+/// evaluate to `true`, then the `emit` template is evaluated and the
+/// result is set as the inferred value of the node.
+///
+/// A capture used in a template evaluates to the value already inferred
+/// by the other rules on the captured node. If one of the captures has
+/// no inferred value, or if a function can't handle its arguments,
+/// the rule is simply not applied.
+///
+/// Rules are usually loaded from a file using [`parse_query_rules`]:
+///
+/// ```text
+/// # "oof"[-1..-3] style string reversing is already handled,
+/// # but this one comes from a custom loader
+/// rule ReverseMethod
+/// match ((invokation_expression
+///          (string_literal) @str
+///          "."
+///          (member_name) @method
+///          (argument_list))
+///        (#match? @method "^(?i)reverse$"))
+/// where is(@str, "str")
+/// emit reverse(@str)
+/// ```
+///
+/// - `rule <name>` starts a new rule, the name can be used with the include/exclude rule filters
+///   and must differ from the names of the built-in rules
+/// - `match <query>` is a tree-sitter query, text predicates like `#eq?` or `#match?` are supported
+///   when grouped with their pattern
+/// - `where <expr>` adds a condition, can be repeated
+/// - `emit <expr>` is the value template
+///
+/// Lines that don't start with a directive continue the previous one,
+/// and lines starting with `#` or `;` are comments.
+///
+/// Expressions are made of captures (`@str`), literals (`"foo"`, `42`, `0x2a`,
+/// `true`, `false`, `null`) and function calls:
+///
+/// - `text(@c)`, `known(@c)`: source text of a capture, and whether it has an inferred value
+/// - `str(x)`, `int(x[, base])`, `type(x)`, `bytes(x)`: conversions
+/// - `concat(...)`, `lower(x)`, `upper(x)`, `reverse(x)`, `replace(s, from, to)`,
+///   `substr(s, start[, len])`, `split(s, sep)`, `join(a[, sep])`, `len(x)`
+/// - `char(x)`, `ord(s)`, `base64(s)`, `utf8(b)`, `utf16(b)`, `hex(s)`, `xor(x, key)`
+/// - `add`, `sub`, `mul`, `div`, `mod`
+/// - `eq`, `ieq`, `ne`, `not`, `and`, `or`, `is(x, "str")`, `matches(x, "regex")`
+///
+/// # Example
+/// ```
+/// use minusone::js::build_javascript_tree;
+/// use minusone::js::forward::Forward;
+/// use minusone::js::string::ParseString;
+/// use minusone::js::linter::Linter;
+/// use minusone::js::JavaScript;
+/// use minusone::query::parse_query_rules;
+///
+/// let rules = parse_query_rules::<JavaScript>(r#"
+/// rule Rot
+/// match ((call_expression
+///          function: (identifier) @f
+///          arguments: (arguments (string) @s))
+///        (#eq? @f "reverse"))
+/// emit reverse(@s)
+/// "#).unwrap();
+///
+/// let mut tree = build_javascript_tree("reverse('olleh');").unwrap();
+/// tree.apply_mut(&mut (
+///     ParseString::default(),
+///     Forward::default(),
+///     &rules[0],
+/// )).unwrap();
+///
+/// let mut linter = Linter::default();
+/// tree.apply(&mut linter).unwrap();
+///
+/// assert_eq!(linter.output, "'hello';");
+/// ```
+pub struct QueryRule<T> {
+    name: String,
+    query: Query,
+    conditions: Vec<Expr>,
+    emit: Expr,
+    language: PhantomData<T>,
+}
+
+impl<T: QueryLanguage> QueryRule<T> {
+    /// Compile a new query rule
+    pub fn new(name: &str, query: &str, conditions: &[&str], emit: &str) -> MinusOneResult<Self> {
+        let compiled = Query::new(&T::language(), query)
+            .map_err(|e| parse_error(format!("rule {}: invalid query: {}", name, e)))?;
+
+        let conditions = conditions
+            .iter()
+            .map(|c| parse_expr(c))
+            .collect::<Result<Vec<Expr>, String>>()
+            .map_err(|e| parse_error(format!("rule {}: invalid condition: {}", name, e)))?;
+
+        let emit = parse_expr(emit)
+            .map_err(|e| parse_error(format!("rule {}: invalid emit template: {}", name, e)))?;
+
+        let mut captures = vec![];
+        conditions
+            .iter()
+            .chain(std::iter::once(&emit))
+            .for_each(|e| collect_captures(e, &mut captures));
+        if let Some(unknown) = captures
+            .iter()
+            .find(|c| compiled.capture_index_for_name(c).is_none())
+        {
+            return Err(parse_error(format!(
+                "rule {}: unknown capture @{}",
+                name, unknown
+            )));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            query: compiled,
+            conditions,
+            emit,
+            language: PhantomData,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn infer(&self, node: &NodeMut<T>) -> Option<T> {
+        let mut cursor = QueryCursor::new();
+        // the pattern must start on the visited node
+        cursor.set_max_start_depth(Some(0));
+
+        let capture_names = self.query.capture_names();
+        let mut matches = cursor.matches(&self.query, node.inner, node.source());
+        while let Some(query_match) = matches.next() {
+            let captures = Captures {
+                nodes: query_match
+                    .captures
+                    .iter()
+                    .map(|c| (capture_names[c.index as usize], node.view_of(c.node)))
+                    .collect(),
+            };
+
+            if self
+                .conditions
+                .iter()
+                .all(|c| captures.eval(c) == Some(QueryValue::Bool(true)))
+                && let Some(value) = captures.eval(&self.emit).and_then(T::from_query_value)
+            {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T: QueryLanguage> RuleMut<'a> for &QueryRule<T> {
+    type Language = T;
+
+    fn enter(
+        &mut self,
+        _node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        Ok(())
+    }
+
+    fn leave(
+        &mut self,
+        node: &mut NodeMut<'a, Self::Language>,
+        _flow: ControlFlow,
+    ) -> MinusOneResult<()> {
+        if let Some(value) = self.infer(node) {
+            trace!(
+                "{} (L): Setting node {} with query rule value",
+                self.name,
+                node.view().kind()
+            );
+            node.set(value);
+        }
+        Ok(())
+    }
+}
+
+/// Parse a set of query rules
+///
+/// See [`QueryRule`] for the file format
+pub fn parse_query_rules<T: QueryLanguage>(src: &str) -> MinusOneResult<Vec<QueryRule<T>>> {
+    #[derive(Default)]
+    struct Draft {
+        name: String,
+        line: usize,
+        query: String,
+        conditions: Vec<String>,
+        emit: Option<String>,
+    }
+
+    #[derive(PartialEq)]
+    enum Directive {
+        Match,
+        Where,
+        Emit,
+    }
+
+    fn finish<T: QueryLanguage>(draft: Draft) -> MinusOneResult<QueryRule<T>> {
+        if draft.query.trim().is_empty() {
+            return Err(parse_error(format!(
+                "rule {} (line {}): missing match directive",
+                draft.name, draft.line
+            )));
+        }
+        let Some(emit) = draft.emit else {
+            return Err(parse_error(format!(
+                "rule {} (line {}): missing emit directive",
+                draft.name, draft.line
+            )));
+        };
+        let conditions: Vec<&str> = draft.conditions.iter().map(String::as_str).collect();
+        QueryRule::new(&draft.name, &draft.query, &conditions, &emit)
+    }
+
+    let mut rules: Vec<QueryRule<T>> = vec![];
+    let mut current: Option<(Draft, Option<Directive>)> = None;
+
+    for (index, line) in src.lines().enumerate() {
+        let line_number = index + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+
+        let (keyword, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .map(|(k, r)| (k, r.trim()))
+            .unwrap_or((trimmed, ""));
+
+        if keyword == "rule" {
+            if let Some((draft, _)) = current.take() {
+                rules.push(finish(draft)?);
+            }
+            if rest.is_empty() || rest.contains(char::is_whitespace) {
+                return Err(parse_error(format!(
+                    "line {}: expected a single rule name",
+                    line_number
+                )));
+            }
+            if rules.iter().any(|r| r.name.eq_ignore_ascii_case(rest)) {
+                return Err(parse_error(format!(
+                    "line {}: duplicated rule name {}",
+                    line_number, rest
+                )));
+            }
+            if T::builtin_rules()
+                .iter()
+                .any(|r| r.name.eq_ignore_ascii_case(rest))
+            {
+                return Err(parse_error(format!(
+                    "line {}: {} is the name of a built-in rule",
+                    line_number, rest
+                )));
+            }
+            current = Some((
+                Draft {
+                    name: rest.to_string(),
+                    line: line_number,
+                    ..Default::default()
+                },
+                None,
+            ));
+            continue;
+        }
+
+        let Some((draft, directive)) = current.as_mut() else {
+            return Err(parse_error(format!(
+                "line {}: expected a rule directive",
+                line_number
+            )));
+        };
+
+        match keyword {
+            "match" => {
+                draft.query.push_str(rest);
+                *directive = Some(Directive::Match);
+            }
+            "where" => {
+                draft.conditions.push(rest.to_string());
+                *directive = Some(Directive::Where);
+            }
+            "emit" => {
+                if draft.emit.is_some() {
+                    return Err(parse_error(format!(
+                        "line {}: rule {} has more than one emit directive",
+                        line_number, draft.name
+                    )));
+                }
+                draft.emit = Some(rest.to_string());
+                *directive = Some(Directive::Emit);
+            }
+            _ => {
+                let target = match directive {
+                    Some(Directive::Match) => Some(&mut draft.query),
+                    Some(Directive::Where) => draft.conditions.last_mut(),
+                    Some(Directive::Emit) => draft.emit.as_mut(),
+                    None => None,
+                };
+                let Some(target) = target else {
+                    return Err(parse_error(format!(
+                        "line {}: unexpected '{}'",
+                        line_number, keyword
+                    )));
+                };
+                target.push('\n');
+                target.push_str(trimmed);
+            }
+        }
+    }
+
+    if let Some((draft, _)) = current.take() {
+        rules.push(finish(draft)?);
+    }
+
+    Ok(rules)
+}
+
+/// Load a set of query rules from a file
+pub fn load_query_rules<T: QueryLanguage>(
+    path: impl AsRef<std::path::Path>,
+) -> MinusOneResult<Vec<QueryRule<T>>> {
+    let path = path.as_ref();
+    let src = std::fs::read_to_string(path).map_err(|e| {
+        Error::new(
+            MinusOneErrorKind::Unknown,
+            &format!("Unable to read rule file {}: {}", path.display(), e),
+        )
+    })?;
+    parse_query_rules(&src)
+}
@@ -1,6 +1,8 @@
 use crate::error::MinusOneResult;
 use crate::tree::{ControlFlow, Node, NodeMut};
 use log::warn;
+use std::fmt::Display;
+use std::str::FromStr;
 
 pub trait RuleMut<'a> {
     type Language;
@@ -29,12 +31,12 @@ pub trait Rule<'a> {
 pub struct RuleSet<'a, T> {
     // keeps the original (non-lowercased) rule name alongside each rule,
     // so callers can report which rule fired without re-deriving casing
-    rules: Vec<(&'a str, Box<dyn RuleMut<'a, Language = T>>)>,
+    rules: Vec<(&'a str, Box<dyn RuleMut<'a, Language = T> + 'a>)>,
 }
 
 impl<'a, T> RuleSet<'a, T> {
     pub fn new(
-        full_ruleset: Vec<(&'a str, Box<dyn RuleMut<'a, Language = T>>)>,
+        full_ruleset: Vec<(&'a str, Box<dyn RuleMut<'a, Language = T> + 'a>)>,
         ctx: RuleSetBuilderType,
     ) -> Self {
         let low_names: Vec<String> = full_ruleset.iter().map(|(n, _)| n.to_lowercase()).collect();
@@ -76,6 +78,188 @@ pub enum RuleSetBuilderType<'a> {
     WithoutRules(Vec<&'a str>),
 }
 
+impl<'a> RuleSetBuilderType<'a> {
+    /// Replace each `@category` entry by the name of the rules of this category
+    ///
+    /// # Example
+    /// ```
+    /// use minusone::rule::{RuleCategory, RuleInfo, RuleSafety, RuleSetBuilderType};
+    ///
+    /// let rules = [RuleInfo {
+    ///     name: "DecodeBase64",
+    ///     description: "Decode calls to FromBase64",
+    ///     category: RuleCategory::Encoding,
+    ///     node_kinds: &["invokation_expression"],
+    ///     safety: RuleSafety::Pure,
+    ///     default_enabled: true,
+    /// }];
+    ///
+    /// let RuleSetBuilderType::WithoutRules(names) =
+    ///     RuleSetBuilderType::WithoutRules(vec!["@encoding", "Forward"]).expand_categories(&rules)
+    /// else {
+    ///     unreachable!()
+    /// };
+    /// assert_eq!(names, vec!["DecodeBase64", "Forward"]);
+    /// ```
+    pub fn expand_categories(self, rules: &[RuleInfo]) -> Self {
+        match self {
+            RuleSetBuilderType::WithRules(names) => {
+                RuleSetBuilderType::WithRules(expand_rule_names(names, rules))
+            }
+            RuleSetBuilderType::WithoutRules(names) => {
+                RuleSetBuilderType::WithoutRules(expand_rule_names(names, rules))
+            }
+        }
+    }
+}
+
+/// Replace each `@category` entry by the names of its rules,
+/// unknown categories are skipped
+pub fn expand_rule_names<'a>(names: Vec<&'a str>, rules: &[RuleInfo]) -> Vec<&'a str> {
+    names
+        .into_iter()
+        .flat_map(|name| match name.strip_prefix('@') {
+            Some(category) => match category.parse::<RuleCategory>() {
+                Ok(category) => rules
+                    .iter()
+                    .filter(|rule| rule.category == category)
+                    .map(|rule| rule.name)
+                    .collect(),
+                Err(e) => {
+                    warn!("{}, skipping", e);
+                    vec![]
+                }
+            },
+            None => vec![name],
+        })
+        .collect()
+}
+
+/// What a rule works on
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum RuleCategory {
+    /// Literals, and values forwarded through transparent nodes
+    Parsing,
+    Arithmetic,
+    /// Booleans, comparisons and logical operators
+    Logic,
+    Strings,
+    Arrays,
+    /// Objects, hashtables, member access and typing
+    Objects,
+    Variables,
+    /// Base64, text encodings and number conversions
+    Encoding,
+    Crypto,
+    Compression,
+    Regex,
+    /// Cmdlets and pipelines
+    Commands,
+    ControlFlow,
+    /// Functions and script blocks, declared and called
+    Functions,
+}
+
+impl RuleCategory {
+    pub const ALL: [RuleCategory; 14] = [
+        RuleCategory::Parsing,
+        RuleCategory::Arithmetic,
+        RuleCategory::Logic,
+        RuleCategory::Strings,
+        RuleCategory::Arrays,
+        RuleCategory::Objects,
+        RuleCategory::Variables,
+        RuleCategory::Encoding,
+        RuleCategory::Crypto,
+        RuleCategory::Compression,
+        RuleCategory::Regex,
+        RuleCategory::Commands,
+        RuleCategory::ControlFlow,
+        RuleCategory::Functions,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuleCategory::Parsing => "parsing",
+            RuleCategory::Arithmetic => "arithmetic",
+            RuleCategory::Logic => "logic",
+            RuleCategory::Strings => "strings",
+            RuleCategory::Arrays => "arrays",
+            RuleCategory::Objects => "objects",
+            RuleCategory::Variables => "variables",
+            RuleCategory::Encoding => "encoding",
+            RuleCategory::Crypto => "crypto",
+            RuleCategory::Compression => "compression",
+            RuleCategory::Regex => "regex",
+            RuleCategory::Commands => "commands",
+            RuleCategory::ControlFlow => "control-flow",
+            RuleCategory::Functions => "functions",
+        }
+    }
+}
+
+impl Display for RuleCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for RuleCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_lowercase().replace('_', "-");
+        RuleCategory::ALL
+            .into_iter()
+            .find(|category| category.name() == name)
+            .ok_or_else(|| format!("Unknown rule category: '{}'", s))
+    }
+}
+
+/// How far a rule goes to infer a value
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum RuleSafety {
+    /// Folds constants and known values
+    Pure,
+    /// Runs logic written in the script, like a function call or a loop body
+    Evaluates,
+}
+
+impl RuleSafety {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuleSafety::Pure => "pure",
+            RuleSafety::Evaluates => "evaluates",
+        }
+    }
+}
+
+impl Display for RuleSafety {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Description of a built-in rule of a language
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RuleInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub category: RuleCategory,
+    /// Kinds of the nodes the rule infers a value for
+    pub node_kinds: &'static [&'static str],
+    pub safety: RuleSafety,
+    /// Part of the rule set used when no rule is given
+    pub default_enabled: bool,
+}
+
+impl RuleInfo {
+    /// The rule runs logic written in the script
+    pub fn executes_code(&self) -> bool {
+        self.safety == RuleSafety::Evaluates
+    }
+}
+
 impl<'a, T> RuleMut<'a> for RuleSet<'a, T> {
     type Language = T;
 
@@ -86,7 +270,7 @@ impl<'a, T> RuleMut<'a> for RuleSet<'a, T> {
     ) -> MinusOneResult<()> {
         self.rules
             .iter_mut()
-            .try_for_each(|(_, r)| r.enter(node, flow))
+            .try_for_each(|(name, r)| r.enter(node, flow).map_err(|e| e.in_rule(name)))
     }
 
     fn leave(
@@ -96,7 +280,7 @@ impl<'a, T> RuleMut<'a> for RuleSet<'a, T> {
     ) -> MinusOneResult<()> {
         self.rules
             .iter_mut()
-            .try_for_each(|(_, r)| r.leave(node, flow))
+            .try_for_each(|(name, r)| r.leave(node, flow).map_err(|e| e.in_rule(name)))
     }
 }
 
@@ -137,7 +321,10 @@ impl<'a, T: Clone + PartialEq> RuleSet<'a, T> {
             };
 
             let name = self.rules[i].0;
-            self.rules[i].1.leave(node, flow)?;
+            self.rules[i]
+                .1
+                .leave(node, flow)
+                .map_err(|e| e.in_rule(name))?;
 
             let after = node.view().data().cloned();
             let changed = match (&before, &after) {
@@ -296,4 +483,24 @@ mod impl_data {
     impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV);
     impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW);
     impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE, BF);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE, BF, BG);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE, BF, BG, BH);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE, BF, BG, BH, BI);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE, BF, BG, BH, BI, BJ);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE, BF, BG, BH, BI, BJ, BK);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE, BF, BG, BH, BI, BJ, BK, BL);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE, BF, BG, BH, BI, BJ, BK, BL, BM);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE, BF, BG, BH, BI, BJ, BK, BL, BM, BN);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE, BF, BG, BH, BI, BJ, BK, BL, BM, BN, BO);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE, BF, BG, BH, BI, BJ, BK, BL, BM, BN, BO, BP);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE, BF, BG, BH, BI, BJ, BK, BL, BM, BN, BO, BP, BQ);
+    impl_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, AB, AC, AD, AE, AF, AG, AH, AI, AJ, AK, AL, AM, AN, AO, AP, AQ, AR, AS, AT, AU, AV, AW, AX, AY, AZ, BA, BB, BC, BD, BE, BF, BG, BH, BI, BJ, BK, BL, BM, BN, BO, BP, BQ, BR);
 }
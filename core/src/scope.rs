@@ -1,3 +1,4 @@
+use log::warn;
 use std::collections::HashMap;
 
 #[derive(Clone)]
@@ -138,7 +139,14 @@ impl<T: Clone> ScopeManager<T> {
     }
 
     pub fn leave(&mut self) {
-        let mut last = self.scopes.pop().unwrap();
+        // the global scope is never left, even by an unbalanced tree
+        if self.scopes.len() < 2 {
+            warn!("Leaving the global scope, ignored");
+            return;
+        }
+        let Some(mut last) = self.scopes.pop() else {
+            return;
+        };
 
         // we will merge the pending scope of transcation
         for (name, value) in last.pending.iter_mut() {
@@ -167,6 +175,10 @@ impl<T: Clone> ScopeManager<T> {
     /// `leave_function()` != `leave()`: nothing from the function's scope is merged
     /// back into the parent, function or block scoped alike.
     pub fn leave_function(&mut self) {
+        if self.scopes.len() < 2 {
+            warn!("Leaving the global scope, ignored");
+            return;
+        }
         self.scopes.pop();
     }
 
@@ -199,3 +211,32 @@ impl<T: Clone> ScopeManager<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unbalanced_leave_keeps_global_scope() {
+        let mut scopes = ScopeManager::<i64>::default();
+        scopes.current_mut().assign("a", 1, false);
+
+        scopes.leave();
+        scopes.leave_function();
+
+        assert_eq!(scopes.current().get_var("a"), Some(&1));
+        scopes.current_mut().assign("b", 2, false);
+        assert_eq!(scopes.current().get_var("b"), Some(&2));
+    }
+
+    #[test]
+    fn test_leave_after_enter() {
+        let mut scopes = ScopeManager::<i64>::default();
+        scopes.enter();
+        scopes.current_mut().assign("a", 1, false);
+        scopes.leave();
+        scopes.leave();
+
+        assert_eq!(scopes.current().get_var("a"), None);
+    }
+}
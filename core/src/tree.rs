@@ -1,4 +1,4 @@
-use crate::error::{Error, MinusOneResult};
+use crate::error::{Error, MinusOneResult, ParseError};
 use crate::rule::{Rule, RuleMut};
 use log::{error, trace, warn};
 use std::collections::HashMap;
@@ -7,6 +7,11 @@ use std::str::Utf8Error;
 use tree_sitter::{Node as TreeNode, Tree as TreeSitter};
 use tree_sitter_traversal2::{Order, traverse};
 
+/// Attach the node a rule was visiting to the error it raised
+fn located(node: TreeNode) -> impl FnOnce(Error) -> Error {
+    move |e| e.at_node(node.kind(), node.byte_range())
+}
+
 /// Node components are stored following
 /// a storage pattern
 pub trait Storage {
@@ -360,6 +365,18 @@ impl<'a, T> NodeMut<'a, T> {
         Node::new(self.inner, self.source, self.storage)
     }
 
+    /// A const view of any tree-sitter node of the same tree
+    /// Use to read data of nodes found without navigating
+    /// from the current node, like query captures
+    pub fn view_of(&self, node: TreeNode<'a>) -> Node<'_, T> {
+        Node::new(node, self.source, self.storage)
+    }
+
+    /// Reference to the original source code
+    pub fn source(&self) -> &'a [u8] {
+        self.source
+    }
+
     /// Set a data to a node
     ///
     /// # Example
@@ -519,7 +536,8 @@ impl<'a, T> NodeMut<'a, T> {
 
             stack.push((node, node.child_count()));
 
-            rule.enter(self, ControlFlow::Continue(BranchFlow::Unpredictable))?;
+            rule.enter(self, ControlFlow::Continue(BranchFlow::Unpredictable))
+                .map_err(located(node))?;
 
             // clean stack
             loop {
@@ -538,7 +556,8 @@ impl<'a, T> NodeMut<'a, T> {
 
                 self.inner = head_element.0;
 
-                rule.leave(self, ControlFlow::Continue(BranchFlow::Unpredictable))?;
+                rule.leave(self, ControlFlow::Continue(BranchFlow::Unpredictable))
+                    .map_err(located(head_element.0))?;
 
                 stack.pop();
 
@@ -622,7 +641,8 @@ impl<'a, T> NodeMut<'a, T> {
             return Ok(());
         }
 
-        rule.enter(self, computed_flow)?;
+        rule.enter(self, computed_flow)
+            .map_err(located(self.inner))?;
 
         let mut cursor = self.inner.walk();
         let current_node = self.inner;
@@ -632,7 +652,8 @@ impl<'a, T> NodeMut<'a, T> {
         }
 
         self.inner = current_node;
-        rule.leave(self, computed_flow)?;
+        rule.leave(self, computed_flow)
+            .map_err(located(self.inner))?;
         Ok(())
     }
 
@@ -726,7 +747,7 @@ impl<'a, T> NodeMut<'a, T> {
             control_flow = control_flow | strategy.control(self.view())?;
 
             if control_flow != ControlFlow::Break {
-                rule.enter(self, control_flow)?;
+                rule.enter(self, control_flow).map_err(located(node))?;
             }
 
             // clean stack
@@ -747,7 +768,8 @@ impl<'a, T> NodeMut<'a, T> {
                 self.inner = head_element.0;
 
                 if control_flow != ControlFlow::Break {
-                    rule.leave(self, control_flow)?;
+                    rule.leave(self, control_flow)
+                        .map_err(located(head_element.0))?;
                 }
 
                 control_flow = head_element.2;
@@ -922,8 +944,10 @@ impl<'a, T> Node<'a, T> {
         for node in traverse(self.node.walk(), Order::Pre) {
             stack.push((node, node.child_count(), is_visiting));
             if is_visiting {
-                is_visiting =
-                    is_visiting && rule.enter(&Node::new(node, self.source, self.storage))?;
+                is_visiting = is_visiting
+                    && rule
+                        .enter(&Node::new(node, self.source, self.storage))
+                        .map_err(located(node))?;
             }
 
             // clean stack
@@ -942,7 +966,8 @@ impl<'a, T> Node<'a, T> {
                 }
 
                 if is_visiting {
-                    rule.leave(&Node::new(head_element.0, self.source, self.storage))?;
+                    rule.leave(&Node::new(head_element.0, self.source, self.storage))
+                        .map_err(located(head_element.0))?;
                 }
 
                 is_visiting = head_element.2;
@@ -1106,4 +1131,50 @@ where
             &self.storage,
         ))
     }
+
+    /// List the regions of the source the parser failed on
+    ///
+    /// # Example
+    /// ```
+    /// use minusone::js::build_javascript_tree;
+    ///
+    /// let tree = build_javascript_tree("var a = 1; )))) var b = 2;").unwrap();
+    /// let errors = tree.parse_errors();
+    ///
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].context.as_deref(), Some("program"));
+    /// ```
+    pub fn parse_errors(&self) -> Vec<ParseError> {
+        let mut errors = vec![];
+        let mut stack = vec![self.tree_sitter.root_node()];
+
+        while let Some(node) = stack.pop() {
+            if node.is_error() || node.is_missing() {
+                errors.push(ParseError {
+                    start: node.start_byte(),
+                    end: node.end_byte(),
+                    context: node.parent().map(|parent| parent.kind().to_string()),
+                    missing: node.is_missing().then(|| node.kind().to_string()),
+                });
+            } else if node.has_error() {
+                // reverse order to report errors following the source order
+                stack.extend(
+                    (0..node.child_count())
+                        .rev()
+                        .filter_map(|index| node.child(index)),
+                );
+            }
+        }
+        errors
+    }
+
+    /// A tree is processable as soon as the parser
+    /// has recognized at least one well-formed construct
+    pub fn is_processable(&self) -> bool {
+        let root = self.tree_sitter.root_node();
+        !root.has_error()
+            || traverse(root.walk(), Order::Pre).any(|node| {
+                node.is_named() && !node.has_error() && !node.is_missing() && node.child_count() > 0
+            })
+    }
 }
@@ -0,0 +1,223 @@
+//! Regression corpus of the samples directory
+//!
+//! Each `<name>.obf.<ext>` sample is deobfuscated with the default rule set of the
+//! language of its extension, and compared with its golden output:
+//! - `<name>.expected`, the blessed output of minusone, compared exactly
+//! - or `<name>.src.<ext>`, the original script, compared once normalised
+//!
+//! Run with `cargo test -p minusone --features integration --test samples`,
+//! and set `MINUSONE_BLESS=1` to write the `.expected` file of every sample instead
+#![cfg(feature = "integration")]
+
+use minusone::engine::{DeobfuscateEngine, DeobfuscationBackend};
+use minusone::error::MinusOneResult;
+use minusone::js::backend::JavaScriptBackend;
+use minusone::ps::backend::PowershellBackend;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy)]
+enum Language {
+    Powershell,
+    JavaScript,
+}
+
+impl Language {
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "ps1" | "psm1" | "psd1" => Some(Language::Powershell),
+            "js" => Some(Language::JavaScript),
+            _ => None,
+        }
+    }
+}
+
+struct Sample {
+    name: String,
+    language: Language,
+    obfuscated: PathBuf,
+    expected: PathBuf,
+    source: Option<PathBuf>,
+}
+
+fn samples_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("samples")
+}
+
+/// Every `<name>.obf.<ext>` file of a directory and its sub directories
+fn discover(dir: &Path, samples: &mut Vec<Sample>) {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("Failed to read {dir:?}: {e}"))
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            discover(&path, samples);
+            continue;
+        }
+        let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+        let Some((name, extension)) = file_name.rsplit_once('.') else {
+            continue;
+        };
+        let (Some(name), Some(language)) = (
+            name.strip_suffix(".obf"),
+            Language::from_extension(extension),
+        ) else {
+            continue;
+        };
+
+        let source = dir.join(format!("{name}.src.{extension}"));
+        samples.push(Sample {
+            name: path
+                .strip_prefix(samples_dir())
+                .unwrap_or(&path)
+                .display()
+                .to_string(),
+            language,
+            obfuscated: path.clone(),
+            expected: dir.join(format!("{name}.expected")),
+            source: source.is_file().then_some(source),
+        });
+    }
+}
+
+fn deobfuscate_with<B: DeobfuscationBackend>(source: &str) -> MinusOneResult<String> {
+    let cleaned = DeobfuscateEngine::<B>::remove_extra(source, false)?;
+    let mut engine = DeobfuscateEngine::<B>::from_source(&cleaned)?;
+    engine.deobfuscate()?;
+    engine.lint(false)
+}
+
+fn deobfuscate(language: Language, source: &str) -> MinusOneResult<String> {
+    match language {
+        Language::Powershell => deobfuscate_with::<PowershellBackend>(source),
+        Language::JavaScript => deobfuscate_with::<JavaScriptBackend>(source),
+    }
+}
+
+/// Layout and quoting left aside, and case for Powershell
+fn normalise(language: Language, script: &str) -> String {
+    let lines: Vec<String> = script
+        .lines()
+        .map(|line| {
+            let line = line
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .replace('"', "'");
+            let line = line.trim_end_matches(';').to_string();
+            match language {
+                Language::Powershell => line.to_lowercase(),
+                Language::JavaScript => line,
+            }
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+    lines.join("\n")
+}
+
+/// Line diff between the golden and the actual output, from their longest common subsequence
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    let mut common = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            out.push_str(&format!("  {}\n", expected[i]));
+            i += 1;
+            j += 1;
+        } else if j < actual.len() && (i == expected.len() || common[i][j + 1] >= common[i + 1][j])
+        {
+            out.push_str(&format!("+ {}\n", actual[j]));
+            j += 1;
+        } else {
+            out.push_str(&format!("- {}\n", expected[i]));
+            i += 1;
+        }
+    }
+    out
+}
+
+#[test]
+fn test_samples() {
+    let bless = std::env::var_os("MINUSONE_BLESS").is_some();
+    let mut samples = vec![];
+    discover(&samples_dir(), &mut samples);
+
+    let mut failures = vec![];
+    let mut compared = 0;
+    for sample in &samples {
+        let obfuscated = std::fs::read_to_string(&sample.obfuscated)
+            .unwrap_or_else(|e| panic!("Failed to read {:?}: {e}", sample.obfuscated));
+        let actual = match deobfuscate(sample.language, &obfuscated) {
+            Ok(actual) => actual,
+            Err(e) => {
+                failures.push(format!("{}: deobfuscation failed: {e}", sample.name));
+                continue;
+            }
+        };
+
+        if bless {
+            std::fs::write(&sample.expected, format!("{}\n", actual.trim_end()))
+                .unwrap_or_else(|e| panic!("Failed to write {:?}: {e}", sample.expected));
+            println!("blessed {}", sample.expected.display());
+            continue;
+        }
+
+        let (expected, actual, mode) = if sample.expected.is_file() {
+            let expected = std::fs::read_to_string(&sample.expected).unwrap();
+            (
+                expected.trim_end().to_string(),
+                actual.trim_end().to_string(),
+                "exact",
+            )
+        } else if let Some(source) = &sample.source {
+            let expected = std::fs::read_to_string(source).unwrap();
+            (
+                normalise(sample.language, &expected),
+                normalise(sample.language, &actual),
+                "normalised",
+            )
+        } else {
+            println!(
+                "{}: no golden output, bless it with MINUSONE_BLESS=1",
+                sample.name
+            );
+            continue;
+        };
+
+        compared += 1;
+        if expected != actual {
+            failures.push(format!(
+                "{}: output differs from the golden one ({mode}):\n{}",
+                sample.name,
+                diff(&expected, &actual)
+            ));
+        }
+    }
+
+    println!("{} samples, {} compared", samples.len(), compared);
+    assert!(
+        failures.is_empty(),
+        "{} sample(s) failed:\n\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
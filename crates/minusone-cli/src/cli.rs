@@ -1,4 +1,6 @@
 use clap::{Parser, ValueEnum};
+use minusone::artefact::DEFAULT_ARTEFACT_THRESHOLD;
+use minusone::profile::RuleProfile;
 use std::fmt::Display;
 
 pub const APPLICATION_NAME: &str = env!("CARGO_PKG_NAME");
@@ -40,6 +42,11 @@ pub struct Cli {
     #[arg(long, short, value_name = "BASE64")]
     pub input: Option<String>,
 
+    /// powershell.exe command line, as logged by process creation events (PowerShell only).
+    /// A script given with -Command - is read from the standard input
+    #[arg(long, short, value_name = "COMMAND_LINE")]
+    pub cmdline: Option<String>,
+
     /// Debug indent size for the debug mode
     #[arg(long, default_value_t = 2, value_name = "INT")]
     pub debug_indent: u32,
@@ -64,14 +71,54 @@ pub struct Cli {
     #[arg(long, short = 'L', alias = "ls")]
     pub list: bool,
 
-    /// Custom comma separated list of rules to apply for the deobfuscation
+    /// Custom comma separated list of rules to apply for the deobfuscation,
+    /// @category stands for all the rules of a category, like @crypto
     #[arg(long, short, value_delimiter = ',')]
     pub rules: Option<Vec<String>>,
 
-    /// Custom comma separated list of rules to skip for the deobfuscation
+    /// Custom comma separated list of rules to skip for the deobfuscation,
+    /// @category stands for all the rules of a category, like @control-flow
     #[arg(long, short = 'R', value_delimiter = ',', value_name = "RULES")]
     pub skip_rules: Option<Vec<String>>,
 
+    /// Named set of rules and budgets: safe (constant folding only), default or aggressive,
+    /// --rules and --skip-rules add rules to it and remove rules from it
+    #[arg(long, short = 'P', value_name = "PROFILE")]
+    pub profile: Option<RuleProfile>,
+
+    /// Comma separated list of query rule files, applied after the language rules
+    #[arg(long, short = 'Q', value_delimiter = ',', value_name = "PATHS")]
+    pub query_rules: Option<Vec<String>>,
+
+    /// Print the obfuscation techniques found in the script and a score, without deobfuscating it
+    #[arg(long, short)]
+    pub analyze: bool,
+
+    /// Powershell host to emulate for environment and automatic variables: 5.1 or 7
+    #[arg(long, value_name = "VERSION")]
+    pub host: Option<String>,
+
+    /// User name of the emulated host
+    #[arg(long, value_name = "NAME")]
+    pub host_user: Option<String>,
+
+    /// Environment block of the emulated host, as printed by the `set` command
+    #[arg(long, value_name = "PATH")]
+    pub host_env: Option<String>,
+
+    /// Print what the deobfuscated script would do: downloads, persistence, AMSI bypass... (PowerShell only)
+    #[arg(long, short)]
+    pub behaviour: bool,
+
+    /// Dump the byte values found by the deobfuscation (PE, .NET assembly, shellcode...) to a directory,
+    /// named by their SHA-256, and reference them in the output
+    #[arg(long, value_name = "DIR")]
+    pub artefacts: Option<String>,
+
+    /// Minimum size of a byte value dumped by --artefacts
+    #[arg(long, default_value_t = DEFAULT_ARTEFACT_THRESHOLD, value_name = "BYTES", requires = "artefacts")]
+    pub artefact_size: usize,
+
     /// Show computation time for the deobfuscation process
     #[arg(long, short)]
     pub time: bool,
@@ -181,10 +228,46 @@ pub static EXAMPLES: &[Example] = &[
         title: "Deobfuscate skipping some rules",
         cmd: "minusone -l powershell --path obf_scr.ps1 --skip-rules rule1,rule2,rule3",
     },
+    Example {
+        title: "Deobfuscate without the control flow and function call rules",
+        cmd: "minusone -l javascript --path obf_scr.js --skip-rules @control-flow,FnCall",
+    },
+    Example {
+        title: "Deobfuscate without evaluating the script, except its function calls",
+        cmd: "minusone -l javascript --path obf_scr.js --profile safe --rules FnCall",
+    },
+    Example {
+        title: "Deobfuscate with every rule and higher evaluation budgets",
+        cmd: "minusone -l javascript --path obf_scr.js --profile aggressive",
+    },
+    Example {
+        title: "Deobfuscate with additional query rules",
+        cmd: "minusone -l powershell --path obf_scr.ps1 --query-rules family.rules",
+    },
+    Example {
+        title: "Score a script and list its obfuscation techniques",
+        cmd: "minusone -l powershell --path obf_scr.ps1 --analyze",
+    },
+    Example {
+        title: "Deobfuscate as if run by Powershell 7 under a specific user",
+        cmd: "minusone -l powershell --path obf_scr.ps1 --host 7 --host-user bob",
+    },
+    Example {
+        title: "Deobfuscate and summarize what the script would do",
+        cmd: "minusone -l powershell --path obf_scr.ps1 --behaviour",
+    },
+    Example {
+        title: "Deobfuscate and dump the embedded executables and shellcodes",
+        cmd: "minusone -l powershell --path obf_scr.ps1 --artefacts ./artefacts",
+    },
     Example {
         title: "Deobfuscate with the maximum debug information",
         cmd: "minusone -l powershell --path obf_scr.ps1 --debug --log-level trace",
     },
+    Example {
+        title: "Deobfuscate the script of a powershell command line",
+        cmd: "minusone -l powershell --cmdline \"cmd /c powershell -nop -enc VwByAGkAdABlAC0ASABvAHMAdAAgADEA\"",
+    },
     Example {
         title: "Deobfuscate from b64 input",
         cmd: "minusone -l javascript --input Y29uc29sZS5sb2coMDE3KQ",
@@ -12,9 +12,14 @@ use base64::{Engine as _, alphabet};
 use clap::{CommandFactory, Parser, ValueEnum};
 use clap_help::Printer;
 use cli::{Cli, INTRO, Language};
-use log::{LevelFilter, error, info};
+use log::{LevelFilter, error, info, warn};
+use minusone::engine::DeobfuscateEngine;
 use minusone::js::backend::JavaScriptBackend;
+use minusone::profile::RuleProfile;
 use minusone::ps::backend::PowershellBackend;
+use minusone::ps::cmdline::{CommandLine, ScriptSource};
+use minusone::rule::{RuleCategory, RuleInfo};
+use std::io::{self, Read};
 use std::{fs, process};
 use termimad::ansi;
 use utils::*;
@@ -86,27 +91,62 @@ fn main() {
     if cli.list {
         let rules = get_available_rules(lang);
         println!("Available rules for {}:", lang);
-        for rule in rules {
-            println!("- {}", rule);
+        for category in RuleCategory::ALL {
+            let rules: Vec<&RuleInfo> = rules
+                .iter()
+                .filter(|rule| rule.category == category)
+                .collect();
+            if rules.is_empty() {
+                continue;
+            }
+
+            println!("\n@{}", category);
+            for rule in rules {
+                println!(
+                    "- {}{}: {}",
+                    rule.name,
+                    if rule.executes_code() {
+                        " (evaluates code)"
+                    } else {
+                        ""
+                    },
+                    rule.description
+                );
+            }
+        }
+
+        println!("\nProfiles:");
+        for profile in RuleProfile::ALL {
+            println!("- {}: {}", profile, profile.description());
         }
 
         return;
     }
 
-    if cli.rules.is_some() && cli.skip_rules.is_some() {
-        error!("Cannot use --rules and --skip-rules at the same time");
+    if cli.rules.is_some() && cli.skip_rules.is_some() && cli.profile.is_none() {
+        error!("Cannot use --rules and --skip-rules at the same time without --profile");
         process::exit(1);
     }
 
-    if cli.path.is_none() && cli.input.is_none() {
+    let sources = [
+        cli.path.is_some(),
+        cli.input.is_some(),
+        cli.cmdline.is_some(),
+    ];
+    if !sources.contains(&true) {
         error!(
-            "No file path provided. Use --path to specify the script file or --input to specify a b64 encoded script"
+            "No file path provided. Use --path to specify the script file, --input to specify a b64 encoded script or --cmdline to specify a powershell command line"
         );
         process::exit(1);
     }
 
-    if cli.path.is_some() && cli.input.is_some() {
-        error!("Cannot use --path and --input at the same time");
+    if sources.iter().filter(|source| **source).count() > 1 {
+        error!("Cannot use --path, --input and --cmdline at the same time");
+        process::exit(1);
+    }
+
+    if cli.cmdline.is_some() && !matches!(lang, Language::Powershell) {
+        error!("--cmdline is only available for powershell");
         process::exit(1);
     }
 
@@ -146,10 +186,86 @@ fn main() {
                 }
             })
             .unwrap()
+    } else if let Some(line) = cli.cmdline {
+        let mut cmdline = CommandLine::parse(&line).unwrap_or_else(|e| {
+            error!("{}", describe_error(&e));
+            process::exit(1);
+        });
+        if cmdline.source == ScriptSource::Stdin && cmdline.script.is_none() {
+            info!("Reading the script of the command line from the standard input");
+            let mut stdin = String::new();
+            io::stdin().read_to_string(&mut stdin).unwrap_or_else(|e| {
+                error!("Failed to read the standard input: {}", e);
+                process::exit(1);
+            });
+            cmdline = cmdline.with_stdin(&stdin);
+        }
+        let (script, cmdline) = PowershellBackend::from_parsed_command_line(cmdline)
+            .unwrap_or_else(|e| {
+                error!("{}", describe_error(&e));
+                process::exit(1);
+            });
+        for wrapper in &cmdline.wrappers {
+            info!("Unwrapped {}", wrapper);
+        }
+        info!(
+            "Launched by {} {}",
+            cmdline.executable,
+            cmdline.flags.to_vec().join(" ")
+        );
+        script
     } else {
         unreachable!()
     };
 
+    if cli.analyze {
+        let report = match lang {
+            Language::Powershell => DeobfuscateEngine::<PowershellBackend>::analyze(&source),
+            Language::Javascript => DeobfuscateEngine::<JavaScriptBackend>::analyze(&source),
+        };
+
+        match report {
+            Ok(report) => print_report(&source, &report),
+            Err(e) => {
+                error!("{}", describe_error(&e));
+                process::exit(1);
+            }
+        }
+
+        return;
+    }
+
+    let host_profile = match cli_host_profile(&cli_clone) {
+        Ok(host_profile) => host_profile,
+        Err(e) => {
+            error!("{}", describe_error(&e));
+            process::exit(1);
+        }
+    };
+
+    if host_profile.is_some() && matches!(lang, Language::Javascript) {
+        warn!("Host profiles only apply to powershell; they will be ignored");
+    }
+
+    if cli.behaviour {
+        let result = match lang {
+            Language::Powershell => {
+                run_behaviour_ps(&source, cli.keep_dead_code, host_profile.as_ref())
+            }
+            Language::Javascript => {
+                error!("The behaviour summary is only available for powershell");
+                process::exit(1);
+            }
+        };
+
+        if let Err(e) = result {
+            error!("{}", describe_error(&e));
+            process::exit(1);
+        }
+
+        return;
+    }
+
     let rule_set = cli
         .rules
         .map(|vals| vals.into_iter().map(|s| s.to_lowercase()).collect());
@@ -168,13 +284,23 @@ fn main() {
             skip_rule_set,
             cli.keep_dead_code,
         ),
-        Language::Powershell => run_deobf::<PowershellBackend>(
-            &source,
-            cli_clone,
-            rule_set,
-            skip_rule_set,
-            cli.keep_dead_code,
-        ),
+        Language::Powershell => match &host_profile {
+            Some(profile) => run_deobf_ps_with_host(
+                &source,
+                cli_clone,
+                rule_set,
+                skip_rule_set,
+                cli.keep_dead_code,
+                profile,
+            ),
+            None => run_deobf::<PowershellBackend>(
+                &source,
+                cli_clone,
+                rule_set,
+                skip_rule_set,
+                cli.keep_dead_code,
+            ),
+        },
         Language::Javascript if cli.step => run_deobf_js_traced(
             &source,
             cli_clone,
@@ -197,7 +323,7 @@ fn main() {
     }
 
     if let Err(e) = result {
-        error!("{:?}", e);
+        error!("{}", describe_error(&e));
         process::exit(1);
     }
 }
@@ -1,13 +1,21 @@
 use crate::cli::{Cli, DebugLevel, Language, StepFormat};
 use crate::trace_view;
 use log::{info, warn};
+use minusone::artefact::{ArtefactLanguage, dump_artefacts};
 use minusone::debug::DebugView;
+use minusone::detect::Report;
 use minusone::engine::{DeobfuscateEngine, DeobfuscationBackend};
-use minusone::error::MinusOneResult;
+use minusone::error::{Error, MinusOneResult, ParseError};
 use minusone::js::backend::JavaScriptBackend;
+use minusone::profile::{RuleProfile, with_limits};
+use minusone::ps::Powershell;
 use minusone::ps::backend::PowershellBackend;
+use minusone::ps::host::{HostProfile, HostVersion};
+use minusone::query::{QueryLanguage, QueryRule, load_query_rules};
+use minusone::rule::{RuleInfo, RuleSetBuilderType};
 use minusone::trace::Step;
 use std::fmt::Debug;
+use std::path::Path;
 
 fn write_steps_output(source: &str, steps: &[Step], format: StepFormat, output: Option<&str>) {
     let (default_path, content) = match format {
@@ -26,6 +34,96 @@ fn write_steps_output(source: &str, steps: &[Step], format: StepFormat, output:
     }
 }
 
+/// Format an error along with the chain of its causes
+pub(crate) fn describe_error(e: &Error) -> String {
+    let mut message = e.to_string();
+    let mut cause = std::error::Error::source(e);
+    while let Some(inner) = cause {
+        message.push_str(": ");
+        message.push_str(&inner.to_string());
+        cause = inner.source();
+    }
+    format!("[{}] {}", e.kind(), message)
+}
+
+fn load_cli_query_rules<T: QueryLanguage>(cli: &Cli) -> MinusOneResult<Vec<QueryRule<T>>> {
+    let mut query_rules = vec![];
+    for path in cli.query_rules.iter().flatten() {
+        let rules = load_query_rules::<T>(path)?;
+        info!("Loaded {} query rule(s) from {}", rules.len(), path);
+        query_rules.extend(rules);
+    }
+    Ok(query_rules)
+}
+
+/// Rules selected by the command line, --rules and --skip-rules are applied to the profile if any
+fn rule_set_builder_type<'a>(
+    rules: &[RuleInfo],
+    profile: Option<RuleProfile>,
+    rule_set: &'a Option<Vec<String>>,
+    skip_rule_set: &'a Option<Vec<String>>,
+) -> RuleSetBuilderType<'a> {
+    let names = |set: &'a Option<Vec<String>>| -> Vec<&'a str> {
+        set.iter().flatten().map(AsRef::as_ref).collect()
+    };
+    if let Some(profile) = profile {
+        return profile.rule_set(rules, &names(rule_set), &names(skip_rule_set));
+    }
+
+    match (rule_set, skip_rule_set) {
+        (Some(_), _) => RuleSetBuilderType::WithRules(names(rule_set)),
+        (None, _) => RuleSetBuilderType::WithoutRules(names(skip_rule_set)),
+    }
+}
+
+/// Host profile selected by the command line, if any
+pub(crate) fn cli_host_profile(cli: &Cli) -> MinusOneResult<Option<HostProfile>> {
+    if cli.host.is_none() && cli.host_user.is_none() && cli.host_env.is_none() {
+        return Ok(None);
+    }
+
+    let version = match &cli.host {
+        Some(version) => version.parse::<HostVersion>()?,
+        None => HostVersion::default(),
+    };
+    let mut profile = HostProfile::new(version);
+    if let Some(user) = &cli.host_user {
+        profile = profile.with_user_name(user);
+    }
+    if let Some(path) = &cli.host_env {
+        profile = profile.with_env_block(&std::fs::read_to_string(path)?);
+        info!("Loaded host environment from {}", path);
+    }
+    Ok(Some(profile))
+}
+
+/// Lint the deobfuscated script, with its artefacts dumped and referenced when asked for
+fn lint_dumping_artefacts<B: DeobfuscationBackend>(
+    engine: &mut DeobfuscateEngine<B>,
+    cli: &Cli,
+    keep_dead_code: bool,
+) -> MinusOneResult<String>
+where
+    <B as DeobfuscationBackend>::Language: ArtefactLanguage,
+{
+    let Some(directory) = &cli.artefacts else {
+        return engine.lint(keep_dead_code);
+    };
+
+    let artefacts = engine.artefacts(cli.artefact_size)?;
+    let paths = dump_artefacts(&artefacts, Path::new(directory))?;
+    for (artefact, path) in artefacts.iter().zip(paths) {
+        info!(
+            "Dumped {} of {} bytes ({}) to {}",
+            artefact.kind,
+            artefact.data.len(),
+            artefact.kind.description(),
+            path.display()
+        );
+    }
+    engine.lint_with_artefacts("    ", keep_dead_code, cli.artefact_size)
+}
+
 pub(crate) fn run_deobf<B: DeobfuscationBackend>(
     source: &str,
     cli: Cli,
@@ -34,13 +132,23 @@ pub(crate) fn run_deobf<B: DeobfuscationBackend>(
     keep_dead_code: bool,
 ) -> MinusOneResult<()>
 where
-    <B as DeobfuscationBackend>::Language: Debug,
+    <B as DeobfuscationBackend>::Language: Debug + QueryLanguage + ArtefactLanguage,
 {
+    let query_rules = load_cli_query_rules::<B::Language>(&cli)?;
+
     let cleaned = DeobfuscateEngine::<B>::remove_extra(source, keep_dead_code)?;
 
     let mut engine = DeobfuscateEngine::<B>::from_source(&cleaned)?;
+    // ranges of the errors refer to the cleaned source the engine was built from
+    warn_parse_errors(&cleaned, &engine.parse_errors());
 
-    if let Some(rules) = rule_set {
+    let _limits = with_limits(cli.profile.unwrap_or_default().limits());
+    if !query_rules.is_empty() || cli.profile.is_some() {
+        engine.deobfuscate_with_query_rules(
+            rule_set_builder_type(&B::language_rules(), cli.profile, &rule_set, &skip_rule_set),
+            &query_rules,
+        )?;
+    } else if let Some(rules) = rule_set {
         engine.deobfuscate_with_custom_ruleset(rules.iter().map(AsRef::as_ref).collect())?;
     } else if let Some(skip_rules) = skip_rule_set {
         engine
@@ -61,7 +169,56 @@ where
         println!("\n\n");
     }
 
-    println!("{}", engine.lint(keep_dead_code)?);
+    println!(
+        "{}",
+        lint_dumping_artefacts(&mut engine, &cli, keep_dead_code)?
+    );
+    Ok(())
+}
+
+pub(crate) fn run_deobf_ps_with_host(
+    source: &str,
+    cli: Cli,
+    rule_set: Option<Vec<String>>,
+    skip_rule_set: Option<Vec<String>>,
+    keep_dead_code: bool,
+    profile: &HostProfile,
+) -> MinusOneResult<()> {
+    let query_rules = load_cli_query_rules::<Powershell>(&cli)?;
+
+    let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(source, keep_dead_code)?;
+
+    let mut engine = DeobfuscateEngine::<PowershellBackend>::from_source(&cleaned)?;
+    // ranges of the errors refer to the cleaned source the engine was built from
+    warn_parse_errors(&cleaned, &engine.parse_errors());
+    let _limits = with_limits(cli.profile.unwrap_or_default().limits());
+    engine.deobfuscate_with_host_profile(
+        rule_set_builder_type(
+            &PowershellBackend::language_rules(),
+            cli.profile,
+            &rule_set,
+            &skip_rule_set,
+        ),
+        &query_rules,
+        profile,
+    )?;
+
+    if cli.debug_level == DebugLevel::Debug || cli.debug_level == DebugLevel::Trace {
+        let debug_view = DebugView::new(
+            cli.debug_indent,
+            !cli.debug_no_text,
+            !cli.debug_no_count,
+            !cli.debug_no_colors,
+        );
+        engine.debug(Some(debug_view));
+
+        println!("\n\n");
+    }
+
+    println!(
+        "{}",
+        lint_dumping_artefacts(&mut engine, &cli, keep_dead_code)?
+    );
     Ok(())
 }
 
@@ -72,9 +229,15 @@ pub(crate) fn run_deobf_js_traced(
     skip_rule_set: Option<Vec<String>>,
     keep_dead_code: bool,
 ) -> MinusOneResult<()> {
-    if rule_set.is_some() || skip_rule_set.is_some() {
+    if rule_set.is_some() || skip_rule_set.is_some() || cli.profile.is_some() {
         warn!("Custom rule selection is not supported in trace mode; running the full ruleset");
     }
+    if cli.query_rules.is_some() {
+        warn!("Query rules are not supported in trace mode; they will be ignored");
+    }
+    if cli.artefacts.is_some() {
+        warn!("Artefacts are not dumped in trace mode");
+    }
 
     let (cleaned, mut steps) =
         JavaScriptBackend::remove_extra_traced(source, keep_dead_code, cli.step_all)?;
@@ -112,9 +275,18 @@ pub(crate) fn run_deobf_ps_traced(
     skip_rule_set: Option<Vec<String>>,
     keep_dead_code: bool,
 ) -> MinusOneResult<()> {
-    if rule_set.is_some() || skip_rule_set.is_some() {
+    if rule_set.is_some() || skip_rule_set.is_some() || cli.profile.is_some() {
         warn!("Custom rule selection is not supported in trace mode; running the full ruleset");
     }
+    if cli.query_rules.is_some() {
+        warn!("Query rules are not supported in trace mode; they will be ignored");
+    }
+    if cli.artefacts.is_some() {
+        warn!("Artefacts are not dumped in trace mode");
+    }
+    if cli.host.is_some() || cli.host_user.is_some() || cli.host_env.is_some() {
+        warn!("Host profiles are not supported in trace mode; the default host is emulated");
+    }
 
     let (cleaned, mut steps) = PowershellBackend::remove_extra_traced(source, cli.step_all)?;
     let mut engine = DeobfuscateEngine::<PowershellBackend>::from_source(&cleaned)?;
@@ -144,11 +316,112 @@ pub(crate) fn run_deobf_ps_traced(
     Ok(())
 }
 
-pub(crate) fn get_available_rules(language: Language) -> Vec<String> {
-    let rules = match language {
+pub(crate) fn run_behaviour_ps(
+    source: &str,
+    keep_dead_code: bool,
+    profile: Option<&HostProfile>,
+) -> MinusOneResult<()> {
+    let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(source, keep_dead_code)?;
+    let mut engine = DeobfuscateEngine::<PowershellBackend>::from_source(&cleaned)?;
+    match profile {
+        Some(profile) => engine.deobfuscate_with_host_profile(
+            RuleSetBuilderType::WithoutRules(vec![]),
+            &[],
+            profile,
+        )?,
+        None => engine.deobfuscate()?,
+    }
+
+    println!("{}", engine.lint(keep_dead_code)?);
+
+    let behaviours = engine.behaviours()?;
+    if behaviours.is_empty() {
+        println!("\nNo behaviour found");
+        return Ok(());
+    }
+
+    println!("\nBehaviours:");
+    for behaviour in behaviours {
+        let arguments: Vec<String> = behaviour
+            .arguments
+            .iter()
+            .map(|argument| match &argument.name {
+                Some(name) => format!("-{} {}", name, argument.value),
+                None => argument.value.clone(),
+            })
+            .collect();
+        println!(
+            "- {} {}: {}",
+            behaviour.kind,
+            behaviour.api,
+            arguments.join(" ")
+        );
+    }
+    Ok(())
+}
+
+pub(crate) fn get_available_rules(language: Language) -> Vec<RuleInfo> {
+    match language {
         Language::Powershell => DeobfuscateEngine::<PowershellBackend>::language_rules(),
         Language::Javascript => DeobfuscateEngine::<JavaScriptBackend>::language_rules(),
-    };
+    }
+}
 
-    rules.into_iter().map(String::from).collect()
+/// line and column (both starting at 1) of a byte offset
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source.as_bytes()[..offset.min(source.len())];
+    let line = before.iter().filter(|&&c| c == b'\n').count() + 1;
+    let column = offset
+        - before
+            .iter()
+            .rposition(|&c| c == b'\n')
+            .map_or(0, |i| i + 1)
+        + 1;
+    (line, column)
+}
+
+fn warn_parse_errors(source: &str, errors: &[ParseError]) {
+    for parse_error in errors {
+        let (line, column) = line_column(source, parse_error.start);
+        match &parse_error.missing {
+            Some(token) => warn!("{}:{}: missing '{}'", line, column, token),
+            None => warn!(
+                "{}:{}: unable to parse {} bytes in {}, kept as is",
+                line,
+                column,
+                parse_error.end - parse_error.start,
+                parse_error.context.as_deref().unwrap_or("source")
+            ),
+        }
+    }
+}
+
+pub(crate) fn print_report(source: &str, report: &Report) {
+    println!("Obfuscation score: {}/100", report.score());
+
+    if report.findings.is_empty() {
+        println!("No obfuscation technique found");
+        return;
+    }
+
+    println!("\nTechniques:");
+    for (technique, count) in report.techniques() {
+        println!("- {} ({}): {}", technique, count, technique.description());
+    }
+
+    println!("\nFindings:");
+    for finding in &report.findings {
+        let (line, column) = line_column(source, finding.start);
+        let snippet: String =
+            String::from_utf8_lossy(&source.as_bytes()[finding.start..finding.end])
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" ");
+        let snippet = if snippet.chars().count() > 60 {
+            format!("{}...", snippet.chars().take(57).collect::<String>())
+        } else {
+            snippet
+        };
+        println!("- {}:{} {} {}", line, column, finding.technique, snippet);
+    }
 }
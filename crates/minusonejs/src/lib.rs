@@ -1,73 +1,98 @@
 extern crate minusone;
 
+use minusone::artefact::{ArtefactLanguage, DEFAULT_ARTEFACT_THRESHOLD};
+use minusone::detect::Report;
+use minusone::engine::DeobfuscateEngine;
 use minusone::engine::DeobfuscationBackend;
+use minusone::error::{Error as MinusoneError, MinusOneErrorKind, MinusOneResult};
 use minusone::js::backend::JavaScriptBackend;
+use minusone::language::{ScriptLanguage, detect_language};
+use minusone::profile::{Limits, RuleProfile, with_limits};
 use minusone::ps::backend::PowershellBackend;
+use minusone::ps::behaviour::{Behaviour, summarize_powershell};
+use minusone::query::{QueryLanguage, QueryValue};
 use minusone::trace::Stepper as CoreStepper;
-use minusone::{engine::DeobfuscateEngine, error::Error as MinusoneError};
+use minusone::tree::{HashMapStorage, Node, Tree};
 use std::cell::RefCell;
-use std::fmt::{Debug, Display};
-
-enum MinusonejsError {
-    MinusoneError(MinusoneError),
-    JsError(String),
-}
-
-impl Display for MinusonejsError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let str = match self {
-            MinusonejsError::JsError(e) => e.clone(),
-            MinusonejsError::MinusoneError(e) => match e {
-                MinusoneError::MinusOneError(inner) => inner.message.clone(),
-                MinusoneError::Utf8Error(u) => u.to_string(),
-            },
-        };
-        write!(f, "{}", str)
-    }
-}
+use std::error::Error as _;
+use std::fmt::Debug;
 
 wit_bindgen::generate!({
     world: "minusone",
 });
 
-const LANGUAGES: [&str; 2] = ["Powershell", "JavaScript"];
+// `Error` is the WIT error variant, brought in by the world
+use airbus_cert::minusone::errors::ErrorDetails;
+use exports::airbus_cert::minusone::engine::{
+    Argument as WitArgument, Artefact as WitArtefact, Behaviour as WitBehaviour, Deobfuscation,
+    Indicator, Limits as WitLimits, Options, ParseError as WitParseError, RuleInfo,
+};
+use minusone::ps::host::{HostProfile as CoreHostProfile, HostVersion};
+use minusone::rule::RuleSetBuilderType;
+
+impl From<MinusoneError> for Error {
+    fn from(error: MinusoneError) -> Self {
+        let mut causes = vec![];
+        let mut source = error.source();
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
 
-fn return_res(res: String) -> (String, String) {
-    (res, String::from(""))
+        let range = error.range();
+        let details = ErrorDetails {
+            message: error.to_string(),
+            causes,
+            rule: error.rule().map(String::from),
+            node_kind: error.node_kind().map(String::from),
+            start: range.as_ref().map(|r| r.start as u32),
+            end: range.as_ref().map(|r| r.end as u32),
+        };
+
+        match error.kind() {
+            MinusOneErrorKind::Parsing => Error::Parsing(details),
+            MinusOneErrorKind::Encoding => Error::Encoding(details),
+            MinusOneErrorKind::Decompression => Error::Decompression(details),
+            MinusOneErrorKind::Decryption => Error::Decryption(details),
+            MinusOneErrorKind::Language => Error::Language(details),
+            MinusOneErrorKind::InvalidChildIndex
+            | MinusOneErrorKind::InvalidParent
+            | MinusOneErrorKind::NestedTransactions => Error::Tree(details),
+            MinusOneErrorKind::Unknown => Error::Unknown(details),
+        }
+    }
 }
 
-fn return_err(err: String) -> (String, String) {
-    (String::from(""), err)
+fn unsupported_language(language: &str) -> Error {
+    Error::UnsupportedLanguage(format!(
+        "Unsupported language: {}. Supported languages are: {:?}",
+        language, LANGUAGES
+    ))
 }
 
+const LANGUAGES: [&str; 2] = ["Powershell", "JavaScript"];
+
 pub(crate) fn run_deobf<B: DeobfuscationBackend>(
     source: &str,
     rule_set: Option<Vec<String>>,
     skip_rule_set: Option<Vec<String>>,
-) -> Result<String, MinusonejsError>
+) -> Result<String, Error>
 where
     <B as DeobfuscationBackend>::Language: Debug,
 {
-    let cleaned = DeobfuscateEngine::<B>::remove_extra(source, false)
-        .map_err(MinusonejsError::MinusoneError)?;
-    let mut engine =
-        DeobfuscateEngine::<B>::from_source(&cleaned).map_err(MinusonejsError::MinusoneError)?;
+    let cleaned = DeobfuscateEngine::<B>::remove_extra(source, false)?;
+    let mut engine = DeobfuscateEngine::<B>::from_source(&cleaned)?;
 
     if let Some(rules) = rule_set {
-        engine
-            .deobfuscate_with_custom_ruleset(rules.iter().map(AsRef::as_ref).collect())
-            .map_err(MinusonejsError::MinusoneError)?;
+        engine.deobfuscate_with_custom_ruleset(rules.iter().map(AsRef::as_ref).collect())?;
     } else if let Some(skip_rules) = skip_rule_set {
         engine
-            .deobfuscate_without_custom_ruleset(skip_rules.iter().map(AsRef::as_ref).collect())
-            .map_err(MinusonejsError::MinusoneError)?;
+            .deobfuscate_without_custom_ruleset(skip_rules.iter().map(AsRef::as_ref).collect())?;
     } else {
-        engine
-            .deobfuscate()
-            .map_err(MinusonejsError::MinusoneError)?;
+        engine.deobfuscate()?;
     }
 
-    engine.lint(false).map_err(MinusonejsError::MinusoneError)
+    Ok(engine.lint(false)?)
 }
 
 struct Minusone;
@@ -76,19 +101,11 @@ impl Guest for Minusone {
         LANGUAGES.iter().map(|s| s.to_string()).collect()
     }
 
-    fn deobfuscate(source: String, language: String) -> (String, String) {
-        let result = match language.to_lowercase().as_str() {
+    fn deobfuscate(source: String, language: String) -> Result<String, Error> {
+        match language.to_lowercase().as_str() {
             "ps" | "ps1" | "powershell" => run_deobf::<PowershellBackend>(&source, None, None),
             "js" | "javascript" => run_deobf::<JavaScriptBackend>(&source, None, None),
-            _ => Err(MinusonejsError::JsError(format!(
-                "Unsupported language: {}. Supported languages are: {:?}",
-                language, LANGUAGES
-            ))),
-        };
-
-        match result {
-            Ok(r) => return_res(r),
-            Err(e) => return_err(e.to_string()),
+            _ => Err(unsupported_language(&language)),
         }
     }
 
@@ -96,21 +113,13 @@ impl Guest for Minusone {
         source: String,
         language: String,
         ruleset: Vec<String>,
-    ) -> (String, String) {
-        let result = match language.to_lowercase().as_str() {
+    ) -> Result<String, Error> {
+        match language.to_lowercase().as_str() {
             "ps" | "ps1" | "powershell" => {
                 run_deobf::<PowershellBackend>(&source, Some(ruleset), None)
             }
             "js" | "javascript" => run_deobf::<JavaScriptBackend>(&source, Some(ruleset), None),
-            _ => Err(MinusonejsError::JsError(format!(
-                "Unsupported language: {}. Supported languages are: {:?}",
-                language, LANGUAGES
-            ))),
-        };
-
-        match result {
-            Ok(r) => return_res(r),
-            Err(e) => return_err(e.to_string()),
+            _ => Err(unsupported_language(&language)),
         }
     }
 
@@ -118,41 +127,49 @@ impl Guest for Minusone {
         source: String,
         language: String,
         ruleset: Vec<String>,
-    ) -> (String, String) {
-        let result = match language.to_lowercase().as_str() {
+    ) -> Result<String, Error> {
+        match language.to_lowercase().as_str() {
             "ps" | "ps1" | "powershell" => {
                 run_deobf::<PowershellBackend>(&source, None, Some(ruleset))
             }
             "js" | "javascript" => run_deobf::<JavaScriptBackend>(&source, None, Some(ruleset)),
-            _ => Err(MinusonejsError::JsError(format!(
-                "Unsupported language: {}. Supported languages are: {:?}",
-                language, LANGUAGES
-            ))),
-        };
+            _ => Err(unsupported_language(&language)),
+        }
+    }
 
-        match result {
-            Ok(r) => return_res(r),
-            Err(e) => return_err(e.to_string()),
+    fn deobfuscate_with_host(source: String, host: HostProfile) -> Result<String, Error> {
+        let mut profile = CoreHostProfile::new(host.version.parse::<HostVersion>()?);
+        if let Some(user_name) = host.user_name {
+            profile = profile.with_user_name(&user_name);
+        }
+        if let Some(computer_name) = host.computer_name {
+            profile = profile.with_computer_name(&computer_name);
+        }
+        for (name, value) in host.env {
+            profile = profile.with_env(&name, &value);
         }
+
+        let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(&source, false)?;
+        let mut engine = DeobfuscateEngine::<PowershellBackend>::from_source(&cleaned)?;
+        engine.deobfuscate_with_host_profile(
+            RuleSetBuilderType::WithoutRules(vec![]),
+            &[],
+            &profile,
+        )?;
+
+        Ok(engine.lint(false)?)
     }
 }
 
-fn run_stepper(
-    source: &str,
-    language: &str,
-    record_all: bool,
-) -> Result<CoreStepper, MinusonejsError> {
+fn run_stepper(source: &str, language: &str, record_all: bool) -> Result<CoreStepper, Error> {
     match language.to_lowercase().as_str() {
         "ps" | "ps1" | "powershell" => PowershellBackend::stepper(source, false, record_all),
         "js" | "javascript" => JavaScriptBackend::stepper(source, false, record_all),
         _ => {
-            return Err(MinusonejsError::JsError(format!(
-                "Unsupported language: {}. Supported languages are: {:?}",
-                language, LANGUAGES
-            )));
+            return Err(unsupported_language(language));
         }
     }
-    .map_err(MinusonejsError::MinusoneError)
+    .map_err(Error::from)
 }
 
 struct StepperResource(RefCell<CoreStepper>);
@@ -181,18 +198,309 @@ impl exports::airbus_cert::minusone::trace::Guest for Minusone {
         source: String,
         language: String,
         record_all: bool,
-    ) -> (
-        Option<exports::airbus_cert::minusone::trace::Stepper>,
-        String,
-    ) {
-        match run_stepper(&source, &language, record_all) {
-            Ok(stepper) => (
-                Some(exports::airbus_cert::minusone::trace::Stepper::new(
-                    StepperResource(RefCell::new(stepper)),
-                )),
-                String::new(),
+    ) -> Result<exports::airbus_cert::minusone::trace::Stepper, Error> {
+        let stepper = run_stepper(&source, &language, record_all)?;
+        Ok(exports::airbus_cert::minusone::trace::Stepper::new(
+            StepperResource(RefCell::new(stepper)),
+        ))
+    }
+}
+
+fn run_analyze(source: &str, language: &str) -> Result<Report, Error> {
+    match language.to_lowercase().as_str() {
+        "ps" | "ps1" | "powershell" => DeobfuscateEngine::<PowershellBackend>::analyze(source),
+        "js" | "javascript" => DeobfuscateEngine::<JavaScriptBackend>::analyze(source),
+        _ => {
+            return Err(unsupported_language(language));
+        }
+    }
+    .map_err(Error::from)
+}
+
+impl From<Report> for exports::airbus_cert::minusone::analysis::Report {
+    fn from(report: Report) -> Self {
+        exports::airbus_cert::minusone::analysis::Report {
+            score: report.score(),
+            findings: report
+                .findings
+                .iter()
+                .map(
+                    |finding| exports::airbus_cert::minusone::analysis::Finding {
+                        technique: finding.technique.to_string(),
+                        description: finding.technique.description().to_string(),
+                        start: finding.start as u32,
+                        end: finding.end as u32,
+                    },
+                )
+                .collect(),
+            techniques: report
+                .techniques()
+                .into_iter()
+                .map(|(technique, count)| {
+                    exports::airbus_cert::minusone::analysis::TechniqueCount {
+                        technique: technique.to_string(),
+                        description: technique.description().to_string(),
+                        count: count as u32,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl exports::airbus_cert::minusone::analysis::Guest for Minusone {
+    fn analyze(
+        source: String,
+        language: String,
+    ) -> Result<exports::airbus_cert::minusone::analysis::Report, Error> {
+        Ok(run_analyze(&source, &language)?.into())
+    }
+}
+
+fn parse_language(language: &str) -> Result<ScriptLanguage, Error> {
+    language
+        .parse::<ScriptLanguage>()
+        .map_err(|_| unsupported_language(language))
+}
+
+fn invalid_options(message: String) -> Error {
+    Error::Unknown(ErrorDetails {
+        message,
+        causes: vec![],
+        rule: None,
+        node_kind: None,
+        start: None,
+        end: None,
+    })
+}
+
+/// URLs written in a string value
+fn find_urls(value: &str) -> Vec<&str> {
+    let mut urls = vec![];
+    for scheme in ["http://", "https://"] {
+        for (start, _) in value.match_indices(scheme) {
+            let url = &value[start..];
+            let end = url
+                .find(|c: char| c.is_whitespace() || "\"'<>()`".contains(c))
+                .unwrap_or(url.len());
+            if end > scheme.len() {
+                urls.push(&url[..end]);
+            }
+        }
+    }
+    urls
+}
+
+/// URLs held by the outermost string values of the tree
+fn collect_urls<T: QueryLanguage>(node: &Node<T>, indicators: &mut Vec<Indicator>) {
+    if let Some(QueryValue::Str(value)) = node.data().and_then(T::to_query_value) {
+        let urls = find_urls(&value);
+        if !urls.is_empty() {
+            for url in urls {
+                if !indicators.iter().any(|indicator| indicator.value == url) {
+                    indicators.push(Indicator {
+                        kind: "url".to_string(),
+                        value: url.to_string(),
+                        start: node.start_abs() as u32,
+                        end: node.end_abs() as u32,
+                    });
+                }
+            }
+            return;
+        }
+    }
+    for child in node.iter() {
+        collect_urls(&child, indicators);
+    }
+}
+
+/// Budgets given by the options, the other ones are left as is
+fn options_limits(mut limits: Limits, options: Option<&WitLimits>) -> Limits {
+    let Some(options) = options else {
+        return limits;
+    };
+    for (budget, value) in [
+        (&mut limits.fncall_depth, options.fncall_depth),
+        (&mut limits.map_filter_depth, options.map_filter_depth),
+        (&mut limits.for_depth, options.for_depth),
+        (&mut limits.for_iterations, options.for_iterations),
+        (&mut limits.pipeline_items, options.pipeline_items),
+        (&mut limits.layer_depth, options.layer_depth),
+        (&mut limits.kdf_iterations, options.kdf_iterations),
+        (&mut limits.derived_bytes, options.derived_bytes),
+    ] {
+        if let Some(value) = value {
+            *budget = value as usize;
+        }
+    }
+    limits
+}
+
+fn run_engine<B: DeobfuscationBackend>(
+    source: &str,
+    language: ScriptLanguage,
+    options: &Options,
+    profile: Option<RuleProfile>,
+    summarize: fn(&Tree<HashMapStorage<B::Language>>) -> MinusOneResult<Vec<Behaviour>>,
+) -> Result<Deobfuscation, Error>
+where
+    <B as DeobfuscationBackend>::Language: Debug + QueryLanguage + ArtefactLanguage,
+{
+    let report = DeobfuscateEngine::<B>::analyze(source)?;
+    let cleaned = DeobfuscateEngine::<B>::remove_extra(source, options.keep_dead_code)?;
+    let mut engine = DeobfuscateEngine::<B>::from_source(&cleaned)?;
+    let parse_errors = engine.parse_errors();
+
+    let _limits = with_limits(options_limits(
+        profile.unwrap_or_default().limits(),
+        options.limits.as_ref(),
+    ));
+    if let Some(profile) = profile {
+        let names = |set: &Option<Vec<String>>| -> Vec<&str> {
+            set.iter().flatten().map(String::as_str).collect()
+        };
+        engine.deobfuscate_with_query_rules(
+            profile.rule_set(
+                &DeobfuscateEngine::<B>::language_rules(),
+                &names(&options.rules),
+                &names(&options.skip_rules),
+            ),
+            &[],
+        )?;
+    } else if let Some(rules) = &options.rules {
+        engine.deobfuscate_with_custom_ruleset(rules.iter().map(String::as_str).collect())?;
+    } else if let Some(skip_rules) = &options.skip_rules {
+        engine
+            .deobfuscate_without_custom_ruleset(skip_rules.iter().map(String::as_str).collect())?;
+    } else {
+        engine.deobfuscate()?;
+    }
+
+    let artefacts = engine.artefacts(DEFAULT_ARTEFACT_THRESHOLD)?;
+    let mut indicators = vec![];
+    collect_urls(&engine.root().root()?, &mut indicators);
+    indicators.extend(artefacts.iter().map(|artefact| Indicator {
+        kind: "sha256".to_string(),
+        value: artefact.sha256.clone(),
+        start: artefact.start as u32,
+        end: artefact.end as u32,
+    }));
+
+    Ok(Deobfuscation {
+        language: language.to_string(),
+        output: engine.lint_format(
+            options.tab.as_deref().unwrap_or("    "),
+            options.keep_dead_code,
+        )?,
+        parse_errors: parse_errors
+            .into_iter()
+            .map(|error| WitParseError {
+                start: error.start as u32,
+                end: error.end as u32,
+                context: error.context,
+                missing: error.missing,
+            })
+            .collect(),
+        report: report.into(),
+        behaviours: summarize(engine.root())?
+            .into_iter()
+            .map(|behaviour| WitBehaviour {
+                kind: behaviour.kind.to_string(),
+                description: behaviour.kind.description().to_string(),
+                api: behaviour.api,
+                arguments: behaviour
+                    .arguments
+                    .into_iter()
+                    .map(|argument| WitArgument {
+                        name: argument.name,
+                        value: argument.value,
+                    })
+                    .collect(),
+                start: behaviour.start as u32,
+                end: behaviour.end as u32,
+            })
+            .collect(),
+        artefacts: artefacts
+            .into_iter()
+            .map(|artefact| WitArtefact {
+                kind: artefact.kind.to_string(),
+                description: artefact.kind.description().to_string(),
+                sha256: artefact.sha256,
+                size: artefact.data.len() as u32,
+                start: artefact.start as u32,
+                end: artefact.end as u32,
+            })
+            .collect(),
+        indicators,
+    })
+}
+
+impl exports::airbus_cert::minusone::engine::Guest for Minusone {
+    fn detect_language(source: String) -> String {
+        detect_language(&source).to_string()
+    }
+
+    fn list_rules(language: String) -> Result<Vec<RuleInfo>, Error> {
+        let rules = match parse_language(&language)? {
+            ScriptLanguage::Powershell => DeobfuscateEngine::<PowershellBackend>::language_rules(),
+            ScriptLanguage::JavaScript => DeobfuscateEngine::<JavaScriptBackend>::language_rules(),
+        };
+        Ok(rules
+            .into_iter()
+            .map(|rule| RuleInfo {
+                name: rule.name.to_string(),
+                description: rule.description.to_string(),
+                category: rule.category.to_string(),
+                node_kinds: rule
+                    .node_kinds
+                    .iter()
+                    .map(|kind| kind.to_string())
+                    .collect(),
+                executes_code: rule.executes_code(),
+                default_enabled: rule.default_enabled,
+            })
+            .collect())
+    }
+
+    fn run(source: String, options: Options) -> Result<Deobfuscation, Error> {
+        if let Some(max_source_size) = options.max_source_size
+            && source.len() > max_source_size as usize
+        {
+            return Err(invalid_options(format!(
+                "The source is {} bytes long, more than the limit of {} bytes",
+                source.len(),
+                max_source_size
+            )));
+        }
+        let profile = options
+            .profile
+            .as_deref()
+            .map(str::parse::<RuleProfile>)
+            .transpose()
+            .map_err(invalid_options)?;
+        if options.rules.is_some() && options.skip_rules.is_some() && profile.is_none() {
+            return Err(invalid_options(
+                "rules and skip-rules can't be used at the same time without a profile".to_string(),
+            ));
+        }
+
+        let language = match options.language.to_lowercase().as_str() {
+            "auto" => detect_language(&source),
+            _ => parse_language(&options.language)?,
+        };
+        match language {
+            ScriptLanguage::Powershell => run_engine::<PowershellBackend>(
+                &source,
+                language,
+                &options,
+                profile,
+                summarize_powershell,
             ),
-            Err(e) => (None, e.to_string()),
+            ScriptLanguage::JavaScript => {
+                run_engine::<JavaScriptBackend>(&source, language, &options, profile, |_| {
+                    Ok(vec![])
+                })
+            }
         }
     }
 }
@@ -1,23 +1,103 @@
+use minusone::detect::{Finding, Report};
 use minusone::engine::{DeobfuscateEngine, DeobfuscationBackend};
+use minusone::error::{MinusOneErrorKind, MinusOneResult, ParseError};
 use minusone::js::backend::JavaScriptBackend;
+use minusone::language::{self, ScriptLanguage};
+use minusone::profile::{Limits, RuleProfile, with_limits};
 use minusone::ps::backend::PowershellBackend;
+use minusone::ps::host::{HostProfile, HostVersion};
+use minusone::query::{QueryLanguage, QueryValue};
+use minusone::rule::RuleSetBuilderType;
 use minusone::trace::Stepper;
-use pyo3::exceptions::{PyRuntimeError, PyStopIteration, PyValueError};
+use minusone::tree::Node;
+use pyo3::IntoPyObjectExt;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyRuntimeError, PyStopIteration, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList};
+use std::collections::HashMap;
+use std::error::Error as _;
 use std::fmt::Debug;
 
+create_exception!(
+    pyminusone,
+    MinusOneError,
+    PyException,
+    "Base class of the errors raised by minusone"
+);
+create_exception!(
+    pyminusone,
+    ParsingError,
+    MinusOneError,
+    "The source could not be parsed"
+);
+create_exception!(
+    pyminusone,
+    EncodingError,
+    MinusOneError,
+    "Invalid UTF-8 in the source or in an inferred buffer"
+);
+create_exception!(
+    pyminusone,
+    DecompressionError,
+    MinusOneError,
+    "A compressed stream could not be inflated"
+);
+create_exception!(
+    pyminusone,
+    DecryptionError,
+    MinusOneError,
+    "A block cipher rejected its key, IV or padding"
+);
+create_exception!(
+    pyminusone,
+    LanguageError,
+    MinusOneError,
+    "The grammar could not be loaded"
+);
+create_exception!(
+    pyminusone,
+    TreeError,
+    MinusOneError,
+    "A rule met an unexpected tree"
+);
+
 struct PyMinusOneError(minusone::error::Error);
 
 impl From<PyMinusOneError> for PyErr {
     fn from(err: PyMinusOneError) -> Self {
-        match err.0 {
-            minusone::error::Error::Utf8Error(_) => {
-                PyErr::new::<PyRuntimeError, _>("Invalid UTF8 token".to_string())
-            }
-            minusone::error::Error::MinusOneError(minusone_error) => {
-                PyErr::new::<PyRuntimeError, _>(minusone_error.message.to_string())
-            }
+        let error = err.0;
+
+        // the context comes first, followed by the chain of causes
+        let mut message = error.to_string();
+        let mut source = error.source();
+        while let Some(cause) = source {
+            message = format!("{}: {}", message, cause);
+            source = cause.source();
         }
+
+        let py_err = match error.kind() {
+            MinusOneErrorKind::Parsing => ParsingError::new_err(message),
+            MinusOneErrorKind::Encoding => EncodingError::new_err(message),
+            MinusOneErrorKind::Decompression => DecompressionError::new_err(message),
+            MinusOneErrorKind::Decryption => DecryptionError::new_err(message),
+            MinusOneErrorKind::Language => LanguageError::new_err(message),
+            MinusOneErrorKind::InvalidChildIndex
+            | MinusOneErrorKind::InvalidParent
+            | MinusOneErrorKind::NestedTransactions => TreeError::new_err(message),
+            MinusOneErrorKind::Unknown => MinusOneError::new_err(message),
+        };
+
+        Python::with_gil(|py| {
+            let value = py_err.value(py);
+            let range = error.range();
+            // the minusone error is raised even when an attribute can't be set on it
+            let _ = value.setattr("rule", error.rule());
+            let _ = value.setattr("node_kind", error.node_kind());
+            let _ = value.setattr("start", range.as_ref().map(|r| r.start));
+            let _ = value.setattr("end", range.as_ref().map(|r| r.end));
+        });
+        py_err
     }
 }
 
@@ -35,11 +115,11 @@ where
 
     if let Some(rules) = rule_set {
         engine
-            .deobfuscate_with_custom_ruleset(rules.iter().map(AsRef::as_ref).collect())
+            .deobfuscate_with_custom_ruleset(rules.iter().map(String::as_str).collect())
             .map_err(PyMinusOneError)?;
     } else if let Some(skip_rules) = skip_rule_set {
         engine
-            .deobfuscate_without_custom_ruleset(skip_rules.iter().map(AsRef::as_ref).collect())
+            .deobfuscate_without_custom_ruleset(skip_rules.iter().map(String::as_str).collect())
             .map_err(PyMinusOneError)?;
     } else {
         engine.deobfuscate().map_err(PyMinusOneError)?;
@@ -84,6 +164,129 @@ fn deobfuscate_without(language: String, source: String, ruleset: Vec<String>) -
     }
 }
 
+#[pyclass(name = "Finding")]
+struct PyFinding {
+    #[pyo3(get)]
+    technique: String,
+    #[pyo3(get)]
+    description: String,
+    #[pyo3(get)]
+    start: usize,
+    #[pyo3(get)]
+    end: usize,
+}
+
+#[pymethods]
+impl PyFinding {
+    fn __repr__(&self) -> String {
+        format!(
+            "Finding(technique={:?}, start={}, end={})",
+            self.technique, self.start, self.end
+        )
+    }
+}
+
+#[pyclass(name = "Report")]
+struct PyReport {
+    #[pyo3(get)]
+    score: u32,
+    #[pyo3(get)]
+    techniques: HashMap<String, usize>,
+    findings: Vec<Finding>,
+}
+
+impl From<Report> for PyReport {
+    fn from(report: Report) -> Self {
+        PyReport {
+            score: report.score(),
+            techniques: report
+                .techniques()
+                .into_iter()
+                .map(|(technique, count)| (technique.to_string(), count))
+                .collect(),
+            findings: report.findings,
+        }
+    }
+}
+
+#[pymethods]
+impl PyReport {
+    #[getter]
+    fn findings(&self) -> Vec<PyFinding> {
+        self.findings
+            .iter()
+            .map(|finding| PyFinding {
+                technique: finding.technique.to_string(),
+                description: finding.technique.description().to_string(),
+                start: finding.start,
+                end: finding.end,
+            })
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Report(score={}, findings={})",
+            self.score,
+            self.findings.len()
+        )
+    }
+}
+
+/// Detect the obfuscation techniques used by a script, without deobfuscating it
+#[pyfunction]
+fn analyze(language: String, source: String) -> PyResult<PyReport> {
+    let report = match language.to_lowercase().as_str() {
+        "ps" | "ps1" | "powershell" => DeobfuscateEngine::<PowershellBackend>::analyze(&source),
+        "js" | "javascript" => DeobfuscateEngine::<JavaScriptBackend>::analyze(&source),
+        _ => {
+            return Err(PyErr::new::<PyRuntimeError, _>(format!(
+                "Unsupported language: {}",
+                language
+            )));
+        }
+    };
+    Ok(report.map_err(PyMinusOneError)?.into())
+}
+
+fn parse_error_to_dict<'py>(py: Python<'py>, error: &ParseError) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("start", error.start)?;
+    dict.set_item("end", error.end)?;
+    dict.set_item("context", &error.context)?;
+    dict.set_item("missing", &error.missing)?;
+    Ok(dict)
+}
+
+/// Regions of a script the parser failed on, without deobfuscating it,
+/// as dicts of start, end, context and missing
+#[pyfunction]
+fn parse_errors<'py>(
+    py: Python<'py>,
+    language: String,
+    source: String,
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    let errors = match language.to_lowercase().as_str() {
+        "ps" | "ps1" | "powershell" => {
+            PowershellBackend::build_deob_tree(&source).map(|tree| tree.parse_errors())
+        }
+        "js" | "javascript" => {
+            JavaScriptBackend::build_deob_tree(&source).map(|tree| tree.parse_errors())
+        }
+        _ => {
+            return Err(PyErr::new::<PyRuntimeError, _>(format!(
+                "Unsupported language: {}",
+                language
+            )));
+        }
+    }
+    .map_err(PyMinusOneError)?;
+    errors
+        .iter()
+        .map(|error| parse_error_to_dict(py, error))
+        .collect()
+}
+
 #[pyclass(name = "Step")]
 struct PyStep {
     #[pyo3(get)]
@@ -167,6 +370,418 @@ fn build_stepper(language: &str, source: &str, record_all: bool) -> PyResult<Ste
     Ok(stepper)
 }
 
+/// Guess the language of a script, "powershell" or "javascript"
+#[pyfunction]
+fn detect_language(source: String) -> &'static str {
+    language::detect_language(&source).name()
+}
+
+/// A node of the deobfuscated tree, with its inferred value
+struct InferredNode {
+    kind: String,
+    start: usize,
+    end: usize,
+    value: Option<QueryValue>,
+    // debug view of the inferred value, for the ones with no native equivalent
+    repr: Option<String>,
+    children: Vec<InferredNode>,
+}
+
+impl InferredNode {
+    fn new<T: QueryLanguage + Debug>(node: &Node<T>, base: usize) -> Self {
+        InferredNode {
+            kind: node.kind().to_string(),
+            start: node.start_abs() - base,
+            end: node.end_abs() - base,
+            value: node.data().and_then(T::to_query_value),
+            repr: node.data().map(|data| format!("{:?}", data)),
+            children: node
+                .iter()
+                .map(|child| InferredNode::new(&child, base))
+                .collect(),
+        }
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>, source: &str) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("kind", &self.kind)?;
+        dict.set_item("text", source.get(self.start..self.end).unwrap_or_default())?;
+        dict.set_item("range", (self.start, self.end))?;
+        dict.set_item(
+            "value",
+            match &self.value {
+                Some(value) => query_value_to_py(py, value)?,
+                None => py.None(),
+            },
+        )?;
+        dict.set_item("repr", &self.repr)?;
+        let children = self
+            .children
+            .iter()
+            .map(|child| child.to_dict(py, source))
+            .collect::<PyResult<Vec<_>>>()?;
+        dict.set_item("children", PyList::new(py, children)?)?;
+        Ok(dict)
+    }
+}
+
+fn query_value_to_py(py: Python<'_>, value: &QueryValue) -> PyResult<PyObject> {
+    match value {
+        QueryValue::Str(s) | QueryValue::Type(s) => s.into_py_any(py),
+        QueryValue::Int(n) => n.into_py_any(py),
+        QueryValue::Float(f) => f.into_py_any(py),
+        QueryValue::Bool(b) => b.into_py_any(py),
+        QueryValue::Bytes(bytes) => PyBytes::new(py, bytes).into_py_any(py),
+        QueryValue::Array(values) => values
+            .iter()
+            .map(|value| query_value_to_py(py, value))
+            .collect::<PyResult<Vec<PyObject>>>()?
+            .into_py_any(py),
+        QueryValue::Null => Ok(py.None()),
+    }
+}
+
+/// Result of the last deobfuscation of an engine
+struct Deobfuscation {
+    language: ScriptLanguage,
+    // source after the pre-processing, the tree ranges refer to it
+    source: String,
+    output: String,
+    tree: InferredNode,
+    parse_errors: Vec<ParseError>,
+}
+
+#[derive(Clone)]
+struct EngineOptions {
+    keep_dead_code: bool,
+    tab: String,
+    rules: Option<Vec<String>>,
+    skip_rules: Option<Vec<String>>,
+    profile: Option<RuleProfile>,
+    record_all: bool,
+    max_source_size: Option<usize>,
+    // budgets of the profile, with the ones given to the engine in place
+    limits: Limits,
+}
+
+/// Budgets given by name, the other ones are left as is
+fn engine_limits(mut limits: Limits, given: HashMap<String, usize>) -> PyResult<Limits> {
+    for (name, value) in given {
+        let budget = match name.as_str() {
+            "fncall_depth" => &mut limits.fncall_depth,
+            "map_filter_depth" => &mut limits.map_filter_depth,
+            "for_depth" => &mut limits.for_depth,
+            "for_iterations" => &mut limits.for_iterations,
+            "pipeline_items" => &mut limits.pipeline_items,
+            "layer_depth" => &mut limits.layer_depth,
+            "kdf_iterations" => &mut limits.kdf_iterations,
+            "derived_bytes" => &mut limits.derived_bytes,
+            _ => {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "Unknown limit: '{}'",
+                    name
+                )));
+            }
+        };
+        *budget = value;
+    }
+    Ok(limits)
+}
+
+fn run_engine<B: DeobfuscationBackend>(
+    language: ScriptLanguage,
+    source: &str,
+    options: &EngineOptions,
+) -> MinusOneResult<Deobfuscation>
+where
+    <B as DeobfuscationBackend>::Language: QueryLanguage + Debug,
+{
+    let cleaned = DeobfuscateEngine::<B>::remove_extra(source, options.keep_dead_code)?;
+    let mut engine = DeobfuscateEngine::<B>::from_source(&cleaned)?;
+    let parse_errors = engine.parse_errors();
+
+    let _limits = with_limits(options.limits);
+    if let Some(profile) = options.profile {
+        let names = |set: &Option<Vec<String>>| -> Vec<&str> {
+            set.iter().flatten().map(String::as_str).collect()
+        };
+        engine.deobfuscate_with_query_rules(
+            profile.rule_set(
+                &DeobfuscateEngine::<B>::language_rules(),
+                &names(&options.rules),
+                &names(&options.skip_rules),
+            ),
+            &[],
+        )?;
+    } else if let Some(rules) = &options.rules {
+        engine.deobfuscate_with_custom_ruleset(rules.iter().map(String::as_str).collect())?;
+    } else if let Some(skip_rules) = &options.skip_rules {
+        engine
+            .deobfuscate_without_custom_ruleset(skip_rules.iter().map(String::as_str).collect())?;
+    } else {
+        engine.deobfuscate()?;
+    }
+
+    let root = engine.root().root()?;
+    let tree = InferredNode::new(&root, root.start_abs());
+    let output = engine.lint_format(&options.tab, options.keep_dead_code)?;
+    Ok(Deobfuscation {
+        language,
+        source: cleaned.clone(),
+        output,
+        tree,
+        parse_errors,
+    })
+}
+
+/// A deobfuscation engine for one language, keeping the state of its last run
+///
+/// The language is "powershell", "javascript" or "auto" to guess it from each source
+#[pyclass(name = "Engine", unsendable)]
+struct PyEngine {
+    language: Option<ScriptLanguage>,
+    options: EngineOptions,
+    last: Option<Deobfuscation>,
+}
+
+impl PyEngine {
+    fn language_of(&self, source: &str) -> PyResult<ScriptLanguage> {
+        if let Some(max_source_size) = self.options.max_source_size
+            && source.len() > max_source_size
+        {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "The source is {} bytes long, more than the limit of {} bytes",
+                source.len(),
+                max_source_size
+            )));
+        }
+        Ok(self
+            .language
+            .unwrap_or_else(|| language::detect_language(source)))
+    }
+
+    fn last(&self) -> PyResult<&Deobfuscation> {
+        self.last.as_ref().ok_or_else(|| {
+            PyErr::new::<PyRuntimeError, _>("No source was deobfuscated by this engine")
+        })
+    }
+}
+
+#[pymethods]
+impl PyEngine {
+    #[new]
+    #[pyo3(signature = (
+        language="auto",
+        *,
+        keep_dead_code=false,
+        tab="    ",
+        rules=None,
+        skip_rules=None,
+        profile=None,
+        record_all=false,
+        max_source_size=None,
+        limits=None
+    ))]
+    fn new(
+        language: &str,
+        keep_dead_code: bool,
+        tab: &str,
+        rules: Option<Vec<String>>,
+        skip_rules: Option<Vec<String>>,
+        profile: Option<&str>,
+        record_all: bool,
+        max_source_size: Option<usize>,
+        limits: Option<HashMap<String, usize>>,
+    ) -> PyResult<Self> {
+        if rules.is_some() && skip_rules.is_some() && profile.is_none() {
+            return Err(PyErr::new::<PyValueError, _>(
+                "rules and skip_rules can't be used at the same time without a profile",
+            ));
+        }
+        let profile = profile
+            .map(str::parse::<RuleProfile>)
+            .transpose()
+            .map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(PyEngine {
+            language: match language {
+                "auto" => None,
+                _ => Some(
+                    language
+                        .parse::<ScriptLanguage>()
+                        .map_err(PyErr::new::<PyValueError, _>)?,
+                ),
+            },
+            options: EngineOptions {
+                keep_dead_code,
+                tab: tab.to_string(),
+                rules,
+                skip_rules,
+                profile,
+                record_all,
+                max_source_size,
+                limits: engine_limits(
+                    profile.unwrap_or_default().limits(),
+                    limits.unwrap_or_default(),
+                )?,
+            },
+            last: None,
+        })
+    }
+
+    /// Language of the engine, or the one guessed for the last source
+    #[getter]
+    fn language(&self) -> &'static str {
+        match (self.language, &self.last) {
+            (Some(language), _) => language.name(),
+            (None, Some(last)) => last.language.name(),
+            (None, None) => "auto",
+        }
+    }
+
+    /// Rules of the language, as dicts of name, description, category,
+    /// node_kinds, safety, executes_code and default_enabled
+    ///
+    /// `rules` and `skip_rules` also accept `@category`, like `@crypto`
+    fn rules<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let rules = match self
+            .language
+            .or(self.last.as_ref().map(|last| last.language))
+        {
+            Some(ScriptLanguage::Powershell) => {
+                DeobfuscateEngine::<PowershellBackend>::language_rules()
+            }
+            Some(ScriptLanguage::JavaScript) => {
+                DeobfuscateEngine::<JavaScriptBackend>::language_rules()
+            }
+            None => {
+                return Err(PyErr::new::<PyRuntimeError, _>(
+                    "The language is guessed from the source, deobfuscate one first",
+                ));
+            }
+        };
+        rules
+            .iter()
+            .map(|rule| {
+                let dict = PyDict::new(py);
+                dict.set_item("name", rule.name)?;
+                dict.set_item("description", rule.description)?;
+                dict.set_item("category", rule.category.name())?;
+                dict.set_item("node_kinds", rule.node_kinds)?;
+                dict.set_item("safety", rule.safety.name())?;
+                dict.set_item("executes_code", rule.executes_code())?;
+                dict.set_item("default_enabled", rule.default_enabled)?;
+                Ok(dict)
+            })
+            .collect()
+    }
+
+    /// Deobfuscate a script, and return its linted output
+    fn deobfuscate(&mut self, source: &str) -> PyResult<String> {
+        let language = self.language_of(source)?;
+        let deobfuscation = match language {
+            ScriptLanguage::Powershell => {
+                run_engine::<PowershellBackend>(language, source, &self.options)
+            }
+            ScriptLanguage::JavaScript => {
+                run_engine::<JavaScriptBackend>(language, source, &self.options)
+            }
+        }
+        .map_err(PyMinusOneError)?;
+
+        let output = deobfuscation.output.clone();
+        self.last = Some(deobfuscation);
+        Ok(output)
+    }
+
+    /// Linted output of the last deobfuscated source
+    fn lint(&self) -> PyResult<String> {
+        Ok(self.last()?.output.clone())
+    }
+
+    /// Tree of the last deobfuscated source, as nested dicts of
+    /// kind, text, range, value, repr and children
+    ///
+    /// Ranges refer to the source after the pre-processing, comments removed
+    fn tree<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let last = self.last()?;
+        last.tree.to_dict(py, &last.source)
+    }
+
+    /// Regions of the last deobfuscated source the parser failed on,
+    /// as dicts of start, end, context and missing
+    fn parse_errors<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.last()?
+            .parse_errors
+            .iter()
+            .map(|error| parse_error_to_dict(py, error))
+            .collect()
+    }
+
+    /// Every transformation made to deobfuscate a script
+    fn steps(&self, source: &str) -> PyResult<Vec<PyStep>> {
+        let language = self.language_of(source)?;
+        if self.options.rules.is_some()
+            || self.options.skip_rules.is_some()
+            || self.options.profile.is_some()
+        {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Steps are recorded with the full ruleset",
+            ));
+        }
+        let _limits = with_limits(self.options.limits);
+        let stepper = match language {
+            ScriptLanguage::Powershell => PowershellBackend::stepper(
+                source,
+                self.options.keep_dead_code,
+                self.options.record_all,
+            ),
+            ScriptLanguage::JavaScript => JavaScriptBackend::stepper(
+                source,
+                self.options.keep_dead_code,
+                self.options.record_all,
+            ),
+        }
+        .map_err(PyMinusOneError)?;
+        Ok(stepper.map(PyStep::from).collect())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Engine(language={:?})", self.language())
+    }
+}
+
+/// Deobfuscate a Powershell script as if it was run on a specific host
+#[pyfunction]
+#[pyo3(signature = (source, host="5.1", user=None, computer=None, env=None))]
+fn deobfuscate_with_host(
+    source: String,
+    host: &str,
+    user: Option<String>,
+    computer: Option<String>,
+    env: Option<HashMap<String, String>>,
+) -> PyResult<String> {
+    let mut profile = HostProfile::new(host.parse::<HostVersion>().map_err(PyMinusOneError)?);
+    if let Some(user) = user {
+        profile = profile.with_user_name(&user);
+    }
+    if let Some(computer) = computer {
+        profile = profile.with_computer_name(&computer);
+    }
+    for (name, value) in env.unwrap_or_default() {
+        profile = profile.with_env(&name, &value);
+    }
+
+    let cleaned = DeobfuscateEngine::<PowershellBackend>::remove_extra(&source, false)
+        .map_err(PyMinusOneError)?;
+    let mut engine =
+        DeobfuscateEngine::<PowershellBackend>::from_source(&cleaned).map_err(PyMinusOneError)?;
+    engine
+        .deobfuscate_with_host_profile(RuleSetBuilderType::WithoutRules(vec![]), &[], &profile)
+        .map_err(PyMinusOneError)?;
+
+    Ok(engine.lint(false).map_err(PyMinusOneError)?)
+}
+
 #[pyfunction]
 #[pyo3(signature = (language, source, record_all=false))]
 fn new_stepper(language: String, source: String, record_all: bool) -> PyResult<PyStepper> {
@@ -180,8 +795,23 @@ fn pyminusone(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(deobfuscate, m)?)?;
     m.add_function(wrap_pyfunction!(deobfuscate_with, m)?)?;
     m.add_function(wrap_pyfunction!(deobfuscate_without, m)?)?;
+    m.add_function(wrap_pyfunction!(deobfuscate_with_host, m)?)?;
     m.add_function(wrap_pyfunction!(new_stepper, m)?)?;
+    m.add_function(wrap_pyfunction!(analyze, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_errors, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_language, m)?)?;
+    m.add_class::<PyEngine>()?;
     m.add_class::<PyStep>()?;
     m.add_class::<PyStepper>()?;
+    m.add_class::<PyReport>()?;
+    m.add_class::<PyFinding>()?;
+    let py = m.py();
+    m.add("MinusOneError", py.get_type::<MinusOneError>())?;
+    m.add("ParsingError", py.get_type::<ParsingError>())?;
+    m.add("EncodingError", py.get_type::<EncodingError>())?;
+    m.add("DecompressionError", py.get_type::<DecompressionError>())?;
+    m.add("DecryptionError", py.get_type::<DecryptionError>())?;
+    m.add("LanguageError", py.get_type::<LanguageError>())?;
+    m.add("TreeError", py.get_type::<TreeError>())?;
     Ok(())
 }
@@ -17,4 +17,74 @@ assert pyminusone.deobfuscate_without("js", "console.log(1+2)", ["MultInt"]) ==
 
 assert pyminusone.deobfuscate("ps", "Write-Host (1+2)") == "Write-Host 3"
 assert pyminusone.deobfuscate_with("powershell", "Write-Host (1+2)", ["ParseInt", "AddInt", "Forward"]) == "Write-Host 3"
-assert pyminusone.deobfuscate_without("ps1", "Write-Host (1+2)", ["MultInt"]) == "Write-Host 3"
\ No newline at end of file
+assert pyminusone.deobfuscate_without("ps1", "Write-Host (1+2)", ["MultInt"]) == "Write-Host 3"
+
+report = pyminusone.analyze("js", "console['lo' + 'g'](atob('aGVsbG8='))")
+print("analyze(js):", report, report.findings)
+assert report.techniques == {"string-concat": 1, "base64": 1}
+assert report.score == 25
+
+try:
+    pyminusone.deobfuscate("js", "}}}))")
+    assert False, "a parsing error was expected"
+except pyminusone.ParsingError as e:
+    print("deobfuscate(invalid js):", e)
+    assert isinstance(e, pyminusone.MinusOneError)
+    assert e.rule is None
+
+errors = pyminusone.parse_errors("js", "var a = 1; )))) var b = 2;")
+print("parse_errors(js):", errors)
+assert len(errors) == 1 and errors[0]["context"] == "program"
+assert pyminusone.parse_errors("ps", "Write-Host 1") == []
+
+print("deobfuscate_with_host(ps1):", pyminusone.deobfuscate_with_host("$env:USERNAME", host="7", user="bob"))
+assert pyminusone.deobfuscate_with_host("$env:USERNAME", host="7", user="bob") == '"bob"'
+
+engine = pyminusone.Engine("auto")
+assert engine.deobfuscate("Write-Host (1+2)") == "Write-Host 3"
+assert engine.language == "powershell"
+assert engine.lint() == "Write-Host 3"
+rules = {rule["name"]: rule for rule in engine.rules()}
+assert rules["Forward"]["category"] == "parsing"
+assert rules["ScriptBlockInvoke"]["executes_code"]
+assert engine.parse_errors() == []
+
+
+def values(node):
+    yield node["value"]
+    for child in node["children"]:
+        yield from values(child)
+
+
+assert 3 in values(engine.tree())
+print("Engine.steps(ps1):", engine.steps("Write-Host (1+2)"))
+
+assert pyminusone.detect_language("var a = 1 + 2;") == "javascript"
+engine = pyminusone.Engine("js", keep_dead_code=True, skip_rules=["MultInt", "@control-flow"])
+assert engine.deobfuscate("console.log(1+2)") == "console.log(3)"
+assert engine.language == "javascript"
+
+engine = pyminusone.Engine("js", profile="safe", rules=["FnCall"], skip_rules=["@control-flow"])
+assert engine.deobfuscate("console.log(1+2)") == "console.log(3)"
+
+try:
+    pyminusone.Engine("js", profile="reckless")
+    assert False, "a profile error was expected"
+except ValueError as e:
+    print("Engine(profile):", e)
+
+loop = "for(var i = 0; i < 5; i++) {} var x = i;"
+assert pyminusone.Engine("js").deobfuscate(loop).endswith("var x = 5;")
+assert not pyminusone.Engine("js", limits={"for_iterations": 3}).deobfuscate(loop).endswith("var x = 5;")
+
+try:
+    pyminusone.Engine("js", limits={"for_loops": 3})
+    assert False, "a limit error was expected"
+except ValueError as e:
+    print("Engine(limits):", e)
+
+try:
+    pyminusone.Engine("ps", max_source_size=4).deobfuscate("Write-Host 1")
+    assert False, "a size error was expected"
+except ValueError as e:
+    print("Engine(max_source_size):", e)
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minusone::js::backend::JavaScriptBackend;
+
+fuzz_target!(|source: &str| {
+    minusone_fuzz::lint_round_trip::<JavaScriptBackend>(source);
+});
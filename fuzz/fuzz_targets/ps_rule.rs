@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minusone::ps::backend::PowershellBackend;
+
+fuzz_target!(|data: &[u8]| {
+    minusone_fuzz::rule_in_isolation::<PowershellBackend>(data);
+});
@@ -0,0 +1,78 @@
+#!/usr/bin/env python3
+"""Seed the corpus of every fuzz target
+
+The seeds are the string literals of the unit tests of each backend, and the
+obfuscated samples of its language. The rule targets read the rule to run from
+their first byte, so their seeds are prefixed with one.
+
+Usage: python3 seed_corpus.py
+"""
+
+import hashlib
+import re
+from pathlib import Path
+
+FUZZ = Path(__file__).resolve().parent
+ROOT = FUZZ.parent
+
+LANGUAGES = {
+    "ps": {"tests": ROOT / "core/src/ps/tests", "samples": (".ps1", ".psm1", ".psd1")},
+    "js": {"tests": ROOT / "core/src/js/tests", "samples": (".js",)},
+}
+
+STRING = re.compile(r'r(#*)"(.*?)"\1|"((?:[^"\\]|\\.)*)"', re.DOTALL)
+ESCAPE = re.compile(r'\\(u\{[0-9a-fA-F]+\}|x[0-9a-fA-F]{2}|\n\s*|.)', re.DOTALL)
+SIMPLE_ESCAPES = {"n": "\n", "r": "\r", "t": "\t", "0": "\0", "\\": "\\", '"': '"', "'": "'"}
+
+
+def unescape(literal):
+    def replace(match):
+        escape = match.group(1)
+        if escape.startswith("u{"):
+            return chr(int(escape[2:-1], 16))
+        if escape.startswith("x"):
+            return chr(int(escape[1:], 16))
+        if escape.startswith("\n"):
+            # line continuation
+            return ""
+        return SIMPLE_ESCAPES.get(escape, escape)
+
+    return ESCAPE.sub(replace, literal)
+
+
+def test_seeds(tests):
+    for path in sorted(tests.glob("*.rs")):
+        for match in STRING.finditer(path.read_text(encoding="utf-8")):
+            if match.group(2) is not None:
+                yield match.group(2)
+            else:
+                yield unescape(match.group(3))
+
+
+def sample_seeds(extensions):
+    for path in sorted((ROOT / "samples").rglob("*.obf.*")):
+        if path.suffix.lower() in extensions:
+            yield path.read_text(encoding="utf-8", errors="replace")
+
+
+def write(corpus, seed):
+    corpus.mkdir(parents=True, exist_ok=True)
+    (corpus / hashlib.sha1(seed).hexdigest()).write_bytes(seed)
+
+
+def main():
+    for prefix, language in LANGUAGES.items():
+        seeds = {
+            seed.encode("utf-8")
+            for seed in (*test_seeds(language["tests"]), *sample_seeds(language["samples"]))
+            if len(seed.strip()) > 2
+        }
+        for index, seed in enumerate(sorted(seeds)):
+            write(FUZZ / "corpus" / f"{prefix}_deobfuscate", seed)
+            write(FUZZ / "corpus" / f"{prefix}_lint_round_trip", seed)
+            write(FUZZ / "corpus" / f"{prefix}_rule", bytes([index % 256]) + seed)
+        print(f"{prefix}: {len(seeds)} seeds")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,74 @@
+//! Fuzzing harness shared by the targets of both backends
+//!
+//! Every helper accepts any valid UTF-8 input: errors are expected on
+//! garbage and ignored, only panics and broken properties are findings.
+
+use minusone::engine::{DeobfuscateEngine, DeobfuscationBackend};
+
+/// Full deobfuscation with the default rule set, then linting
+pub fn deobfuscate<B: DeobfuscationBackend>(source: &str) {
+    let Ok(cleaned) = DeobfuscateEngine::<B>::remove_extra(source, false) else {
+        return;
+    };
+    let Ok(mut engine) = DeobfuscateEngine::<B>::from_source(&cleaned) else {
+        return;
+    };
+    if engine.deobfuscate().is_ok() {
+        let _ = engine.lint(false);
+    }
+}
+
+/// Deobfuscation with a single rule, picked by the first byte of the input
+pub fn rule_in_isolation<B: DeobfuscationBackend>(data: &[u8]) {
+    let Some((selector, data)) = data.split_first() else {
+        return;
+    };
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+    let rules = DeobfuscateEngine::<B>::language_rules();
+    if rules.is_empty() {
+        return;
+    }
+    let rule = rules[*selector as usize % rules.len()].name;
+
+    let Ok(cleaned) = DeobfuscateEngine::<B>::remove_extra(source, false) else {
+        return;
+    };
+    let Ok(mut engine) = DeobfuscateEngine::<B>::from_source(&cleaned) else {
+        return;
+    };
+    if engine.deobfuscate_with_custom_ruleset(vec![rule]).is_ok() {
+        let _ = engine.lint(false);
+    }
+}
+
+/// The linted output parses again, without more parse errors than the input had
+pub fn lint_round_trip<B: DeobfuscationBackend>(source: &str) {
+    let Ok(cleaned) = DeobfuscateEngine::<B>::remove_extra(source, true) else {
+        return;
+    };
+    let Ok(mut engine) = DeobfuscateEngine::<B>::from_source(&cleaned) else {
+        return;
+    };
+    let errors = engine.parse_errors();
+    if engine.deobfuscate().is_err() {
+        return;
+    }
+    let Ok(output) = engine.lint(true) else {
+        return;
+    };
+
+    let reparsed = B::build_deob_tree(&output)
+        .unwrap_or_else(|e| panic!("linted output does not parse: {e}\n{output}"));
+    let new_errors = reparsed.parse_errors();
+    assert!(
+        new_errors.len() <= errors.len(),
+        "linted output has {} parse error(s), the input had {}\ninput:\n{}\noutput:\n{}\nerrors: {:?}",
+        new_errors.len(),
+        errors.len(),
+        cleaned,
+        output,
+        new_errors
+    );
+}